@@ -0,0 +1,408 @@
+# Shadow Swap UI strings, English. Built into the game and used for anything a translation leaves out.
+# Format: `message-id = text`, one per line. `{ $name }` is filled in by the game; keep those as they are.
+
+language-name = English
+on = On
+off = Off
+
+## Player colors
+color-green = Green
+color-red = Red
+color-blue = Blue
+color-purple = Purple
+color-teal = Teal
+color-pink = Pink
+color-gold = Gold
+color-silver = Silver
+color-bronze = Bronze
+color-lime = Lime
+color-orange = Orange
+color-cyan = Cyan
+
+## Match rules
+mode-classic = Classic
+mode-capture-zones = Capture Zones
+mode-tag = Tag
+mode-king-of-the-hill = King of the Hill
+mode-hunter-vs-shade = Hunter vs Shade
+schedule-meter = Charge meter
+schedule-fixed = Fixed
+schedule-random = Random
+schedule-escalating = Escalating
+schedule-endgame = Endgame
+shadows-opponent = Opponent-controlled
+shadows-mirror = Mirror
+shadows-echo = Echo
+arena-open = Open
+arena-portals = Portals
+arena-vortex = Vortex
+
+## Match setup
+setting-who-you = you
+setting-who-opponent = opponent
+setting-mode = Game mode
+setting-hunter = Hunts first
+setting-hunter-me = Me
+setting-hunter-opponent = Opponent
+setting-schedule = Inverse schedule
+setting-shadows = Shadows
+setting-echo-delay = Echo delay
+setting-arena = Arena
+setting-speed = Speed ({ $who })
+setting-trap-radius = Trap radius against { $who }
+setting-preloaded-traps = Traps against { $who } at start
+setting-afk-timeout = AFK timeout
+setting-tether = Shadow tether
+setting-wrap = Wrap-around edges
+setting-steal = Shadow steal
+setting-fog = Fog of war
+setting-streak-bonus = Bonus trap for { $count } in a row
+
+## Settings
+palette-picked = Picked colors
+palette-red-green = Red-green safe (blue / orange)
+palette-blue-yellow = Blue-yellow safe (crimson / teal)
+palette-monochrome = Monochrome
+announce-log = Event log
+announce-speech = Event log + speech
+pref-language = Language
+pref-theme = Theme
+pref-window = Window
+pref-resolution = Resolution
+window-windowed = Windowed
+window-fullscreen = Fullscreen
+window-borderless = Borderless fullscreen
+resolution = { $width } x { $height }
+resolution-monitor = Monitor size
+pref-palette = Player colors
+pref-high-contrast = High contrast
+pref-reduced-motion = Reduced motion
+pref-announce = Announce events
+pref-hud-scale = HUD scale
+pref-graphics = Graphics
+pref-master-volume = Master volume
+pref-music-volume = Music volume
+pref-sfx-volume = Sound effects volume
+pref-announcer = Announcer
+muted = Muted
+muted-hint = Muted (Ctrl+M)
+graphics-low = Low
+graphics-high = High (bloom)
+
+## Main menu
+menu-host = Host a game
+menu-join = Join a game
+menu-tutorial = Tutorial (single player)
+menu-daily = Daily challenge (single player)
+menu-puzzle = Puzzle campaign (single player)
+menu-history = Match history
+menu-leaderboard = Leaderboard
+menu-settings = Settings
+menu-profile = Profile: { $name }, { $color }
+menu-profile-guest = Guest: { $name }, { $color }
+menu-profile-unset = Guest: (no name), { $color }
+hint-main-menu = Up/Down + Enter (or a number key) to choose, Esc to quit
+setup-title = MATCH SETUP
+setup-start = Start hosting on port { $port }
+hint-setup = Up/Down to pick, Left/Right to change, Enter on Start, Esc to go back
+join-address = Host IP address:
+join-same-computer = Same computer: 127.0.0.1
+join-local-network = Local network: the host's local IP (e.g. 192.168.1.31)
+hint-join = Type the address, Enter to connect, Esc to go back
+name-prompt = Your name:
+name-explainer = Shown above your character and in your opponent's HUD
+name-color = Color: < { $color } >
+hint-name = Type your name, Left/Right for color, Up/Down for more, Enter or Esc when done
+profile-controls = Key bindings
+profile-cosmetics = Cosmetics
+cosmetics-title = COSMETICS
+cosmetics-trail = Trail
+cosmetics-swap = Swap effect
+cosmetics-achievements = Achievements: { $earned } of { $total }
+cosmetics-unlocks = { $goal } - unlocks { $reward }
+cosmetic-trail = the { $name } trail
+cosmetic-swap = the { $name } swap effect
+cosmetics-colors = the { $set } colors
+colors-veteran = metal
+colors-marathon = neon
+hint-cosmetics = Up/Down to pick, Left/Right to change, Esc to go back
+hint-cosmetics-guest = Guests can't earn achievements: pick or make a profile to unlock more
+trail-plain = Plain
+trail-sparks = Sparks
+trail-comet = Comet
+swap-effect-burst = Burst
+swap-effect-ring = Ring
+swap-effect-implode = Implode
+achievement-first-win = First Win
+achievement-first-win-goal = Win a match
+achievement-regular = Regular
+achievement-regular-goal = Play 10 matches
+achievement-trapper = Trapper
+achievement-trapper-goal = Trap your opponents 25 times
+achievement-flawless = Flawless
+achievement-flawless-goal = Win a match without being trapped
+achievement-veteran = Veteran
+achievement-veteran-goal = Win 10 matches
+achievement-marathon = Marathon
+achievement-marathon-goal = Spend an hour in play
+achievement-unlocked = Achievement: { $name }! Unlocked { $reward }
+profile-switch = Switch profile
+profiles-title = WHO'S PLAYING?
+profiles-new = New profile
+profiles-guest = Play as guest
+profiles-guest-name = Guest
+hint-profiles = Up/Down + Enter to choose, Delete to remove a profile, Esc to go back
+controls-screen-title = KEYS FOR { $name }
+controls-press-key = Press a key...
+controls-reset = Reset to defaults
+hint-controls = Up/Down to pick, Enter to change a key, Esc to go back
+hint-rebinding = Press the new key, Esc to cancel
+action-up = Move up
+action-down = Move down
+action-left = Move left
+action-right = Move right
+action-swap = Swap with your shadow
+action-inverse = Inverse mode
+action-freeze = Freeze
+error-profile-exists = There is already a profile called { $name }
+error-profile-load = Could not load the profile { $name }: { $error }
+error-graphics = Could not set up graphics: { $error }
+settings-title = SETTINGS
+settings-back = Back
+hint-settings = Up/Down to pick, Left/Right to change, Esc to go back
+history-title = MATCH HISTORY
+history-empty = No finished matches yet. Host or join one!
+history-summary = { $played } played: { $won } won, { $lost } lost
+history-date = Date
+history-opponent = Opponent
+history-mode = Mode
+history-result = Result
+history-traps = Traps dealt / taken
+history-time = Time
+history-won = Won
+history-lost = Lost
+hint-history = Up/Down to scroll, Esc to go back
+leaderboard-title = LEADERBOARD
+leaderboard-filter = < { $mode } >    { $order } (Tab)
+leaderboard-all = All modes
+leaderboard-by-rating = By rating
+leaderboard-by-wins = By wins
+leaderboard-empty = Nobody has finished a match in this mode yet
+leaderboard-rank = #
+leaderboard-player = Player
+leaderboard-rating = Rating
+leaderboard-wins = Wins
+leaderboard-losses = Losses
+leaderboard-win-rate = Win rate
+hint-leaderboard = Left/Right for the mode, Tab to change the order, Up/Down to scroll, Esc to go back
+error-no-address = Type the host's IP address first
+error-puzzle-levels = Could not load puzzle levels: { $error }
+error-report = Could not export the match report: { $error }
+error-port-in-use = Port { $port } is already in use, maybe by another game hosting on it. Pick another with --port or in the config file
+error-resolve = Could not resolve host { $address }
+error-network = Network error: { $error }
+crash-reported = The game crashed last time, report saved to { $path }
+screenshot-saved = Screenshot saved to { $path }
+error-screenshot = Could not save the screenshot: { $error }
+clip-saved = Saved the last 10 seconds to { $path }
+error-clip = Could not save the clip: { $error }
+failed-title = Couldn't start the game
+failed-retry = Try again
+failed-back = Back
+hint-failed = Up/Down + Enter to choose, Esc to go back
+
+## Connecting
+connect-local-network = Local network: join { $ip }
+connect-no-local-ip = Local network: couldn't detect this machine's IP
+connect-same-computer = Same computer: join 127.0.0.1
+connect-internet = Over the internet: forward UDP port { $port } and share your public IP
+connect-waiting = Waiting for opponent on port { $port }
+connect-no-answer = No answer yet. Check the address and that the host is waiting.
+connect-to = Connecting to { $address }
+connect-to-host = Connecting to the host
+connect-cancel = Cancel
+connect-cancel-key = (or Esc)
+
+## Lobby
+lobby-title = PLAYERS CONNECTED
+lobby-players = { $first } (Player 1, rated { $first_rating }) vs { $second } (Player 2, rated { $second_rating })
+lobby-inverse = Inverse: { $schedule }
+lobby-shadows = Shadows: { $shadows }
+lobby-streak-bonus = Streak bonus
+lobby-color = Your color: < { $color } >
+lobby-color-taken = Your color: < { $color } > (taken, using { $shown })
+lobby-color-hint = Left/Right to change
+lobby-color-hint-palette = Left/Right to change (shown here as: { $palette })
+lobby-ready = READY
+lobby-not-ready = not ready
+lobby-ready-status = You: { $you }    Opponent: { $opponent }
+lobby-unready-hint = Enter to cancel
+lobby-ready-hint = Press Enter when you're ready
+
+## HUD
+hud-you = { $name } (YOU)
+hud-trapped = Trapped: { $score } / { $max }
+hud-opponent-trapped = { $name }: { $score } / { $max }
+hud-zones = Zones: { $mine } - { $theirs } (first to { $max })
+hud-tag-you-it = YOU are it!
+hud-tag-opponent-it = Opponent is it
+hud-tag = { $it } | It time: { $mine }s vs { $theirs }s | { $left }s left
+hud-role-hunter = You are the HUNTER: touch the shade, avoid its shadow
+hud-role-shade = You are the SHADE: steer your shadow onto the hunter
+hud-daily = DAILY { $date }  |  Time { $time }s  |  Best { $best }
+hud-puzzle-trap = Trap the dummy { $done }/{ $count }
+hud-puzzle-survive = Survive!
+hud-puzzle = LEVEL { $level }/{ $levels }: { $name }  |  { $goal }  |  { $time }s
+hud-round = { $mode } - { $arena } - Round { $round }
+hud-steer-own-shadow = You steer: your shadow
+hud-steer-own-character = You steer: your character
+hud-steer-shadow = You steer: { $name }'s shadow
+hud-steer-character = You steer: { $name }'s character
+hud-steer-scripted = { $steer } (scripted)
+hud-inverse-endgame = ⚡ INVERSE ENDGAME! ⚡
+hud-inverse-active = ⚡ INVERSE MODE! ⚡ ({ $time }s)
+hud-inverse-incoming = Inverse incoming ({ $time }s)
+hud-control-ready = Shadow Control - { $key } to INVERT!
+hud-control = Shadow Control
+hud-control-endgame = Shadow Control (inverse at { $traps } traps)
+hud-control-unknown = Shadow Control (??)
+hud-control-timer = Shadow Control ({ $time }s)
+hud-schedule = { $schedule } inverse schedule
+hud-triggered-by-you = triggered by you
+hud-triggered-by = triggered by { $name }
+hud-danger = DANGER
+hud-slowmo = SLOW-MO
+hud-handicap = HC: { $speed }x speed, { $trap }x trap, +{ $traps }
+tag-hunter = HUNTER
+tag-it = IT
+frozen = FROZEN { $time }
+controlled = CONTROLLED!
+steal-done = SHADOW STOLEN!
+steal-victim = YOUR SHADOW WAS STOLEN!
+
+## Controls panel (F1)
+controls-title = CONTROLS:
+controls-move-opponent = { $keys }/Arrows → Move opponent's shadow/character
+controls-move-mirror = { $keys }/Arrows → Move YOURSELF (your shadow mirrors you)
+controls-move-echo = { $keys }/Arrows → Move YOURSELF (your shadow retraces your path)
+controls-swap = { $key } → Swap YOUR position with YOUR shadow
+controls-goal = GOAL → Trap opponent { $count } times to win!
+controls-keys = { $inverse } → Inverse (full meter)    { $freeze } → Freeze ({ $freezes } left)    Y/N → Rematch vote    P → Pause    Hold F → Forfeit
+controls-hint = F1 → Controls
+
+## Rounds and callouts
+round-trapped = { $name } TRAPPED!
+round-scores = Trapped: { $first } { $first_score } / { $max }  -  { $second } { $second_score } / { $max }
+round-next = Next round in { $seconds }...
+round-get-ready = Get ready!
+round-number = Round { $round }
+round-go = GO!
+clash-title = CLASH!
+clash-detail = Both trapped at once - no score
+streak-double = DOUBLE TRAP!
+streak-triple = TRIPLE TRAP!
+streak-dominating = DOMINATING!
+streak-unstoppable = UNSTOPPABLE!
+streak-callout = { $name } { $streak } x{ $count }
+streak-bonus = +1 BONUS TRAP
+inverse-countdown = INVERSE IN { $seconds }!
+afk-you = You are AFK! Move to stay in the match ({ $seconds }s)
+afk-opponent = { $name } is AFK - forfeit in { $seconds }s
+forfeit-holding = Forfeiting... keep holding F
+pause-title = PAUSED
+pause-by-you = Paused by You
+pause-by = Paused by { $name }
+pause-resuming = Resuming in { $seconds }...
+pause-resume-hint = Press P to resume
+
+## Scoreboard (Tab) and killcam
+scoreboard-title = SCOREBOARD
+scoreboard-player = Player
+scoreboard-dealt = Traps dealt
+scoreboard-received = Trapped
+scoreboard-swaps = Swaps
+scoreboard-you = { $name } (you)
+scoreboard-offline = offline
+scoreboard-footer = Ping: { $ping }    Series: { $first } - { $second }
+killcam-title = KILLCAM - { $name }
+
+## Game over
+win-title = { $name } WINS!
+win-forfeit = { $name } forfeited!
+win-trapped = { $name } was trapped too many times!
+win-tag = { $name } spent the least time being it!
+win-hill = { $name } ruled the hill!
+win-zones = { $name } held the zones!
+rematch-waiting = waiting...
+rematch-wants = wants a rematch
+rematch-declined = declined
+rematch-asked = Opponent wants a rematch! Y = accept, N = decline
+rematch-prompt = Rematch? Y/R = accept, N = decline
+rematch-votes = You: { $you }    Opponent: { $opponent }
+rematch-replay-hint = V = watch the final moments, J = export a match report
+rematch-report-hint = J = export a match report
+report-exported = Match report saved to { $path }
+report-signed = Signed match report saved to { $path }
+rematch-heatmap-hint = H = heatmap of where everyone spent the game
+heatmap-both = Where both players spent the game (X = trapped there)
+heatmap-player = Where { $name } spent the game (X = trapped there)
+heatmap-hint = H = next view
+replay-title = REPLAY
+replay-hint = V or Esc to go back, I to show or hide the inset view
+replay-camera-hint = Drag or { $keys } to pan, wheel to zoom, 1 / 2 to follow a player, 0 for the whole arena
+replay-following = Following { $name }
+replay-free = Free camera
+ghost-label = BEST
+ghost-loaded = Racing your best run: { $time }s (G hides it)
+ghost-new-best = New best run: { $time }s! It's your ghost from now on
+ghost-shown = Ghost shown
+ghost-hidden = Ghost hidden
+desync-resync = Out of sync with the host, resyncing
+camera-dynamic = Camera: follows close chases
+camera-fixed = Camera: whole arena
+pip-title = { $name }'s view
+
+## Single player
+puzzle-campaign-complete = Campaign complete! Y to play it again
+puzzle-level-cleared = Level { $level } cleared! Y for the next level
+puzzle-out-of-time = Out of time! Y to retry
+puzzle-trapped = You got trapped! Y to retry
+tutorial-steer-title = STEP 1/4: STEER
+tutorial-steer-1 = Your keys ({ $keys }/arrows) move the DUMMY's shadow, not you. Steer it around!
+tutorial-steer-2 = Careful: its yellow ring is what traps YOU, so keep it away from yourself.
+tutorial-swap-title = STEP 2/4: SWAP
+tutorial-swap-1 = Press { $swap } to swap places with your own shadow.
+tutorial-swap-2 = Swapping is how you dodge, and how you drop your shadow somewhere new.
+tutorial-inverse-title = STEP 3/4: INVERSE MODE
+tutorial-inverse-1 = Your inverse meter is full: press { $inverse }.
+tutorial-inverse-2 = After a 3 second warning you steer the dummy's CHARACTER, and it steers yours.
+tutorial-inverse-active-1 = You're steering the dummy itself now!
+tutorial-inverse-active-2 = Push it around until inverse mode wears off.
+tutorial-inverse-wait-1 = Here it comes...
+tutorial-inverse-wait-2 = Watch the countdown: inverse mode starts at zero.
+tutorial-trap-title = STEP 4/4: TRAP
+tutorial-trap-1 = The dummy walks right past you. Press { $swap } so your shadow lands where you stood,
+tutorial-trap-2 = then let the dummy walk into your shadow to trap it!
+tutorial-complete = Tutorial complete! Y to run it again
+tutorial-trapped = Trapped too often! Y to pick up where you left off
+
+## Event log, notifications and speech
+event-you = You
+event-connected = { $name } connected
+event-disconnected = { $name } disconnected
+event-reconnected = { $name } is back
+event-inverse-soon = Inverse mode in { $seconds }s
+event-inverse-started = Inverse mode started
+event-inverse-ended = Inverse mode ended
+event-trapped-you = { $name } trapped you, { $score } of { $max }
+event-you-trapped = You trapped { $name }, { $score } of { $max }
+event-match-point = Match point
+event-clash = Clash, both trapped, no score
+event-forfeited = { $name } forfeited
+event-you-won = You won the match
+event-won = { $name } won the match
+event-packet-loss = High packet loss ({ $percent }%)
+event-connection-recovered = Connection recovered
+event-chat-hazard = Chat voted: a hazard pulls at the arena!
+event-chat-shrink = Chat voted: trap radius shrinks for { $seconds } seconds!
+chat-vote = Chat vote: !hazard { $hazard }  !shrink { $shrink }  ({ $seconds }s)
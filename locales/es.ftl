@@ -0,0 +1,408 @@
+# Shadow Swap UI strings, Spanish. Anything missing here falls back to English (en.ftl).
+# Format: `message-id = text`, one per line. `{ $name }` is filled in by the game; keep those as they are.
+
+language-name = Español
+on = Sí
+off = No
+
+## Player colors
+color-green = Verde
+color-red = Rojo
+color-blue = Azul
+color-purple = Morado
+color-teal = Turquesa
+color-pink = Rosa
+color-gold = Oro
+color-silver = Plata
+color-bronze = Bronce
+color-lime = Lima
+color-orange = Naranja
+color-cyan = Cian
+
+## Match rules
+mode-classic = Clásico
+mode-capture-zones = Captura de zonas
+mode-tag = Pilla-pilla
+mode-king-of-the-hill = Rey de la colina
+mode-hunter-vs-shade = Cazador contra sombra
+schedule-meter = Medidor de carga
+schedule-fixed = Fijo
+schedule-random = Aleatorio
+schedule-escalating = Creciente
+schedule-endgame = Final de partida
+shadows-opponent = Controladas por el rival
+shadows-mirror = Espejo
+shadows-echo = Eco
+arena-open = Abierta
+arena-portals = Portales
+arena-vortex = Vórtice
+
+## Match setup
+setting-who-you = ti
+setting-who-opponent = el rival
+setting-mode = Modo de juego
+setting-hunter = Caza primero
+setting-hunter-me = Yo
+setting-hunter-opponent = El rival
+setting-schedule = Calendario de inversión
+setting-shadows = Sombras
+setting-echo-delay = Retardo del eco
+setting-arena = Arena
+setting-speed = Velocidad ({ $who })
+setting-trap-radius = Radio de trampa contra { $who }
+setting-preloaded-traps = Trampas contra { $who } al empezar
+setting-afk-timeout = Tiempo de inactividad
+setting-tether = Correa de sombra
+setting-wrap = Bordes conectados
+setting-steal = Robo de sombra
+setting-fog = Niebla de guerra
+setting-streak-bonus = Trampa extra por { $count } seguidas
+
+## Settings
+palette-picked = Colores elegidos
+palette-red-green = Apta rojo-verde (azul / naranja)
+palette-blue-yellow = Apta azul-amarillo (carmesí / turquesa)
+palette-monochrome = Monocromo
+announce-log = Registro de eventos
+announce-speech = Registro + voz
+pref-language = Idioma
+pref-theme = Tema
+pref-window = Ventana
+pref-resolution = Resolución
+window-windowed = En ventana
+window-fullscreen = Pantalla completa
+window-borderless = Pantalla completa sin bordes
+resolution = { $width } x { $height }
+resolution-monitor = Tamaño del monitor
+pref-palette = Colores de los jugadores
+pref-high-contrast = Alto contraste
+pref-reduced-motion = Movimiento reducido
+pref-announce = Anunciar eventos
+pref-hud-scale = Tamaño del HUD
+pref-graphics = Gráficos
+pref-master-volume = Volumen general
+pref-music-volume = Volumen de la música
+pref-sfx-volume = Volumen de los efectos
+pref-announcer = Locutor
+muted = Silenciado
+muted-hint = Silenciado (Ctrl+M)
+graphics-low = Bajos
+graphics-high = Altos (resplandor)
+
+## Main menu
+menu-host = Crear partida
+menu-join = Unirse a una partida
+menu-tutorial = Tutorial (un jugador)
+menu-daily = Reto diario (un jugador)
+menu-puzzle = Campaña de puzles (un jugador)
+menu-history = Historial de partidas
+menu-leaderboard = Clasificación
+menu-settings = Ajustes
+menu-profile = Perfil: { $name }, { $color }
+menu-profile-guest = Invitado: { $name }, { $color }
+menu-profile-unset = Invitado: (sin nombre), { $color }
+hint-main-menu = Arriba/Abajo + Enter (o un número) para elegir, Esc para salir
+setup-title = CONFIGURAR PARTIDA
+setup-start = Crear partida en el puerto { $port }
+hint-setup = Arriba/Abajo para elegir, Izq./Der. para cambiar, Enter en Crear, Esc para volver
+join-address = Dirección IP del anfitrión:
+join-same-computer = Mismo ordenador: 127.0.0.1
+join-local-network = Red local: la IP local del anfitrión (p. ej. 192.168.1.31)
+hint-join = Escribe la dirección, Enter para conectar, Esc para volver
+name-prompt = Tu nombre:
+name-explainer = Aparece sobre tu personaje y en el HUD de tu rival
+name-color = Color: < { $color } >
+hint-name = Escribe tu nombre, Izq./Der. para el color, Arriba/Abajo para más, Enter o Esc al terminar
+profile-controls = Teclas
+profile-cosmetics = Aspecto
+cosmetics-title = ASPECTO
+cosmetics-trail = Estela
+cosmetics-swap = Efecto de cambio
+cosmetics-achievements = Logros: { $earned } de { $total }
+cosmetics-unlocks = { $goal } - desbloquea { $reward }
+cosmetic-trail = la estela { $name }
+cosmetic-swap = el efecto de cambio { $name }
+cosmetics-colors = los colores { $set }
+colors-veteran = metálicos
+colors-marathon = neón
+hint-cosmetics = Arriba/Abajo para elegir, Izq./Der. para cambiar, Esc para volver
+hint-cosmetics-guest = Los invitados no ganan logros: elige o crea un perfil para desbloquear más
+trail-plain = Simple
+trail-sparks = Chispas
+trail-comet = Cometa
+swap-effect-burst = Estallido
+swap-effect-ring = Anillo
+swap-effect-implode = Implosión
+achievement-first-win = Primera victoria
+achievement-first-win-goal = Gana una partida
+achievement-regular = Habitual
+achievement-regular-goal = Juega 10 partidas
+achievement-trapper = Trampero
+achievement-trapper-goal = Atrapa a tus rivales 25 veces
+achievement-flawless = Impecable
+achievement-flawless-goal = Gana una partida sin que te atrapen
+achievement-veteran = Veterano
+achievement-veteran-goal = Gana 10 partidas
+achievement-marathon = Maratón
+achievement-marathon-goal = Pasa una hora jugando
+achievement-unlocked = ¡Logro: { $name }! Desbloqueado: { $reward }
+profile-switch = Cambiar de perfil
+profiles-title = ¿QUIÉN JUEGA?
+profiles-new = Nuevo perfil
+profiles-guest = Jugar como invitado
+profiles-guest-name = Invitado
+hint-profiles = Arriba/Abajo + Enter para elegir, Supr para borrar un perfil, Esc para volver
+controls-screen-title = TECLAS DE { $name }
+controls-press-key = Pulsa una tecla...
+controls-reset = Restablecer
+hint-controls = Arriba/Abajo para elegir, Enter para cambiar una tecla, Esc para volver
+hint-rebinding = Pulsa la nueva tecla, Esc para cancelar
+action-up = Subir
+action-down = Bajar
+action-left = Izquierda
+action-right = Derecha
+action-swap = Cambiarte con tu sombra
+action-inverse = Modo inverso
+action-freeze = Congelar
+error-profile-exists = Ya hay un perfil llamado { $name }
+error-profile-load = No se pudo cargar el perfil { $name }: { $error }
+error-graphics = No se pudieron preparar los gráficos: { $error }
+settings-title = AJUSTES
+settings-back = Volver
+hint-settings = Arriba/Abajo para elegir, Izq./Der. para cambiar, Esc para volver
+history-title = HISTORIAL DE PARTIDAS
+history-empty = Aún no hay partidas terminadas. ¡Crea una o únete!
+history-summary = { $played } jugadas: { $won } ganadas, { $lost } perdidas
+history-date = Fecha
+history-opponent = Rival
+history-mode = Modo
+history-result = Resultado
+history-traps = Trampas hechas / recibidas
+history-time = Tiempo
+history-won = Ganada
+history-lost = Perdida
+hint-history = Arriba/Abajo para desplazarte, Esc para volver
+leaderboard-title = CLASIFICACIÓN
+leaderboard-filter = < { $mode } >    { $order } (Tab)
+leaderboard-all = Todos los modos
+leaderboard-by-rating = Por puntuación
+leaderboard-by-wins = Por victorias
+leaderboard-empty = Nadie ha terminado aún una partida en este modo
+leaderboard-rank = #
+leaderboard-player = Jugador
+leaderboard-rating = Puntuación
+leaderboard-wins = Victorias
+leaderboard-losses = Derrotas
+leaderboard-win-rate = % de victorias
+hint-leaderboard = Izq./Der. para el modo, Tab para cambiar el orden, Arriba/Abajo para desplazarte, Esc para volver
+error-no-address = Escribe primero la IP del anfitrión
+error-puzzle-levels = No se pudieron cargar los niveles: { $error }
+error-report = No se pudo exportar el informe de la partida: { $error }
+error-port-in-use = El puerto { $port } ya está en uso, quizá por otra partida alojada en él. Elige otro con --port o en el archivo de configuración
+error-resolve = No se pudo resolver el anfitrión { $address }
+error-network = Error de red: { $error }
+crash-reported = El juego falló la última vez, informe guardado en { $path }
+screenshot-saved = Captura guardada en { $path }
+error-screenshot = No se pudo guardar la captura: { $error }
+clip-saved = Últimos 10 segundos guardados en { $path }
+error-clip = No se pudo guardar el clip: { $error }
+failed-title = No se pudo iniciar la partida
+failed-retry = Reintentar
+failed-back = Volver
+hint-failed = Arriba/Abajo + Intro para elegir, Esc para volver
+
+## Connecting
+connect-local-network = Red local: únete a { $ip }
+connect-no-local-ip = Red local: no se pudo detectar la IP de este equipo
+connect-same-computer = Mismo ordenador: únete a 127.0.0.1
+connect-internet = Por internet: redirige el puerto UDP { $port } y comparte tu IP pública
+connect-waiting = Esperando al rival en el puerto { $port }
+connect-no-answer = Sin respuesta. Revisa la dirección y que el anfitrión esté esperando.
+connect-to = Conectando con { $address }
+connect-to-host = Conectando con el anfitrión
+connect-cancel = Cancelar
+connect-cancel-key = (o Esc)
+
+## Lobby
+lobby-title = JUGADORES CONECTADOS
+lobby-players = { $first } (Jugador 1, puntuación { $first_rating }) contra { $second } (Jugador 2, puntuación { $second_rating })
+lobby-inverse = Inversión: { $schedule }
+lobby-shadows = Sombras: { $shadows }
+lobby-streak-bonus = Extra por racha
+lobby-color = Tu color: < { $color } >
+lobby-color-taken = Tu color: < { $color } > (ocupado, usando { $shown })
+lobby-color-hint = Izq./Der. para cambiar
+lobby-color-hint-palette = Izq./Der. para cambiar (aquí se ve como: { $palette })
+lobby-ready = LISTO
+lobby-not-ready = no listo
+lobby-ready-status = Tú: { $you }    Rival: { $opponent }
+lobby-unready-hint = Enter para cancelar
+lobby-ready-hint = Pulsa Enter cuando estés listo
+
+## HUD
+hud-you = { $name } (TÚ)
+hud-trapped = Atrapado: { $score } / { $max }
+hud-opponent-trapped = { $name }: { $score } / { $max }
+hud-zones = Zonas: { $mine } - { $theirs } (gana quien llegue a { $max })
+hud-tag-you-it = ¡TE TOCA PILLAR!
+hud-tag-opponent-it = Pilla el rival
+hud-tag = { $it } | Pillando: { $mine }s contra { $theirs }s | quedan { $left }s
+hud-role-hunter = Eres el CAZADOR: toca a la sombra y esquiva la suya
+hud-role-shade = Eres la SOMBRA: lleva tu sombra hasta el cazador
+hud-daily = DIARIO { $date }  |  Tiempo { $time }s  |  Récord { $best }
+hud-puzzle-trap = Atrapa al muñeco { $done }/{ $count }
+hud-puzzle-survive = ¡Sobrevive!
+hud-puzzle = NIVEL { $level }/{ $levels }: { $name }  |  { $goal }  |  { $time }s
+hud-round = { $mode } - { $arena } - Ronda { $round }
+hud-steer-own-shadow = Controlas: tu sombra
+hud-steer-own-character = Controlas: tu personaje
+hud-steer-shadow = Controlas: la sombra de { $name }
+hud-steer-character = Controlas: el personaje de { $name }
+hud-steer-scripted = { $steer } (guionizado)
+hud-inverse-endgame = ⚡ ¡INVERSIÓN FINAL! ⚡
+hud-inverse-active = ⚡ ¡MODO INVERSO! ⚡ ({ $time }s)
+hud-inverse-incoming = Inversión en camino ({ $time }s)
+hud-control-ready = Control de sombra - ¡{ $key } para INVERTIR!
+hud-control = Control de sombra
+hud-control-endgame = Control de sombra (inversión con { $traps } trampas)
+hud-control-unknown = Control de sombra (??)
+hud-control-timer = Control de sombra ({ $time }s)
+hud-schedule = Calendario de inversión: { $schedule }
+hud-triggered-by-you = activado por ti
+hud-triggered-by = activado por { $name }
+hud-danger = PELIGRO
+hud-slowmo = CÁMARA LENTA
+hud-handicap = HC: { $speed }x velocidad, { $trap }x trampa, +{ $traps }
+tag-hunter = CAZADOR
+tag-it = PILLA
+frozen = CONGELADO { $time }
+controlled = ¡CONTROLADO!
+steal-done = ¡SOMBRA ROBADA!
+steal-victim = ¡TE HAN ROBADO LA SOMBRA!
+
+## Controls panel (F1)
+controls-title = CONTROLES:
+controls-move-opponent = { $keys }/Flechas → Mover la sombra/personaje del rival
+controls-move-mirror = { $keys }/Flechas → Moverte TÚ (tu sombra te imita)
+controls-move-echo = { $keys }/Flechas → Moverte TÚ (tu sombra repite tu camino)
+controls-swap = { $key } → Cambia TU posición con TU sombra
+controls-goal = OBJETIVO → ¡Atrapa al rival { $count } veces para ganar!
+controls-keys = { $inverse } → Inversión (medidor lleno)    { $freeze } → Congelar (quedan { $freezes })    Y/N → Revancha    P → Pausa    Mantén F → Rendirse
+controls-hint = F1 → Controles
+
+## Rounds and callouts
+round-trapped = ¡{ $name } ATRAPADO!
+round-scores = Atrapado: { $first } { $first_score } / { $max }  -  { $second } { $second_score } / { $max }
+round-next = Siguiente ronda en { $seconds }...
+round-get-ready = ¡Preparados!
+round-number = Ronda { $round }
+round-go = ¡YA!
+clash-title = ¡CHOQUE!
+clash-detail = Los dos atrapados a la vez - sin puntos
+streak-double = ¡TRAMPA DOBLE!
+streak-triple = ¡TRAMPA TRIPLE!
+streak-dominating = ¡DOMINANDO!
+streak-unstoppable = ¡IMPARABLE!
+streak-callout = { $name } { $streak } x{ $count }
+streak-bonus = +1 TRAMPA EXTRA
+inverse-countdown = ¡INVERSIÓN EN { $seconds }!
+afk-you = ¡Estás inactivo! Muévete para seguir en la partida ({ $seconds }s)
+afk-opponent = { $name } está inactivo - se rinde en { $seconds }s
+forfeit-holding = Rindiéndote... mantén pulsada F
+pause-title = PAUSA
+pause-by-you = Pausado por ti
+pause-by = Pausado por { $name }
+pause-resuming = Se reanuda en { $seconds }...
+pause-resume-hint = Pulsa P para reanudar
+
+## Scoreboard (Tab) and killcam
+scoreboard-title = MARCADOR
+scoreboard-player = Jugador
+scoreboard-dealt = Trampas hechas
+scoreboard-received = Atrapado
+scoreboard-swaps = Cambios
+scoreboard-you = { $name } (tú)
+scoreboard-offline = sin conexión
+scoreboard-footer = Ping: { $ping }    Serie: { $first } - { $second }
+killcam-title = REPETICIÓN - { $name }
+
+## Game over
+win-title = ¡{ $name } GANA!
+win-forfeit = ¡{ $name } se ha rendido!
+win-trapped = ¡{ $name } fue atrapado demasiadas veces!
+win-tag = ¡{ $name } fue quien menos tiempo pilló!
+win-hill = ¡{ $name } dominó la colina!
+win-zones = ¡{ $name } controló las zonas!
+rematch-waiting = esperando...
+rematch-wants = quiere la revancha
+rematch-declined = no quiere
+rematch-asked = ¡El rival quiere la revancha! Y = aceptar, N = rechazar
+rematch-prompt = ¿Revancha? Y/R = aceptar, N = rechazar
+rematch-votes = Tú: { $you }    Rival: { $opponent }
+rematch-replay-hint = V = ver los últimos momentos, J = exportar un informe de la partida
+rematch-report-hint = J = exportar un informe de la partida
+report-exported = Informe de la partida guardado en { $path }
+report-signed = Informe firmado de la partida guardado en { $path }
+rematch-heatmap-hint = H = mapa de calor de dónde pasó cada uno la partida
+heatmap-both = Dónde pasaron la partida ambos jugadores (X = atrapado ahí)
+heatmap-player = Dónde pasó la partida { $name } (X = atrapado ahí)
+heatmap-hint = H = siguiente vista
+replay-title = REPETICIÓN
+replay-hint = V o Esc para volver, I para mostrar u ocultar la vista pequeña
+replay-camera-hint = Arrastra o { $keys } para mover, rueda para acercar, 1 / 2 para seguir a un jugador, 0 para toda la arena
+replay-following = Siguiendo a { $name }
+replay-free = Cámara libre
+ghost-label = MEJOR
+ghost-loaded = Compites con tu mejor carrera: { $time }s (G la oculta)
+ghost-new-best = ¡Nueva mejor carrera: { $time }s! Desde ahora es tu fantasma
+ghost-shown = Fantasma visible
+ghost-hidden = Fantasma oculto
+desync-resync = Desincronizado con el anfitrión, resincronizando
+camera-dynamic = Cámara: sigue las persecuciones cercanas
+camera-fixed = Cámara: toda la arena
+pip-title = Vista de { $name }
+
+## Single player
+puzzle-campaign-complete = ¡Campaña completada! Y para jugarla otra vez
+puzzle-level-cleared = ¡Nivel { $level } superado! Y para el siguiente
+puzzle-out-of-time = ¡Se acabó el tiempo! Y para reintentar
+puzzle-trapped = ¡Te han atrapado! Y para reintentar
+tutorial-steer-title = PASO 1/4: DIRIGIR
+tutorial-steer-1 = Tus teclas ({ $keys }/flechas) mueven la sombra del MUÑECO, no a ti. ¡Muévela!
+tutorial-steer-2 = Cuidado: su anillo amarillo es lo que TE atrapa, así que aléjalo de ti.
+tutorial-swap-title = PASO 2/4: CAMBIO
+tutorial-swap-1 = Pulsa { $swap } para cambiarte de sitio con tu propia sombra.
+tutorial-swap-2 = Así esquivas, y así dejas tu sombra en otro lugar.
+tutorial-inverse-title = PASO 3/4: MODO INVERSO
+tutorial-inverse-1 = Tu medidor de inversión está lleno: pulsa { $inverse }.
+tutorial-inverse-2 = Tras un aviso de 3 segundos controlas el PERSONAJE del muñeco, y él el tuyo.
+tutorial-inverse-active-1 = ¡Ahora controlas al propio muñeco!
+tutorial-inverse-active-2 = Muévelo hasta que se acabe el modo inverso.
+tutorial-inverse-wait-1 = Ahí viene...
+tutorial-inverse-wait-2 = Mira la cuenta atrás: el modo inverso empieza en cero.
+tutorial-trap-title = PASO 4/4: TRAMPA
+tutorial-trap-1 = El muñeco pasa justo a tu lado. Pulsa { $swap } para que tu sombra quede donde estabas,
+tutorial-trap-2 = ¡y deja que el muñeco entre en tu sombra para atraparlo!
+tutorial-complete = ¡Tutorial completado! Y para repetirlo
+tutorial-trapped = ¡Demasiadas trampas! Y para seguir donde lo dejaste
+
+## Event log, notifications and speech
+event-you = Tú
+event-connected = { $name } se ha conectado
+event-disconnected = { $name } se ha desconectado
+event-reconnected = { $name } ha vuelto
+event-inverse-soon = Modo inverso en { $seconds }s
+event-inverse-started = Empieza el modo inverso
+event-inverse-ended = Termina el modo inverso
+event-trapped-you = { $name } te ha atrapado, { $score } de { $max }
+event-you-trapped = Has atrapado a { $name }, { $score } de { $max }
+event-match-point = Punto de partido
+event-clash = Choque, los dos atrapados, sin puntos
+event-forfeited = { $name } se ha rendido
+event-you-won = Has ganado la partida
+event-won = { $name } ha ganado la partida
+event-packet-loss = Pérdida de paquetes alta ({ $percent }%)
+event-connection-recovered = Conexión recuperada
+event-chat-hazard = El chat votó: ¡un peligro atrae a todos en la arena!
+event-chat-shrink = El chat votó: ¡el radio de captura se encoge durante { $seconds } segundos!
+chat-vote = Votación del chat: !hazard { $hazard }  !shrink { $shrink }  ({ $seconds } s)
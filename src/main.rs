@@ -1,18 +1,841 @@
 use raylib::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::net::{UdpSocket, SocketAddr};
-use std::time::{Duration, Instant};
+use std::collections::{HashMap, VecDeque};
+use std::net::{UdpSocket, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{LazyLock, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 const SCREEN_WIDTH: i32 = 1200;
 const SCREEN_HEIGHT: i32 = 800;
 const PLAYER_SIZE: f32 = 20.0;
 const SHADOW_SIZE: f32 = 18.0;
-const PLAYER_SPEED: f32 = 200.0;
-const INVERSE_DURATION: f32 = 5.0; // seconds
-const INVERSE_COOLDOWN: f32 = 10.0; // seconds between inversions
-const PORT: u16 = 5555;
-const TRAP_RADIUS: f32 = 50.0;
+const INVERSE_RANDOM_GAP: (f32, f32) = (6.0, 16.0); // random schedule: range of seconds between inversions
+const INVERSE_ESCALATION: f32 = 1.5; // escalating schedule: extra seconds of inverse per round
+const INVERSE_MAX_DURATION: f32 = 12.0;
+const INVERSE_CHARGE_DISTANCE: f32 = 4000.0; // pixels of steering that fill the inverse meter
+const NEAR_MISS_CHARGE: f32 = 0.25; // meter gained per second spent dodging just outside a trap
+const NEAR_MISS_RANGE: f32 = 1.5; // near-miss band, as a multiple of the trap radius
+const DANGER_RANGE: f32 = 3.0; // danger meter starts rising within this multiple of the trap radius
 const WIN_SCORE: i32 = 3; // First to get trapped 3 times loses
+const STREAK_BONUS_AT: u32 = 3; // traps in a row that earn a bonus trap (when enabled)
+const STREAK_CALLOUT_TIME: f32 = 2.5; // seconds a streak callout stays up
+const CLASH_TOLERANCE: f32 = 0.02; // trap depths this close count as a simultaneous clash
+const CLASH_FX_TIME: f32 = 1.5; // seconds the clash callout stays up
+const ZONE_RADIUS: f32 = 70.0;
+const ZONE_TICK: f32 = 1.0; // seconds a zone must be held per point
+const ZONE_WIN_SCORE: i32 = 10; // First to 10 zone points wins (Capture Zones)
+const CAPTURE_ZONES: [(f32, f32); 3] = [(0.5, 0.35), (0.25, 0.65), (0.75, 0.65)]; // fractions of the screen
+const TAG_MATCH_TIME: f32 = 90.0; // seconds per Tag match
+const TAG_IMMUNITY: f32 = 1.5; // seconds before the tag can be passed back
+const HILL_RADIUS: f32 = 90.0;
+const HILL_WIN_TIME: f32 = 30.0; // seconds on the hill needed to win (King of the Hill)
+const HUNTER_SPEED_MULT: f32 = 1.35; // Hunter moves faster to make up for having no shadow
+const HUNTER_CONTACT_RADIUS: f32 = PLAYER_SIZE * 2.0; // Hunter traps by touching the shade
+const ECHO_DELAY: f32 = 2.0; // default seconds an echo shadow lags behind its owner
+const DAILY_BEST_FILE: &str = "daily_challenge.txt"; // in the data folder: "<day> <best seconds>" per line
+const DAILY_WAYPOINTS: usize = 8; // points in each of the bot's scripted loops
+const DAILY_BOT_SPEED: f32 = 0.8; // the bot's speed, as a fraction of the player speed
+const STATS_FILE: &str = "stats.txt"; // in the data folder: every finished match, see `MatchRecord`
+const PROFILES_DIR: &str = "profiles"; // in the data folder: one file per player, see `Profile`
+const ELO_START: f32 = 1000.0; // rating of a name with no recorded matches
+const ELO_K: f32 = 32.0; // most a single result can move a rating
+const HISTORY_ROWS: usize = 12; // matches shown at once on the history screen
+const LEVELS_DIR: &str = "levels"; // puzzle level files, played in file name order
+const SPRITES_DIR: &str = "assets/sprites"; // optional PNG sprites; missing ones are drawn as shapes
+const MUSIC_DIR: &str = "assets/music"; // optional looping music: base.ogg, and intense.ogg layered over it
+const SOUNDS_DIR: &str = "assets/sounds"; // optional sound effects, one file per `Sfx`
+const ANNOUNCER_DIR: &str = "assets/announcer"; // optional announcer voice pack, one file per `Line`
+const ANNOUNCER_STALE: f32 = 2.0; // seconds a queued announcer line waits before it's dropped as old news
+const SOUND_FAR: f32 = 1000.0; // an effect this far from our character plays at its quietest
+const SOUND_MIN_GAIN: f32 = 0.3; // ...which is this fraction of its full volume
+const SOUND_MAX_PAN: f32 = 0.4; // how far off center an effect at the side of the arena pans (0.5 is fully)
+const MUSIC_FADE: f32 = 1.5; // seconds the intense music stem takes to fade fully in or out
+const VOLUME_STEP: f32 = 0.1; // volume settings change by this much per key press
+const TUTORIAL_STEER: f32 = 600.0; // tutorial: pixels of shadow steering that complete the first step
+const KILLCAM_SAMPLE: f32 = 1.0 / 30.0; // seconds between recorded killcam frames
+const KILLCAM_FRAMES: usize = 120; // ~4 seconds of history
+const REPLAY_SAMPLE: f32 = 1.0 / 20.0; // seconds between frames in a saved match replay
+const REPLAYS_DIR: &str = "replays"; // in the data folder: one `<unix time>.replay` per match, see `Replay`
+const REPLAY_MAGIC: &[u8; 4] = b"SSRP"; // first bytes of every replay file
+const CLIPS_DIR: &str = "clips"; // in the data folder: `<unix millis>.replay` and `.gif` of each F9 clip
+const CLIP_SECONDS: f32 = 10.0; // how much of the match before F9 a clip holds
+const CLIP_SCALE: i32 = 2; // clip GIFs are 1/CLIP_SCALE of the canvas size
+const REPLAY_VERSION: u16 = 2; // bumped whenever `Replay` changes shape
+const GHOSTS_DIR: &str = "ghosts"; // in the data folder: the best winning replay of each challenge, see `Challenge`
+const MAX_REPLAYS: usize = 50; // older replay files are deleted past this many
+const REPORTS_DIR: &str = "reports"; // in the data folder: `<unix time>-<game number>.json` match reports, exported with J after a match
+const SCREENSHOTS_DIR: &str = "screenshots"; // in the data folder: `<unix millis>.png` frames captured with F12
+const METRICS_INTERVAL: f32 = 10.0; // seconds between rows of the --metrics CSV
+const METRICS_MAX_BYTES: u64 = 1_000_000; // the --metrics CSV rolls over to `<file>.1` past this size
+const METRICS_HEADER: &str = "unix_time,tick_ms_avg,tick_ms_max,packets_in_per_s,packets_out_per_s,loss_pct,ping_ms,peers,desyncs,matches";
+const CHAT_VOTE_WINDOW: f32 = 20.0; // seconds of play a --twitch vote stays open; the winner fires as it closes
+const CHAT_EVENT_TIME: f32 = 10.0; // seconds a chat-voted hazard or shrink lasts
+const CHAT_SHRINK: f32 = 0.6; // trap radius multiplier while chat's shrink is on
+const TWITCH_IRC: &str = "irc.chat.twitch.tv:6667";
+const TWITCH_RETRY: u64 = 10; // seconds before reconnecting to Twitch chat
+const OVERLAY_INTERVAL: f32 = 0.1; // seconds between --overlay state messages
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"; // RFC 6455's handshake suffix
+const INPUTS_MAGIC: &[u8; 4] = b"SSIN"; // first bytes of an input recording, see `InputLog`
+const CRASHES_DIR: &str = "crashes"; // in the data folder: `<unix time>.txt` crash reports, see `CrashContext`
+const CRASH_NOTICE: &str = "crashed"; // in the data folder: the last crash report's path, until the next start shows it
+const CRASH_EVENTS: usize = 30; // the newest game events a crash report lists
+const NET_DUMP_MAGIC: &[u8; 4] = b"SSND"; // first bytes of a --net-dump capture, see `NetDump`
+const HEATMAP_CELL: i32 = 40; // pixels per side of a post-match heatmap cell
+const HEATMAP_COLS: usize = (SCREEN_WIDTH / HEATMAP_CELL) as usize;
+const HEATMAP_ROWS: usize = (SCREEN_HEIGHT / HEATMAP_CELL) as usize;
+const KILLCAM_ZOOM: f32 = 0.6; // killcam view scale (smaller shows more of the arena)
+const CAMERA_CHASE_RANGE: f32 = 220.0; // gap to the trap radius at which the play camera starts closing in
+const CAMERA_MAX_ZOOM: f32 = 1.6; // play camera zoom at the edge of a trap radius
+const CAMERA_EASE: f32 = 2.5; // how quickly the play camera catches up, per second
+const FREE_CAMERA_ZOOM: (f32, f32) = (0.5, 4.0); // replay camera zoom range
+const FREE_CAMERA_ZOOM_STEP: f32 = 1.15; // zoom factor per mouse wheel notch
+const FREE_CAMERA_FOLLOW_ZOOM: f32 = 2.0; // zoom when snapping to a player
+const FREE_CAMERA_PAN_SPEED: f32 = 700.0; // screen pixels per second with the pan keys
+const ROUND_COUNTDOWN: f32 = 3.0; // seconds counted down into the next round
+const CONFIG_FILE: &str = "shadow-swap.toml"; // in the config folder: tunables, written with the defaults on first run
+const HOT_RELOAD_POLL: f32 = 0.5; // seconds between checks for edited config, theme, volume and level files
+const MAX_NAME_LEN: usize = 16; // characters kept from a display name
+const PLAYER_COLORS: [&str; 6] = [ // message ids of the names, and the theme keys of the colors
+    "color-green",
+    "color-red",
+    "color-blue",
+    "color-purple",
+    "color-teal",
+    "color-pink",
+];
+/// Player colors past PLAYER_COLORS, unlocked in sets by achievements: message id of the name,
+/// the achievement and the color. Themes don't restyle these.
+const EXTRA_COLORS: [(&str, Achievement, Color); 6] = [
+    ("color-gold", Achievement::Veteran, Color::new(240, 190, 40, 255)),
+    ("color-silver", Achievement::Veteran, Color::new(200, 205, 215, 255)),
+    ("color-bronze", Achievement::Veteran, Color::new(205, 127, 50, 255)),
+    ("color-lime", Achievement::Marathon, Color::new(170, 255, 0, 255)),
+    ("color-orange", Achievement::Marathon, Color::new(255, 120, 0, 255)),
+    ("color-cyan", Achievement::Marathon, Color::new(0, 240, 255, 255)),
+];
+const GO_FLASH_TIME: f32 = 0.8; // seconds "GO!" stays up once the pre-match countdown ends
+const HUD_TOP: f32 = 140.0; // the title bar covers the arena above this
+const CONTROLS_PANEL_HEIGHT: f32 = 120.0; // the F1 controls panel covers this much of the arena's bottom
+const THREAT_ARROW_RANGE: f32 = 400.0; // threat indicator: distance past the trap radius where it turns fully calm
+const DEBUG_FRAMES: usize = 120; // frame times kept for the F3 overlay's graph
+const CONSOLE_LINES: usize = 12; // lines of history the dev console shows
+const USAGE: &str = "usage: rayq [options]\n\
+    \x20 --host                     host a game with the default rules\n\
+    \x20 --join <address>           join the game hosted at <address>\n\
+    \x20 --bot [easy|normal|hard]   play an offline match against a bot\n\
+    \x20 --port <port>              network port, over the config file's\n\
+    \x20 --name <name>              display name, as a guest\n\
+    \x20 --profile <name>           play as this profile, creating it if needed\n\
+    \x20 --lang <code>              UI language, e.g. es\n\
+    \x20 --theme <name>             color theme\n\
+    \x20 --fullscreen               start in fullscreen\n\
+    \x20 --config <path>            config file to use instead of shadow-swap.toml\n\
+    \x20 --dev                      enable the developer console\n\
+    \x20 --portable                 keep settings and progress beside the game\n\
+    \x20 --inspect-replay <file>    print what a saved replay holds, then exit\n\
+    \x20 --record-inputs <file>     with --bot, record the game's inputs to <file>\n\
+    \x20 --check-inputs <file>      play an input recording headlessly and check it ends the same\n\
+    \x20 --metrics <file>           with --host, append runtime metrics to a CSV every 10 seconds\n\
+    \x20 --net-dump <file>          with --host or --join, write every datagram sent or received to <file>\n\
+    \x20 --print-dump <file>        print a --net-dump capture with its messages decoded, then exit\n\
+    \x20 --overlay <port>           stream match state as JSON over a WebSocket on localhost:<port>\n\
+    \x20 --status <port>            with --host, answer GET /status on <port> with the match as JSON\n\
+    \x20 --admin <port>             with --host, take admin commands over TCP on <port>\n\
+    \x20 --admin-key <file>         the key admin connections must send first, in plain text\n\
+    \x20 --feed-delay <seconds>     hold --overlay and --status back by <seconds>\n\
+    \x20 --tournament <key file>    export a signed match report when each match ends\n\
+    \x20 --verify-report <file>     with --tournament, check a report's signature, then exit\n\
+    \x20 --twitch <channel>         let the channel's chat vote for arena events in games you host\n\
+    \x20 --help                     show this help";
+const CONSOLE_HELP: &str = "commands: set <trap_radius|speed|echo_delay|afk_timeout|tether> <number>\n\
+    set <wrap|fog|steal|streak> <on|off>\n\
+    bot add [easy|normal|hard], bot remove\n\
+    netsim loss <0-1>, netsim off\n\
+    state dump";
+const ADMIN_HELP: &str = "list: the match, its client and the arena rotation\n\
+    kick: drop the client, who forfeits a match being played\n\
+    pause, resume\n\
+    rotate <open|portals|vortex>...: arenas each rematch moves through; no arenas turns it off\n\
+    shutdown: quit once no round is being played";
+const ADMIN_AUTH_TIMEOUT: f32 = 5.0; // seconds an admin connection has to send its key
+const ADMIN_MAX_PENDING: usize = 4; // admin connections waiting to authenticate; more are refused
+const TOAST_TIME: f32 = 4.0; // seconds a corner notification stays up
+const TOAST_FADE: f32 = 0.3; // seconds a notification takes to fade in and out
+const MAX_TOASTS: usize = 4; // older notifications are dropped beyond this
+const PING_TIMEOUT: f32 = 2.0; // seconds before an unanswered ping counts as lost
+const PEER_TIMEOUT: f32 = 3.0; // seconds of silence before the opponent counts as disconnected
+const EVENT_LOG_LINES: usize = 5; // announcements kept in the on-screen event log
+const LOSS_SAMPLES: usize = 10; // recent pings the packet loss estimate looks at
+const DESYNC_GRACE: f32 = 1.0; // seconds state hashes must disagree before it's a desync, not just messages in flight
+const LOSS_WARNING: f32 = 0.3; // fraction of lost pings that raises a packet loss warning
+const RESUME_COUNTDOWN: f32 = 3.0; // seconds counted down before a pause ends
+const FREEZE_DURATION: f32 = 1.5; // seconds a frozen character can't move
+const FREEZE_CHARGES: u8 = 2; // freezes each player gets per game
+const SLOWMO_RANGE: f32 = 1.2; // slow-mo kicks in within this multiple of the trap radius
+const SLOWMO_SCALE: f32 = 0.5; // simulation speed during slow-mo
+const SLOWMO_HOLD: f32 = 0.4; // real seconds slow-mo lingers after the danger passes
+const SLOWMO_EASE: f32 = 4.0; // how fast the time scale eases in and out, per real second
+const SPAWN_PROTECTION: f32 = 2.0; // seconds of trap immunity once play (re)starts
+const FORFEIT_HOLD_TIME: f32 = 2.0; // seconds F must be held to forfeit
+const AFK_TIMEOUT: f32 = 45.0; // default seconds without input/packets before a player is flagged AFK
+const AFK_GRACE: f32 = 15.0; // seconds an AFK player has to come back before forfeiting
+const TELEPORTER_RADIUS: f32 = 28.0;
+const TELEPORT_COOLDOWN: f32 = 2.0; // seconds before the same player can teleport again
+const TELEPORT_FX_TIME: f32 = 0.6; // seconds the teleport flash lasts
+const STEAL_RANGE: f32 = 1.6; // shadow steal: how close to the opponent's shadow SPACE steals it, as a multiple of the trap radius
+const STEAL_FX_TIME: f32 = 1.2; // seconds the steal streak and callout last
+const TRAIL_SAMPLE: f32 = 1.0 / 60.0; // seconds between motion trail points
+const TRAIL_POINTS: usize = 12; // motion trail length, in points
+const TRAIL_SPEED: f32 = 300.0; // speed at which a motion trail shows at full strength
+const TRAIL_BREAK: f32 = 80.0; // a jump this long (swap, teleport, wrap) starts the trail over
+const SWAP_FX_TIME: f32 = 0.25; // seconds a character squashes after landing a swap
+const BLOB_POINTS: usize = 24; // outline points of a squashed character
+const SHADOW_FAR: f32 = 500.0; // an untethered shadow this far from its owner is drawn fully thinned out
+const MAX_PARTICLES: usize = 512; // particle pool size; the oldest particle is reused when it's full
+const PARTICLE_DRAG: f32 = 2.5; // share of particle speed lost per second
+const WELL_RADIUS: f32 = 170.0; // reach of a gravity well's pull
+const FOG_VISION: f32 = 220.0; // fog of war: how far your character and shadow can see
+const WELL_PULL: f32 = 110.0; // pull speed at the center of a well, fading to 0 at its edge
+const HUD_SCALE_RANGE: (f32, f32) = (0.75, 1.5); // HUD scale setting limits
+const HUD_SCALE_STEP: f32 = 0.05;
+const RESOLUTIONS: [(i32, i32); 6] = [(1200, 800), (1280, 720), (1440, 900), (1600, 900), (1920, 1080), (2560, 1440)];
+const LOCALES_DIR: &str = "locales"; // translations, one `<language>.ftl` file each
+const ENGLISH_FTL: &str = include_str!("../locales/en.ftl"); // built in, and the fallback for missing messages
+const THEMES_DIR: &str = "themes"; // color themes, one `<name>.theme` file each
+const DARK_THEME: &str = include_str!("../themes/dark.theme"); // built in, and the default
+
+/// A translation resource: message ids and their templates, from a small subset of Fluent (FTL).
+/// One `id = text` per line, `#` starts a comment line, and `{ $name }` is filled in from arguments.
+struct Locale {
+    language: String,
+    messages: HashMap<String, String>,
+}
+
+impl Locale {
+    fn parse(language: &str, text: &str) -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((id, template)) = line.split_once('=') else {
+                return Err(format!("line {}: expected 'id = text'", n + 1));
+            };
+            let id = id.trim();
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                return Err(format!("line {}: '{}' is not a message id", n + 1, id));
+            }
+            if messages.insert(id.to_string(), template.trim().to_string()).is_some() {
+                return Err(format!("line {}: '{}' is defined twice", n + 1, id));
+            }
+        }
+        Ok(Locale { language: language.to_string(), messages })
+    }
+
+    fn english() -> Self {
+        Self::parse("en", ENGLISH_FTL).expect("built-in English strings parse")
+    }
+
+    /// Loads `<language>.ftl` from LOCALES_DIR; English is always available, even without the folder.
+    fn load(language: &str) -> Result<Self, String> {
+        if language == "en" {
+            return Ok(Self::english());
+        }
+        let path = format!("{}/{}.ftl", LOCALES_DIR, language);
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(language, &text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Languages that can be picked: English plus every `.ftl` file in LOCALES_DIR, sorted.
+    fn available() -> Vec<String> {
+        let mut languages = vec!["en".to_string()];
+        if let Ok(entries) = std::fs::read_dir(LOCALES_DIR) {
+            for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+                if path.extension().is_some_and(|ext| ext == "ftl")
+                    && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+                    && stem != "en"
+                {
+                    languages.push(stem.to_string());
+                }
+            }
+        }
+        languages[1..].sort();
+        languages
+    }
+}
+
+static ENGLISH: LazyLock<Locale> = LazyLock::new(Locale::english);
+static LOCALE: LazyLock<RwLock<Locale>> = LazyLock::new(|| RwLock::new(Locale::english()));
+
+/// Switches every UI string to another language.
+fn set_language(language: &str) -> Result<(), String> {
+    let locale = Locale::load(language)?;
+    *LOCALE.write().unwrap_or_else(|e| e.into_inner()) = locale;
+    Ok(())
+}
+
+fn language() -> String {
+    LOCALE.read().unwrap_or_else(|e| e.into_inner()).language.clone()
+}
+
+/// Looks up a message in the current language, falling back to English and then to the id itself,
+/// and fills in its `{ $name }` placeables. Use through `tr!`.
+fn translate(id: &str, args: &[(&str, String)]) -> String {
+    let locale = LOCALE.read().unwrap_or_else(|e| e.into_inner());
+    let Some(template) = locale.messages.get(id).or_else(|| ENGLISH.messages.get(id)) else {
+        return id.to_string();
+    };
+    let mut text = template.clone();
+    for (name, value) in args {
+        text = text.replace(&format!("{{ ${} }}", name), value);
+    }
+    text
+}
+
+/// `tr!("hud-trapped", score = 2, max = WIN_SCORE)`: a UI string in the current language.
+macro_rules! tr {
+    ($id:expr) => {
+        translate($id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        translate($id, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// Colors the menus, HUD frame and arena are drawn with, from a `<name>.theme` file: one
+/// `key = #rrggbb` or `key = #rrggbbaa` per line, `#` starts a comment line. Every key must be there.
+/// Accessibility palettes still override the player colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Theme {
+    background: Color, // Menus and lobby
+    arena: Color,
+    grid: Color,
+    divider: Color, // The arena's center line
+    accent: Color, // Big titles and frames
+    title: Color, // Titles on the HUD's dark bars and panels
+    text: Color, // Menu text
+    hint: Color, // Key hints and secondary text
+    selected: Color, // Selected menu row
+    unselected: Color,
+    row: Color, // Bar behind the selected menu row
+    field: Color, // Text input boxes
+    meter: Color, // Empty part of meters
+    overlay: Color, // Title bar and full-screen dimming
+    trap: Color, // Trap radius rings
+    panel: Color, // Boxes over the arena: scoreboard, tutorial, toasts, event log, killcam
+    panel_text: Color, // Body text on those boxes; their headings use `title`
+    console: Color, // Developer console background
+    console_text: Color,
+    players: [Color; 6], // PLAYER_COLORS, in order
+}
+
+impl Theme {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut colors = HashMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected 'key = #rrggbb'", n + 1));
+            };
+            let color = parse_color(value.trim())
+                .ok_or_else(|| format!("line {}: '{}' is not a #rrggbb or #rrggbbaa color", n + 1, value.trim()))?;
+            if colors.insert(key.trim().to_string(), color).is_some() {
+                return Err(format!("line {}: '{}' is defined twice", n + 1, key.trim()));
+            }
+        }
+        let mut take = |key: &str| colors.remove(key).ok_or_else(|| format!("'{}' is missing", key));
+        let mut players = [Color::BLACK; 6];
+        for (color, key) in players.iter_mut().zip(PLAYER_COLORS) {
+            *color = take(key)?;
+        }
+        let theme = Theme {
+            background: take("background")?,
+            arena: take("arena")?,
+            grid: take("grid")?,
+            divider: take("divider")?,
+            accent: take("accent")?,
+            title: take("title")?,
+            text: take("text")?,
+            hint: take("hint")?,
+            selected: take("selected")?,
+            unselected: take("unselected")?,
+            row: take("row")?,
+            field: take("field")?,
+            meter: take("meter")?,
+            overlay: take("overlay")?,
+            trap: take("trap")?,
+            panel: take("panel")?,
+            panel_text: take("panel-text")?,
+            console: take("console")?,
+            console_text: take("console-text")?,
+            players,
+        };
+        if let Some(key) = colors.keys().next() {
+            return Err(format!("'{}' is not a theme color", key));
+        }
+        Ok(theme)
+    }
+
+    fn dark() -> Self {
+        Self::parse(DARK_THEME).expect("built-in dark theme parses")
+    }
+
+    /// Loads `<name>.theme` from THEMES_DIR; dark is always available, even without the folder.
+    fn load(name: &str) -> Result<Self, String> {
+        if name == "dark" {
+            return Ok(Self::dark());
+        }
+        let path = format!("{}/{}.theme", THEMES_DIR, name);
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Themes that can be picked: dark plus every `.theme` file in THEMES_DIR, sorted.
+    fn available() -> Vec<String> {
+        let mut names = vec!["dark".to_string()];
+        if let Ok(entries) = std::fs::read_dir(THEMES_DIR) {
+            for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+                if path.extension().is_some_and(|ext| ext == "theme")
+                    && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+                    && stem != "dark"
+                {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+        names[1..].sort();
+        names
+    }
+}
+
+/// `#rrggbb` or `#rrggbbaa`.
+fn parse_color(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    let alpha = if digits.len() == 8 { byte(6)? } else { 255 };
+    Some(Color::new(byte(0)?, byte(2)?, byte(4)?, alpha))
+}
+
+static THEME: LazyLock<RwLock<(String, Theme)>> = LazyLock::new(|| RwLock::new(("dark".to_string(), Theme::dark())));
+
+/// Switches every themed color over to another theme.
+fn set_theme(name: &str) -> Result<(), String> {
+    let theme = Theme::load(name)?;
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = (name.to_string(), theme);
+    Ok(())
+}
+
+fn theme_name() -> String {
+    THEME.read().unwrap_or_else(|e| e.into_inner()).0.clone()
+}
+
+/// The current theme's colors.
+fn theme() -> Theme {
+    THEME.read().unwrap_or_else(|e| e.into_inner()).1
+}
+
+/// Gameplay values the host decides for everyone: part of `MatchConfig`, so a client plays by the
+/// host's `[gameplay]` settings rather than its own.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct Rules {
+    player_speed: f32, // pixels per second
+    trap_radius: f32,
+    inverse_duration: f32, // seconds
+    inverse_cooldown: f32, // seconds between inversions (fixed and escalating schedules)
+    inverse_warning: f32, // seconds of warning before an inversion starts
+    intermission: f32, // seconds the score is shown after a trap
+}
+
+/// The numbers CONFIG_FILE can change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Tunables {
+    window: (i32, i32), // starting window size; the game is still drawn on a SCREEN_WIDTH x SCREEN_HEIGHT canvas
+    fps: u32,
+    port: u16,
+    send_rate: u32, // state updates sent per second
+    rules: Rules,
+    bots: [BotTuning; 3], // By BotLevel
+}
+
+/// How a bot level plays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct BotTuning {
+    reaction: f32, // Seconds between decisions
+    speed: f32, // Fraction of full speed it steers with
+}
+
+impl Default for Tunables {
+    fn default() -> Self {
+        Tunables {
+            window: (SCREEN_WIDTH, SCREEN_HEIGHT),
+            fps: 60,
+            port: 5555,
+            send_rate: 60,
+            rules: Rules {
+                player_speed: 200.0,
+                trap_radius: 50.0,
+                inverse_duration: 5.0,
+                inverse_cooldown: 10.0,
+                inverse_warning: 3.0,
+                intermission: 3.0,
+            },
+            bots: [
+                BotTuning { reaction: 0.6, speed: 0.5 },
+                BotTuning { reaction: 0.3, speed: 0.8 },
+                BotTuning { reaction: 0.1, speed: 1.0 },
+            ],
+        }
+    }
+}
+
+/// CONFIG_FILE: a small subset of TOML. `[section]` headers, then `key = value` lines where a value
+/// is a number or a "quoted string", and `#` starts a comment. Missing keys keep their defaults.
+#[derive(Clone, Debug, PartialEq)]
+struct ConfigFile {
+    tunables: Tunables,
+    theme: String,
+    language: String,
+    hud_scale: f32,
+    volume: Volume, // The [audio] table, rewritten in place by `Volume::save`
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        ConfigFile {
+            tunables: Tunables::default(),
+            theme: "dark".to_string(),
+            language: "en".to_string(),
+            hud_scale: 1.0,
+            volume: Volume::default(),
+        }
+    }
+}
+
+impl ConfigFile {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut config = ConfigFile::default();
+        let mut section = String::new();
+        let mut seen = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            // A `#` inside a string isn't a comment
+            let mut quoted = false;
+            let end = line.find(|c| {
+                quoted ^= c == '"';
+                c == '#' && !quoted
+            });
+            let line = line[..end.unwrap_or(line.len())].trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected 'key = value'", n + 1));
+            };
+            let (key, value) = (format!("{}.{}", section, key.trim()), value.trim());
+            if seen.contains(&key) {
+                return Err(format!("line {}: '{}' is set twice", n + 1, key));
+            }
+            let bad = |what: &str| format!("line {}: '{}' should be {}", n + 1, key, what);
+            let number = || value.parse::<f32>().ok().filter(|v| v.is_finite() && *v > 0.0).ok_or_else(|| bad("a number above 0"));
+            let fraction = || value.parse::<f32>().ok().filter(|v| *v > 0.0 && *v <= 1.0).ok_or_else(|| bad("a number above 0, up to 1"));
+            let level = || value.parse::<f32>().ok().filter(|v| (0.0..=1.0).contains(v)).ok_or_else(|| bad("a number from 0 to 1"));
+            let switch = || match value {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(bad("true or false")),
+            };
+            let whole = || value.parse::<u32>().ok().filter(|v| *v > 0).ok_or_else(|| bad("a whole number above 0"));
+            let string = || {
+                value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).map(str::to_string).ok_or_else(|| bad("a \"quoted\" string"))
+            };
+            let tunables = &mut config.tunables;
+            match key.as_str() {
+                "window.width" => tunables.window.0 = whole()? as i32,
+                "window.height" => tunables.window.1 = whole()? as i32,
+                "window.fps" => tunables.fps = whole()?,
+                "network.port" => tunables.port = u16::try_from(whole()?).map_err(|_| bad("a port number"))?,
+                "network.send_rate" => tunables.send_rate = whole()?,
+                "gameplay.player_speed" => tunables.rules.player_speed = number()?,
+                "gameplay.trap_radius" => tunables.rules.trap_radius = number()?,
+                "gameplay.inverse_duration" => tunables.rules.inverse_duration = number()?,
+                "gameplay.inverse_cooldown" => tunables.rules.inverse_cooldown = number()?,
+                "gameplay.inverse_warning" => tunables.rules.inverse_warning = number()?,
+                "gameplay.intermission" => tunables.rules.intermission = number()?,
+                "look.theme" => config.theme = string()?,
+                "look.language" => config.language = string()?,
+                "look.hud_scale" => config.hud_scale = number()?.clamp(HUD_SCALE_RANGE.0, HUD_SCALE_RANGE.1),
+                "audio.master" => config.volume.master = level()?,
+                "audio.music" => config.volume.music = level()?,
+                "audio.sfx" => config.volume.sfx = level()?,
+                "audio.muted" => config.volume.muted = switch()?,
+                "audio.announcer" => config.volume.announcer = switch()?,
+                "bot.easy_reaction" => tunables.bots[0].reaction = number()?,
+                "bot.easy_speed" => tunables.bots[0].speed = fraction()?,
+                "bot.normal_reaction" => tunables.bots[1].reaction = number()?,
+                "bot.normal_speed" => tunables.bots[1].speed = fraction()?,
+                "bot.hard_reaction" => tunables.bots[2].reaction = number()?,
+                "bot.hard_speed" => tunables.bots[2].speed = fraction()?,
+                _ => return Err(format!("line {}: '{}' is not a setting", n + 1, key)),
+            }
+            seen.push(key);
+        }
+        Ok(config)
+    }
+
+    /// The file as written on first run.
+    fn text(&self) -> String {
+        let (tunables, rules, bots) = (&self.tunables, &self.tunables.rules, &self.tunables.bots);
+        format!(
+            "# Shadow Swap settings. Remove a line to go back to its default.\n\
+             # Command line flags (--lang, --theme, --port) win over what's set here; see --help.\n\
+             # Saved changes are picked up while the game runs, except the window size and port.\n\
+             \n\
+             [window]\n\
+             width = {}\n\
+             height = {}\n\
+             fps = {}\n\
+             \n\
+             [network]\n\
+             port = {}\n\
+             send_rate = {} # state updates sent per second\n\
+             \n\
+             [gameplay] # when joining, the host's values are used\n\
+             player_speed = {:?}\n\
+             trap_radius = {:?}\n\
+             inverse_duration = {:?}\n\
+             inverse_cooldown = {:?}\n\
+             inverse_warning = {:?}\n\
+             intermission = {:?}\n\
+             \n\
+             [look]\n\
+             theme = \"{}\"\n\
+             language = \"{}\"\n\
+             hud_scale = {:?}\n\
+             \n\
+             {}\
+             \n\
+             [bot] # seconds between decisions, and the share of full speed it steers with\n\
+             easy_reaction = {:?}\n\
+             easy_speed = {:?}\n\
+             normal_reaction = {:?}\n\
+             normal_speed = {:?}\n\
+             hard_reaction = {:?}\n\
+             hard_speed = {:?}\n",
+            tunables.window.0,
+            tunables.window.1,
+            tunables.fps,
+            tunables.port,
+            tunables.send_rate,
+            rules.player_speed,
+            rules.trap_radius,
+            rules.inverse_duration,
+            rules.inverse_cooldown,
+            rules.inverse_warning,
+            rules.intermission,
+            self.theme,
+            self.language,
+            self.hud_scale,
+            self.volume.table(),
+            bots[0].reaction,
+            bots[0].speed,
+            bots[1].reaction,
+            bots[1].speed,
+            bots[2].reaction,
+            bots[2].speed,
+        )
+    }
+
+    /// Reads the config file at `path`, writing it out with the defaults first if it isn't there yet.
+    fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let config = ConfigFile::default();
+                if let Err(e) = std::fs::write(path, config.text()) {
+                    eprintln!("Could not write {}: {}", path.display(), e);
+                }
+                Ok(config)
+            }
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+}
+
+static TUNABLES: LazyLock<RwLock<Tunables>> = LazyLock::new(|| RwLock::new(Tunables::default()));
+
+fn set_tunables(tunables: Tunables) {
+    *TUNABLES.write().unwrap_or_else(|e| e.into_inner()) = tunables;
+}
+
+/// The numbers this copy of the game was started with.
+fn tunables() -> Tunables {
+    *TUNABLES.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Where settings and progress are saved: the platform's standard folders (XDG on Linux, AppData on
+/// Windows, Application Support on macOS), or beside the game with --portable. Shipped files
+/// (levels, locales, themes and assets) are always read from beside the game.
+mod paths {
+    use std::env::var_os;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    const APP: &str = "Shadow Swap";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    const APP: &str = "shadow-swap";
+
+    static PORTABLE: AtomicBool = AtomicBool::new(false);
+
+    pub fn set_portable(portable: bool) {
+        PORTABLE.store(portable, Ordering::Relaxed);
+    }
+
+    /// A settings file: the config file or the volume levels.
+    pub fn config(file: &str) -> PathBuf {
+        within(config_dir(), file)
+    }
+
+    /// A progress file, such as the daily challenge bests.
+    pub fn data(file: &str) -> PathBuf {
+        within(data_dir(), file)
+    }
+
+    fn within(base: Option<PathBuf>, file: &str) -> PathBuf {
+        let Some(base) = base.filter(|_| !PORTABLE.load(Ordering::Relaxed)) else {
+            return beside_game(file);
+        };
+        let dir = base.join(APP);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!("Could not create {}: {}, saving beside the game instead", dir.display(), e);
+            return beside_game(file);
+        }
+        dir.join(file)
+    }
+
+    fn beside_game(file: &str) -> PathBuf {
+        let exe = std::env::current_exe().ok();
+        exe.as_ref().and_then(|exe| exe.parent()).map_or_else(|| PathBuf::from(file), |dir| dir.join(file))
+    }
+
+    #[cfg(target_os = "windows")]
+    fn config_dir() -> Option<PathBuf> {
+        var_os("APPDATA").map(PathBuf::from)
+    }
+
+    #[cfg(target_os = "windows")]
+    fn data_dir() -> Option<PathBuf> {
+        config_dir()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn config_dir() -> Option<PathBuf> {
+        var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    }
+
+    #[cfg(target_os = "macos")]
+    fn data_dir() -> Option<PathBuf> {
+        config_dir()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn config_dir() -> Option<PathBuf> {
+        xdg("XDG_CONFIG_HOME", ".config")
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn data_dir() -> Option<PathBuf> {
+        xdg("XDG_DATA_HOME", ".local/share")
+    }
+
+    /// `$var`, or `~/<fallback>` when it's unset or relative, as the XDG spec asks.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn xdg(var: &str, fallback: &str) -> Option<PathBuf> {
+        var_os(var)
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+    }
+}
+
+/// Files picked up again when they're saved while the game runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Watched {
+    Config,
+    Theme, // The current theme's file
+    Levels, // The whole LEVELS_DIR
+}
+
+impl Watched {
+    const ALL: [Watched; 3] = [Watched::Config, Watched::Theme, Watched::Levels];
+}
+
+/// Polls the watched files' modification times every HOT_RELOAD_POLL seconds.
+struct Watcher {
+    config: PathBuf,
+    stamps: [Option<SystemTime>; 3], // By Watched::ALL
+    wait: f32,
+}
+
+impl Watcher {
+    fn new(config: PathBuf) -> Self {
+        let mut watcher = Watcher { config, stamps: [None; 3], wait: HOT_RELOAD_POLL };
+        watcher.stamps = Watched::ALL.map(|file| modified(&watcher.path(file)));
+        watcher
+    }
+
+    fn path(&self, file: Watched) -> PathBuf {
+        match file {
+            Watched::Config => self.config.clone(),
+            Watched::Theme => PathBuf::from(format!("{}/{}.theme", THEMES_DIR, theme_name())),
+            Watched::Levels => PathBuf::from(LEVELS_DIR),
+        }
+    }
+
+    /// The files that changed since the last check.
+    fn poll(&mut self, dt: f32) -> Vec<Watched> {
+        self.wait -= dt;
+        if self.wait > 0.0 {
+            return Vec::new();
+        }
+        self.wait = HOT_RELOAD_POLL;
+        let mut changed = Vec::new();
+        for (i, file) in Watched::ALL.into_iter().enumerate() {
+            let stamp = modified(&self.path(file));
+            if stamp != self.stamps[i] {
+                self.stamps[i] = stamp;
+                changed.push(file);
+            }
+        }
+        changed
+    }
+}
+
+/// When a file, or the newest file in a folder, was last changed. Adding or removing a file
+/// changes the folder's own time.
+fn modified(path: &Path) -> Option<SystemTime> {
+    let stamp = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let newest = std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max();
+    stamp.max(newest)
+}
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 struct Vec2 {
@@ -32,616 +855,11174 @@ impl From<Vector2> for Vec2 {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
-struct Player {
-    id: u8,
-    pos: Vec2,
-    shadow_pos: Vec2,
-    score: i32,
-    is_trapped: bool,
+impl Vec2 {
+    fn distance(self, other: Vec2) -> f32 {
+        let dx = self.x - other.x;
+        let dy = self.y - other.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Distance on a wrap-around arena, going across the edges when that is shorter.
+    fn wrapped_distance(self, other: Vec2) -> f32 {
+        let (w, h) = (SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        let dx = (self.x - other.x).abs().min(w - (self.x - other.x).abs());
+        let dy = (self.y - other.y).abs().min(h - (self.y - other.y).abs());
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// The shortest step from here to `other` on a wrap-around arena, across the edges when that
+    /// is shorter.
+    fn wrapped_delta(self, other: Vec2) -> Vec2 {
+        let wrap = |d: f32, size: f32| d - size * (d / size).round();
+        Vec2 { x: wrap(other.x - self.x, SCREEN_WIDTH as f32), y: wrap(other.y - self.y, SCREEN_HEIGHT as f32) }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-enum Message {
-    PlayerUpdate(Player),
-    InverseControl { active: bool, time_left: f32 },
-    TrapEvent { player_id: u8 },
-    GameReset,
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum GameMode {
+    Classic,
+    CaptureZone, // Score by holding zones with your shadow over the opponent
+    Tag, // Whoever is "it" passes the tag by touching the runner with their shadow
+    KingOfTheHill, // Score by standing on the center hill
+    HunterVsShade, // Asymmetric: a shadowless hunter chases a shade who plays the shadow game
 }
 
-struct GameState {
-    players: [Player; 2],
-    is_host: bool,
-    player_id: u8,
-    last_send: Instant,
-    socket: Option<UdpSocket>,
-    client_addr: Option<SocketAddr>,
-    inverse_active: bool,
-    inverse_timer: f32,
-    inverse_cooldown: f32,
-    trap_flash_timer: [f32; 2], // Visual feedback when trapped
-    game_time: f32, // For visual effects
+impl GameMode {
+    const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::CaptureZone,
+        GameMode::Tag,
+        GameMode::KingOfTheHill,
+        GameMode::HunterVsShade,
+    ];
+
+    fn name(self) -> String {
+        tr!(match self {
+            GameMode::Classic => "mode-classic",
+            GameMode::CaptureZone => "mode-capture-zones",
+            GameMode::Tag => "mode-tag",
+            GameMode::KingOfTheHill => "mode-king-of-the-hill",
+            GameMode::HunterVsShade => "mode-hunter-vs-shade",
+        })
+    }
 }
 
-impl GameState {
-    fn new(is_host: bool) -> Self {
-        let player_id = if is_host { 0 } else { 1 };
-        GameState {
-            players: [
-                Player {
-                    id: 0,
-                    pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 },
-                    shadow_pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 + 100.0 },
-                    score: 0,
-                    is_trapped: false,
-                },
-                Player {
-                    id: 1,
-                    pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 },
-                    shadow_pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 - 100.0 },
-                    score: 0,
-                    is_trapped: false,
-                },
-            ],
-            is_host,
-            player_id,
-            last_send: Instant::now(),
-            socket: None,
-            client_addr: None,
-            inverse_active: false,
-            inverse_timer: 0.0,
-            inverse_cooldown: 0.0,
-            trap_flash_timer: [0.0, 0.0],
-            game_time: 0.0,
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum RoundPhase {
+    Playing,
+    Intermission, // Action paused after a trap while the score is shown
+    Countdown, // Positions reset, counting down into the next round
+}
+
+/// How inverse mode gets triggered during a match.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum InverseSchedule {
+    Meter, // Players spend a charge meter on demand
+    Fixed, // Every `inverse_cooldown` seconds
+    Random, // Random gaps, announced shortly before
+    Escalating, // Fixed gaps, but each round's inversions last longer
+    Endgame, // Off until someone reaches match point, then permanently on
+}
+
+impl InverseSchedule {
+    fn name(self) -> String {
+        tr!(match self {
+            InverseSchedule::Meter => "schedule-meter",
+            InverseSchedule::Fixed => "schedule-fixed",
+            InverseSchedule::Random => "schedule-random",
+            InverseSchedule::Escalating => "schedule-escalating",
+            InverseSchedule::Endgame => "schedule-endgame",
+        })
+    }
+
+    /// Whether inversions come on a clock rather than from a meter or the scores.
+    fn clocked(self) -> bool {
+        !matches!(self, InverseSchedule::Meter | InverseSchedule::Endgame)
+    }
+
+    /// Seconds until the next inversion, for schedules that run on a clock. Random gaps are
+    /// drawn from the game's seeded `rng`.
+    fn next_gap(self, rules: &Rules, rng: &mut StdRng) -> Option<f32> {
+        match self {
+            InverseSchedule::Fixed | InverseSchedule::Escalating => Some(rules.inverse_cooldown),
+            InverseSchedule::Random => Some(rng.random_range(INVERSE_RANDOM_GAP.0..INVERSE_RANDOM_GAP.1)),
+            InverseSchedule::Meter | InverseSchedule::Endgame => None,
         }
     }
 
-    fn connect(&mut self, addr: &str) -> Result<(), String> {
-        let socket = if self.is_host {
-            UdpSocket::bind(format!("0.0.0.0:{}", PORT)).map_err(|e| e.to_string())?
-        } else {
-            let sock = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
-            sock.connect(addr).map_err(|e| e.to_string())?;
-            sock
-        };
-        socket.set_nonblocking(true).map_err(|e| e.to_string())?;
-        self.socket = Some(socket);
-        Ok(())
+    /// How long an inversion lasts in the given round.
+    fn duration(self, round: u32, rules: &Rules) -> f32 {
+        match self {
+            InverseSchedule::Escalating => {
+                (rules.inverse_duration + round.saturating_sub(1) as f32 * INVERSE_ESCALATION).min(INVERSE_MAX_DURATION)
+            }
+            _ => rules.inverse_duration,
+        }
     }
+}
 
-    fn send_message(&mut self, msg: Message) {
-        if let Some(ref socket) = self.socket {
-            if let Ok(data) = bincode::serialize(&msg) {
-                if self.is_host {
-                    if let Some(addr) = self.client_addr {
-                        let _ = socket.send_to(&data, addr);
-                    }
-                } else {
-                    let _ = socket.send(&data);
-                }
+/// Where each player's shadow gets its movement from.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum ShadowControl {
+    Opponent, // Steered by the opponent (the classic game)
+    Mirror, // Mirrors its owner across the arena center; you move your own character
+    Echo, // Replays its owner's path from `echo_delay` seconds ago; you move your own character
+}
+
+impl ShadowControl {
+    fn name(self) -> String {
+        tr!(match self {
+            ShadowControl::Opponent => "shadows-opponent",
+            ShadowControl::Mirror => "shadows-mirror",
+            ShadowControl::Echo => "shadows-echo",
+        })
+    }
+}
+
+/// A piece on the board that a player's movement keys can drive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Piece {
+    Character(usize),
+    Shadow(usize),
+}
+
+/// One recorded frame for the killcam and replay: where everything was, and which piece each
+/// player's keys were driving at the time.
+#[derive(Clone, Copy, Debug)]
+struct Snapshot {
+    players: [Player; 2],
+    steering: [Piece; 2],
+}
+
+/// Where each character spent the game and where the traps happened, for the post-match heatmap.
+#[derive(Clone, Debug, Default)]
+struct Heatmap {
+    time: [Vec<f32>; 2], // Seconds in play per HEATMAP_CELL cell, row by row, for each player's character
+    traps: Vec<(usize, Vec2)>, // Who was trapped, and where
+}
+
+impl Heatmap {
+    fn cell(pos: Vec2) -> usize {
+        let col = (pos.x.max(0.0) as usize / HEATMAP_CELL as usize).min(HEATMAP_COLS - 1);
+        let row = (pos.y.max(0.0) as usize / HEATMAP_CELL as usize).min(HEATMAP_ROWS - 1);
+        row * HEATMAP_COLS + col
+    }
+
+    fn record(&mut self, players: &[Player; 2], dt: f32) {
+        for (time, player) in self.time.iter_mut().zip(players) {
+            if time.is_empty() {
+                time.resize(HEATMAP_COLS * HEATMAP_ROWS, 0.0);
             }
+            time[Heatmap::cell(player.pos)] += dt;
         }
     }
 
-    fn receive_messages(&mut self) {
-        let mut should_reset = false;
-        if let Some(ref socket) = self.socket {
-            let mut buf = [0u8; 1024];
-            while let Ok((size, peer_addr)) = socket.recv_from(&mut buf) {
-                if self.is_host && self.client_addr.is_none() {
-                    self.client_addr = Some(peer_addr);
-                    println!("Client connected from: {}", peer_addr);
-                }
-                
-                if let Ok(msg) = bincode::deserialize::<Message>(&buf[..size]) {
-                    match msg {
-                        Message::PlayerUpdate(player) => {
-                            // Always update the player data we receive
-                            self.players[player.id as usize] = player;
-                        }
-                        Message::InverseControl { active, time_left } => {
-                            self.inverse_active = active;
-                            self.inverse_timer = time_left;
-                        }
-                        Message::TrapEvent { player_id } => {
-                            let pid = player_id as usize;
-                            self.players[pid].is_trapped = true;
-                            self.players[pid].score += 1;
-                            self.trap_flash_timer[pid] = 1.0;
-                        }
-                        Message::GameReset => {
-                            should_reset = true;
-                        }
+    fn clear(&mut self) {
+        *self = Heatmap::default();
+    }
+}
+
+/// What the game over screen's heatmap shows; H steps through them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HeatmapView {
+    Off,
+    Both,
+    Player(usize),
+}
+
+impl HeatmapView {
+    fn next(self) -> Self {
+        match self {
+            HeatmapView::Off => HeatmapView::Both,
+            HeatmapView::Both => HeatmapView::Player(0),
+            HeatmapView::Player(0) => HeatmapView::Player(1),
+            HeatmapView::Player(_) => HeatmapView::Off,
+        }
+    }
+}
+
+/// Point reflected across the arena center.
+fn mirrored(p: Vec2) -> Vec2 {
+    Vec2 { x: SCREEN_WIDTH as f32 - p.x, y: SCREEN_HEIGHT as f32 - p.y }
+}
+
+/// Arena layout chosen by the host.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum Arena {
+    Open,
+    Portals, // Linked teleporter pads in the corners
+    Vortex, // Gravity wells that drag characters towards them
+}
+
+impl Arena {
+    const ALL: [Arena; 3] = [Arena::Open, Arena::Portals, Arena::Vortex];
+
+    /// The arena called `name` in level files and the admin console.
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(Arena::Open),
+            "portals" => Some(Arena::Portals),
+            "vortex" => Some(Arena::Vortex),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> String {
+        tr!(match self {
+            Arena::Open => "arena-open",
+            Arena::Portals => "arena-portals",
+            Arena::Vortex => "arena-vortex",
+        })
+    }
+
+    /// Linked teleporter pad pairs in screen coordinates.
+    fn teleporters(self) -> Vec<(Vec2, Vec2)> {
+        let at = |fx: f32, fy: f32| Vec2 { x: SCREEN_WIDTH as f32 * fx, y: SCREEN_HEIGHT as f32 * fy };
+        match self {
+            Arena::Portals => vec![(at(0.08, 0.25), at(0.92, 0.9)), (at(0.92, 0.25), at(0.08, 0.9))],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Gravity well centers in screen coordinates.
+    fn gravity_wells(self) -> Vec<Vec2> {
+        let at = |fx: f32, fy: f32| Vec2 { x: SCREEN_WIDTH as f32 * fx, y: SCREEN_HEIGHT as f32 * fy };
+        match self {
+            Arena::Vortex => vec![at(0.3, 0.4), at(0.7, 0.7)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// What happened when the host checked both trap radii in the same tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TrapOutcome {
+    Clear,
+    Trapped(usize), // This player was trapped
+    Clash, // Both trapped equally deep: nobody scores and the round restarts
+}
+
+/// Resolves a tick's traps. `depth[i]` is player i's distance to the threat as a fraction of the
+/// trap radius, when inside it. If both are inside, the deeper one (smaller fraction) is trapped,
+/// so the player who got their shadow closer wins the exchange; near-equal depths clash.
+fn resolve_traps(depth: [Option<f32>; 2]) -> TrapOutcome {
+    match depth {
+        [None, None] => TrapOutcome::Clear,
+        [Some(_), None] => TrapOutcome::Trapped(0),
+        [None, Some(_)] => TrapOutcome::Trapped(1),
+        [Some(a), Some(b)] if (a - b).abs() <= CLASH_TOLERANCE => TrapOutcome::Clash,
+        [Some(a), Some(b)] => TrapOutcome::Trapped(if a < b { 0 } else { 1 }),
+    }
+}
+
+/// Per-player handicap, so players of different skill can have close games.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct Handicap {
+    speed: f32, // Multiplier for everything this player steers
+    trap_radius: f32, // Multiplier for trap radius against this player
+    preloaded_traps: i32, // Traps counted against this player from the start
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap { speed: 1.0, trap_radius: 1.0, preloaded_traps: 0 }
+    }
+}
+
+impl Handicap {
+    /// Short scoreboard label, or None when no handicap is set.
+    fn label(&self) -> Option<String> {
+        if *self == Handicap::default() {
+            return None;
+        }
+        Some(tr!(
+            "hud-handicap",
+            speed = format!("{:.2}", self.speed),
+            trap = format!("{:.2}", self.trap_radius),
+            traps = self.preloaded_traps,
+        ))
+    }
+}
+
+/// Match rules chosen by the host and broadcast to the client.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct MatchConfig {
+    mode: GameMode,
+    arena: Arena,
+    inverse_schedule: InverseSchedule,
+    shadow_control: ShadowControl,
+    echo_delay: f32, // Echo shadows: seconds behind their owner
+    hunter: u8, // Hunter vs Shade: player who hunts this game
+    handicaps: [Handicap; 2],
+    afk_timeout: f32, // Seconds idle before a player is flagged AFK (0 = off)
+    tether_range: f32, // Max shadow distance from its owner in pixels (0 = off)
+    wrap: bool, // Leaving one edge re-enters from the opposite one
+    fog: bool, // Opponent's character is only visible near your character or shadow
+    shadow_steal: bool, // SPACE next to the opponent's shadow swaps you with it instead of your own
+    streak_bonus: bool, // Every STREAK_BONUS_AT traps in a row count one extra against the victim
+    rules: Rules,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            mode: GameMode::Classic,
+            arena: Arena::Open,
+            inverse_schedule: InverseSchedule::Meter,
+            shadow_control: ShadowControl::Opponent,
+            echo_delay: ECHO_DELAY,
+            hunter: 0,
+            handicaps: [Handicap::default(); 2],
+            afk_timeout: AFK_TIMEOUT,
+            tether_range: 0.0,
+            wrap: false,
+            fog: false,
+            shadow_steal: false,
+            streak_bonus: false,
+            rules: tunables().rules,
+        }
+    }
+}
+
+fn hill_center() -> Vec2 {
+    Vec2 { x: SCREEN_WIDTH as f32 / 2.0, y: SCREEN_HEIGHT as f32 / 2.0 }
+}
+
+fn capture_zones() -> [Vec2; 3] {
+    CAPTURE_ZONES.map(|(fx, fy)| Vec2 { x: SCREEN_WIDTH as f32 * fx, y: SCREEN_HEIGHT as f32 * fy })
+}
+
+/// Per-game counters shown on the scoreboard.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+struct PlayerStats {
+    traps_dealt: u32,
+    traps_received: u32,
+    swaps: u32,
+}
+
+/// A finished match, as kept in STATS_FILE: one tab-separated line of
+/// `<unix time> <mode> <won|lost> <traps dealt> <traps received> <seconds> <name> <opponent>`.
+#[derive(Clone, Debug, PartialEq)]
+struct MatchRecord {
+    ended: u64, // Unix seconds
+    mode: GameMode,
+    won: bool,
+    traps_dealt: u32,
+    traps_received: u32,
+    duration: f32, // Seconds in play
+    player: String, // Our name at the time
+    opponent: String,
+}
+
+impl MatchRecord {
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.splitn(8, '\t').collect();
+        let [ended, mode, result, dealt, received, duration, player, opponent] = fields[..] else {
+            return None;
+        };
+        Some(MatchRecord {
+            ended: ended.parse().ok()?,
+            mode: *GameMode::ALL.iter().find(|m| format!("{:?}", m) == mode)?,
+            won: match result {
+                "won" => true,
+                "lost" => false,
+                _ => return None,
+            },
+            traps_dealt: dealt.parse().ok()?,
+            traps_received: received.parse().ok()?,
+            duration: duration.parse().ok()?,
+            player: player.to_string(),
+            opponent: opponent.to_string(),
+        })
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "{}\t{:?}\t{}\t{}\t{}\t{:.1}\t{}\t{}",
+            self.ended,
+            self.mode,
+            if self.won { "won" } else { "lost" },
+            self.traps_dealt,
+            self.traps_received,
+            self.duration,
+            self.player.replace(['\t', '\n'], " "),
+            self.opponent.replace(['\t', '\n'], " "),
+        )
+    }
+
+    /// Every recorded match, oldest first. Lines that don't parse are skipped.
+    fn load_all() -> Vec<Self> {
+        let text = std::fs::read_to_string(paths::data(STATS_FILE)).unwrap_or_default();
+        text.lines().filter_map(Self::parse).collect()
+    }
+
+    fn save(&self) {
+        use std::io::Write;
+        let saved = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(paths::data(STATS_FILE))
+            .and_then(|mut file| writeln!(file, "{}", self.line()));
+        if let Err(e) = saved {
+            eprintln!("Could not save match stats: {}", e);
+        }
+    }
+}
+
+/// Elo ratings by player name, replayed from the match history: everyone starts at ELO_START, and
+/// each result moves the winner up and the loser down by up to ELO_K, more for an upset.
+#[derive(Clone, Debug, Default)]
+struct Ratings(HashMap<String, f32>);
+
+impl Ratings {
+    fn load() -> Self {
+        let mut ratings = Ratings::default();
+        for record in MatchRecord::load_all() {
+            ratings.record(&record);
+        }
+        ratings
+    }
+
+    fn record(&mut self, record: &MatchRecord) {
+        let (ours, theirs) = (self.of(&record.player), self.of(&record.opponent));
+        let expected = 1.0 / (1.0 + 10f32.powf((theirs - ours) / 400.0));
+        let change = ELO_K * (if record.won { 1.0 } else { 0.0 } - expected);
+        self.0.insert(record.player.clone(), ours + change);
+        self.0.insert(record.opponent.clone(), theirs - change);
+    }
+
+    fn of(&self, name: &str) -> f32 {
+        self.0.get(name).copied().unwrap_or(ELO_START)
+    }
+}
+
+/// A player's line on the leaderboard.
+#[derive(Clone, Debug, PartialEq)]
+struct Standing {
+    name: String,
+    rating: f32,
+    wins: u32,
+    losses: u32,
+}
+
+/// Everyone in the match history, best first: by rating (from just that mode's matches, when
+/// filtered), or by wins. Ties go to the other measure, then the name.
+fn standings(records: &[MatchRecord], mode: Option<GameMode>, by_wins: bool) -> Vec<Standing> {
+    let mut ratings = Ratings::default();
+    let mut results: HashMap<&str, (u32, u32)> = HashMap::new();
+    for record in records.iter().filter(|record| mode.is_none_or(|mode| record.mode == mode)) {
+        ratings.record(record);
+        let (winner, loser) = if record.won { (&record.player, &record.opponent) } else { (&record.opponent, &record.player) };
+        results.entry(winner).or_default().0 += 1;
+        results.entry(loser).or_default().1 += 1;
+    }
+    let mut standings: Vec<Standing> = results
+        .into_iter()
+        .map(|(name, (wins, losses))| Standing { name: name.to_string(), rating: ratings.of(name), wins, losses })
+        .collect();
+    standings.sort_by(|a, b| {
+        let (rating, wins) = (b.rating.total_cmp(&a.rating), b.wins.cmp(&a.wins));
+        let order = if by_wins { wins.then(rating) } else { rating.then(wins) };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+    standings
+}
+
+/// A goal reached over a profile's match history. Each one unlocks a cosmetic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Achievement {
+    FirstWin,
+    Regular, // 10 matches played
+    Trapper, // 25 traps dealt
+    Flawless, // A win without being trapped
+    Veteran, // 10 wins
+    Marathon, // An hour in play
+}
+
+impl Achievement {
+    const ALL: [Achievement; 6] = [
+        Achievement::FirstWin,
+        Achievement::Regular,
+        Achievement::Trapper,
+        Achievement::Flawless,
+        Achievement::Veteran,
+        Achievement::Marathon,
+    ];
+
+    /// Its key in profile files, and its message ids after "achievement-".
+    fn key(self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "first-win",
+            Achievement::Regular => "regular",
+            Achievement::Trapper => "trapper",
+            Achievement::Flawless => "flawless",
+            Achievement::Veteran => "veteran",
+            Achievement::Marathon => "marathon",
+        }
+    }
+
+    fn name(self) -> String {
+        tr!(&format!("achievement-{}", self.key()))
+    }
+
+    fn goal(self) -> String {
+        tr!(&format!("achievement-{}-goal", self.key()))
+    }
+
+    /// What it unlocks, for the cosmetics screen and the unlock toast.
+    fn reward(self) -> String {
+        match self {
+            Achievement::FirstWin => tr!("cosmetic-trail", name = TrailStyle::Sparks.name()),
+            Achievement::Regular => tr!("cosmetic-swap", name = SwapEffect::Ring.name()),
+            Achievement::Trapper => tr!("cosmetic-trail", name = TrailStyle::Comet.name()),
+            Achievement::Flawless => tr!("cosmetic-swap", name = SwapEffect::Implode.name()),
+            Achievement::Veteran | Achievement::Marathon => tr!("cosmetic-colors", set = tr!(&format!("colors-{}", self.key()))),
+        }
+    }
+
+    fn earned(self, records: &[MatchRecord]) -> bool {
+        let wins = records.iter().filter(|record| record.won).count();
+        match self {
+            Achievement::FirstWin => wins >= 1,
+            Achievement::Regular => records.len() >= 10,
+            Achievement::Trapper => records.iter().map(|record| record.traps_dealt).sum::<u32>() >= 25,
+            Achievement::Flawless => records.iter().any(|record| record.won && record.traps_received == 0),
+            Achievement::Veteran => wins >= 10,
+            Achievement::Marathon => records.iter().map(|record| record.duration).sum::<f32>() >= 3600.0,
+        }
+    }
+
+    /// Ones earned in `records` (a profile's matches) and missing from `have`.
+    fn new_in(records: &[MatchRecord], have: &[Achievement]) -> Vec<Achievement> {
+        Achievement::ALL.into_iter().filter(|a| !have.contains(a) && a.earned(records)).collect()
+    }
+}
+
+/// How a player's motion trail is drawn.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+enum TrailStyle {
+    #[default]
+    Plain,
+    Sparks, // Glinting dots instead of a streak
+    Comet, // A wide glow with a white-hot core
+}
+
+/// What a player's swap looks like.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+enum SwapEffect {
+    #[default]
+    Burst,
+    Ring, // An even ring flying outward
+    Implode, // Sparks rushing in to where the character lands
+}
+
+impl TrailStyle {
+    const ALL: [TrailStyle; 3] = [TrailStyle::Plain, TrailStyle::Sparks, TrailStyle::Comet];
+
+    fn key(self) -> &'static str {
+        match self {
+            TrailStyle::Plain => "plain",
+            TrailStyle::Sparks => "sparks",
+            TrailStyle::Comet => "comet",
+        }
+    }
+
+    fn name(self) -> String {
+        tr!(&format!("trail-{}", self.key()))
+    }
+
+    fn unlocked_by(self) -> Option<Achievement> {
+        match self {
+            TrailStyle::Plain => None,
+            TrailStyle::Sparks => Some(Achievement::FirstWin),
+            TrailStyle::Comet => Some(Achievement::Trapper),
+        }
+    }
+}
+
+impl SwapEffect {
+    const ALL: [SwapEffect; 3] = [SwapEffect::Burst, SwapEffect::Ring, SwapEffect::Implode];
+
+    fn key(self) -> &'static str {
+        match self {
+            SwapEffect::Burst => "burst",
+            SwapEffect::Ring => "ring",
+            SwapEffect::Implode => "implode",
+        }
+    }
+
+    fn name(self) -> String {
+        tr!(&format!("swap-effect-{}", self.key()))
+    }
+
+    fn unlocked_by(self) -> Option<Achievement> {
+        match self {
+            SwapEffect::Burst => None,
+            SwapEffect::Ring => Some(Achievement::Regular),
+            SwapEffect::Implode => Some(Achievement::Flawless),
+        }
+    }
+}
+
+/// A player's picked trail and swap effect, sent with their name and color so the opponent sees them too.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+struct Cosmetics {
+    trail: TrailStyle,
+    swap: SwapEffect,
+}
+
+/// Color indices a player with `achievements` can pick: every PLAYER_COLORS entry, then the unlocked EXTRA_COLORS.
+fn available_colors(achievements: &[Achievement]) -> Vec<u8> {
+    let extras = EXTRA_COLORS.iter().enumerate().filter(|(_, (_, by, _))| achievements.contains(by));
+    (0..PLAYER_COLORS.len()).chain(extras.map(|(i, _)| PLAYER_COLORS.len() + i)).map(|i| i as u8).collect()
+}
+
+/// Player color by index: PLAYER_COLORS from the theme, then EXTRA_COLORS.
+/// A color's name as files and tools outside the game see it, such as "red" or "gold".
+fn color_key(index: usize) -> &'static str {
+    let id = EXTRA_COLORS.get(index.wrapping_sub(PLAYER_COLORS.len())).map_or(PLAYER_COLORS[index % PLAYER_COLORS.len()], |extra| extra.0);
+    id.trim_start_matches("color-")
+}
+
+fn color_value(index: usize) -> Color {
+    match EXTRA_COLORS.get(index.wrapping_sub(PLAYER_COLORS.len())) {
+        Some(&(_, _, color)) => color,
+        None => theme().players[index % PLAYER_COLORS.len()],
+    }
+}
+
+/// Something a player does from the keyboard, in the order profiles and the controls screen list them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Swap,
+    Inverse,
+    Freeze,
+}
+
+impl Action {
+    const ALL: [Action; 7] = [Action::Up, Action::Down, Action::Left, Action::Right, Action::Swap, Action::Inverse, Action::Freeze];
+
+    /// Its key in profile files.
+    fn key(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Swap => "swap",
+            Action::Inverse => "inverse",
+            Action::Freeze => "freeze",
+        }
+    }
+
+    fn label(self) -> String {
+        tr!(&format!("action-{}", self.key()))
+    }
+}
+
+/// The key for each action, in `Action::ALL` order. The arrow keys always move as well.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bindings([KeyboardKey; 7]);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use KeyboardKey::*;
+        Bindings([KEY_W, KEY_S, KEY_A, KEY_D, KEY_SPACE, KEY_E, KEY_Q])
+    }
+}
+
+impl Bindings {
+    fn key(&self, action: Action) -> KeyboardKey {
+        self.0[action as usize]
+    }
+
+    /// Binds `key` to `action`. An action that had the key takes this one's old key, so no key does two things.
+    fn set(&mut self, action: Action, key: KeyboardKey) {
+        if let Some(other) = self.0.iter().position(|&bound| bound == key) {
+            self.0[other] = self.0[action as usize];
+        }
+        self.0[action as usize] = key;
+    }
+
+    /// The four movement keys, e.g. "WASD", for hints.
+    fn movement(&self) -> String {
+        let keys = [Action::Up, Action::Left, Action::Down, Action::Right].map(|action| key_name(self.key(action)));
+        if keys.iter().all(|key| key.chars().count() == 1) { keys.concat() } else { keys.join("/") }
+    }
+}
+
+/// A key as players know it: KEY_LEFT_SHIFT is "LEFT SHIFT".
+fn key_name(key: KeyboardKey) -> String {
+    format!("{:?}", key).trim_start_matches("KEY_").replace('_', " ")
+}
+
+/// The key called `name` by `key_name`, in any case.
+fn parse_key(name: &str) -> Option<KeyboardKey> {
+    (0..=348).filter_map(key_from_i32).find(|&key| key_name(key).eq_ignore_ascii_case(name.trim()))
+}
+
+/// One person on this machine: their display name, color, keys, cosmetics and achievements, in
+/// PROFILES_DIR as `<name>.profile` with one `<key> <value>` per line. Their history and rating
+/// are the match records under the same name.
+#[derive(Clone, Debug, PartialEq)]
+struct Profile {
+    name: String,
+    color: u8, // See `color_value`
+    bindings: Bindings,
+    cosmetics: Cosmetics,
+    achievements: Vec<Achievement>, // In the order they were earned
+}
+
+impl Profile {
+    fn new(name: &str) -> Self {
+        Profile { name: name.to_string(), color: 0, bindings: Bindings::default(), cosmetics: Cosmetics::default(), achievements: Vec::new() }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut profile = Profile::new("");
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once(' ').ok_or_else(|| format!("'{}' has no value", line))?;
+            let value = value.trim();
+            match key {
+                "name" => profile.name = value.chars().filter(|c| !c.is_control()).take(MAX_NAME_LEN).collect(),
+                "color" => {
+                    profile.color = value.parse().ok().filter(|&color| (color as usize) < PLAYER_COLORS.len() + EXTRA_COLORS.len())
+                        .ok_or_else(|| format!("unknown color '{}'", value))?;
+                }
+                "trail" => {
+                    profile.cosmetics.trail = *TrailStyle::ALL.iter().find(|style| style.key() == value)
+                        .ok_or_else(|| format!("unknown trail '{}'", value))?;
+                }
+                "swap-effect" => {
+                    profile.cosmetics.swap = *SwapEffect::ALL.iter().find(|effect| effect.key() == value)
+                        .ok_or_else(|| format!("unknown swap effect '{}'", value))?;
+                }
+                "achievements" => {
+                    for key in value.split_whitespace() {
+                        let achievement = Achievement::ALL.into_iter().find(|a| a.key() == key)
+                            .ok_or_else(|| format!("unknown achievement '{}'", key))?;
+                        if !profile.achievements.contains(&achievement) {
+                            profile.achievements.push(achievement);
+                        }
+                    }
+                }
+                _ => {
+                    let action = Action::ALL.into_iter().find(|action| action.key() == key).ok_or_else(|| format!("unknown setting '{}'", key))?;
+                    profile.bindings.0[action as usize] = parse_key(value).ok_or_else(|| format!("unknown key '{}'", value))?;
+                }
+            }
+        }
+        if profile.name.is_empty() {
+            return Err("no name".to_string());
+        }
+        Ok(profile)
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!("name {}\ncolor {}\n", self.name, self.color);
+        for action in Action::ALL {
+            text += &format!("{} {}\n", action.key(), key_name(self.bindings.key(action)));
+        }
+        text += &format!("trail {}\nswap-effect {}\n", self.cosmetics.trail.key(), self.cosmetics.swap.key());
+        if !self.achievements.is_empty() {
+            let keys: Vec<&str> = self.achievements.iter().map(|a| a.key()).collect();
+            text += &format!("achievements {}\n", keys.join(" "));
+        }
+        text
+    }
+
+    /// Where the profile named `name` lives. Characters that can't go in a file name become '_'.
+    fn path(name: &str) -> PathBuf {
+        let stem: String = name.chars().map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' }).collect();
+        paths::data(PROFILES_DIR).join(format!("{}.profile", stem))
+    }
+
+    /// Every saved profile, by name. Ones that don't parse are reported and skipped.
+    fn load_all() -> Vec<Self> {
+        let entries = std::fs::read_dir(paths::data(PROFILES_DIR)).into_iter().flatten().flatten();
+        let mut profiles: Vec<Profile> = entries
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "profile"))
+            .filter_map(|path| {
+                let text = std::fs::read_to_string(&path).map_err(|e| e.to_string());
+                text.and_then(|text| Profile::parse(&text))
+                    .inspect_err(|e| eprintln!("Skipping profile {}: {}", path.display(), e))
+                    .ok()
+            })
+            .collect();
+        profiles.sort_by_key(|profile| profile.name.to_lowercase());
+        profiles
+    }
+
+    /// The profile called `name`, or None if there's no file for it. A file that holds another
+    /// profile, whose name maps to the same file, is an error rather than a match.
+    fn load(name: &str) -> Result<Option<Self>, String> {
+        let text = match std::fs::read_to_string(Profile::path(name)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let profile = Profile::parse(&text)?;
+        if profile.name != name {
+            return Err(format!("its file belongs to the profile '{}'", profile.name));
+        }
+        Ok(Some(profile))
+    }
+
+    fn save(&self) {
+        let saved = std::fs::create_dir_all(paths::data(PROFILES_DIR))
+            .and_then(|_| std::fs::write(Profile::path(&self.name), self.text()));
+        if let Err(e) = saved {
+            eprintln!("Could not save profile '{}': {}", self.name, e);
+        }
+    }
+
+    fn delete(name: &str) {
+        if let Err(e) = std::fs::remove_file(Profile::path(name)) {
+            eprintln!("Could not delete profile '{}': {}", name, e);
+        }
+    }
+}
+
+/// How a recorded match ended.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum ReplayEnd {
+    Won(u8), // By this player
+    Disconnected, // The opponent dropped; saved again if they come back
+    Abandoned, // We left, or closed the game, mid-match
+    Clip, // An F9 clip of a match still being played. Added last, so older files still read
+}
+
+/// A single-player run worth racing: today's daily challenge, or practice against a bot level in an arena.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum Challenge {
+    None, // Network matches, the tutorial and puzzles
+    Daily(u64), // By day number
+    Practice(BotLevel, Arena),
+}
+
+impl Challenge {
+    /// Where its fastest win is kept as a ghost, or None if it has no ghost.
+    fn ghost_path(self) -> Option<PathBuf> {
+        let name = match self {
+            Challenge::None => return None,
+            Challenge::Daily(day) => format!("daily-{}", day),
+            Challenge::Practice(level, arena) => format!("practice-{:?}-{:?}", level, arena).to_lowercase(),
+        };
+        Some(paths::data(GHOSTS_DIR).join(format!("{}.replay", name)))
+    }
+}
+
+/// One REPLAY_SAMPLE tick of a recording, positions rounded to whole pixels to keep files small.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct ReplayFrame {
+    time: f32, // Seconds in play
+    pieces: [[i16; 4]; 2], // Per player: character x, y, shadow x, y
+    scores: [i8; 2],
+    steering: [u8; 2], // What each player's keys drove: 0 or 1 for a character, 2 or 3 for a shadow
+    inverse: bool,
+}
+
+impl ReplayFrame {
+    fn capture(game: &GameState) -> Self {
+        let pixels = |player: &Player| [player.pos.x, player.pos.y, player.shadow_pos.x, player.shadow_pos.y].map(|v| v.round() as i16);
+        ReplayFrame {
+            time: game.play_time,
+            pieces: game.players.each_ref().map(pixels),
+            scores: game.players.each_ref().map(|player| player.score.clamp(i8::MIN as i32, i8::MAX as i32) as i8),
+            steering: [0, 1].map(|i| match game.steered_piece(i) {
+                Piece::Character(id) => id as u8,
+                Piece::Shadow(id) => 2 + id as u8,
+            }),
+            inverse: game.inverse_active,
+        }
+    }
+}
+
+/// A whole match as kept in REPLAYS_DIR: the rules, who played and where everything was every
+/// REPLAY_SAMPLE seconds, bincode-encoded after REPLAY_MAGIC. Positions are recorded rather than
+/// inputs, so network matches and the host's random timings play back as they happened.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Replay {
+    version: u16, // REPLAY_VERSION it was written with
+    started: u64, // Unix seconds of the first frame, also the file name
+    config: MatchConfig,
+    recorded_by: u8, // Player id of whoever saved it
+    challenge: Challenge,
+    names: [String; 2],
+    colors: [u8; 2],
+    cosmetics: [Cosmetics; 2],
+    end: ReplayEnd,
+    frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    fn encode(&self) -> Result<Vec<u8>, String> {
+        let body = bincode::serialize(self).map_err(|e| e.to_string())?;
+        Ok([REPLAY_MAGIC.as_slice(), &body].concat())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let body = bytes.strip_prefix(REPLAY_MAGIC.as_slice()).ok_or("not a replay file")?;
+        // The version comes first, so older files are told apart before the rest is read
+        let version: u16 = bincode::deserialize(body).map_err(|e| e.to_string())?;
+        if version != REPLAY_VERSION {
+            return Err(format!("replay version {} (this game reads {})", version, REPLAY_VERSION));
+        }
+        bincode::deserialize(body).map_err(|e| e.to_string())
+    }
+
+    fn load(path: &Path) -> Result<Self, String> {
+        Replay::decode(&std::fs::read(path).map_err(|e| e.to_string())?)
+    }
+
+    fn path(&self) -> PathBuf {
+        paths::data(REPLAYS_DIR).join(format!("{}.replay", self.started))
+    }
+
+    /// Seconds in play it covers.
+    fn length(&self) -> f32 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+
+    /// Where the recording player's character and shadow were `time` seconds into play, between
+    /// the nearest frames. None past the end.
+    fn pieces_at(&self, time: f32) -> Option<(Vector2, Vector2)> {
+        let next = self.frames.iter().position(|frame| frame.time >= time)?;
+        let at = |frame: &ReplayFrame| {
+            let [x, y, shadow_x, shadow_y] = frame.pieces[self.recorded_by as usize % 2].map(f32::from);
+            (Vector2::new(x, y), Vector2::new(shadow_x, shadow_y))
+        };
+        let Some(before) = next.checked_sub(1).map(|i| &self.frames[i]) else {
+            return Some(at(&self.frames[0]));
+        };
+        let after = &self.frames[next];
+        let t = ((time - before.time) / (after.time - before.time).max(f32::EPSILON)).clamp(0.0, 1.0);
+        let ((pos_a, shadow_a), (pos_b, shadow_b)) = (at(before), at(after));
+        Some((pos_a.lerp(pos_b, t), shadow_a.lerp(shadow_b, t)))
+    }
+
+    /// Keeps this as its challenge's ghost if it's a win faster than the one kept so far.
+    /// Returns whether it was.
+    fn save_ghost(&self) -> bool {
+        let Some(path) = self.challenge.ghost_path() else {
+            return false;
+        };
+        if self.end != ReplayEnd::Won(self.recorded_by) || Replay::load(&path).is_ok_and(|best| best.length() <= self.length()) {
+            return false;
+        }
+        let saved = self.encode().map_err(std::io::Error::other).and_then(|bytes| {
+            std::fs::create_dir_all(paths::data(GHOSTS_DIR))?;
+            std::fs::write(&path, bytes)
+        });
+        if let Err(e) = saved {
+            eprintln!("Could not save the ghost: {}", e);
+            return false;
+        }
+        true
+    }
+
+    /// One line for --inspect-replay.
+    fn summary(&self) -> String {
+        let end = match self.end {
+            ReplayEnd::Won(winner) => format!("won by {}", self.names[winner as usize % 2]),
+            ReplayEnd::Disconnected => "opponent disconnected".to_string(),
+            ReplayEnd::Abandoned => "left unfinished".to_string(),
+            ReplayEnd::Clip => "clip".to_string(),
+        };
+        let length = self.length();
+        format!(
+            "{} {}: {} vs {}, {:?}, {}, {:.1}s in play, {} frames",
+            calendar_date(self.started / 86_400),
+            self.started,
+            self.names[0],
+            self.names[1],
+            self.config.mode,
+            end,
+            length,
+            self.frames.len(),
+        )
+    }
+
+    /// Writes the replay, over an earlier save of the same match, then drops the oldest files past MAX_REPLAYS.
+    fn save(&self) {
+        let saved = self.encode().map_err(std::io::Error::other).and_then(|bytes| {
+            std::fs::create_dir_all(paths::data(REPLAYS_DIR))?;
+            std::fs::write(self.path(), bytes)
+        });
+        if let Err(e) = saved {
+            eprintln!("Could not save the replay: {}", e);
+            return;
+        }
+        let entries = std::fs::read_dir(paths::data(REPLAYS_DIR)).into_iter().flatten().flatten();
+        let mut files: Vec<PathBuf> = entries.map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|ext| ext == "replay")).collect();
+        // Same-length stamps sort by time; an oddly named file just sorts oddly
+        files.sort_by_key(|path| path.file_stem().and_then(|stem| stem.to_str()?.parse::<u64>().ok()).unwrap_or(0));
+        for old in files.iter().take(files.len().saturating_sub(MAX_REPLAYS)) {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+}
+
+/// An F9 clip as a looping GIF, drawn in software so it can be made on its own thread while the
+/// match goes on: the arena, each shadow as a dim disc and each character as a bright one in its
+/// player's color, a pip per point along the top, and a white border while inverse mode is on.
+fn clip_gif(clip: &Replay, colors: [Color; 2]) -> Vec<u8> {
+    let (width, height) = (SCREEN_WIDTH / CLIP_SCALE, SCREEN_HEIGHT / CLIP_SCALE);
+    let dim = |color: Color, arena: Color| Color::new(color.r / 2 + arena.r / 2, color.g / 2 + arena.g / 2, color.b / 2 + arena.b / 2, 255);
+    let arena = theme().arena;
+    // Indices: arena, grid, inverse border, unused, characters, shadows
+    let palette = [arena, theme().grid, Color::WHITE, arena, colors[0], colors[1], dim(colors[0], arena), dim(colors[1], arena)];
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend((width as u16).to_le_bytes());
+    gif.extend((height as u16).to_le_bytes());
+    gif.extend([0xf2, 0, 0]); // A global table of 8 colors
+    gif.extend(palette.iter().flat_map(|color| [color.r, color.g, color.b]));
+    gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00"); // Loop forever
+    let mut pixels = vec![0u8; (width * height) as usize];
+    for (i, frame) in clip.frames.iter().enumerate() {
+        pixels.fill(0);
+        let mut fill = |x0: i32, y0: i32, x1: i32, y1: i32, index: u8| {
+            for y in y0.max(0)..y1.min(height) {
+                for x in x0.max(0)..x1.min(width) {
+                    pixels[(y * width + x) as usize] = index;
+                }
+            }
+        };
+        let step = 100 / CLIP_SCALE; // The arena's background grid
+        for x in (step..width).step_by(step as usize) {
+            fill(x, 0, x + 1, height, 1);
+        }
+        for y in (step..height).step_by(step as usize) {
+            fill(0, y, width, y + 1, 1);
+        }
+        if frame.inverse {
+            let border = 3;
+            fill(0, 0, width, border, 2);
+            fill(0, height - border, width, height, 2);
+            fill(0, 0, border, height, 2);
+            fill(width - border, 0, width, height, 2);
+        }
+        for (id, score) in frame.scores.iter().enumerate() {
+            for pip in 0..(*score).max(0) as i32 {
+                let x = if id == 0 { 8 + pip * 10 } else { width - 14 - pip * 10 };
+                fill(x, 8, x + 6, 14, 4 + id as u8);
+            }
+        }
+        let mut disc = |x: i16, y: i16, radius: f32, index: u8| {
+            let (cx, cy, r) = (x as f32 / CLIP_SCALE as f32, y as f32 / CLIP_SCALE as f32, radius / CLIP_SCALE as f32);
+            for py in (cy - r) as i32..=(cy + r) as i32 {
+                for px in (cx - r) as i32..=(cx + r) as i32 {
+                    let (dx, dy) = (px as f32 + 0.5 - cx, py as f32 + 0.5 - cy);
+                    if dx * dx + dy * dy <= r * r && (0..width).contains(&px) && (0..height).contains(&py) {
+                        pixels[(py * width + px) as usize] = index;
+                    }
+                }
+            }
+        };
+        for (id, [_, _, shadow_x, shadow_y]) in frame.pieces.iter().enumerate() {
+            disc(*shadow_x, *shadow_y, SHADOW_SIZE, 6 + id as u8);
+        }
+        for (id, [x, y, _, _]) in frame.pieces.iter().enumerate() {
+            disc(*x, *y, PLAYER_SIZE, 4 + id as u8);
+        }
+        // Each frame stays up until the next, in hundredths of a second; the last one holds a second
+        let delay = clip.frames.get(i + 1).map_or(100, |next| ((next.time - frame.time) * 100.0).round().max(2.0) as u16);
+        gif.extend([0x21, 0xf9, 4, 0]);
+        gif.extend(delay.to_le_bytes());
+        gif.extend([0, 0, 0x2c, 0, 0, 0, 0]);
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        gif.extend([0, 3]); // No local table; LZW codes start at 3 bits for 8 colors
+        for block in lzw(3, &pixels).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend(block);
+        }
+        gif.push(0);
+    }
+    gif.push(0x3b);
+    gif
+}
+
+/// GIF's variable-width LZW: codes grow from `min_size + 1` bits up to 12, and the table starts
+/// over with a clear code once it's full.
+fn lzw(min_size: u8, pixels: &[u8]) -> Vec<u8> {
+    let (clear, end) = (1u16 << min_size, (1u16 << min_size) + 1);
+    let mut out = Vec::new();
+    let (mut bits, mut count) = (0u32, 0u8);
+    let mut write = |code: u16, size: u8, out: &mut Vec<u8>| {
+        bits |= (code as u32) << count;
+        count += size;
+        while count >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            count -= 8;
+        }
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let (mut next, mut size) = (end + 1, min_size + 1);
+    write(clear, size, &mut out);
+    let Some((&first, rest)) = pixels.split_first() else {
+        write(end, size, &mut out);
+        write(0, 7, &mut out); // Flush the last partial byte
+        return out;
+    };
+    let mut prefix = first as u16;
+    for &pixel in rest {
+        if let Some(&code) = table.get(&(prefix, pixel)) {
+            prefix = code;
+            continue;
+        }
+        write(prefix, size, &mut out);
+        // The decoder adds each entry a code later, so widen once the one just written used up the size
+        if next > (1 << size) - 1 && size < 12 {
+            size += 1;
+        }
+        if next < 4096 {
+            table.insert((prefix, pixel), next);
+            next += 1;
+        } else {
+            write(clear, size, &mut out);
+            table.clear();
+            (next, size) = (end + 1, min_size + 1);
+        }
+        prefix = pixel as u16;
+    }
+    write(prefix, size, &mut out);
+    if next > (1 << size) - 1 && size < 12 {
+        size += 1;
+    }
+    write(end, size, &mut out);
+    write(0, 7, &mut out);
+    out
+}
+
+/// Something a player did in one tick of an input recording.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+enum TickInput {
+    Move(u8, Vec2), // Player and their movement, bots' already slowed down
+    Swap(u8),
+    Inverse(u8),
+    Freeze(u8),
+    Pause(u8, bool),
+    Forfeit(u8),
+    Chat(ChatEvent), // What Twitch chat voted for
+}
+
+/// One frame of an input recording: its real time step and the inputs in it, in the order they came.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct InputTick {
+    dt: f32,
+    inputs: Vec<TickInput>,
+}
+
+/// A bot practice game as its seed, rules and every tick's inputs (--record-inputs), bincode-encoded
+/// after INPUTS_MAGIC. Unlike a `Replay` it holds no positions: `play` runs the inputs through the
+/// same simulation steps as a real match, so it must end on the recorded `checksum`, and a change
+/// that moves trap detection or inverse timing shows up as a mismatch (--check-inputs).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct InputLog {
+    seed: u64,
+    config: MatchConfig,
+    ticks: Vec<InputTick>,
+    checksum: u64, // `GameState::checksum` where the recording stopped
+}
+
+impl InputLog {
+    fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let body = bytes.strip_prefix(INPUTS_MAGIC.as_slice()).ok_or("not an input recording")?;
+        bincode::deserialize(body).map_err(|e| e.to_string())
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let body = bincode::serialize(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, [INPUTS_MAGIC.as_slice(), &body].concat()).map_err(|e| e.to_string())
+    }
+
+    /// Runs the recording headlessly on a fresh host game, started the way bot practice starts one.
+    fn play(&self) -> GameState {
+        let mut game = GameState::seeded(true, self.config, self.seed);
+        game.start_round(1, ROUND_COUNTDOWN);
+        for tick in &self.ticks {
+            let dt = game.begin_tick(tick.dt);
+            for &input in &tick.inputs {
+                game.apply_input(input, dt);
+            }
+            game.end_tick(dt);
+        }
+        game
+    }
+}
+
+/// A day number since the Unix epoch as YYYY-MM-DD (proleptic Gregorian).
+fn calendar_date(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct Player {
+    id: u8,
+    pos: Vec2,
+    shadow_pos: Vec2,
+    score: i32,
+    is_trapped: bool,
+    warps: u32, // Host-side jumps (teleports, steals) so far; the host drops updates that predate the latest one
+    inverse_charge: f32, // 0..=1, full meter can be spent on inverse mode (host decides)
+}
+
+/// --net-dump: every datagram one side sent or received, byte for byte, so a report of odd jumps
+/// can be followed packet by packet (--print-dump). The file is NET_DUMP_MAGIC and 1 if the host
+/// wrote it, then per datagram: seconds since the dump started (f64), 1 if sent or 0 if received,
+/// its length (u16) and its bytes, little-endian.
+struct NetDump {
+    file: Option<std::fs::File>, // Dropped after a failed write, so a full disk doesn't spam errors
+    start: Instant,
+}
+
+impl NetDump {
+    fn create(path: &Path, is_host: bool) -> std::io::Result<Self> {
+        use std::io::Write;
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(&[NET_DUMP_MAGIC.as_slice(), &[is_host as u8]].concat())?;
+        Ok(NetDump { file: Some(file), start: Instant::now() })
+    }
+
+    fn record(&mut self, sent: bool, data: &[u8]) {
+        use std::io::Write;
+        let record = NetDump::encode(self.start.elapsed().as_secs_f64(), sent, data);
+        if let Some(file) = &mut self.file
+            && let Err(e) = file.write_all(&record)
+        {
+            eprintln!("Stopped the network dump: {}", e);
+            self.file = None;
+        }
+    }
+
+    fn encode(time: f64, sent: bool, data: &[u8]) -> Vec<u8> {
+        [&time.to_le_bytes()[..], &[sent as u8], &(data.len() as u16).to_le_bytes(), data].concat()
+    }
+
+    /// A dump as text for --print-dump: one line per datagram with its message decoded, then totals.
+    fn print(bytes: &[u8]) -> Result<String, String> {
+        let body = bytes.strip_prefix(NET_DUMP_MAGIC.as_slice()).ok_or("not a network dump")?;
+        let (&host, mut rest) = body.split_first().ok_or("not a network dump")?;
+        let mut lines = vec![format!("Captured by the {}", if host == 1 { "host" } else { "client" })];
+        let mut counts = [0; 2]; // Received, sent
+        while !rest.is_empty() {
+            let Some(((head, data), tail)) = rest.split_at_checked(11).and_then(|(head, tail)| {
+                let len = u16::from_le_bytes([head[9], head[10]]) as usize;
+                tail.split_at_checked(len).map(|(data, tail)| ((head, data), tail))
+            }) else {
+                lines.push("(cut off mid-datagram)".to_string());
+                break;
+            };
+            let time = f64::from_le_bytes(head[..8].try_into().unwrap_or_default());
+            let sent = head[8] == 1;
+            let message = match bincode::deserialize::<Message>(data) {
+                Ok(message) => format!("{:?}", message),
+                Err(_) => "(not a message)".to_string(),
+            };
+            lines.push(format!("{:>10.3}  {}  {:>4} B  {}", time, if sent { "sent" } else { "recv" }, data.len(), message));
+            counts[sent as usize] += 1;
+            rest = tail;
+        }
+        lines.push(format!("{} datagrams: {} sent, {} received", counts[0] + counts[1], counts[1], counts[0]));
+        Ok(lines.join("\n"))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Message {
+    PlayerUpdate(Player),
+    InverseControl { active: bool, time_left: f32, by: u8, next_in: f32, armed: bool },
+    InverseRequest, // Client -> host: spend the meter on inverse mode
+    TrapEvent { event_id: u32, player_id: u8, scores: [i32; 2], streaks: [u32; 2] },
+    GameReset { game_number: u32 }, // Host -> client once both accepted; number of the new game
+    MatchConfig(MatchConfig),
+    ZoneScore { player_id: u8, points: i32 },
+    TagState { it: u8, it_time: [f32; 2], time_left: f32 },
+    HillState { time: [f32; 2] },
+    RoundStart { round: u32, countdown: f32 },
+    PauseRequest { pause: bool }, // Client -> host, host decides
+    PauseState { paused: bool, owner: u8, resume_in: f32 },
+    RematchVote { accept: bool, game_number: u32 },
+    Forfeit { player_id: u8, game_number: u32 },
+    Idle { seconds: f32 }, // Client -> host: time since the client's last input
+    PlayerInfo { player_id: u8, name: String, color: u8, cosmetics: Cosmetics, ready: bool }, // Sent while connecting and in the lobby
+    CountdownStart { stamp: f64, countdown: f32 }, // Host -> client: pre-match countdown, `countdown` seconds from `stamp` (host clock)
+    Swaps { player_id: u8, count: u32 }, // Each peer counts its own swaps for the scoreboard
+    Ping { stamp: f64 }, // Sender's clock; answered with a Pong carrying the same stamp
+    Pong { stamp: f64 },
+    AfkState { player_id: Option<u8>, time_left: f32 },
+    SpawnProtection { time_left: [f32; 2] },
+    FreezeRequest, // Client -> host: freeze the host's character
+    FreezeState { frozen: [f32; 2], charges: [u8; 2] },
+    TimeScale { scale: f32 },
+    Teleported { player_id: u8, from: Vec2 },
+    StealRequest, // Client -> host: swap with the host's shadow
+    ShadowSteal { player_id: u8, from: Vec2, to: Vec2 },
+    GameOver { winner: u8, game_number: u32 }, // Host decides who won; both sides render this
+    Clash, // Both trapped in the same tick: no score, the round restarts
+    StateHash { tick: u32, hash: u64 }, // Host -> client with every update: `SyncState::hash` at that host tick
+    ResyncRequest { tick: u32 }, // Client -> host: hashes disagreed since this host tick
+    Resync { tick: u32, state: SyncState, players: [Player; 2] }, // Host -> client: the full snapshot to adopt
+    ChatEvent(ChatEvent), // Host -> client: Twitch chat voted for this
+    Hazard(Option<Vec2>), // Host -> client with every update: where chat's gravity well is
+}
+
+impl Message {
+    /// Whether every player id in the message is 0 or 1. Anything else came from a broken or
+    /// hostile peer, and would index past the two players.
+    fn ids_valid(&self) -> bool {
+        let ids: Vec<u8> = match self {
+            Message::PlayerUpdate(player) => vec![player.id],
+            Message::InverseControl { by, .. } => vec![*by],
+            Message::TrapEvent { player_id, .. }
+            | Message::ZoneScore { player_id, .. }
+            | Message::Forfeit { player_id, .. }
+            | Message::PlayerInfo { player_id, .. }
+            | Message::Swaps { player_id, .. }
+            | Message::Teleported { player_id, .. }
+            | Message::ShadowSteal { player_id, .. } => vec![*player_id],
+            Message::TagState { it, .. } => vec![*it],
+            Message::PauseState { owner, .. } => vec![*owner],
+            Message::AfkState { player_id, .. } => player_id.iter().copied().collect(),
+            Message::GameOver { winner, .. } => vec![*winner],
+            Message::Resync { state, players, .. } => [state.inverse.2, state.tag_it]
+                .into_iter()
+                .chain(state.forfeited)
+                .chain(state.game_over)
+                .chain(players.iter().map(|player| player.id))
+                .collect(),
+            _ => Vec::new(),
+        };
+        ids.iter().all(|&id| id < 2)
+    }
+}
+
+/// The part of the match the host decides and the client must mirror exactly: scores, rounds,
+/// inversions, freezes, the modes' standings and the result. Positions aren't in it, since each
+/// side sees the other's pieces a little late; a `Resync` carries those separately.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct SyncState {
+    game_number: u32,
+    scores: [i32; 2],
+    streaks: [u32; 2],
+    trap_event_id: u32,
+    round: u32,
+    round_phase: RoundPhase,
+    paused: bool,
+    inverse: (bool, bool, u8), // Active, armed, by whom
+    frozen: [bool; 2],
+    freeze_charges: [u8; 2],
+    zone_points: [i32; 2],
+    tag_it: u8,
+    forfeited: Option<u8>,
+    game_over: Option<u8>,
+}
+
+impl SyncState {
+    fn hash(&self) -> u64 {
+        fnv1a(&bincode::serialize(self).unwrap_or_default())
+    }
+}
+
+/// 64-bit FNV-1a, which unlike std's hasher gives the same answer on every build and machine.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Something worth telling the player about. Every toast, event log line and spoken
+/// announcement starts as one of these, queued with `GameState::emit`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GameEvent {
+    Connected(usize),
+    Disconnected(usize), // Nothing heard from them for PEER_TIMEOUT
+    Reconnected(usize),
+    InverseSoon(f32), // Seconds until the inversion
+    InverseStarted,
+    InverseEnded,
+    Trapped(usize), // Who got trapped
+    MatchPoint, // Someone is one trap from losing
+    Swapped(usize), // Who swapped with their shadow (particles only, too frequent to announce)
+    Clash,
+    Forfeited(usize),
+    Won(usize),
+    PacketLoss(f32),
+    ConnectionRecovered,
+    ChatVote(ChatEvent), // What Twitch chat voted for
+}
+
+impl GameEvent {
+    /// Connection and schedule news also gets a corner notification; the rest already has a banner.
+    fn toast(self) -> bool {
+        matches!(
+            self,
+            GameEvent::Connected(_)
+                | GameEvent::Disconnected(_)
+                | GameEvent::Reconnected(_)
+                | GameEvent::InverseSoon(_)
+                | GameEvent::PacketLoss(_)
+                | GameEvent::ConnectionRecovered
+                | GameEvent::ChatVote(_)
+        )
+    }
+
+    /// The event's fields in a match report, after its time.
+    fn json(self) -> String {
+        let (kind, player, value) = match self {
+            GameEvent::Connected(id) => ("connected", Some(id), None),
+            GameEvent::Disconnected(id) => ("disconnected", Some(id), None),
+            GameEvent::Reconnected(id) => ("reconnected", Some(id), None),
+            GameEvent::InverseSoon(seconds) => ("inverse_soon", None, Some(("seconds", seconds))),
+            GameEvent::InverseStarted => ("inverse_started", None, None),
+            GameEvent::InverseEnded => ("inverse_ended", None, None),
+            GameEvent::Trapped(id) => ("trapped", Some(id), None),
+            GameEvent::MatchPoint => ("match_point", None, None),
+            GameEvent::Swapped(id) => ("swapped", Some(id), None),
+            GameEvent::Clash => ("clash", None, None),
+            GameEvent::Forfeited(id) => ("forfeited", Some(id), None),
+            GameEvent::Won(id) => ("won", Some(id), None),
+            GameEvent::PacketLoss(share) => ("packet_loss", None, Some(("share", share))),
+            GameEvent::ConnectionRecovered => ("connection_recovered", None, None),
+            GameEvent::ChatVote(ChatEvent::Hazard) => ("chat_hazard", None, None),
+            GameEvent::ChatVote(ChatEvent::Shrink) => ("chat_shrink", None, None),
+        };
+        let mut json = format!("\"type\": \"{}\"", kind);
+        if let Some(id) = player {
+            json += &format!(", \"player\": {}", id);
+        }
+        if let Some((name, value)) = value {
+            json += &format!(", \"{}\": {:.2}", name, value);
+        }
+        json
+    }
+}
+
+/// `text` as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Speaks a line with the platform's text-to-speech command, without holding up the frame.
+/// Missing speech tools are silently skipped; the on-screen log still has the line.
+fn speak(text: String) {
+    std::thread::spawn(move || {
+        let commands: Vec<(&str, Vec<String>)> = if cfg!(target_os = "macos") {
+            vec![("say", vec![text])]
+        } else if cfg!(windows) {
+            let script = format!(
+                "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak('{}')",
+                text.replace('\'', "''")
+            );
+            vec![("powershell", vec!["-NoProfile".to_string(), "-Command".to_string(), script])]
+        } else {
+            vec![("spd-say", vec![text.clone()]), ("espeak", vec![text])]
+        };
+        for (program, args) in commands {
+            if Command::new(program).args(args).status().is_ok() {
+                break;
+            }
+        }
+    });
+}
+
+/// One spark, puff or wisp, in arena coordinates. Dead once `life` runs out.
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    pos: Vector2,
+    vel: Vector2,
+    life: f32, // Seconds left
+    lifetime: f32, // Seconds it started with, for fading
+    size: f32,
+    color: Color,
+}
+
+const DEAD_PARTICLE: Particle = Particle {
+    pos: Vector2 { x: 0.0, y: 0.0 },
+    vel: Vector2 { x: 0.0, y: 0.0 },
+    life: 0.0,
+    lifetime: 1.0,
+    size: 0.0,
+    color: Color::BLANK,
+};
+
+/// Fixed pool of particles. Spawning takes the next slot round the pool, overwriting the oldest
+/// particle once it's full, so effects never allocate during play. Purely visual and local.
+struct Particles {
+    pool: [Particle; MAX_PARTICLES],
+    next: usize, // Slot the next particle goes in
+}
+
+impl Particles {
+    fn new() -> Self {
+        Particles { pool: [DEAD_PARTICLE; MAX_PARTICLES], next: 0 }
+    }
+
+    fn spawn(&mut self, pos: Vector2, vel: Vector2, lifetime: f32, size: f32, color: Color) {
+        self.pool[self.next] = Particle { pos, vel, life: lifetime, lifetime, size, color };
+        self.next = (self.next + 1) % MAX_PARTICLES;
+    }
+
+    /// `count` particles flying out of `pos` in every direction, at up to `speed`.
+    fn burst(&mut self, pos: Vector2, count: usize, speed: f32, lifetime: f32, size: f32, color: Color) {
+        for _ in 0..count {
+            let angle = rand::random_range(0.0..std::f32::consts::TAU);
+            let vel = Vector2::new(angle.cos(), angle.sin()) * rand::random_range(0.3..1.0) * speed;
+            let lifetime = lifetime * rand::random_range(0.6..1.0);
+            self.spawn(pos, vel, lifetime, size * rand::random_range(0.6..1.2), color);
+        }
+    }
+
+    /// `count` swap-sized particles evenly spaced on a circle of `radius` around `pos`, moving out
+    /// at `speed` (in, when negative).
+    fn ring(&mut self, pos: Vector2, radius: f32, count: usize, speed: f32, lifetime: f32, color: Color) {
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            let dir = Vector2::new(angle.cos(), angle.sin());
+            self.spawn(pos + dir * radius, dir * speed, lifetime, 4.0, color);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let keep = (1.0 - PARTICLE_DRAG * dt).max(0.0);
+        for particle in self.pool.iter_mut().filter(|particle| particle.life > 0.0) {
+            particle.life -= dt;
+            particle.pos += particle.vel * dt;
+            particle.vel *= keep;
+        }
+    }
+
+    /// Live particles, fading and shrinking as they age.
+    fn draw(&self, d: &mut RaylibDrawHandle) {
+        for particle in self.pool.iter().filter(|particle| particle.life > 0.0) {
+            let t = particle.life / particle.lifetime;
+            let color = Color::new(particle.color.r, particle.color.g, particle.color.b, (particle.color.a as f32 * t) as u8);
+            d.draw_circle_v(particle.pos, particle.size * (0.4 + 0.6 * t), color);
+        }
+    }
+}
+
+/// Why something the player asked for couldn't happen. Shown in the window, in the player's
+/// language, so a busy port or a mistyped address never crashes the game.
+#[derive(Debug, thiserror::Error)]
+enum GameError {
+    #[error("{}", tr!("error-port-in-use", port = .0))]
+    PortInUse(u16),
+    #[error("{}", tr!("error-resolve", address = .0))]
+    Resolve(String),
+    #[error("{}", tr!("error-network", error = .0))]
+    Network(#[from] std::io::Error),
+    #[error("{}", tr!("error-no-address"))]
+    NoAddress,
+    #[error("{}", tr!("error-puzzle-levels", error = .0))]
+    PuzzleLevels(String),
+    #[error("{}", tr!("error-profile-exists", name = .0))]
+    ProfileExists(String),
+    #[error("{}", tr!("error-graphics", error = .0))]
+    Graphics(String),
+    #[error("{}", tr!("error-profile-load", name = .0, error = .1))]
+    ProfileLoad(String, String),
+}
+
+/// How datagrams travel between the two players. Plain UDP is the only one built in; another
+/// backend would implement this too, giving each of its peers a stand-in address.
+trait Transport {
+    /// Sends one datagram: to `to` on the host, to the host on the client. Returns whether it went.
+    fn send(&self, data: &[u8], to: Option<SocketAddr>) -> bool;
+
+    /// The next datagram that has arrived and who sent it, without waiting.
+    fn receive(&self, buf: &mut [u8]) -> Option<(usize, SocketAddr)>;
+
+    /// Who the client is talking to, for the connecting screen.
+    fn peer(&self) -> Option<SocketAddr>;
+}
+
+impl Transport for UdpSocket {
+    fn send(&self, data: &[u8], to: Option<SocketAddr>) -> bool {
+        match to {
+            Some(addr) => self.send_to(data, addr).is_ok(),
+            None => UdpSocket::send(self, data).is_ok(),
+        }
+    }
+
+    fn receive(&self, buf: &mut [u8]) -> Option<(usize, SocketAddr)> {
+        self.recv_from(buf).ok()
+    }
+
+    fn peer(&self) -> Option<SocketAddr> {
+        self.peer_addr().ok()
+    }
+}
+
+struct GameState {
+    players: [Player; 2],
+    is_host: bool,
+    player_id: u8,
+    last_send: Instant,
+    transport: Option<Box<dyn Transport>>, // None offline
+    client_addr: Option<SocketAddr>,
+    kicked: Option<SocketAddr>, // Host: a client the admin console kicked, no longer listened to
+    arena_rotation: Vec<Arena>, // Host: arenas each rematch moves through, set from the admin console
+    inverse_active: bool,
+    inverse_timer: f32,
+    inverse_by: u8, // Player who triggered the current inverse mode
+    inverse_cooldown: f32, // Clock-driven schedules, or an armed meter: time until the next inversion
+    inverse_armed: bool, // Meter schedule: a spent meter's inversion is counting down
+    last_steered: [Vec2; 2], // Host: where each player's steered piece was last frame
+    trap_flash_timer: [f32; 2], // Visual feedback when trapped
+    streaks: [u32; 2], // Traps in a row by each player without being trapped (host decides)
+    trap_event_id: u32, // Host: ID of the last trap event sent; client: last one applied
+    streak_callout: f32, // Time left on the streak banner
+    clash_fx: f32, // Time left on the clash callout
+    echo_trail: [VecDeque<(f32, Vec2)>; 2], // Echo shadows: each character's recent path, timestamped
+    echo_clock: f32, // Echo shadows: simulation time the trails are stamped with
+    motion_trails: [[VecDeque<Vec2>; 2]; 2], // Each player's recent character and shadow positions, newest last
+    trail_timer: f32, // Time since the last motion trail point
+    history: VecDeque<Snapshot>, // Recent frames, replayed by the killcam after a trap
+    history_timer: f32, // Time since the last recorded frame
+    replay_frames: Vec<ReplayFrame>, // This game so far, for its replay file
+    replay_timer: f32, // Time since the last replay frame
+    replay_started: u64, // Unix seconds of the first replay frame
+    challenge: Challenge, // What the replay counts towards, for ghosts
+    spawn_protection: [f32; 2], // Trap immunity left after a reset (counts down while live)
+    frozen: [f32; 2], // Time each character stays frozen (host enforces)
+    hazard: Option<(Vec2, f32)>, // Twitch chat's gravity well and its time left (host's clock; the client is sent where)
+    shrink: Option<(f32, f32)>, // Twitch chat's trap radius shrink: time left, and the radius to go back to
+    freeze_charges: [u8; 2], // Freezes each player has left this game
+    game_time: f32, // For visual effects
+    time_scale: f32, // Simulation speed; dips for slow-mo (host decides)
+    slowmo_timer: f32, // Host: real time left before slow-mo eases out
+    config: MatchConfig,
+    zone_points: [i32; 2], // Capture Zones score per player
+    zone_timer: [f32; 2], // Time the current zone hold has lasted (host only)
+    tag_it: u8, // Tag: player who is currently "it"
+    tag_time: [f32; 2], // Tag: total time each player has been "it"
+    tag_time_left: f32, // Tag: remaining match time
+    tag_immunity: f32, // Tag: no tag-backs while this is running (host only)
+    hill_time: [f32; 2], // King of the Hill: time each player has held the hill
+    round: u32,
+    round_phase: RoundPhase,
+    round_timer: f32, // Time left in the intermission or countdown
+    paused: bool, // Simulation frozen on both peers (host decides)
+    pause_owner: u8,
+    resume_timer: f32, // Counting down to resume while > 0
+    rematch_votes: [Option<bool>; 2], // Game over: each player's rematch answer
+    forfeited: Option<u8>, // Player who conceded the match
+    game_over: Option<u8>, // Winner announced by the host, the single source of truth
+    game_number: u32, // Bumped on every reset, so late packets from the previous game are ignored
+    idle_time: f32, // Time since our last input during play
+    peer_idle: f32, // Host: idle time reported by the client
+    last_packet: Instant, // Host: when the client was last heard from
+    peer_seen: bool, // Heard from the other player at least once
+    names: [String; 2], // Display names, "Player N" until one is set
+    colors: [u8; 2], // Picked color indices (see `color_index` for clashes)
+    cosmetics: [Cosmetics; 2], // Each player's trail and swap effect
+    accessibility: Accessibility, // Local display settings
+    stats: [PlayerStats; 2], // This game's scoreboard counters
+    play_time: f32, // Real seconds this game has been in play, for the match history
+    series: [u32; 2], // Games won by each player since connecting
+    clock: Instant, // Reference for ping stamps
+    last_ping: Instant,
+    ping_ms: Option<f32>, // Smoothed round trip to the other player
+    pings_out: VecDeque<f64>, // Stamps of pings still waiting for their pong
+    ping_results: VecDeque<bool>, // Whether each recent ping was answered, newest last
+    lossy: bool, // Packet loss warning raised and not yet cleared
+    inverse_warned: bool, // Scheduled inversion already announced
+    inverse_tick: Option<u8>, // Whole seconds left on the countdown cue's last tick
+    toasts: VecDeque<(String, f32)>, // Corner notifications and their age, newest last
+    events: VecDeque<GameEvent>, // Event bus: queued by `emit`, handed out by `update_events`
+    sounds: Vec<(Sfx, Playback)>, // Sound effects due this frame, played by the `Mixer`
+    lines: Vec<Line>, // Announcer lines due this frame, queued by the `Announcer`
+    match_point_called: bool, // MatchPoint already emitted for the current scores
+    event_log: VecDeque<String>, // Recent announcements for the accessible log, newest last
+    inverse_announced: bool, // Inversion state last announced, to catch starts and ends
+    peer_lost: bool, // Opponent went quiet for PEER_TIMEOUT
+    packets: (u32, u32), // Packets received and sent since `packets_since`
+    packet_rates: (u32, u32), // Packets in and out over the last full second
+    packets_since: Instant,
+    last_update: [Option<Instant>; 2], // When a PlayerUpdate for each player last arrived
+    correction: [f32; 2], // How far the last PlayerUpdate moved each character from our copy (smoothed)
+    netsim_loss: f32, // Dev console: share of outgoing packets dropped on purpose
+    net_dump: Option<NetDump>, // --net-dump: where every datagram is written
+    ready: [bool; 2], // Lobby: each player's ready toggle
+    countdown_stamp: f64, // Client: host clock stamp of the newest CountdownStart applied
+    go_flash: f32, // Time left on the "GO!" banner
+    afk_player: Option<u8>, // Player currently flagged AFK
+    afk_grace: f32, // Time the AFK player has left before forfeiting
+    forfeit_hold: f32, // How long the forfeit key has been held
+    teleport_cooldown: [f32; 2], // Host: time until each player can teleport again
+    teleport_fx: [(Vec2, f32); 2], // Where each player last teleported from, and flash time left
+    steal_fx: (u8, Vec2, Vec2, f32), // Last shadow steal: thief, from, to, and effect time left
+    particles: Particles, // Swap bursts, trap explosions, inverse sparks and shadow wisps
+    swap_fx: [f32; 2], // Time left on each character's swap squash
+    seed: u64, // What `rng` started from
+    rng: StdRng, // Simulation randomness (random inverse gaps); effects use the thread RNG
+    input_log: Option<(PathBuf, InputLog)>, // --record-inputs: where to save, and the ticks so far
+    tick: u32, // Simulation ticks run since connecting; the host's number each `StateHash`
+    desync_since: Option<(u32, Instant)>, // Client: host tick and time our state hash first disagreed
+    desyncs: u32, // Desyncs since connecting: found by the client, reported to the host
+    timeline: Vec<(f32, GameEvent)>, // This game's events as handed out, stamped with `play_time`, for its report
+    heatmap: Heatmap, // This game's time per arena cell and trap spots
+}
+
+impl GameState {
+    fn new(is_host: bool, config: MatchConfig) -> Self {
+        GameState::seeded(is_host, config, rand::random())
+    }
+
+    /// A game whose random timings all come from `seed`, so an input recording plays it again exactly.
+    fn seeded(is_host: bool, config: MatchConfig, seed: u64) -> Self {
+        let player_id = if is_host { 0 } else { 1 };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut game = GameState {
+            players: [
+                Player {
+                    id: 0,
+                    pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 },
+                    shadow_pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 + 100.0 },
+                    score: 0,
+                    is_trapped: false,
+                    warps: 0,
+                    inverse_charge: 0.0,
+                },
+                Player {
+                    id: 1,
+                    pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 },
+                    shadow_pos: Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 - 100.0 },
+                    score: 0,
+                    is_trapped: false,
+                    warps: 0,
+                    inverse_charge: 0.0,
+                },
+            ],
+            is_host,
+            player_id,
+            last_send: Instant::now(),
+            transport: None,
+            client_addr: None,
+            kicked: None,
+            arena_rotation: Vec::new(),
+            inverse_active: false,
+            inverse_timer: 0.0,
+            inverse_by: 0,
+            inverse_cooldown: config.inverse_schedule.next_gap(&config.rules, &mut rng).unwrap_or(0.0),
+            inverse_armed: false,
+            last_steered: [Vec2 { x: 0.0, y: 0.0 }; 2],
+            trap_flash_timer: [0.0, 0.0],
+            streaks: [0, 0],
+            trap_event_id: 0,
+            streak_callout: 0.0,
+            clash_fx: 0.0,
+            echo_trail: [VecDeque::new(), VecDeque::new()],
+            echo_clock: 0.0,
+            motion_trails: Default::default(),
+            trail_timer: 0.0,
+            history: VecDeque::with_capacity(KILLCAM_FRAMES),
+            history_timer: 0.0,
+            replay_frames: Vec::new(),
+            replay_timer: 0.0,
+            replay_started: 0,
+            challenge: Challenge::None,
+            spawn_protection: [SPAWN_PROTECTION; 2],
+            frozen: [0.0, 0.0],
+            hazard: None,
+            shrink: None,
+            freeze_charges: [FREEZE_CHARGES; 2],
+            game_time: 0.0,
+            time_scale: 1.0,
+            slowmo_timer: 0.0,
+            config,
+            zone_points: [0, 0],
+            zone_timer: [0.0, 0.0],
+            tag_it: 0,
+            tag_time: [0.0, 0.0],
+            tag_time_left: TAG_MATCH_TIME,
+            tag_immunity: 0.0,
+            hill_time: [0.0, 0.0],
+            round: 1,
+            round_phase: RoundPhase::Playing,
+            round_timer: 0.0,
+            paused: false,
+            pause_owner: 0,
+            resume_timer: 0.0,
+            rematch_votes: [None, None],
+            forfeited: None,
+            game_over: None,
+            forfeit_hold: 0.0,
+            game_number: 0,
+            idle_time: 0.0,
+            peer_idle: 0.0,
+            last_packet: Instant::now(),
+            peer_seen: false,
+            names: ["Player 1".to_string(), "Player 2".to_string()],
+            colors: [0, 1],
+            cosmetics: [Cosmetics::default(); 2],
+            accessibility: Accessibility::default(),
+            stats: [PlayerStats::default(); 2],
+            play_time: 0.0,
+            series: [0, 0],
+            clock: Instant::now(),
+            last_ping: Instant::now(),
+            ping_ms: None,
+            pings_out: VecDeque::new(),
+            ping_results: VecDeque::with_capacity(LOSS_SAMPLES),
+            lossy: false,
+            inverse_warned: false,
+            inverse_tick: None,
+            toasts: VecDeque::new(),
+            events: VecDeque::new(),
+            sounds: Vec::new(),
+            lines: Vec::new(),
+            match_point_called: false,
+            event_log: VecDeque::new(),
+            inverse_announced: false,
+            peer_lost: false,
+            packets: (0, 0),
+            packet_rates: (0, 0),
+            packets_since: Instant::now(),
+            last_update: [None, None],
+            correction: [0.0, 0.0],
+            netsim_loss: 0.0,
+            net_dump: None,
+            ready: [false, false],
+            countdown_stamp: 0.0,
+            go_flash: 0.0,
+            afk_player: None,
+            afk_grace: 0.0,
+            teleport_cooldown: [0.0, 0.0],
+            teleport_fx: [(Vec2 { x: 0.0, y: 0.0 }, 0.0); 2],
+            steal_fx: (0, Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 0.0, y: 0.0 }, 0.0),
+            particles: Particles::new(),
+            swap_fx: [0.0, 0.0],
+            seed,
+            rng,
+            input_log: None,
+            tick: 0,
+            desync_since: None,
+            desyncs: 0,
+            timeline: Vec::new(),
+            heatmap: Heatmap::default(),
+        };
+        game.reset_positions();
+        for (player, handicap) in game.players.iter_mut().zip(config.handicaps) {
+            player.score = handicap.preloaded_traps;
+        }
+        game
+    }
+
+    fn connect(&mut self, addr: &str) -> Result<(), GameError> {
+        let socket = if self.is_host {
+            let port = tunables().port;
+            UdpSocket::bind(("0.0.0.0", port)).map_err(|e| match e.kind() {
+                std::io::ErrorKind::AddrInUse => GameError::PortInUse(port),
+                _ => e.into(),
+            })?
+        } else {
+            let host = addr.to_socket_addrs().ok().and_then(|mut found| found.next()).ok_or_else(|| GameError::Resolve(addr.to_string()))?;
+            let sock = UdpSocket::bind("0.0.0.0:0")?;
+            sock.connect(host)?;
+            sock
+        };
+        socket.set_nonblocking(true)?;
+        self.transport = Some(Box::new(socket));
+        Ok(())
+    }
+
+    fn send_message(&mut self, msg: Message) {
+        if self.netsim_loss > 0.0 && rand::random::<f32>() < self.netsim_loss {
+            return; // Simulated packet loss
+        }
+        if let Some(transport) = &self.transport
+            && let Ok(data) = bincode::serialize(&msg)
+        {
+            let sent = if self.is_host {
+                self.client_addr.is_some_and(|addr| transport.send(&data, Some(addr)))
+            } else {
+                transport.send(&data, None)
+            };
+            if sent {
+                self.packets.1 += 1;
+                if let Some(dump) = &mut self.net_dump {
+                    dump.record(true, &data);
+                }
+            }
+        }
+    }
+
+    /// Sets a display name, trimmed and cut to MAX_NAME_LEN. Blank names keep the current one.
+    fn set_name(&mut self, id: usize, name: &str) {
+        let name: String = name.trim().chars().filter(|c| !c.is_control()).take(MAX_NAME_LEN).collect();
+        if !name.is_empty() {
+            self.names[id] = name;
+        }
+    }
+
+    /// Tells the other player our display name, color, cosmetics and whether we're ready.
+    fn send_player_info(&mut self) {
+        let name = self.names[self.player_id as usize].clone();
+        let color = self.colors[self.player_id as usize];
+        let cosmetics = self.cosmetics[self.player_id as usize];
+        let ready = self.ready[self.player_id as usize];
+        self.send_message(Message::PlayerInfo { player_id: self.player_id, name, color, cosmetics, ready });
+    }
+
+    /// Color index a player is drawn with. Player 1 always gets their pick;
+    /// if player 2 picked the same color, they get the next PLAYER_COLORS one instead.
+    fn color_index(&self, id: usize) -> usize {
+        let pick = |i: usize| self.colors[i] as usize % (PLAYER_COLORS.len() + EXTRA_COLORS.len());
+        if id == 1 && pick(1) == pick(0) {
+            (pick(1) + 1) % PLAYER_COLORS.len()
+        } else {
+            pick(id)
+        }
+    }
+
+    /// Color a player is drawn with on this screen: their pick, unless an accessibility palette overrides it.
+    fn player_color(&self, id: usize) -> Color {
+        match self.accessibility.palette.colors() {
+            Some(colors) => colors[id],
+            None => color_value(self.color_index(id)),
+        }
+    }
+
+    /// Steps our own color pick through the ones we can pick (see `available_colors`).
+    fn cycle_color(&mut self, dir: i32, available: &[u8]) {
+        let me = self.player_id as usize;
+        self.colors[me] = cycle(available, self.colors[me], dir);
+    }
+
+    /// Queues a corner notification, dropping the oldest once there are too many.
+    fn toast(&mut self, text: String) {
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back((text, 0.0));
+    }
+
+    /// Share of recent pings that never got a pong, once there are enough to judge.
+    fn packet_loss(&self) -> Option<f32> {
+        if self.ping_results.len() < LOSS_SAMPLES / 2 {
+            return None;
+        }
+        let lost = self.ping_results.iter().filter(|&&answered| !answered).count();
+        Some(lost as f32 / self.ping_results.len() as f32)
+    }
+
+    /// "triggered by ..." line for a meter inversion.
+    fn inverse_trigger_text(&self) -> String {
+        if self.inverse_by == self.player_id {
+            tr!("hud-triggered-by-you")
+        } else {
+            tr!("hud-triggered-by", name = self.names[self.inverse_by as usize])
+        }
+    }
+
+    /// Queues an event on the bus; `update_events` announces it the same frame.
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push_back(event);
+    }
+
+    /// What an event says, from our point of view; nothing for events that are only shown.
+    fn describe(&self, event: GameEvent) -> Option<String> {
+        let me = self.player_id as usize;
+        let name = |id: usize| if id == me { tr!("event-you") } else { self.names[id].clone() };
+        let text = match event {
+            GameEvent::Connected(id) => tr!("event-connected", name = &self.names[id]),
+            GameEvent::Disconnected(id) => tr!("event-disconnected", name = &self.names[id]),
+            GameEvent::Reconnected(id) => tr!("event-reconnected", name = &self.names[id]),
+            GameEvent::InverseSoon(seconds) => tr!("event-inverse-soon", seconds = format!("{:.0}", seconds.max(0.0).ceil())),
+            GameEvent::InverseStarted => tr!("event-inverse-started"),
+            GameEvent::InverseEnded => tr!("event-inverse-ended"),
+            GameEvent::Trapped(id) if id == me => tr!(
+                "event-trapped-you",
+                name = &self.names[1 - id],
+                score = self.players[id].score,
+                max = WIN_SCORE
+            ),
+            GameEvent::Trapped(id) => tr!(
+                "event-you-trapped",
+                name = &self.names[id],
+                score = self.players[id].score,
+                max = WIN_SCORE
+            ),
+            GameEvent::MatchPoint => tr!("event-match-point"),
+            GameEvent::Clash => tr!("event-clash"),
+            GameEvent::Forfeited(id) => tr!("event-forfeited", name = name(id)),
+            GameEvent::Won(id) if id == me => tr!("event-you-won"),
+            GameEvent::Won(id) => tr!("event-won", name = &self.names[id]),
+            GameEvent::PacketLoss(loss) => tr!("event-packet-loss", percent = format!("{:.0}", loss * 100.0)),
+            GameEvent::ConnectionRecovered => tr!("event-connection-recovered"),
+            GameEvent::ChatVote(ChatEvent::Hazard) => tr!("event-chat-hazard"),
+            GameEvent::ChatVote(ChatEvent::Shrink) => tr!("event-chat-shrink", seconds = CHAT_EVENT_TIME),
+            GameEvent::Swapped(_) => return None,
+        };
+        Some(text)
+    }
+
+    /// Ages notifications, raises events for state changes worth a heads-up, then hands every
+    /// queued event to the toasts, the event log and speech, as the accessibility settings ask.
+    fn update_events(&mut self, real_dt: f32) {
+        for (_, age) in self.toasts.iter_mut() {
+            *age += real_dt;
+        }
+        self.toasts.retain(|(_, age)| *age < TOAST_TIME);
+
+        // Scheduled inversion coming up, starting or ending
+        let warning = self.inverse_warning() && self.is_live();
+        if warning && !self.inverse_warned {
+            self.emit(GameEvent::InverseSoon(self.inverse_cooldown));
+        }
+        self.inverse_warned = warning;
+        // Tick-tock once a second through the warning, each beat higher and louder than the last
+        let tick = warning.then(|| self.inverse_cooldown.max(0.0).ceil() as u8).filter(|&left| left > 0);
+        if tick != self.inverse_tick
+            && let Some(left) = tick
+        {
+            let beat = self.config.rules.inverse_warning - left as f32; // 0 on the first tick
+            let sfx = if left % 2 == 1 { Sfx::Tick } else { Sfx::Tock };
+            self.sounds.push((sfx, Playback { gain: 0.6 + 0.2 * beat, pitch: 1.0 + 0.12 * beat, ..Playback::CENTER }));
+        }
+        self.inverse_tick = tick;
+        let match_point = self.match_point() && self.winner().is_none();
+        if match_point && !self.match_point_called {
+            self.emit(GameEvent::MatchPoint);
+        }
+        self.match_point_called = match_point;
+        if self.inverse_active != self.inverse_announced {
+            self.inverse_announced = self.inverse_active;
+            self.emit(if self.inverse_active { GameEvent::InverseStarted } else { GameEvent::InverseEnded });
+        }
+
+        // Opponent gone quiet, or back
+        if self.transport.is_some() && self.peer_seen {
+            let peer = 1 - self.player_id as usize;
+            let lost = self.last_packet.elapsed().as_secs_f32() > PEER_TIMEOUT;
+            if lost != self.peer_lost {
+                self.peer_lost = lost;
+                self.emit(if lost { GameEvent::Disconnected(peer) } else { GameEvent::Reconnected(peer) });
+                if lost && self.winner().is_none() {
+                    self.save_replay(ReplayEnd::Disconnected);
+                }
+            }
+        }
+
+        // Pings without a pong for too long count as lost
+        let now = self.clock.elapsed().as_secs_f64();
+        while self.pings_out.front().is_some_and(|&sent| now - sent > PING_TIMEOUT as f64) {
+            self.pings_out.pop_front();
+            self.ping_results.push_back(false);
+        }
+        while self.ping_results.len() > LOSS_SAMPLES {
+            self.ping_results.pop_front();
+        }
+        if let Some(loss) = self.packet_loss() {
+            if loss >= LOSS_WARNING && !self.lossy {
+                self.lossy = true;
+                self.emit(GameEvent::PacketLoss(loss));
+            } else if loss < LOSS_WARNING / 2.0 && self.lossy {
+                self.lossy = false;
+                self.emit(GameEvent::ConnectionRecovered);
+            }
+        }
+
+        while let Some(event) = self.events.pop_front() {
+            self.timeline.push((self.play_time, event));
+            if let GameEvent::Trapped(id) = event {
+                self.heatmap.traps.push((id, self.players[id].pos));
+            }
+            if let GameEvent::Swapped(id) = event {
+                self.swap_fx[id] = SWAP_FX_TIME;
+            }
+            self.spawn_event_particles(event);
+            self.sounds.extend(self.event_sound(event));
+            self.lines.extend(Line::for_event(event, self.player_id as usize));
+            let Some(text) = self.describe(event) else {
+                continue;
+            };
+            if event.toast() {
+                self.toast(text.clone());
+            }
+            if self.accessibility.announce == Announce::Off {
+                continue;
+            }
+            println!("[event] {}", text);
+            if self.accessibility.announce == Announce::Speech {
+                speak(text.clone());
+            }
+            if self.event_log.len() == EVENT_LOG_LINES {
+                self.event_log.pop_front();
+            }
+            self.event_log.push_back(text);
+        }
+    }
+
+    /// Particle effects for an event, where it happened. Nothing with reduced motion, and nothing
+    /// that would give away a character hidden by fog.
+    fn spawn_event_particles(&mut self, event: GameEvent) {
+        if !self.accessibility.motion() {
+            return;
+        }
+        let me = self.player_id as usize;
+        match event {
+            GameEvent::Swapped(id) => {
+                // A puff where the character left (now the shadow), and their swap effect where it landed
+                let color = self.player_color(id);
+                let (pos, shadow_pos) = (Vector2::from(self.players[id].pos), Vector2::from(self.players[id].shadow_pos));
+                self.particles.burst(shadow_pos, 12, 90.0, 0.5, 4.0, Color::new(color.r, color.g, color.b, 140));
+                if self.can_see(me, id) {
+                    match self.cosmetics[id].swap {
+                        SwapEffect::Burst => self.particles.burst(pos, 24, 220.0, 0.6, 4.0, color),
+                        SwapEffect::Ring => self.particles.ring(pos, 0.0, 28, 240.0, 0.6, color),
+                        SwapEffect::Implode => self.particles.ring(pos, 70.0, 28, -140.0, 0.5, color),
+                    }
+                }
+            }
+            GameEvent::Trapped(id) => {
+                let pos = Vector2::from(self.players[id].pos);
+                let color = self.player_color(id);
+                self.particles.burst(pos, 50, 360.0, 0.9, 5.0, Color::new(255, 80, 40, 255));
+                self.particles.burst(pos, 30, 200.0, 1.2, 4.0, color);
+                self.particles.burst(pos, 20, 120.0, 0.7, 3.0, Color::new(255, 230, 120, 255));
+            }
+            GameEvent::InverseStarted => {
+                for id in 0..2 {
+                    if let Some(pos) = self.visible_steered_pos(id) {
+                        self.particles.burst(pos, 30, 260.0, 0.8, 3.0, Color::YELLOW);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The sound an event makes, placed where it happened: swaps and traps come from the
+    /// character's spot, the rest from the middle. Fog doesn't silence hidden characters, so
+    /// their swaps are still heard, roughly where they are.
+    fn event_sound(&self, event: GameEvent) -> Option<(Sfx, Playback)> {
+        let sfx = Sfx::for_event(event)?;
+        let playback = match event {
+            GameEvent::Swapped(id) | GameEvent::Trapped(id) => self.place_sound(self.players[id].pos),
+            _ => Playback::CENTER,
+        };
+        Some((sfx, playback))
+    }
+
+    /// Pan and gain for a sound at `at`, heard from our own character: panned towards its side
+    /// and quieter the farther off it is, across the edges on a wrap-around arena.
+    fn place_sound(&self, at: Vec2) -> Playback {
+        let me = self.players[self.player_id as usize].pos;
+        let (w, h) = (SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        let (mut dx, mut dy) = (at.x - me.x, at.y - me.y);
+        if self.config.wrap {
+            dx -= w * (dx / w).round();
+            dy -= h * (dy / h).round();
+        }
+        let far = ((dx * dx + dy * dy).sqrt() / SOUND_FAR).min(1.0);
+        Playback {
+            pan: 0.5 - (dx / (w / 2.0)).clamp(-1.0, 1.0) * SOUND_MAX_PAN,
+            gain: 1.0 - (1.0 - SOUND_MIN_GAIN) * far,
+            pitch: 1.0,
+        }
+    }
+
+    /// What player `i`'s character is doing, for its animation. Speed comes off the motion trail.
+    fn pose(&self, i: usize) -> Pose {
+        if self.players[i].is_trapped {
+            return Pose::Trapped;
+        }
+        if self.swap_fx[i] > 0.0 {
+            return Pose::Swapping(self.swap_fx[i] / SWAP_FX_TIME);
+        }
+        let trail = &self.motion_trails[i][0];
+        if trail.len() >= 2 {
+            let (from, to) = (trail[trail.len() - 2], trail[trail.len() - 1]);
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let speed = (dx * dx + dy * dy).sqrt() / TRAIL_SAMPLE;
+            if speed > 20.0 {
+                return Pose::Moving { speed: speed / self.config.rules.player_speed, angle: dy.atan2(dx).to_degrees() };
+            }
+        }
+        Pose::Idle
+    }
+
+    /// How player `i`'s shadow looks on this screen. It thins out and shrinks as it strays from its
+    /// owner (towards the tether range, if there is one) and swells and solidifies as the
+    /// character it can trap nears its radius. Fog hides whatever would give away a hidden character.
+    fn shadow_look(&self, i: usize) -> ShadowLook {
+        let viewer = self.player_id as usize;
+        let stretch = if self.can_see(viewer, i) {
+            let reach = if self.config.tether_range > 0.0 { self.config.tether_range } else { SHADOW_FAR };
+            (self.arena_distance(self.players[i].pos, self.players[i].shadow_pos) / reach).min(1.0)
+        } else {
+            0.0
+        };
+        let victim = 1 - i;
+        let menace = if self.can_see(viewer, victim) && !self.is_hunter(victim) { self.danger(victim) } else { 0.0 };
+        ShadowLook {
+            size: SHADOW_SIZE * (1.0 - 0.2 * stretch + 0.3 * menace),
+            alpha: (150.0 - 60.0 * stretch + 105.0 * menace).clamp(70.0, 255.0) as u8,
+            stretch,
+        }
+    }
+
+    /// Where a player's steered piece is, unless fog hides it from us.
+    fn visible_steered_pos(&self, id: usize) -> Option<Vector2> {
+        match self.steered_piece(id) {
+            Piece::Character(p) if !self.can_see(self.player_id as usize, p) => None,
+            _ => Some(Vector2::from(self.steered_pos(id))),
+        }
+    }
+
+    /// Moves the particles and keeps the steady emitters going: sparks off both steered pieces
+    /// while inverse mode is on, and slow wisps rising off each shadow. High contrast drops the
+    /// wisps with the other glow layers.
+    fn update_particles(&mut self, dt: f32) {
+        self.particles.update(dt);
+        if !self.accessibility.motion() || self.paused {
+            return;
+        }
+        let chance = |rate: f32| rand::random::<f32>() < rate * dt;
+        for id in 0..2 {
+            if self.inverse_active
+                && chance(40.0)
+                && let Some(pos) = self.visible_steered_pos(id)
+            {
+                let vel = Vector2::new(rand::random_range(-80.0..80.0), rand::random_range(-140.0..-40.0));
+                self.particles.spawn(pos, vel, 0.5, 2.5, Color::new(255, 240, 100, 255));
+            }
+            if !self.accessibility.high_contrast && !self.is_hunter(id) && chance(10.0) {
+                let color = self.player_color(id);
+                let shadow = self.players[id].shadow_pos;
+                let pos = Vector2::new(shadow.x + rand::random_range(-SHADOW_SIZE..SHADOW_SIZE), shadow.y);
+                let vel = Vector2::new(rand::random_range(-10.0..10.0), rand::random_range(-40.0..-15.0));
+                self.particles.spawn(pos, vel, 1.2, 3.0, Color::new(color.r / 2, color.g / 2, color.b / 2, 110));
+            }
+        }
+    }
+
+    /// Multi-line summary of the simulation for the dev console's `state dump`.
+    fn dump(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{} #{} round {} {:?}, {}",
+                if self.is_host { "host" } else { "client" },
+                self.game_number,
+                self.round,
+                self.round_phase,
+                if self.paused { "paused" } else { "running" }
+            ),
+            format!(
+                "inverse: active {} ({:.1}s), armed {}, next {:.1}s, by P{}",
+                self.inverse_active,
+                self.inverse_timer,
+                self.inverse_armed,
+                self.inverse_cooldown,
+                self.inverse_by + 1
+            ),
+        ];
+        for (i, player) in self.players.iter().enumerate() {
+            lines.push(format!(
+                "P{} {}: pos ({:.0}, {:.0}) shadow ({:.0}, {:.0}) trapped {} times, meter {:.2}, streak {}",
+                i + 1,
+                self.names[i],
+                player.pos.x,
+                player.pos.y,
+                player.shadow_pos.x,
+                player.shadow_pos.y,
+                player.score,
+                player.inverse_charge,
+                self.streaks[i]
+            ));
+        }
+        lines.push(format!("config: {:?}", self.config));
+        lines.join("\n")
+    }
+
+    /// Periodic state broadcast (`send_rate` times a second): both players, and host-owned state from the host.
+    fn send_updates(&mut self) {
+        if self.last_send.elapsed().as_secs_f32() >= 1.0 / tunables().send_rate as f32 {
+            // Always send our own player update
+            self.send_message(Message::PlayerUpdate(self.players[self.player_id as usize]));
+            
+            // If we're controlling the opponent's shadow/character, send their update too
+            let other_id = 1 - self.player_id as usize;
+            self.send_message(Message::PlayerUpdate(self.players[other_id]));
+            self.send_message(Message::Swaps {
+                player_id: self.player_id,
+                count: self.stats[self.player_id as usize].swaps,
+            });
+            if self.last_ping.elapsed().as_secs_f32() >= 1.0 {
+                let stamp = self.clock.elapsed().as_secs_f64();
+                self.send_message(Message::Ping { stamp });
+                if self.peer_seen {
+                    self.pings_out.push_back(stamp);
+                }
+                self.last_ping = Instant::now();
+            }
+            
+            if self.is_host {
+                self.send_inverse_state();
+                self.send_message(Message::MatchConfig(self.config));
+                self.send_message(Message::Hazard(self.hazard.map(|(pos, _)| pos)));
+                self.send_message(Message::StateHash { tick: self.tick, hash: self.sync_state().hash() });
+                if self.round_phase == RoundPhase::Countdown && self.round == 1 {
+                    // Only the pre-match countdown runs in round 1
+                    let stamp = self.clock.elapsed().as_secs_f64();
+                    self.send_message(Message::CountdownStart { stamp, countdown: self.round_timer });
+                } else if self.round_phase == RoundPhase::Countdown {
+                    self.send_message(Message::RoundStart { round: self.round, countdown: self.round_timer });
+                }
+                self.send_pause_state();
+                match self.config.mode {
+                    GameMode::Tag => self.send_message(Message::TagState {
+                        it: self.tag_it,
+                        it_time: self.tag_time,
+                        time_left: self.tag_time_left,
+                    }),
+                    GameMode::KingOfTheHill => self.send_message(Message::HillState { time: self.hill_time }),
+                    _ => {}
+                }
+                self.send_message(Message::AfkState { player_id: self.afk_player, time_left: self.afk_grace });
+                self.send_message(Message::SpawnProtection { time_left: self.spawn_protection });
+                self.send_message(Message::FreezeState { frozen: self.frozen, charges: self.freeze_charges });
+                self.send_message(Message::TimeScale { scale: self.time_scale });
+            } else {
+                self.send_message(Message::Idle { seconds: self.idle_time });
+            }
+            // Keep repeating the result so a dropped packet can't leave the client without one
+            if self.is_host && let Some(winner) = self.game_over {
+                self.send_message(Message::GameOver { winner, game_number: self.game_number });
+            }
+            // Keep repeating our rematch answer, it's the only way out of the victory screen
+            if self.winner().is_some() && let Some(accept) = self.rematch_votes[self.player_id as usize] {
+                self.send_message(Message::RematchVote { accept, game_number: self.game_number });
+            }
+            // Keep repeating a forfeit so a dropped packet can't leave the opponent hanging
+            if let Some(player_id) = self.forfeited {
+                self.send_message(Message::Forfeit { player_id, game_number: self.game_number });
+            }
+            self.last_send = Instant::now();
+        }
+    }
+
+    fn receive_messages(&mut self) {
+        let mut should_reset = None;
+        let mut round_start = None;
+        let mut pause_request = None;
+        let mut inverse_request = false;
+        let mut freeze_request = false;
+        let mut steal_request = false;
+        let mut peer_info = None;
+        let mut pong = None;
+        let mut countdown_start = None;
+        let mut state_hash = None;
+        let mut resync_request = None;
+        let mut resync = None;
+        let was_seen = self.peer_seen;
+        if let Some(transport) = &self.transport {
+            let mut buf = [0u8; 1024];
+            while let Some((size, peer_addr)) = transport.receive(&mut buf) {
+                if self.kicked == Some(peer_addr) {
+                    continue;
+                }
+                if self.is_host && self.client_addr.is_none() {
+                    self.client_addr = Some(peer_addr);
+                }
+                if self.is_host && self.client_addr != Some(peer_addr) {
+                    continue; // Only the client playing this match is listened to
+                }
+                self.last_packet = Instant::now();
+                self.peer_seen = true;
+                self.packets.0 += 1;
+                if let Some(dump) = &mut self.net_dump {
+                    dump.record(false, &buf[..size]);
+                }
+                
+                if let Ok(msg) = bincode::deserialize::<Message>(&buf[..size])
+                    && msg.ids_valid()
+                {
+                    match msg {
+                        Message::PlayerUpdate(player) => {
+                            let current = self.players[player.id as usize];
+                            if self.is_host && player.warps != current.warps {
+                                // Sent before the client saw our teleport or steal; the host's position wins
+                                continue;
+                            }
+                            let mut player = player;
+                            if self.is_host {
+                                player.inverse_charge = current.inverse_charge; // Host owns the meters
+                                if self.frozen[player.id as usize] > 0.0 {
+                                    player.pos = current.pos; // Frozen characters stay put
+                                }
+                            }
+                            // Always update the player data we receive
+                            let id = player.id as usize;
+                            let jump = current.pos.distance(player.pos);
+                            self.correction[id] = self.correction[id] * 0.9 + jump * 0.1;
+                            self.last_update[id] = Some(Instant::now());
+                            self.players[id] = player;
+                        }
+                        Message::InverseControl { active, time_left, by, next_in, armed } => {
+                            self.inverse_active = active;
+                            self.inverse_timer = time_left;
+                            self.inverse_by = by;
+                            self.inverse_cooldown = next_in;
+                            self.inverse_armed = armed;
+                        }
+                        Message::InverseRequest => {
+                            if self.is_host {
+                                inverse_request = true;
+                            }
+                        }
+                        Message::TrapEvent { event_id, player_id, scores, streaks } => {
+                            // IDs only go up, so duplicated or late packets are applied at most once
+                            if event_id <= self.trap_event_id {
+                                continue;
+                            }
+                            self.trap_event_id = event_id;
+                            let pid = player_id as usize;
+                            self.players[pid].is_trapped = true;
+                            self.players[0].score = scores[0];
+                            self.players[1].score = scores[1];
+                            self.streaks = streaks;
+                            if streaks[1 - pid] >= 2 {
+                                self.streak_callout = STREAK_CALLOUT_TIME;
+                            }
+                            self.trap_flash_timer[pid] = 1.0;
+                            self.events.push_back(GameEvent::Trapped(pid));
+                            self.stats[pid].traps_received += 1;
+                            self.stats[1 - pid].traps_dealt += 1;
+                            self.round_phase = RoundPhase::Intermission;
+                            self.round_timer = self.config.rules.intermission;
+                        }
+                        Message::GameReset { game_number } => {
+                            // Only the host restarts, and a duplicated packet must not reset twice
+                            if !self.is_host && game_number > self.game_number {
+                                should_reset = Some(game_number);
+                            }
+                        }
+                        Message::MatchConfig(config) => {
+                            self.config = config;
+                        }
+                        Message::ZoneScore { player_id, points } => {
+                            self.zone_points[player_id as usize] = points;
+                        }
+                        Message::TagState { it, it_time, time_left } => {
+                            self.tag_it = it;
+                            self.tag_time = it_time;
+                            self.tag_time_left = time_left;
+                        }
+                        Message::HillState { time } => {
+                            self.hill_time = time;
+                        }
+                        Message::RoundStart { round, countdown } => {
+                            // Resent while counting down, so only the first copy starts the round
+                            if round > self.round {
+                                round_start = Some((round, countdown));
+                            }
+                        }
+                        Message::PauseRequest { pause } => {
+                            if self.is_host {
+                                pause_request = Some(pause);
+                            }
+                        }
+                        Message::Forfeit { player_id, game_number } => {
+                            if game_number == self.game_number && self.forfeited.is_none() && self.winner().is_none() {
+                                self.forfeited = Some(player_id);
+                                self.events.push_back(GameEvent::Forfeited(player_id as usize));
+                            }
+                        }
+                        Message::Idle { seconds } => {
+                            self.peer_idle = seconds;
+                        }
+                        Message::Swaps { player_id, count } => {
+                            if player_id != self.player_id && player_id < 2 {
+                                // Their swap count going up is how we hear about their swaps
+                                if count > self.stats[player_id as usize].swaps {
+                                    self.events.push_back(GameEvent::Swapped(player_id as usize));
+                                }
+                                self.stats[player_id as usize].swaps = count;
+                            }
+                        }
+                        Message::Ping { stamp } => {
+                            pong = Some(stamp);
+                        }
+                        Message::Pong { stamp } => {
+                            let rtt = ((self.clock.elapsed().as_secs_f64() - stamp) * 1000.0) as f32;
+                            self.ping_ms = Some(self.ping_ms.map_or(rtt, |ping| ping * 0.8 + rtt * 0.2));
+                            if let Some(i) = self.pings_out.iter().position(|&sent| sent == stamp) {
+                                self.pings_out.remove(i);
+                                self.ping_results.push_back(true);
+                            }
+                        }
+                        Message::PlayerInfo { player_id, name, color, cosmetics, ready } => {
+                            if player_id != self.player_id && player_id < 2 {
+                                peer_info = Some((name, color, cosmetics, ready));
+                            }
+                        }
+                        Message::CountdownStart { stamp, countdown } => {
+                            // Resent every tick; newer stamps just re-sync the clock
+                            if !self.is_host && stamp > self.countdown_stamp {
+                                self.countdown_stamp = stamp;
+                                let latency = self.ping_ms.unwrap_or(0.0) / 2000.0;
+                                countdown_start = Some((countdown - latency).max(0.0));
+                            }
+                        }
+                        Message::FreezeRequest => {
+                            if self.is_host {
+                                freeze_request = true;
+                            }
+                        }
+                        Message::Teleported { player_id, from } => {
+                            self.teleport_fx[player_id as usize] = (from, TELEPORT_FX_TIME);
+                        }
+                        Message::StealRequest => {
+                            if self.is_host {
+                                steal_request = true;
+                            }
+                        }
+                        Message::ChatEvent(event) => {
+                            self.events.push_back(GameEvent::ChatVote(event));
+                        }
+                        Message::Hazard(at) => {
+                            self.hazard = at.map(|pos| (pos, 0.0));
+                        }
+                        Message::Clash => {
+                            self.clash_fx = CLASH_FX_TIME;
+                            self.events.push_back(GameEvent::Clash);
+                        }
+                        Message::GameOver { winner, game_number } => {
+                            if game_number == self.game_number && self.game_over.is_none() && winner < 2 {
+                                self.game_over = Some(winner);
+                                self.series[winner as usize] += 1;
+                                self.events.push_back(GameEvent::Won(winner as usize));
+                            }
+                        }
+                        Message::ShadowSteal { player_id, from, to } => {
+                            self.steal_fx = (player_id, from, to, STEAL_FX_TIME);
+                        }
+                        Message::TimeScale { scale } => {
+                            self.time_scale = scale;
+                        }
+                        Message::FreezeState { frozen, charges } => {
+                            self.frozen = frozen;
+                            self.freeze_charges = charges;
+                        }
+                        Message::SpawnProtection { time_left } => {
+                            self.spawn_protection = time_left;
+                        }
+                        Message::AfkState { player_id, time_left } => {
+                            self.afk_player = player_id;
+                            self.afk_grace = time_left;
+                        }
+                        Message::RematchVote { accept, game_number } => {
+                            if game_number == self.game_number && self.winner().is_some() {
+                                self.rematch_votes[1 - self.player_id as usize] = Some(accept);
+                            }
+                        }
+                        Message::PauseState { paused, owner, resume_in } => {
+                            self.paused = paused;
+                            self.pause_owner = owner;
+                            self.resume_timer = resume_in;
+                        }
+                        Message::StateHash { tick, hash } => {
+                            if !self.is_host {
+                                state_hash = Some((tick, hash)); // Checked after the rest of the batch is applied
+                            }
+                        }
+                        Message::ResyncRequest { tick } => {
+                            if self.is_host {
+                                resync_request = Some(tick);
+                            }
+                        }
+                        Message::Resync { tick, state, players } => {
+                            if !self.is_host {
+                                resync = Some((tick, state, players));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(game_number) = should_reset {
+            self.reset_game();
+            self.game_number = game_number;
+        }
+        if let Some((round, countdown)) = round_start {
+            self.start_round(round, countdown);
+        }
+        if let Some(pause) = pause_request {
+            self.request_pause(1 - self.player_id, pause);
+        }
+        if inverse_request {
+            self.request_inverse(1 - self.player_id);
+        }
+        if freeze_request {
+            self.request_freeze(1 - self.player_id);
+        }
+        if steal_request {
+            self.steal_shadow(1 - self.player_id as usize);
+        }
+        if let Some((name, color, cosmetics, ready)) = peer_info {
+            let peer = 1 - self.player_id as usize;
+            self.set_name(peer, &name);
+            self.colors[peer] = color;
+            self.cosmetics[peer] = cosmetics;
+            self.ready[peer] = ready;
+        }
+        if !was_seen && self.peer_seen {
+            let peer = 1 - self.player_id as usize;
+            self.emit(GameEvent::Connected(peer));
+        }
+        if let Some(countdown) = countdown_start {
+            if self.round_phase == RoundPhase::Countdown && self.round == 1 {
+                self.round_timer = countdown;
+            } else if self.round == 1 && self.game_number == 0 {
+                self.start_round(1, countdown);
+            }
+        }
+        if let Some(stamp) = pong {
+            self.send_message(Message::Pong { stamp });
+        }
+        if let Some(since) = resync_request {
+            self.desyncs += 1;
+            eprintln!("Client desynced from tick {}; sending a snapshot at tick {}", since, self.tick);
+            self.send_message(Message::Resync { tick: self.tick, state: self.sync_state(), players: self.players });
+        }
+        if let Some((tick, state, players)) = resync {
+            self.apply_resync(state, players);
+            self.check_sync(tick, state.hash());
+        } else if let Some((tick, hash)) = state_hash {
+            self.check_sync(tick, hash);
+        }
+        if self.packets_since.elapsed().as_secs_f32() >= 1.0 {
+            self.packet_rates = self.packets;
+            self.packets = (0, 0);
+            self.packets_since = Instant::now();
+        }
+        self.check_rematch();
+    }
+
+    /// Host-side inverse scheduler; what triggers an inversion depends on the match's schedule.
+    fn update_inverse_timer(&mut self, dt: f32) {
+        if !self.is_host || !self.is_live() {
+            return;
+        }
+
+        let schedule = self.config.inverse_schedule;
+        if schedule == InverseSchedule::Endgame {
+            // Inverse-only once either player is one trap from losing
+            let endgame = self.match_point();
+            if endgame != self.inverse_active {
+                self.inverse_active = endgame;
+                self.send_inverse_state();
+            }
+            return;
+        }
+
+        if self.inverse_active {
+            self.inverse_timer -= dt;
+            if self.inverse_timer <= 0.0 {
+                self.inverse_active = false;
+                self.inverse_cooldown = schedule.next_gap(&self.config.rules, &mut self.rng).unwrap_or(0.0);
+                self.send_inverse_state();
+            }
+        } else if schedule.clocked() || self.inverse_armed {
+            self.inverse_cooldown -= dt;
+            if self.inverse_cooldown <= 0.0 {
+                self.inverse_active = true;
+                self.inverse_armed = false;
+                self.inverse_timer = schedule.duration(self.round, &self.config.rules);
+                self.send_inverse_state();
+            }
+        }
+    }
+
+    fn send_inverse_state(&mut self) {
+        self.send_message(Message::InverseControl {
+            active: self.inverse_active,
+            time_left: self.inverse_timer,
+            by: self.inverse_by,
+            next_in: self.inverse_cooldown,
+            armed: self.inverse_armed,
+        });
+    }
+
+    /// Uses one of our freeze charges on the opponent's character; the host enforces it.
+    fn trigger_freeze(&mut self) {
+        if self.is_host {
+            self.request_freeze(self.player_id);
+        } else if self.freeze_charges[self.player_id as usize] > 0 {
+            self.send_message(Message::FreezeRequest);
+        }
+    }
+
+    fn request_freeze(&mut self, requester: u8) {
+        self.log_input(TickInput::Freeze(requester));
+        let id = requester as usize;
+        let target = 1 - id;
+        if !self.is_live() || self.freeze_charges[id] == 0 || self.frozen[target] > 0.0 {
+            return;
+        }
+        self.freeze_charges[id] -= 1;
+        self.frozen[target] = FREEZE_DURATION;
+        self.send_message(Message::FreezeState { frozen: self.frozen, charges: self.freeze_charges });
+    }
+
+    /// Host: starts what Twitch chat voted for, telling the client so it can announce it too.
+    fn chat_event(&mut self, event: ChatEvent) {
+        self.log_input(TickInput::Chat(event));
+        match event {
+            ChatEvent::Hazard => {
+                let pos = Vec2 {
+                    x: self.rng.random_range(0.15..0.85) * SCREEN_WIDTH as f32,
+                    y: self.rng.random_range(0.2..0.8) * SCREEN_HEIGHT as f32,
+                };
+                self.hazard = Some((pos, CHAT_EVENT_TIME));
+            }
+            ChatEvent::Shrink => match &mut self.shrink {
+                Some((time_left, _)) => *time_left = CHAT_EVENT_TIME,
+                None => {
+                    self.shrink = Some((CHAT_EVENT_TIME, self.config.rules.trap_radius));
+                    self.config.rules.trap_radius *= CHAT_SHRINK;
+                }
+            },
+        }
+        self.emit(GameEvent::ChatVote(event));
+        self.send_message(Message::ChatEvent(event));
+    }
+
+    /// Host: runs down chat's hazard and shrink. The shrunk radius reaches the client with the rules.
+    fn update_chat_events(&mut self, dt: f32) {
+        if !self.is_host || !self.is_live() {
+            return;
+        }
+        if let Some((_, time_left)) = &mut self.hazard {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                self.hazard = None;
+            }
+        }
+        if let Some((time_left, _)) = &mut self.shrink {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                self.end_chat_events();
+            }
+        }
+    }
+
+    /// Ends chat's hazard and shrink early, putting the trap radius back.
+    fn end_chat_events(&mut self) {
+        self.hazard = None;
+        if let Some((_, radius)) = self.shrink.take() {
+            self.config.rules.trap_radius = radius;
+        }
+    }
+
+    /// Host: thaws frozen characters over time.
+    fn update_freeze(&mut self, dt: f32) {
+        if !self.is_host || !self.is_live() {
+            return;
+        }
+        for frozen in self.frozen.iter_mut() {
+            *frozen = (*frozen - dt).max(0.0);
+        }
+    }
+
+    /// Whether an inversion is about to start, from the clock or an armed meter.
+    fn inverse_warning(&self) -> bool {
+        (self.config.inverse_schedule.clocked() || self.inverse_armed) && !self.inverse_active && self.inverse_cooldown <= self.config.rules.inverse_warning
+    }
+
+    /// Piece a player is currently steering with their movement keys.
+    fn steered_pos(&self, id: usize) -> Vec2 {
+        match self.steered_piece(id) {
+            Piece::Character(p) => self.players[p].pos,
+            Piece::Shadow(p) => self.players[p].shadow_pos,
+        }
+    }
+
+    /// Whether the other player's keys are driving this player's character (inverse mode).
+    fn is_controlled(&self, id: usize) -> bool {
+        self.steered_piece(1 - id) == Piece::Character(id)
+    }
+
+    /// Movement strategy: which piece a player's keys drive, given the mode and shadow rules.
+    fn steered_piece(&self, id: usize) -> Piece {
+        let other_id = 1 - id;
+        if self.inverse_active {
+            // Control other player's actual character (INVERSE MODE!)
+            return Piece::Character(other_id);
+        }
+        if self.is_hunter(id) {
+            // Hunter runs down the shade directly
+            return Piece::Character(id);
+        }
+        match self.config.shadow_control {
+            // Mirrored and echo shadows follow their owner, so you move yourself
+            ShadowControl::Mirror | ShadowControl::Echo => Piece::Character(id),
+            // The shade steers its own shadow
+            ShadowControl::Opponent if self.config.mode == GameMode::HunterVsShade => Piece::Shadow(id),
+            ShadowControl::Opponent => Piece::Shadow(other_id),
+        }
+    }
+
+    /// Host: fills each player's inverse meter by how far they steered and by dodging near misses.
+    fn update_inverse_meters(&mut self, dt: f32) {
+        if !self.is_host || self.config.inverse_schedule != InverseSchedule::Meter {
+            return;
+        }
+
+        for i in 0..2 {
+            let steered = self.steered_pos(i);
+            let moved = steered.distance(self.last_steered[i]);
+            self.last_steered[i] = steered;
+            if !self.is_live() || self.inverse_active || self.inverse_armed {
+                continue;
+            }
+
+            // Jumps (swaps, teleports, resets) aren't steering
+            let mut gain = if moved < 100.0 { moved / INVERSE_CHARGE_DISTANCE } else { 0.0 };
+
+            let (threat_pos, trap_radius) = self.threat(i);
+            let dist = self.arena_distance(self.players[i].pos, threat_pos);
+            if dist >= trap_radius && dist < trap_radius * NEAR_MISS_RANGE {
+                gain += NEAR_MISS_CHARGE * dt;
+            }
+            self.players[i].inverse_charge = (self.players[i].inverse_charge + gain).min(1.0);
+        }
+    }
+
+    /// Spends our full meter on inverse mode; the host grants it.
+    fn trigger_inverse(&mut self) {
+        if self.is_host {
+            self.request_inverse(self.player_id);
+        } else if self.players[self.player_id as usize].inverse_charge >= 1.0 {
+            self.send_message(Message::InverseRequest);
+        }
+    }
+
+    /// Host arbitration: the first full-meter request wins, a second one while armed or active is refused.
+    /// The inversion itself starts `inverse_warning` seconds later, so both players see it coming.
+    fn request_inverse(&mut self, requester: u8) {
+        self.log_input(TickInput::Inverse(requester));
+        let id = requester as usize;
+        if self.config.inverse_schedule != InverseSchedule::Meter
+            || !self.is_live()
+            || self.inverse_active
+            || self.inverse_armed
+            || self.players[id].inverse_charge < 1.0
+        {
+            return;
+        }
+        self.players[id].inverse_charge = 0.0;
+        self.inverse_armed = true;
+        self.inverse_cooldown = self.config.rules.inverse_warning;
+        self.inverse_by = requester;
+        self.send_inverse_state();
+    }
+
+    /// Moves whatever this player's keys drive.
+    fn update_player(&mut self, my_id: usize, input: Vector2, dt: f32) {
+        self.log_input(TickInput::Move(my_id as u8, input.into()));
+        let speed = self.config.rules.player_speed * self.config.handicaps[my_id].speed;
+        
+        let step = if self.config.wrap { move_wrapped } else { move_clamped };
+        
+        // Determine what we're controlling
+        match self.steered_piece(my_id) {
+            Piece::Character(id) if self.frozen[id] > 0.0 => {} // Frozen characters don't move
+            Piece::Character(id) => {
+                let speed = if self.is_hunter(id) && !self.inverse_active { speed * HUNTER_SPEED_MULT } else { speed };
+                step(&mut self.players[id].pos, input, speed, dt);
+            }
+            Piece::Shadow(id) => step(&mut self.players[id].shadow_pos, input, speed, dt),
+        }
+
+        self.apply_shadow_rules();
+        self.enforce_tethers();
+    }
+
+    /// Echo shadows: records each character's path and moves the shadow to where
+    /// its owner was `echo_delay` seconds ago. Runs every tick while live.
+    fn update_echo(&mut self, dt: f32) {
+        if self.config.shadow_control != ShadowControl::Echo || !self.is_live() {
+            return;
+        }
+        self.echo_clock += dt;
+        for (player, trail) in self.players.iter_mut().zip(self.echo_trail.iter_mut()) {
+            trail.push_back((self.echo_clock, player.pos));
+            while let Some(&(stamp, pos)) = trail.front() {
+                if self.echo_clock - stamp < self.config.echo_delay {
+                    break;
+                }
+                player.shadow_pos = pos;
+                trail.pop_front();
+            }
+        }
+    }
+
+    /// Samples each character and shadow into its motion trail. Jumps like swaps and teleports
+    /// start the trail over rather than drawing a streak across the arena.
+    fn update_trails(&mut self, dt: f32) {
+        self.trail_timer += dt;
+        if self.trail_timer < TRAIL_SAMPLE {
+            return;
+        }
+        self.trail_timer = (self.trail_timer - TRAIL_SAMPLE).min(TRAIL_SAMPLE);
+        for (player, trails) in self.players.iter().zip(self.motion_trails.iter_mut()) {
+            for (trail, pos) in trails.iter_mut().zip([player.pos, player.shadow_pos]) {
+                if trail.back().is_some_and(|last| last.distance(pos) > TRAIL_BREAK) {
+                    trail.clear();
+                }
+                if trail.len() == TRAIL_POINTS {
+                    trail.pop_front();
+                }
+                trail.push_back(pos);
+            }
+        }
+    }
+
+    /// Shadows that aren't steered directly follow their owner's character.
+    fn apply_shadow_rules(&mut self) {
+        if self.config.shadow_control == ShadowControl::Mirror {
+            for player in self.players.iter_mut() {
+                player.shadow_pos = mirrored(player.pos);
+            }
+        }
+    }
+
+    /// Tether rule: pulls each shadow back within range of its owner.
+    /// Runs on the steering peer and again on the host, which has the final say.
+    fn enforce_tethers(&mut self) {
+        let range = self.config.tether_range;
+        if range <= 0.0 {
+            return;
+        }
+        for i in 0..2 {
+            let (pos, shadow) = (self.players[i].pos, self.players[i].shadow_pos);
+            let delta = self.arena_delta(pos, shadow);
+            let dist = (delta.x * delta.x + delta.y * delta.y).sqrt();
+            if dist > range {
+                let t = range / dist;
+                let mut pulled = Vec2 { x: pos.x + delta.x * t, y: pos.y + delta.y * t };
+                if self.config.wrap {
+                    // Pulled back across the edge it was steered over
+                    pulled.x = pulled.x.rem_euclid(SCREEN_WIDTH as f32);
+                    pulled.y = pulled.y.rem_euclid(SCREEN_HEIGHT as f32);
+                }
+                self.players[i].shadow_pos = pulled;
+            }
+        }
+    }
+
+    /// Whether a player's shadow is at the end of its tether.
+    fn is_tether_taut(&self, id: usize) -> bool {
+        let range = self.config.tether_range;
+        range > 0.0 && self.arena_distance(self.players[id].pos, self.players[id].shadow_pos) >= range * 0.98
+    }
+
+    /// Distance between two points in this match's arena (wrap-aware).
+    fn arena_distance(&self, a: Vec2, b: Vec2) -> f32 {
+        if self.config.wrap { a.wrapped_distance(b) } else { a.distance(b) }
+    }
+
+    /// The step from `a` to `b` in this match's arena (wrap-aware).
+    fn arena_delta(&self, a: Vec2, b: Vec2) -> Vec2 {
+        if self.config.wrap { a.wrapped_delta(b) } else { Vec2 { x: b.x - a.x, y: b.y - a.y } }
+    }
+
+    /// What traps player `i` and how close it has to get: the opponent's shadow,
+    /// or the hunter's own body in Hunter vs Shade.
+    fn threat(&self, i: usize) -> (Vec2, f32) {
+        let other_id = 1 - i;
+        if self.is_hunter(other_id) {
+            (self.players[other_id].pos, HUNTER_CONTACT_RADIUS)
+        } else {
+            (self.players[other_id].shadow_pos, self.config.rules.trap_radius * self.config.handicaps[i].trap_radius)
+        }
+    }
+
+    /// The closest trap chase `viewer` can see, as the point between the character and what can trap
+    /// it, and the gap left to the trap radius. Fog never gives away a hidden character, and Tag
+    /// has no traps to chase.
+    fn closest_chase(&self, viewer: usize) -> Option<(Vec2, f32)> {
+        if self.config.mode == GameMode::Tag {
+            return None;
+        }
+        let opponent_seen = self.can_see(viewer, 1 - viewer);
+        [viewer, 1 - viewer]
+            .into_iter()
+            .filter(|&i| opponent_seen || (i == viewer && !self.is_hunter(1 - viewer)))
+            .map(|i| {
+                let (threat_pos, trap_radius) = self.threat(i);
+                let pos = self.players[i].pos;
+                // On a wrap-around arena, meet the threat across the edge when that is closer
+                let (w, h) = (SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+                let mut dx = threat_pos.x - pos.x;
+                let mut dy = threat_pos.y - pos.y;
+                if self.config.wrap {
+                    dx -= w * (dx / w).round();
+                    dy -= h * (dy / h).round();
+                }
+                let middle = Vec2 { x: pos.x + dx / 2.0, y: pos.y + dy / 2.0 };
+                (middle, self.arena_distance(pos, threat_pos) - trap_radius)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// How far a player's character is from what can trap them, and the trap radius it has to stay out of.
+    fn threat_distance(&self, i: usize) -> (f32, f32) {
+        let (threat_pos, trap_radius) = self.threat(i);
+        (self.arena_distance(self.players[i].pos, threat_pos), trap_radius)
+    }
+
+    /// Danger meter: 0 beyond DANGER_RANGE trap radii, rising to 1 at the edge of the trap radius.
+    fn danger(&self, i: usize) -> f32 {
+        let (dist, trap_radius) = self.threat_distance(i);
+        let band = trap_radius * (DANGER_RANGE - 1.0);
+        (1.0 - (dist - trap_radius) / band).clamp(0.0, 1.0)
+    }
+
+    /// Fog of war: whether `viewer` can see `target`'s character. Render-time only;
+    /// the host still simulates everything.
+    fn can_see(&self, viewer: usize, target: usize) -> bool {
+        if !self.config.fog || viewer == target {
+            return true;
+        }
+        let target_pos = self.players[target].pos;
+        self.arena_distance(self.players[viewer].pos, target_pos) < FOG_VISION
+            || (!self.is_hunter(viewer) && self.arena_distance(self.players[viewer].shadow_pos, target_pos) < FOG_VISION)
+    }
+
+    /// Hunter vs Shade: whether this player is the (shadowless) hunter.
+    fn is_hunter(&self, id: usize) -> bool {
+        self.config.mode == GameMode::HunterVsShade && self.config.hunter as usize == id
+    }
+
+    fn swap_with_shadow(&mut self, id: usize) {
+        self.log_input(TickInput::Swap(id as u8));
+        if self.is_hunter(id) {
+            return; // Nothing to swap with
+        }
+        if self.frozen[id] > 0.0 {
+            return; // Can't escape while frozen
+        }
+        self.stats[id].swaps += 1;
+        if self.can_steal(id) {
+            // The host arbitrates steals, so the client doesn't predict them
+            if self.is_host {
+                self.steal_shadow(id);
+            } else {
+                self.send_message(Message::StealRequest);
+            }
+            return;
+        }
+        let player = &mut self.players[id];
+        std::mem::swap(&mut player.pos, &mut player.shadow_pos);
+        self.emit(GameEvent::Swapped(id));
+    }
+
+    /// Shadow steal rule: whether this player is close enough to the opponent's shadow to take it.
+    fn can_steal(&self, id: usize) -> bool {
+        let other_id = 1 - id;
+        self.config.shadow_steal
+            && self.config.shadow_control == ShadowControl::Opponent
+            && !self.is_hunter(other_id)
+            && self.arena_distance(self.players[id].pos, self.players[other_id].shadow_pos) < STEAL_RANGE * self.config.rules.trap_radius
+    }
+
+    /// Host: swaps a player with the opponent's shadow, re-checking the range on the host's state.
+    fn steal_shadow(&mut self, thief: usize) {
+        if !self.is_live() || self.frozen[thief] > 0.0 || !self.can_steal(thief) {
+            return;
+        }
+        let victim = 1 - thief;
+        let from = self.players[thief].pos;
+        let to = self.players[victim].shadow_pos;
+        self.players[thief].pos = to;
+        self.players[victim].shadow_pos = from;
+        // Both pieces jumped, so stale client updates for either are dropped
+        for id in [thief, victim] {
+            self.players[id].warps += 1;
+            self.send_message(Message::PlayerUpdate(self.players[id]));
+        }
+        self.steal_fx = (thief as u8, from, to, STEAL_FX_TIME);
+        self.send_message(Message::ShadowSteal { player_id: thief as u8, from, to });
+    }
+
+    fn reset_positions(&mut self) {
+        for trail in self.echo_trail.iter_mut() {
+            trail.clear();
+        }
+        if self.config.shadow_control == ShadowControl::Mirror {
+            // Side by side spawns would mirror straight onto each other
+            self.players[0].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 * 0.3 };
+            self.players[1].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 * 0.3 };
+            self.players[0].is_trapped = false;
+            self.players[1].is_trapped = false;
+            self.apply_shadow_rules();
+            return;
+        }
+        self.players[0].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 };
+        self.players[0].shadow_pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 + 100.0 };
+        self.players[0].is_trapped = false;
+        
+        self.players[1].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 };
+        self.players[1].shadow_pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 - 100.0 };
+        self.players[1].is_trapped = false;
+    }
+
+    fn reset_game(&mut self) {
+        // Reset player positions
+        self.reset_positions();
+        self.stats = [PlayerStats::default(); 2];
+        self.play_time = 0.0;
+        self.players[0].score = self.config.handicaps[0].preloaded_traps;
+        self.players[1].score = self.config.handicaps[1].preloaded_traps;
+        self.round = 1;
+        self.round_phase = RoundPhase::Playing;
+        self.round_timer = 0.0;
+        self.rematch_votes = [None, None];
+        self.forfeited = None;
+        self.game_over = None;
+        self.forfeit_hold = 0.0;
+        self.game_number += 1;
+        self.idle_time = 0.0;
+        self.peer_idle = 0.0;
+        self.afk_player = None;
+        
+        // Reset timers
+        self.inverse_active = false;
+        self.inverse_armed = false;
+        self.inverse_timer = 0.0;
+        self.inverse_cooldown = self.config.inverse_schedule.next_gap(&self.config.rules, &mut self.rng).unwrap_or(0.0);
+        self.players[0].inverse_charge = 0.0;
+        self.players[1].inverse_charge = 0.0;
+        self.trap_flash_timer = [0.0, 0.0];
+        self.streaks = [0, 0];
+        self.streak_callout = 0.0;
+        self.history.clear();
+        self.replay_frames.clear();
+        self.timeline.clear();
+        self.heatmap.clear();
+        self.spawn_protection = [SPAWN_PROTECTION; 2];
+        self.frozen = [0.0, 0.0];
+        self.freeze_charges = [FREEZE_CHARGES; 2];
+        self.end_chat_events();
+        self.zone_points = [0, 0];
+        self.zone_timer = [0.0, 0.0];
+        self.tag_it = 0;
+        self.tag_time = [0.0, 0.0];
+        self.tag_time_left = TAG_MATCH_TIME;
+        self.tag_immunity = 0.0;
+        self.hill_time = [0.0, 0.0];
+        // Hunter vs Shade: roles swap every game of the series
+        if self.config.mode == GameMode::HunterVsShade {
+            self.config.hunter = 1 - self.config.hunter;
+        }
+        // Note: game_time is not reset to keep visual effects smooth
+    }
+
+    /// Hold-to-forfeit: conceding ends the match in the opponent's favor on both sides.
+    fn update_forfeit_hold(&mut self, held: bool, dt: f32) {
+        if !held || self.winner().is_some() || self.forfeited.is_some() {
+            self.forfeit_hold = 0.0;
+            return;
+        }
+        self.forfeit_hold += dt;
+        if self.forfeit_hold >= FORFEIT_HOLD_TIME {
+            self.forfeit_hold = 0.0;
+            self.forfeit(self.player_id);
+        }
+    }
+
+    fn forfeit(&mut self, player_id: u8) {
+        self.log_input(TickInput::Forfeit(player_id));
+        self.forfeited = Some(player_id);
+        self.emit(GameEvent::Forfeited(player_id as usize));
+        self.send_message(Message::Forfeit { player_id, game_number: self.game_number });
+    }
+
+    /// Host: stops listening to the client, who forfeits a match still being played. Another
+    /// player can then join in their place.
+    fn kick(&mut self, forfeit: bool) -> Option<SocketAddr> {
+        let client = self.client_addr?;
+        if forfeit && self.forfeited.is_none() && self.winner().is_none() {
+            self.forfeit(1 - self.player_id);
+        }
+        self.kicked = self.client_addr.take();
+        Some(client)
+    }
+
+    /// Tracks how long we've gone without input while the round is live.
+    fn update_idle(&mut self, had_input: bool, dt: f32) {
+        if had_input {
+            self.idle_time = 0.0;
+        } else if self.is_live() {
+            self.idle_time += dt;
+        }
+    }
+
+    /// Host: flags a player AFK after the configured idle time, warns both sides,
+    /// and forfeits them if they don't come back within the grace period.
+    fn update_afk(&mut self, dt: f32) {
+        if !self.is_host || self.client_addr.is_none() || !self.is_live() {
+            return;
+        }
+        if self.config.afk_timeout <= 0.0 || self.winner().is_some() {
+            self.afk_player = None;
+            return;
+        }
+
+        // No packets counts as idle too, so a dropped client can't stall the match
+        let client_idle = self.peer_idle.max(self.last_packet.elapsed().as_secs_f32());
+        let idle = [self.idle_time, client_idle]; // Host is always player 1
+        match (0..2).find(|&pid| idle[pid] >= self.config.afk_timeout) {
+            Some(pid) => {
+                if self.afk_player != Some(pid as u8) {
+                    self.afk_player = Some(pid as u8);
+                    self.afk_grace = AFK_GRACE;
+                }
+                self.afk_grace -= dt;
+                if self.afk_grace <= 0.0 {
+                    self.afk_player = None;
+                    self.forfeited = Some(pid as u8);
+                    self.emit(GameEvent::Forfeited(pid));
+                    self.send_message(Message::Forfeit { player_id: pid as u8, game_number: self.game_number });
+                }
+            }
+            None => self.afk_player = None,
+        }
+    }
+
+    /// Game over: record our rematch answer and share it with the opponent.
+    fn vote_rematch(&mut self, accept: bool) {
+        if self.winner().is_none() || self.rematch_votes[self.player_id as usize].is_some() {
+            return;
+        }
+        self.rematch_votes[self.player_id as usize] = Some(accept);
+        self.send_message(Message::RematchVote { accept, game_number: self.game_number });
+        self.check_rematch();
+    }
+
+    /// Host starts the rematch once both players have accepted.
+    fn check_rematch(&mut self) {
+        if self.is_host && self.rematch_votes == [Some(true), Some(true)] {
+            // The next arena in the rotation, if the admin console set one; it reaches the client with the rules
+            if let Some(i) = self.arena_rotation.iter().position(|&arena| arena == self.config.arena) {
+                self.config.arena = self.arena_rotation[(i + 1) % self.arena_rotation.len()];
+            } else if let Some(&first) = self.arena_rotation.first() {
+                self.config.arena = first;
+            }
+            self.reset_game();
+            self.send_message(Message::GameReset { game_number: self.game_number });
+        }
+    }
+
+    /// Whether the round is in play (not paused, in an intermission or countdown).
+    fn is_live(&self) -> bool {
+        self.round_phase == RoundPhase::Playing && !self.paused
+    }
+
+    /// Pause key pressed locally: the host decides, the client asks the host.
+    fn toggle_pause(&mut self) {
+        let pause = !self.paused || self.resume_timer > 0.0;
+        if self.is_host {
+            self.request_pause(self.player_id, pause);
+        } else {
+            self.send_message(Message::PauseRequest { pause });
+        }
+    }
+
+    /// Host arbitration: first pause wins; resuming runs a countdown before play continues.
+    fn request_pause(&mut self, requester: u8, pause: bool) {
+        self.log_input(TickInput::Pause(requester, pause));
+        if pause {
+            self.paused = true;
+            self.pause_owner = requester;
+            self.resume_timer = 0.0;
+        } else if self.paused && self.resume_timer <= 0.0 {
+            self.resume_timer = RESUME_COUNTDOWN;
+        }
+        self.send_pause_state();
+    }
+
+    fn send_pause_state(&mut self) {
+        self.send_message(Message::PauseState {
+            paused: self.paused,
+            owner: self.pause_owner,
+            resume_in: self.resume_timer,
+        });
+    }
+
+    fn update_pause(&mut self, dt: f32) {
+        if !self.paused || self.resume_timer <= 0.0 {
+            return;
+        }
+        self.resume_timer -= dt;
+        if self.is_host && self.resume_timer <= 0.0 {
+            self.paused = false;
+            self.resume_timer = 0.0;
+            self.send_pause_state();
+        }
+    }
+
+    fn start_round(&mut self, round: u32, countdown: f32) {
+        self.reset_positions();
+        self.history.clear();
+        self.spawn_protection = [SPAWN_PROTECTION; 2];
+        self.round = round;
+        self.round_phase = RoundPhase::Countdown;
+        self.round_timer = countdown;
+    }
+
+    /// Records both players' positions and focus during play, keeping the last few seconds for the killcam.
+    fn record_history(&mut self, dt: f32) {
+        if !self.is_live() {
+            return;
+        }
+        self.history_timer += dt;
+        if self.history_timer >= KILLCAM_SAMPLE {
+            self.history_timer = 0.0;
+            if self.history.len() == KILLCAM_FRAMES {
+                self.history.pop_front();
+            }
+            let steering = [self.steered_piece(0), self.steered_piece(1)];
+            self.history.push_back(Snapshot { players: self.players, steering });
+        }
+    }
+
+    /// Adds this tick to the heatmap under each character.
+    fn record_heatmap(&mut self, dt: f32) {
+        if self.is_live() && self.winner().is_none() {
+            self.heatmap.record(&self.players, dt);
+        }
+    }
+
+    /// Samples the game into its replay every REPLAY_SAMPLE seconds of play.
+    fn record_replay(&mut self, dt: f32) {
+        if !self.is_live() {
+            return;
+        }
+        self.replay_timer += dt;
+        if self.replay_frames.is_empty() || self.replay_timer >= REPLAY_SAMPLE {
+            self.replay_timer = 0.0;
+            if self.replay_frames.is_empty() {
+                self.replay_started = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            }
+            self.replay_frames.push(ReplayFrame::capture(self));
+        }
+    }
+
+    /// This game's replay so far, or None before anything was recorded.
+    fn replay(&self, end: ReplayEnd) -> Option<Replay> {
+        if self.replay_frames.is_empty() {
+            return None;
+        }
+        Some(Replay {
+            version: REPLAY_VERSION,
+            started: self.replay_started,
+            config: self.config,
+            recorded_by: self.player_id,
+            challenge: self.challenge,
+            names: self.names.clone(),
+            colors: self.colors,
+            cosmetics: self.cosmetics,
+            end,
+            frames: self.replay_frames.clone(),
+        })
+    }
+
+    /// The last CLIP_SECONDS of this game's replay, or None before anything was recorded.
+    fn clip(&self) -> Option<Replay> {
+        let end = self.winner().map_or(ReplayEnd::Clip, |winner| ReplayEnd::Won(winner as u8));
+        let mut clip = self.replay(end)?;
+        let from = clip.length() - CLIP_SECONDS;
+        clip.frames.retain(|frame| frame.time >= from);
+        Some(clip)
+    }
+
+    /// F9: saves the clip as a replay, then draws it as a GIF beside it on another thread.
+    /// Returns where the GIF will be.
+    fn export_clip(&self) -> Result<PathBuf, String> {
+        let clip = self.clip().ok_or("nothing has been played yet")?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+        let path = paths::data(CLIPS_DIR).join(format!("{}.replay", now));
+        std::fs::create_dir_all(paths::data(CLIPS_DIR)).map_err(|e| e.to_string())?;
+        std::fs::write(&path, clip.encode()?).map_err(|e| e.to_string())?;
+        let gif_path = path.with_extension("gif");
+        let (colors, target) = ([self.player_color(0), self.player_color(1)], gif_path.clone());
+        std::thread::spawn(move || {
+            if let Err(e) = std::fs::write(&target, clip_gif(&clip, colors)) {
+                eprintln!("Could not save the clip's GIF: {}", e);
+            }
+        });
+        Ok(gif_path)
+    }
+
+    /// Saves this game's replay: when it's won, when the opponent drops, or when we leave mid-match.
+    fn save_replay(&self, end: ReplayEnd) {
+        if let Some(replay) = self.replay(end) {
+            replay.save();
+        }
+    }
+
+    /// The recorded frame `progress` (0 to 1) of the way through the history.
+    fn history_at(&self, progress: f32) -> Option<Snapshot> {
+        let last = self.history.len().checked_sub(1)?;
+        let sample = ((progress * self.history.len() as f32) as usize).min(last);
+        Some(self.history[sample])
+    }
+
+    /// Round state machine: trap -> intermission -> countdown -> playing.
+    /// The host decides when the next round starts; both sides run the countdown.
+    fn update_round(&mut self, dt: f32) {
+        if self.paused {
+            return;
+        }
+        match self.round_phase {
+            RoundPhase::Playing => {}
+            RoundPhase::Intermission => {
+                self.round_timer -= dt;
+                if self.is_host && self.round_timer <= 0.0 && self.winner().is_none() {
+                    self.start_round(self.round + 1, ROUND_COUNTDOWN);
+                    self.send_message(Message::RoundStart { round: self.round, countdown: ROUND_COUNTDOWN });
+                }
+            }
+            RoundPhase::Countdown => {
+                self.round_timer -= dt;
+                if self.round_timer <= 0.0 {
+                    self.round_phase = RoundPhase::Playing;
+                    if self.round == 1 {
+                        self.go_flash = GO_FLASH_TIME;
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_traps(&mut self, dt: f32) {
+        if !self.is_host {
+            return;
+        }
+
+        // Update flash timers
+        for i in 0..2 {
+            if self.trap_flash_timer[i] > 0.0 {
+                self.trap_flash_timer[i] -= dt;
+            }
+        }
+
+        // Spawn protection only runs out during play
+        if self.is_live() {
+            for protection in self.spawn_protection.iter_mut() {
+                *protection = (*protection - dt).max(0.0);
+            }
+        }
+
+        // Tag replaces trapping with passing the tag
+        if self.config.mode == GameMode::Tag || !self.is_live() {
+            return;
+        }
+
+        // How deep each player is inside the other's trap radius, if at all
+        let depth = [0, 1].map(|i| {
+            if self.spawn_protection[i] > 0.0 || self.players[i].is_trapped {
+                return None; // Just respawned, can't be chain-trapped
+            }
+            let (dist, trap_radius) = self.threat_distance(i);
+            (dist < trap_radius).then_some(dist / trap_radius)
+        });
+
+        match resolve_traps(depth) {
+            TrapOutcome::Clear => {}
+            TrapOutcome::Clash => {
+                // Neither scores; straight into a fresh round
+                self.clash_fx = CLASH_FX_TIME;
+                self.emit(GameEvent::Clash);
+                self.send_message(Message::Clash);
+                self.start_round(self.round + 1, ROUND_COUNTDOWN);
+                self.send_message(Message::RoundStart { round: self.round, countdown: ROUND_COUNTDOWN });
+            }
+            TrapOutcome::Trapped(i) => {
+                self.players[i].is_trapped = true;
+                self.players[i].score += 1; // Positive score = times trapped (bad!)
+                self.trap_flash_timer[i] = 1.0; // Flash for 1 second
+                self.emit(GameEvent::Trapped(i));
+
+                // Streaks: the trapper extends theirs, the victim's ends
+                let trapper = 1 - i;
+                self.stats[i].traps_received += 1;
+                self.stats[trapper].traps_dealt += 1;
+                self.streaks[trapper] += 1;
+                self.streaks[i] = 0;
+                if self.config.streak_bonus && self.streaks[trapper].is_multiple_of(STREAK_BONUS_AT) {
+                    self.players[i].score += 1;
+                }
+                if self.streaks[trapper] >= 2 {
+                    self.streak_callout = STREAK_CALLOUT_TIME;
+                }
+                self.trap_event_id += 1;
+                self.send_message(Message::TrapEvent {
+                    event_id: self.trap_event_id,
+                    player_id: i as u8,
+                    scores: [self.players[0].score, self.players[1].score],
+                    streaks: self.streaks,
+                });
+
+                // Pause for the intermission; the next round resets positions
+                self.round_phase = RoundPhase::Intermission;
+                self.round_timer = self.config.rules.intermission;
+            }
+        }
+    }
+
+    /// Teleporter pads move characters (not shadows) to the linked pad.
+    /// Simulated on the host only and sent out straight away, so the client never predicts a jump.
+    fn update_teleporters(&mut self, dt: f32) {
+        for i in 0..2 {
+            self.teleport_fx[i].1 = (self.teleport_fx[i].1 - dt).max(0.0);
+        }
+        if !self.is_host || !self.is_live() {
+            return;
+        }
+
+        let pads = self.config.arena.teleporters();
+        for i in 0..2 {
+            self.teleport_cooldown[i] -= dt;
+            if self.teleport_cooldown[i] > 0.0 || self.frozen[i] > 0.0 {
+                continue;
+            }
+            let pos = self.players[i].pos;
+            let target = pads.iter().find_map(|&(a, b)| {
+                if pos.distance(a) < TELEPORTER_RADIUS {
+                    Some(b)
+                } else if pos.distance(b) < TELEPORTER_RADIUS {
+                    Some(a)
+                } else {
+                    None
+                }
+            });
+            if let Some(target) = target {
+                self.teleport_fx[i] = (pos, TELEPORT_FX_TIME);
+                self.teleport_cooldown[i] = TELEPORT_COOLDOWN;
+                self.players[i].pos = target;
+                self.players[i].warps += 1;
+                self.send_message(Message::PlayerUpdate(self.players[i]));
+                self.send_message(Message::Teleported { player_id: i as u8, from: pos });
+            }
+        }
+    }
+
+    /// Gravity wells pull characters (not shadows) in, harder the closer they are.
+    /// Host only, like the rest of the arena simulation.
+    fn update_gravity_wells(&mut self, dt: f32) {
+        if !self.is_host || !self.is_live() {
+            return;
+        }
+
+        for well in self.config.arena.gravity_wells().into_iter().chain(self.hazard.map(|(pos, _)| pos)) {
+            for (player, &frozen) in self.players.iter_mut().zip(&self.frozen) {
+                if frozen > 0.0 {
+                    continue;
+                }
+                let dist = player.pos.distance(well);
+                if dist < WELL_RADIUS && dist > 1.0 {
+                    let pull = (WELL_PULL * (1.0 - dist / WELL_RADIUS) * dt).min(dist);
+                    player.pos.x += (well.x - player.pos.x) / dist * pull;
+                    player.pos.y += (well.y - player.pos.y) / dist * pull;
+                }
+            }
+        }
+    }
+
+    /// Host simulation up to the players' inputs: slow-mo, the clocks, pauses, rounds, inversions
+    /// and freezes. Returns the scaled time step the rest of the tick runs on. `Match::update` and
+    /// `InputLog::play` share it and `end_tick`, so a recording plays back on the very same steps.
+    fn begin_tick(&mut self, real_dt: f32) -> f32 {
+        self.tick += 1;
+        if let Some((_, log)) = &mut self.input_log {
+            log.ticks.push(InputTick { dt: real_dt, inputs: Vec::new() });
+        }
+        if self.is_host {
+            self.apply_shadow_rules();
+            self.enforce_tethers();
+        }
+
+        // Simulation time runs through the time scale (slow-mo); player-facing timers use real time
+        self.update_time_scale(real_dt);
+        let dt = real_dt * self.time_scale;
+        self.game_time += dt;
+        if self.is_live() && self.winner().is_none() {
+            self.play_time += real_dt;
+        }
+        self.update_pause(real_dt);
+
+        // Intermissions and countdowns between rounds, then inverse mode and freezes
+        self.update_round(dt);
+        self.update_inverse_timer(dt);
+        self.update_inverse_meters(dt);
+        self.update_freeze(dt);
+        self.update_chat_events(dt);
+        dt
+    }
+
+    /// Host simulation after the players' inputs: echo shadows, then teleporters, traps, zones and
+    /// the mode's scoring, and whether that decided the match.
+    fn end_tick(&mut self, dt: f32) {
+        self.update_echo(dt);
+        self.update_teleporters(dt);
+        self.update_gravity_wells(dt);
+        self.check_traps(dt);
+        self.update_zones(dt);
+        self.update_tag(dt);
+        self.update_hill(dt);
+        self.check_game_over();
+    }
+
+    /// Adds an input to the tick being recorded, if there is one.
+    fn log_input(&mut self, input: TickInput) {
+        if let Some((_, log)) = &mut self.input_log
+            && let Some(tick) = log.ticks.last_mut()
+        {
+            tick.inputs.push(input);
+        }
+    }
+
+    /// Plays a recorded input back through the call that recorded it.
+    fn apply_input(&mut self, input: TickInput, dt: f32) {
+        match input {
+            TickInput::Move(id, dir) => self.update_player(id as usize, dir.into(), dt),
+            TickInput::Swap(id) => self.swap_with_shadow(id as usize),
+            TickInput::Inverse(id) => self.request_inverse(id),
+            TickInput::Freeze(id) => self.request_freeze(id),
+            TickInput::Pause(id, pause) => self.request_pause(id, pause),
+            TickInput::Forfeit(id) => self.forfeit(id),
+            TickInput::Chat(event) => self.chat_event(event),
+        }
+    }
+
+    /// Starts recording every tick's inputs from here, for `save_input_log` to write to `path`.
+    fn record_inputs(&mut self, path: PathBuf) {
+        let log = InputLog { seed: self.seed, config: self.config, ticks: Vec::new(), checksum: 0 };
+        self.input_log = Some((path, log));
+    }
+
+    /// Stops recording inputs and saves them with the checksum of where the game got to.
+    fn save_input_log(&mut self) {
+        let Some((path, mut log)) = self.input_log.take() else {
+            return;
+        };
+        log.checksum = self.checksum();
+        match log.save(&path) {
+            Ok(()) => println!("Recorded {} ticks to {} (checksum {:016x})", log.ticks.len(), path.display(), log.checksum),
+            Err(e) => eprintln!("Could not save the input recording: {}", e),
+        }
+    }
+
+    /// Fingerprint of everything the simulation decides: positions, scores, rounds, inversions,
+    /// freezes, the modes' clocks and the result. Two runs of the same inputs must agree on it.
+    fn checksum(&self) -> u64 {
+        let inverse = (self.inverse_active, self.inverse_armed, self.inverse_timer, self.inverse_cooldown, self.inverse_by);
+        let modes = (self.zone_points, self.tag_it, self.tag_time, self.tag_time_left, self.hill_time);
+        let state = (
+            self.players,
+            (self.round, self.round_phase, self.round_timer),
+            inverse,
+            (self.streaks, self.spawn_protection, self.frozen, self.freeze_charges),
+            modes,
+            (self.paused, self.forfeited, self.game_over),
+        );
+        fnv1a(&bincode::serialize(&state).unwrap_or_default())
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            game_number: self.game_number,
+            scores: [self.players[0].score, self.players[1].score],
+            streaks: self.streaks,
+            trap_event_id: self.trap_event_id,
+            round: self.round,
+            round_phase: self.round_phase,
+            paused: self.paused,
+            inverse: (self.inverse_active, self.inverse_armed, self.inverse_by),
+            frozen: self.frozen.map(|time| time > 0.0),
+            freeze_charges: self.freeze_charges,
+            zone_points: self.zone_points,
+            tag_it: self.tag_it,
+            forfeited: self.forfeited,
+            game_over: self.game_over,
+        }
+    }
+
+    /// Client: checks our copy of the match against the host's hash. Messages in flight make the
+    /// two disagree for a moment now and then; a mismatch lasting DESYNC_GRACE is a desync, which
+    /// is reported with the host tick it began at and fixed with a snapshot from the host.
+    fn check_sync(&mut self, tick: u32, hash: u64) {
+        let ours = self.sync_state();
+        if ours.hash() == hash {
+            self.desync_since = None;
+            return;
+        }
+        let (since, at) = *self.desync_since.get_or_insert((tick, Instant::now()));
+        if at.elapsed().as_secs_f32() < DESYNC_GRACE {
+            return;
+        }
+        self.desync_since = None;
+        self.desyncs += 1;
+        eprintln!(
+            "Desync #{}: state diverged from the host at host tick {} (now {}), host {:016x}, ours {:016x}\n  ours: {:?}",
+            self.desyncs, since, tick, hash, ours.hash(), ours
+        );
+        self.toast(tr!("desync-resync"));
+        self.send_message(Message::ResyncRequest { tick: since });
+    }
+
+    /// Client: adopts the host's snapshot wholesale.
+    fn apply_resync(&mut self, state: SyncState, players: [Player; 2]) {
+        self.game_number = state.game_number;
+        self.players = players;
+        self.streaks = state.streaks;
+        self.trap_event_id = state.trap_event_id;
+        self.round = state.round;
+        self.round_phase = state.round_phase;
+        self.paused = state.paused;
+        (self.inverse_active, self.inverse_armed, self.inverse_by) = state.inverse;
+        for (frozen, now) in self.frozen.iter_mut().zip(state.frozen) {
+            if !now {
+                *frozen = 0.0; // A freeze still running keeps its clock, the next FreezeState corrects it
+            }
+        }
+        self.freeze_charges = state.freeze_charges;
+        self.zone_points = state.zone_points;
+        self.tag_it = state.tag_it;
+        self.forfeited = state.forfeited;
+        self.game_over = state.game_over;
+    }
+
+    /// Host: dips the simulation to slow-mo while a character is close to being trapped.
+    /// Takes real (unscaled) time, so slow-mo doesn't stretch itself out.
+    fn update_time_scale(&mut self, real_dt: f32) {
+        if !self.is_host {
+            return;
+        }
+
+        let near_trap = self.is_live()
+            && self.config.mode != GameMode::Tag
+            && (0..2).any(|i| {
+                let (threat_pos, trap_radius) = self.threat(i);
+                self.spawn_protection[i] <= 0.0
+                    && self.arena_distance(self.players[i].pos, threat_pos) < trap_radius * SLOWMO_RANGE
+            });
+        if near_trap {
+            self.slowmo_timer = SLOWMO_HOLD;
+        } else if self.is_live() {
+            self.slowmo_timer = (self.slowmo_timer - real_dt).max(0.0);
+        } else {
+            self.slowmo_timer = 0.0;
+        }
+
+        let target = if self.slowmo_timer > 0.0 { SLOWMO_SCALE } else { 1.0 };
+        let step = SLOWMO_EASE * real_dt;
+        self.time_scale = if self.time_scale < target {
+            (self.time_scale + step).min(target)
+        } else {
+            (self.time_scale - step).max(target)
+        };
+    }
+
+    /// Capture Zones: a player scores while their shadow shares a zone with the opponent's character.
+    fn update_zones(&mut self, dt: f32) {
+        if !self.is_host || self.config.mode != GameMode::CaptureZone || !self.is_live() || self.winner().is_some() {
+            return;
+        }
+
+        let zones = capture_zones();
+        for i in 0..2 {
+            let shadow_pos = self.players[i].shadow_pos;
+            let victim_pos = self.players[1 - i].pos;
+            let holding = zones.iter().any(|&zone| {
+                shadow_pos.distance(zone) < ZONE_RADIUS && victim_pos.distance(zone) < ZONE_RADIUS
+            });
+
+            if holding {
+                self.zone_timer[i] += dt;
+                if self.zone_timer[i] >= ZONE_TICK {
+                    self.zone_timer[i] -= ZONE_TICK;
+                    self.zone_points[i] += 1;
+                    self.send_message(Message::ZoneScore { player_id: i as u8, points: self.zone_points[i] });
+                }
+            } else {
+                self.zone_timer[i] = 0.0;
+            }
+        }
+    }
+
+    /// Tag: "it" passes the tag when their shadow touches the runner's character.
+    fn update_tag(&mut self, dt: f32) {
+        if !self.is_host || self.config.mode != GameMode::Tag || !self.is_live() || self.winner().is_some() {
+            return;
+        }
+
+        let it = self.tag_it as usize;
+        self.tag_time[it] += dt;
+        self.tag_time_left = (self.tag_time_left - dt).max(0.0);
+        self.tag_immunity -= dt;
+
+        let runner = 1 - it;
+        let touching = self.arena_distance(self.players[it].shadow_pos, self.players[runner].pos) < PLAYER_SIZE + SHADOW_SIZE;
+        if touching && self.tag_immunity <= 0.0 {
+            self.tag_it = runner as u8;
+            self.tag_immunity = TAG_IMMUNITY;
+            self.trap_flash_timer[runner] = 1.0;
+            self.send_message(Message::TagState {
+                it: self.tag_it,
+                it_time: self.tag_time,
+                time_left: self.tag_time_left,
+            });
+        }
+    }
+
+    /// King of the Hill: standing alone on the hill earns time towards the win.
+    fn update_hill(&mut self, dt: f32) {
+        if !self.is_host || self.config.mode != GameMode::KingOfTheHill || !self.is_live() || self.winner().is_some() {
+            return;
+        }
+
+        let on_hill = [0, 1].map(|i| self.players[i].pos.distance(hill_center()) < HILL_RADIUS);
+        // A contested hill scores for nobody
+        if on_hill[0] != on_hill[1] {
+            let holder = if on_hill[0] { 0 } else { 1 };
+            self.hill_time[holder] = (self.hill_time[holder] + dt).min(HILL_WIN_TIME);
+        }
+    }
+
+    /// Whether either player is one trap from losing.
+    fn match_point(&self) -> bool {
+        self.players.iter().any(|p| p.score >= WIN_SCORE - 1)
+    }
+
+    /// Index of the winning player, once the host has called the match.
+    fn winner(&self) -> Option<usize> {
+        self.game_over.map(|winner| winner as usize)
+    }
+
+    /// This game's result from our side, for the match history. None until someone has won.
+    fn match_record(&self) -> Option<MatchRecord> {
+        let winner = self.winner()?;
+        let (me, other) = (self.player_id as usize, 1 - self.player_id as usize);
+        Some(MatchRecord {
+            ended: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            mode: self.config.mode,
+            won: winner == me,
+            traps_dealt: self.stats[me].traps_dealt,
+            traps_received: self.stats[me].traps_received,
+            duration: self.play_time,
+            player: self.names[me].clone(),
+            opponent: self.names[other].clone(),
+        })
+    }
+
+    /// The finished game as a JSON report for tools outside the game: when and how it ended, the
+    /// settings it was played with, each player's totals and every event on this side, timed in
+    /// seconds of play. None until someone has won.
+    fn match_report(&self, ended: u64) -> Option<String> {
+        let winner = self.winner()?;
+        let result = if self.forfeited.is_some() {
+            "forfeit"
+        } else if self.players[1 - winner].score >= WIN_SCORE {
+            "traps"
+        } else {
+            "mode"
+        };
+        let config = &self.config;
+        let rules = &config.rules;
+        let handicaps: Vec<String> = config
+            .handicaps
+            .iter()
+            .map(|h| format!("{{\"speed\": {:.2}, \"trap_radius\": {:.2}, \"preloaded_traps\": {}}}", h.speed, h.trap_radius, h.preloaded_traps))
+            .collect();
+        let players: Vec<String> = (0..2)
+            .map(|i| {
+                let stats = self.stats[i];
+                format!(
+                    "    {{\"id\": {}, \"name\": {}, \"color\": {}, \"score\": {}, \"traps_dealt\": {}, \"traps_received\": {}, \"swaps\": {}, \"freezes_used\": {}, \"zone_points\": {}, \"tag_time\": {:.2}, \"hill_time\": {:.2}, \"series_wins\": {}}}",
+                    i,
+                    json_string(&self.names[i]),
+                    json_string(color_key(self.colors[i] as usize)),
+                    self.players[i].score,
+                    stats.traps_dealt,
+                    stats.traps_received,
+                    stats.swaps,
+                    FREEZE_CHARGES - self.freeze_charges[i],
+                    self.zone_points[i],
+                    self.tag_time[i],
+                    self.hill_time[i],
+                    self.series[i],
+                )
+            })
+            .collect();
+        let events: Vec<String> = self.timeline.iter().map(|(time, event)| format!("    {{\"time\": {:.2}, {}}}", time, event.json())).collect();
+        Some(format!(
+            "{{\n  \"report_version\": 1,\n  \"ended\": {},\n  \"date\": \"{}\",\n  \"game\": {},\n  \"recorded_by\": {},\n  \"winner\": {},\n  \"result\": \"{}\",\n  \"duration\": {:.2},\n  \"rounds\": {},\n  \
+             \"settings\": {{\"mode\": \"{:?}\", \"arena\": \"{:?}\", \"inverse_schedule\": \"{:?}\", \"shadow_control\": \"{:?}\", \"echo_delay\": {:.2}, \"hunter\": {}, \"afk_timeout\": {:.2}, \"tether_range\": {:.2}, \"wrap\": {}, \"fog\": {}, \"shadow_steal\": {}, \"streak_bonus\": {}, \
+             \"rules\": {{\"player_speed\": {:.2}, \"trap_radius\": {:.2}, \"inverse_duration\": {:.2}, \"inverse_cooldown\": {:.2}, \"inverse_warning\": {:.2}, \"intermission\": {:.2}}}, \"handicaps\": [{}]}},\n  \
+             \"players\": [\n{}\n  ],\n  \"events\": [\n{}\n  ]\n}}\n",
+            ended,
+            calendar_date(ended / 86_400),
+            self.game_number + 1,
+            self.player_id,
+            winner,
+            result,
+            self.play_time,
+            self.round,
+            config.mode,
+            config.arena,
+            config.inverse_schedule,
+            config.shadow_control,
+            config.echo_delay,
+            config.hunter,
+            config.afk_timeout,
+            config.tether_range,
+            config.wrap,
+            config.fog,
+            config.shadow_steal,
+            config.streak_bonus,
+            rules.player_speed,
+            rules.trap_radius,
+            rules.inverse_duration,
+            rules.inverse_cooldown,
+            rules.inverse_warning,
+            rules.intermission,
+            handicaps.join(", "),
+            players.join(",\n"),
+            events.join(",\n"),
+        ))
+    }
+
+    /// Writes `match_report` to REPORTS_DIR, returning where it went. In a tournament, its signature
+    /// goes beside it as `<report>.sig`.
+    fn export_report(&self, tournament: Option<&Tournament>) -> Result<PathBuf, String> {
+        let ended = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let report = self.match_report(ended).ok_or("the match isn't over")?;
+        let dir = paths::data(REPORTS_DIR);
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        // Never overwrite an earlier report: two ending in the same second get a `-2`, `-3`, ... suffix
+        let mut copy = 1;
+        let path = loop {
+            let suffix = if copy == 1 { String::new() } else { format!("-{}", copy) };
+            let path = dir.join(format!("{}-{}{}.json", ended, self.game_number, suffix));
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    file.write_all(report.as_bytes()).map_err(|e| e.to_string())?;
+                    break path;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => copy += 1,
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        if let Some(tournament) = tournament {
+            std::fs::write(Tournament::signature_path(&path), tournament.sign(report.as_bytes())).map_err(|e| e.to_string())?;
+        }
+        Ok(path)
+    }
+
+    /// Host: ends the match as soon as someone has won and tells the client.
+    fn check_game_over(&mut self) {
+        if !self.is_host || self.game_over.is_some() {
+            return;
+        }
+        if let Some(winner) = self.detect_winner() {
+            self.declare_winner(winner);
+        }
+    }
+
+    /// Host: ends the match with this winner, counts it towards the series and tells the client.
+    fn declare_winner(&mut self, winner: usize) {
+        self.game_over = Some(winner as u8);
+        self.series[winner] += 1;
+        self.emit(GameEvent::Won(winner));
+        self.send_message(Message::GameOver { winner: winner as u8, game_number: self.game_number });
+    }
+
+    /// Host: works out the winner from the current scores, if the match is decided.
+    fn detect_winner(&self) -> Option<usize> {
+        if let Some(quitter) = self.forfeited {
+            return Some(1 - quitter as usize);
+        }
+        if let Some(loser) = (0..2).find(|&i| self.players[i].score >= WIN_SCORE) {
+            return Some(1 - loser);
+        }
+        match self.config.mode {
+            GameMode::Classic | GameMode::HunterVsShade => None,
+            GameMode::CaptureZone => (0..2).find(|&i| self.zone_points[i] >= ZONE_WIN_SCORE),
+            GameMode::Tag if self.tag_time_left <= 0.0 => {
+                // Least time spent "it" wins; a tie goes to whoever isn't "it" at the buzzer
+                let runner = 1 - self.tag_it as usize;
+                if self.tag_time[0] < self.tag_time[1] {
+                    Some(0)
+                } else if self.tag_time[1] < self.tag_time[0] {
+                    Some(1)
+                } else {
+                    Some(runner)
+                }
+            }
+            GameMode::Tag => None,
+            GameMode::KingOfTheHill => (0..2).find(|&i| self.hill_time[i] >= HILL_WIN_TIME),
+        }
+    }
+}
+
+/// A piece walked around a loop of waypoints by the game itself (bots and puzzle scripts).
+#[derive(Clone, Debug)]
+struct ScriptedPath {
+    piece: Piece,
+    points: Vec<Vec2>,
+    speed: f32,
+    next: usize, // Index of the waypoint being walked to
+}
+
+impl ScriptedPath {
+    fn new(piece: Piece, points: Vec<Vec2>, speed: f32) -> Self {
+        ScriptedPath { piece, points, speed, next: 0 }
+    }
+
+    /// Steps the piece towards its next waypoint, moving on to the one after once it arrives.
+    /// A frozen character stays put.
+    fn update(&mut self, game: &mut GameState, dt: f32) {
+        if self.points.is_empty() {
+            return;
+        }
+        let step = if game.config.wrap { move_wrapped } else { move_clamped };
+        let pos = match self.piece {
+            Piece::Character(id) if game.frozen[id] > 0.0 => return,
+            Piece::Character(id) => &mut game.players[id].pos,
+            Piece::Shadow(id) => &mut game.players[id].shadow_pos,
+        };
+        let target = self.points[self.next];
+        let dist = pos.distance(target);
+        if dist < 8.0 {
+            self.next = (self.next + 1) % self.points.len();
+            return;
+        }
+        let dir = Vector2::new((target.x - pos.x) / dist, (target.y - pos.y) / dist);
+        step(pos, dir, self.speed, dt);
+    }
+}
+
+/// What a puzzle level asks of the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PuzzleGoal {
+    Trap(i32), // Trap the dummy this many times before time runs out
+    Survive,   // Don't get trapped until time runs out
+}
+
+/// One puzzle level, loaded from a text file in LEVELS_DIR. One directive per line, `#` starts a comment:
+///
+/// ```text
+/// name First Steps
+/// goal trap 1               (or: goal survive)
+/// time 30                   seconds on the clock
+/// arena open                open, portals or vortex
+/// shadows mirror            opponent, mirror or echo
+/// wrap                      wrap-around edges
+/// spawn character 2 900 400 start position of a piece (player 1 is you, 2 is the dummy)
+/// path shadow 2 150 300 400 900 400 ...   scripted loop: piece, player, speed, then x y pairs
+/// ```
+#[derive(Clone, Debug)]
+struct PuzzleLevel {
+    name: String,
+    goal: PuzzleGoal,
+    time_limit: f32,
+    config: MatchConfig,
+    spawns: Vec<(Piece, Vec2)>,
+    scripts: Vec<ScriptedPath>,
+}
+
+impl PuzzleLevel {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut level = PuzzleLevel {
+            name: "Untitled".to_string(),
+            goal: PuzzleGoal::Trap(1),
+            time_limit: 30.0,
+            config: MatchConfig::default(),
+            spawns: Vec::new(),
+            scripts: Vec::new(),
+        };
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut words = line.split_whitespace();
+            let Some(directive) = words.next() else {
+                continue;
+            };
+            let args: Vec<&str> = words.collect();
+            let error = |msg: &str| format!("line {}: {}", n + 1, msg);
+            let number = |word: &str| word.parse::<f32>().map_err(|_| error(&format!("'{}' is not a number", word)));
+            let piece = |args: &[&str]| -> Result<Piece, String> {
+                let player = match args.get(1) {
+                    Some(&"1") => 0,
+                    Some(&"2") => 1,
+                    _ => return Err(error("player must be 1 or 2")),
+                };
+                match args.first() {
+                    Some(&"character") => Ok(Piece::Character(player)),
+                    Some(&"shadow") => Ok(Piece::Shadow(player)),
+                    _ => Err(error("piece must be 'character' or 'shadow'")),
+                }
+            };
+            match directive {
+                "name" => level.name = args.join(" "),
+                "goal" => {
+                    level.goal = match args.as_slice() {
+                        ["trap", count] => PuzzleGoal::Trap(count.parse().map_err(|_| error("trap count must be a whole number"))?),
+                        ["survive"] => PuzzleGoal::Survive,
+                        _ => return Err(error("goal must be 'trap <count>' or 'survive'")),
+                    }
+                }
+                "time" => level.time_limit = number(args.first().copied().unwrap_or(""))?,
+                "arena" => {
+                    level.config.arena = args.first().and_then(|name| Arena::parse(name))
+                        .ok_or_else(|| error("arena must be open, portals or vortex"))?;
+                }
+                "shadows" => {
+                    level.config.shadow_control = match args.first().copied() {
+                        Some("opponent") => ShadowControl::Opponent,
+                        Some("mirror") => ShadowControl::Mirror,
+                        Some("echo") => ShadowControl::Echo,
+                        _ => return Err(error("shadows must be opponent, mirror or echo")),
+                    }
+                }
+                "wrap" => level.config.wrap = true,
+                "spawn" => {
+                    let [_, _, x, y] = args.as_slice() else {
+                        return Err(error("spawn needs a piece, a player and a position"));
+                    };
+                    level.spawns.push((piece(&args)?, Vec2 { x: number(x)?, y: number(y)? }));
+                }
+                "path" => {
+                    if args.len() < 5 || args.len().is_multiple_of(2) {
+                        return Err(error("path needs a piece, a player, a speed and x y pairs"));
+                    }
+                    let points = args[3..]
+                        .chunks(2)
+                        .map(|xy| Ok(Vec2 { x: number(xy[0])?, y: number(xy[1])? }))
+                        .collect::<Result<Vec<_>, String>>()?;
+                    level.scripts.push(ScriptedPath::new(piece(&args)?, points, number(args[2])?));
+                }
+                _ => return Err(error(&format!("unknown directive '{}'", directive))),
+            }
+        }
+        Ok(level)
+    }
+
+    /// Loads every level in `dir`, sorted by file name.
+    fn load_all(dir: &str) -> Result<Vec<Self>, String> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| format!("{}: {}", dir, e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+        let mut levels = Vec::new();
+        for path in paths {
+            let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            levels.push(Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?);
+        }
+        if levels.is_empty() {
+            return Err(format!("{}: no levels found", dir));
+        }
+        Ok(levels)
+    }
+}
+
+/// Single-player puzzle campaign: each level scripts some pieces, and you must trap the dummy
+/// (player 2) or survive before the clock runs out. Getting trapped once fails the level.
+struct PuzzleCampaign {
+    levels: Vec<PuzzleLevel>,
+    current: usize,
+    scripts: Vec<ScriptedPath>, // Running copies of the current level's scripts
+    time_left: f32,
+    started: (u32, u32), // Game number and round the pieces were last placed for
+    passed: Option<bool>, // Result of the current attempt, once it's over
+}
+
+impl PuzzleCampaign {
+    fn new(levels: Vec<PuzzleLevel>) -> Self {
+        PuzzleCampaign {
+            levels,
+            current: 0,
+            scripts: Vec::new(),
+            time_left: 0.0,
+            started: (u32::MAX, 0),
+            passed: None,
+        }
+    }
+
+    fn level(&self) -> &PuzzleLevel {
+        &self.levels[self.current]
+    }
+
+    /// Whether the current level's scripts drive this piece (movement keys leave it alone).
+    fn is_scripted(&self, piece: Piece) -> bool {
+        self.scripts.iter().any(|script| script.piece == piece)
+    }
+
+    /// Places the level's pieces for a new attempt or round.
+    fn place_pieces(&mut self, game: &mut GameState) {
+        let level = &self.levels[self.current];
+        game.reset_positions();
+        for &(piece, pos) in &level.spawns {
+            match piece {
+                Piece::Character(id) => game.players[id].pos = pos,
+                Piece::Shadow(id) => game.players[id].shadow_pos = pos,
+            }
+        }
+        game.apply_shadow_rules();
+        self.scripts = level.scripts.clone();
+        if self.started.0 != game.game_number {
+            self.time_left = level.time_limit;
+        }
+        self.started = (game.game_number, game.round);
+    }
+
+    /// Runs the level: scripts, the clock and the win/lose checks. The dummy always wants to go again.
+    fn update(&mut self, game: &mut GameState, dt: f32) {
+        if game.winner().is_some() {
+            game.rematch_votes[1] = Some(true);
+            return;
+        }
+        if let Some(passed) = self.passed.take() {
+            // Rematch started: next level on a pass, the same one again on a fail
+            if passed {
+                self.current = (self.current + 1) % self.levels.len();
+            }
+            game.config = self.level().config;
+        }
+        if self.started != (game.game_number, game.round) {
+            self.place_pieces(game);
+        }
+
+        let result = if game.players[0].score > 0 {
+            Some(false)
+        } else if let PuzzleGoal::Trap(count) = self.level().goal
+            && game.players[1].score >= count
+        {
+            Some(true)
+        } else if self.time_left <= 0.0 {
+            Some(self.level().goal == PuzzleGoal::Survive)
+        } else {
+            None
+        };
+        if let Some(passed) = result {
+            self.passed = Some(passed);
+            game.declare_winner(if passed { 0 } else { 1 });
+            return;
+        }
+
+        if !game.is_live() {
+            return;
+        }
+        self.time_left -= dt;
+        for script in self.scripts.iter_mut() {
+            script.update(game, dt);
+        }
+    }
+
+    /// Swaps in edited level files and starts the current level over from its new layout, with a
+    /// full clock (after the rematch, if the attempt is already over). Stays within the campaign if
+    /// files were removed.
+    fn reload(&mut self, levels: Vec<PuzzleLevel>, game: &mut GameState) {
+        self.levels = levels;
+        self.current = self.current.min(self.levels.len() - 1);
+        self.passed = None;
+        self.started = (u32::MAX, 0);
+        game.config = self.level().config;
+        if game.winner().is_none() {
+            for player in game.players.iter_mut() {
+                player.score = 0;
+            }
+        }
+    }
+
+    /// Line shown under the game over banner.
+    fn result_text(&self) -> String {
+        match self.passed {
+            Some(true) if self.current + 1 == self.levels.len() => tr!("puzzle-campaign-complete"),
+            Some(true) => tr!("puzzle-level-cleared", level = self.current + 1),
+            _ if self.time_left <= 0.0 => tr!("puzzle-out-of-time"),
+            _ => tr!("puzzle-trapped"),
+        }
+    }
+}
+
+/// Steps of the guided tutorial, in order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TutorialStep {
+    Steer,
+    Swap,
+    Inverse,
+    Trap,
+    Done,
+}
+
+/// Single-player tutorial against a dummy: steer its shadow, swap, sit through an inversion,
+/// then trap it. Each step finishes on its own once the game sees the player do it.
+struct Tutorial {
+    step: TutorialStep,
+    progress: f32, // Steer: pixels steered so far
+    last_steered: Vec2,
+    swaps: u32, // Swaps made when the step started
+    dummy_score: i32, // Times the dummy had been trapped when the step started
+    inverse_seen: bool, // Inverse: the inversion was armed or running at some point
+    script: ScriptedPath, // Trap: the dummy walking past the player
+    started: (u32, u32), // Game number and round the pieces were last placed for
+}
+
+impl Tutorial {
+    fn new() -> Self {
+        Tutorial {
+            step: TutorialStep::Steer,
+            progress: 0.0,
+            last_steered: Vec2 { x: 0.0, y: 0.0 },
+            swaps: 0,
+            dummy_score: 0,
+            inverse_seen: false,
+            script: Self::dummy_walk(),
+            started: (u32::MAX, 0),
+        }
+    }
+
+    /// Trap step: back and forth along the middle, straight through the player's spawn.
+    fn dummy_walk() -> ScriptedPath {
+        let y = SCREEN_HEIGHT as f32 / 2.0;
+        let points = vec![Vec2 { x: SCREEN_WIDTH as f32 * 0.15, y }, Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y }];
+        ScriptedPath::new(Piece::Character(1), points, tunables().rules.player_speed * 0.6)
+    }
+
+    fn begin(&mut self, step: TutorialStep, game: &mut GameState) {
+        self.step = step;
+        self.progress = 0.0;
+        self.swaps = game.stats[0].swaps;
+        self.dummy_score = game.players[1].score;
+        self.inverse_seen = false;
+        self.script = Self::dummy_walk();
+        self.place_pieces(game);
+    }
+
+    /// Back to the spawns for a new step or round.
+    fn place_pieces(&mut self, game: &mut GameState) {
+        game.reset_positions();
+        self.last_steered = game.steered_pos(0);
+        self.started = (game.game_number, game.round);
+    }
+
+    /// Watches for the current step being done. The dummy always wants to go again.
+    fn update(&mut self, game: &mut GameState, dt: f32) {
+        if game.winner().is_some() {
+            game.rematch_votes[1] = Some(true);
+            return;
+        }
+        if self.step == TutorialStep::Done {
+            // Played it again from the end
+            self.begin(TutorialStep::Steer, game);
+        }
+        if self.started != (game.game_number, game.round) {
+            self.place_pieces(game);
+        }
+        if !game.is_live() {
+            return;
+        }
+
+        match self.step {
+            TutorialStep::Steer => {
+                let steered = game.steered_pos(0);
+                let moved = steered.distance(self.last_steered);
+                self.last_steered = steered;
+                if moved < 100.0 {
+                    self.progress += moved;
+                }
+                if self.progress >= TUTORIAL_STEER {
+                    self.begin(TutorialStep::Swap, game);
+                }
+            }
+            TutorialStep::Swap => {
+                if game.stats[0].swaps > self.swaps {
+                    self.begin(TutorialStep::Inverse, game);
+                }
+            }
+            TutorialStep::Inverse => {
+                let running = game.inverse_armed || game.inverse_active;
+                self.inverse_seen |= running;
+                if !self.inverse_seen {
+                    game.players[0].inverse_charge = 1.0; // Skip charging the meter
+                } else if !running {
+                    self.begin(TutorialStep::Trap, game);
+                }
+            }
+            TutorialStep::Trap => {
+                if game.players[1].score > self.dummy_score {
+                    self.step = TutorialStep::Done;
+                    game.declare_winner(0);
+                    return;
+                }
+                self.script.update(game, dt);
+            }
+            TutorialStep::Done => {}
+        }
+    }
+
+    /// Current step's title and what to do, one line each.
+    fn instructions(&self, game: &GameState, bindings: &Bindings) -> (String, [String; 2]) {
+        let (title, lines) = match self.step {
+            TutorialStep::Steer => ("tutorial-steer-title", ["tutorial-steer-1", "tutorial-steer-2"]),
+            TutorialStep::Swap => ("tutorial-swap-title", ["tutorial-swap-1", "tutorial-swap-2"]),
+            TutorialStep::Inverse if !self.inverse_seen => ("tutorial-inverse-title", ["tutorial-inverse-1", "tutorial-inverse-2"]),
+            TutorialStep::Inverse if game.inverse_active => {
+                ("tutorial-inverse-title", ["tutorial-inverse-active-1", "tutorial-inverse-active-2"])
+            }
+            TutorialStep::Inverse => ("tutorial-inverse-title", ["tutorial-inverse-wait-1", "tutorial-inverse-wait-2"]),
+            TutorialStep::Trap | TutorialStep::Done => ("tutorial-trap-title", ["tutorial-trap-1", "tutorial-trap-2"]),
+        };
+        let key = |action| key_name(bindings.key(action));
+        (tr!(title), lines.map(|line| tr!(line, keys = bindings.movement(), swap = key(Action::Swap), inverse = key(Action::Inverse))))
+    }
+
+    /// Instructions panel under the title bar, with a progress bar for the steering step.
+    fn draw(&self, d: &mut RaylibDrawHandle, game: &GameState, bindings: &Bindings) {
+        let (title, lines) = self.instructions(game, bindings);
+        let (w, h) = (760, 84);
+        let x = SCREEN_WIDTH / 2 - w / 2;
+        let y = HUD_TOP as i32 + 8;
+        let theme = theme();
+        d.draw_rectangle(x, y, w, h, theme.panel);
+        d.draw_rectangle_lines(x, y, w, h, theme.accent);
+        d.draw_text(&title, x + 14, y + 8, 22, theme.title);
+        for (i, line) in lines.iter().enumerate() {
+            d.draw_text(line, x + 14, y + 36 + i as i32 * 22, 18, theme.panel_text);
+        }
+        if self.step == TutorialStep::Steer {
+            let progress = (self.progress / TUTORIAL_STEER).min(1.0);
+            d.draw_rectangle(x + w - 174, y + 12, 160, 12, theme.meter);
+            d.draw_rectangle(x + w - 174, y + 12, (160.0 * progress) as i32, 12, theme.accent);
+        }
+    }
+
+    /// Line shown under the game over banner.
+    fn result_text(&self) -> String {
+        if self.step == TutorialStep::Done {
+            tr!("tutorial-complete")
+        } else {
+            tr!("tutorial-trapped")
+        }
+    }
+}
+
+/// Single-player daily challenge. Today's date seeds the arena and the bot's scripted loops:
+/// the bot walks its character around one loop and drags your shadow around another.
+/// Trap it WIN_SCORE times as fast as you can; the best time per day is kept locally.
+struct DailyChallenge {
+    day: u64, // Days since the Unix epoch, also the seed
+    scripts: [ScriptedPath; 2], // The bot's character and your shadow
+    elapsed: f32,
+    best: Option<f32>,
+    finished: bool,
+}
+
+impl DailyChallenge {
+    fn today() -> Self {
+        let day = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() / 86_400).unwrap_or(0);
+        let mut rng = StdRng::seed_from_u64(day);
+        let mut path = || -> Vec<Vec2> {
+            (0..DAILY_WAYPOINTS)
+                .map(|_| Vec2 {
+                    x: rng.random_range(80.0..SCREEN_WIDTH as f32 - 80.0),
+                    y: rng.random_range(180.0..SCREEN_HEIGHT as f32 - 160.0),
+                })
+                .collect()
+        };
+        let scripts = [
+            ScriptedPath::new(Piece::Character(1), path(), DAILY_BOT_SPEED * tunables().rules.player_speed),
+            ScriptedPath::new(Piece::Shadow(0), path(), DAILY_BOT_SPEED * tunables().rules.player_speed),
+        ];
+        DailyChallenge {
+            day,
+            scripts,
+            elapsed: 0.0,
+            best: Self::load_best(day),
+            finished: false,
+        }
+    }
+
+    /// Match rules for today, picked from the same seed as the paths.
+    fn config(&self) -> MatchConfig {
+        let mut rng = StdRng::seed_from_u64(self.day.wrapping_mul(31).wrapping_add(7));
+        MatchConfig {
+            arena: Arena::ALL[rng.random_range(0..3)],
+            wrap: rng.random_bool(0.3),
+            ..MatchConfig::default()
+        }
+    }
+
+    /// Today's date as YYYY-MM-DD.
+    fn date(&self) -> String {
+        calendar_date(self.day)
+    }
+
+    fn load_best(day: u64) -> Option<f32> {
+        let text = std::fs::read_to_string(paths::data(DAILY_BEST_FILE)).ok()?;
+        text.lines().find_map(|line| {
+            let (d, time) = line.split_once(' ')?;
+            if d.parse::<u64>().ok()? == day { time.parse().ok() } else { None }
+        })
+    }
+
+    fn save_best(&self, time: f32) {
+        let path = paths::data(DAILY_BEST_FILE);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut lines: Vec<String> = text
+            .lines()
+            .filter(|line| line.split_once(' ').map(|(d, _)| d != self.day.to_string()).unwrap_or(false))
+            .map(str::to_string)
+            .collect();
+        lines.push(format!("{} {:.2}", self.day, time));
+        if let Err(e) = std::fs::write(&path, lines.join("\n") + "\n") {
+            eprintln!("Could not save daily challenge time: {}", e);
+        }
+    }
+
+    /// Drives the bot (player 1) and the run clock. The bot always wants a rematch.
+    fn update(&mut self, game: &mut GameState, dt: f32) {
+        if let Some(winner) = game.winner() {
+            if !self.finished {
+                self.finished = true;
+                if winner == 0 && self.best.is_none_or(|best| self.elapsed < best) {
+                    self.best = Some(self.elapsed);
+                    self.save_best(self.elapsed);
+                }
+            }
+            game.rematch_votes[1] = Some(true);
+            return;
+        }
+        if self.finished {
+            // Rematch started: same challenge, fresh clock
+            self.finished = false;
+            self.elapsed = 0.0;
+            for script in self.scripts.iter_mut() {
+                script.next = 0;
+            }
+        }
+        if !game.is_live() {
+            return;
+        }
+        self.elapsed += dt;
+        for script in self.scripts.iter_mut() {
+            script.update(game, dt);
+        }
+    }
+}
+
+/// One adjustable row on the host's match setup screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Setting {
+    Mode,
+    Hunter,
+    Schedule,
+    Shadows,
+    EchoDelay,
+    Arena,
+    Speed(usize),
+    TrapRadius(usize),
+    PreloadedTraps(usize),
+    AfkTimeout,
+    TetherRange,
+    Wrap,
+    ShadowSteal,
+    Fog,
+    StreakBonus,
+}
+
+/// Steps through `options` from `current`, wrapping around at either end.
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, dir: i32) -> T {
+    let i = options.iter().position(|&option| option == current).unwrap_or(0) as i32;
+    options[(i + dir).rem_euclid(options.len() as i32) as usize]
+}
+
+/// Rounds to one decimal, so repeated 0.1 steps don't drift.
+fn tenths(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+impl Setting {
+    /// Rows for the current config. The hunter and echo delay rows only show when they apply.
+    fn rows(config: &MatchConfig) -> Vec<Setting> {
+        let mut rows = vec![Setting::Mode];
+        if config.mode == GameMode::HunterVsShade {
+            rows.push(Setting::Hunter);
+        }
+        rows.extend([Setting::Schedule, Setting::Shadows]);
+        if config.shadow_control == ShadowControl::Echo {
+            rows.push(Setting::EchoDelay);
+        }
+        rows.push(Setting::Arena);
+        for i in 0..2 {
+            rows.extend([Setting::Speed(i), Setting::TrapRadius(i), Setting::PreloadedTraps(i)]);
+        }
+        rows.extend([
+            Setting::AfkTimeout,
+            Setting::TetherRange,
+            Setting::Wrap,
+            Setting::ShadowSteal,
+            Setting::Fog,
+            Setting::StreakBonus,
+        ]);
+        rows
+    }
+
+    fn label(self) -> String {
+        let who = |i: usize| tr!(if i == 0 { "setting-who-you" } else { "setting-who-opponent" });
+        match self {
+            Setting::Mode => tr!("setting-mode"),
+            Setting::Hunter => tr!("setting-hunter"),
+            Setting::Schedule => tr!("setting-schedule"),
+            Setting::Shadows => tr!("setting-shadows"),
+            Setting::EchoDelay => tr!("setting-echo-delay"),
+            Setting::Arena => tr!("setting-arena"),
+            Setting::Speed(i) => tr!("setting-speed", who = who(i)),
+            Setting::TrapRadius(i) => tr!("setting-trap-radius", who = who(i)),
+            Setting::PreloadedTraps(i) => tr!("setting-preloaded-traps", who = who(i)),
+            Setting::AfkTimeout => tr!("setting-afk-timeout"),
+            Setting::TetherRange => tr!("setting-tether"),
+            Setting::Wrap => tr!("setting-wrap"),
+            Setting::ShadowSteal => tr!("setting-steal"),
+            Setting::Fog => tr!("setting-fog"),
+            Setting::StreakBonus => tr!("setting-streak-bonus", count = STREAK_BONUS_AT),
+        }
+    }
+
+    fn value(self, config: &MatchConfig) -> String {
+        let on_off = |on: bool| tr!(if on { "on" } else { "off" });
+        match self {
+            Setting::Mode => config.mode.name(),
+            Setting::Hunter => tr!(if config.hunter == 0 { "setting-hunter-me" } else { "setting-hunter-opponent" }),
+            Setting::Schedule => config.inverse_schedule.name(),
+            Setting::Shadows => config.shadow_control.name(),
+            Setting::EchoDelay => format!("{:.1}s", config.echo_delay),
+            Setting::Arena => config.arena.name(),
+            Setting::Speed(i) => format!("x{:.1}", config.handicaps[i].speed),
+            Setting::TrapRadius(i) => format!("x{:.1}", config.handicaps[i].trap_radius),
+            Setting::PreloadedTraps(i) => config.handicaps[i].preloaded_traps.to_string(),
+            Setting::AfkTimeout if config.afk_timeout <= 0.0 => tr!("off"),
+            Setting::AfkTimeout => format!("{:.0}s", config.afk_timeout),
+            Setting::TetherRange if config.tether_range <= 0.0 => tr!("off"),
+            Setting::TetherRange => format!("{:.0}px", config.tether_range),
+            Setting::Wrap => on_off(config.wrap),
+            Setting::ShadowSteal => on_off(config.shadow_steal),
+            Setting::Fog => on_off(config.fog),
+            Setting::StreakBonus => on_off(config.streak_bonus),
+        }
+    }
+
+    /// Moves the value one step left (`dir` -1) or right (+1).
+    fn adjust(self, config: &mut MatchConfig, dir: i32) {
+        let step = dir as f32;
+        match self {
+            Setting::Mode => config.mode = cycle(&GameMode::ALL, config.mode, dir),
+            Setting::Hunter => config.hunter = 1 - config.hunter,
+            Setting::Schedule => {
+                let schedules = [
+                    InverseSchedule::Meter,
+                    InverseSchedule::Fixed,
+                    InverseSchedule::Random,
+                    InverseSchedule::Escalating,
+                    InverseSchedule::Endgame,
+                ];
+                config.inverse_schedule = cycle(&schedules, config.inverse_schedule, dir);
+            }
+            Setting::Shadows => {
+                let controls = [ShadowControl::Opponent, ShadowControl::Mirror, ShadowControl::Echo];
+                config.shadow_control = cycle(&controls, config.shadow_control, dir);
+            }
+            Setting::EchoDelay => config.echo_delay = (config.echo_delay + 0.5 * step).clamp(0.5, 5.0),
+            Setting::Arena => config.arena = cycle(&Arena::ALL, config.arena, dir),
+            Setting::Speed(i) => {
+                let handicap = &mut config.handicaps[i];
+                handicap.speed = tenths(handicap.speed + 0.1 * step).clamp(0.5, 2.0);
+            }
+            Setting::TrapRadius(i) => {
+                let handicap = &mut config.handicaps[i];
+                handicap.trap_radius = tenths(handicap.trap_radius + 0.1 * step).clamp(0.5, 2.0);
+            }
+            Setting::PreloadedTraps(i) => {
+                let handicap = &mut config.handicaps[i];
+                handicap.preloaded_traps = (handicap.preloaded_traps + dir).clamp(0, WIN_SCORE - 1);
+            }
+            Setting::AfkTimeout => config.afk_timeout = (config.afk_timeout + 15.0 * step).clamp(0.0, 300.0),
+            Setting::TetherRange => config.tether_range = (config.tether_range + 50.0 * step).clamp(0.0, 600.0),
+            Setting::Wrap => config.wrap = !config.wrap,
+            Setting::ShadowSteal => config.shadow_steal = !config.shadow_steal,
+            Setting::Fog => config.fog = !config.fog,
+            Setting::StreakBonus => config.streak_bonus = !config.streak_bonus,
+        }
+    }
+}
+
+/// Player colors for color vision deficiencies. Anything but the picked colors also marks
+/// each player with a shape (triangle for player 1, square for player 2).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Palette {
+    #[default]
+    Picked, // The colors players chose for themselves
+    RedGreen, // Blue vs orange: safe for deuteranopia and protanopia
+    BlueYellow, // Crimson vs teal: safe for tritanopia
+    Monochrome, // Light vs dark, told apart by shape
+}
+
+impl Palette {
+    fn name(self) -> String {
+        tr!(match self {
+            Palette::Picked => "palette-picked",
+            Palette::RedGreen => "palette-red-green",
+            Palette::BlueYellow => "palette-blue-yellow",
+            Palette::Monochrome => "palette-monochrome",
+        })
+    }
+
+    /// Colors for player 1 and player 2, or None to use their picks.
+    fn colors(self) -> Option<[Color; 2]> {
+        match self {
+            Palette::Picked => None,
+            Palette::RedGreen => Some([Color::new(0, 114, 178, 255), Color::new(230, 159, 0, 255)]),
+            Palette::BlueYellow => Some([Color::new(220, 50, 80, 255), Color::new(0, 170, 170, 255)]),
+            Palette::Monochrome => Some([Color::new(240, 240, 240, 255), Color::new(120, 120, 120, 255)]),
+        }
+    }
+}
+
+/// Local display settings. They only change how this screen draws the match, so they're never sent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Accessibility {
+    palette: Palette,
+    high_contrast: bool, // Thick outlines and a solid trap ring, no grid or glow layers
+    reduced_motion: bool, // No shaking, pulsing, spinning or flashing; static stand-ins instead
+    announce: Announce, // Where match events are announced
+    hud_scale: f32, // Size of HUD text and panels, within HUD_SCALE_RANGE
+    graphics: Graphics, // Post-processing, for GPUs that can afford it
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Accessibility {
+            palette: Palette::default(),
+            high_contrast: false,
+            reduced_motion: false,
+            announce: Announce::default(),
+            hud_scale: 1.0,
+            graphics: Graphics::default(),
+        }
+    }
+}
+
+/// Graphics quality. High runs the canvas through the post-process shader (see `PostFx`);
+/// Low draws the old layered-circle glow instead, for weak GPUs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Graphics {
+    Low,
+    #[default]
+    High,
+}
+
+impl Graphics {
+    fn name(self) -> String {
+        tr!(match self {
+            Graphics::Low => "graphics-low",
+            Graphics::High => "graphics-high",
+        })
+    }
+}
+
+/// How match events (traps, inversions, disconnects...) are announced beyond the usual visuals.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Announce {
+    #[default]
+    Off,
+    Log, // On-screen event log, echoed to stdout for screen readers following the terminal
+    Speech, // The log, plus the platform's text-to-speech
+}
+
+impl Announce {
+    fn name(self) -> String {
+        tr!(match self {
+            Announce::Off => "off",
+            Announce::Log => "announce-log",
+            Announce::Speech => "announce-speech",
+        })
+    }
+}
+
+impl Accessibility {
+    /// Per-player shapes are drawn with any palette but the picked colors.
+    fn shapes(&self) -> bool {
+        self.palette != Palette::Picked
+    }
+
+    /// Oscillation in -1..=1 behind every pulsing effect. Holds at 1 with reduced motion,
+    /// so pulses turn into their steady, fully visible state.
+    fn wave(&self, phase: f32) -> f32 {
+        if self.reduced_motion { 1.0 } else { phase.sin() }
+    }
+
+    /// Rotation in degrees for spinning effects, frozen at 0 with reduced motion.
+    fn spin(&self, degrees: f32) -> f32 {
+        if self.reduced_motion { 0.0 } else { degrees % 360.0 }
+    }
+
+    /// Whether shakes, flashes, jitter and zooms play at all; their call sites draw a static
+    /// indicator instead when they don't.
+    fn motion(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    /// Whether the bloom shader does the glowing, so the arena skips its fake glow layers.
+    /// High contrast keeps edges crisp and goes without either.
+    fn bloom(&self) -> bool {
+        self.graphics == Graphics::High && !self.high_contrast
+    }
+}
+
+/// How the game window is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WindowMode {
+    Windowed,
+    Fullscreen,
+    Borderless, // A window covering the monitor at its own resolution
+}
+
+impl WindowMode {
+    fn name(self) -> String {
+        tr!(match self {
+            WindowMode::Windowed => "window-windowed",
+            WindowMode::Fullscreen => "window-fullscreen",
+            WindowMode::Borderless => "window-borderless",
+        })
+    }
+}
+
+/// Window mode and size. Whatever the window ends up as, the game is drawn on a
+/// SCREEN_WIDTH x SCREEN_HEIGHT canvas scaled to fit it (see `letterbox`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Display {
+    mode: WindowMode,
+    resolution: (i32, i32),
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display { mode: WindowMode::Windowed, resolution: tunables().window }
+    }
+}
+
+impl Display {
+    /// Switches the window over from `previous`, which is what's currently applied.
+    fn apply(self, previous: Display, rl: &mut RaylibHandle) {
+        match previous.mode {
+            WindowMode::Windowed => {}
+            WindowMode::Fullscreen => rl.toggle_fullscreen(),
+            WindowMode::Borderless => rl.toggle_borderless_windowed(),
+        }
+        let (width, height) = self.resolution;
+        match self.mode {
+            WindowMode::Windowed => {
+                rl.set_window_size(width, height);
+                let monitor = get_current_monitor();
+                let (monitor_width, monitor_height) = (get_monitor_width(monitor), get_monitor_height(monitor));
+                rl.set_window_position((monitor_width - width).max(0) / 2, (monitor_height - height).max(0) / 2);
+            }
+            WindowMode::Fullscreen => {
+                // Fullscreen takes on the window's size as the video mode
+                rl.set_window_size(width, height);
+                rl.toggle_fullscreen();
+            }
+            WindowMode::Borderless => rl.toggle_borderless_windowed(),
+        }
+    }
+
+    /// Alt+Enter: fullscreen from a window, back to a window from either fullscreen mode.
+    fn toggle_fullscreen(&mut self, rl: &mut RaylibHandle) {
+        let previous = *self;
+        self.mode = if self.mode == WindowMode::Windowed { WindowMode::Fullscreen } else { WindowMode::Windowed };
+        self.apply(previous, rl);
+    }
+}
+
+/// Volume levels, each 0 to 1, and the announcer switch. Kept in the config file's [audio] table
+/// between runs; Ctrl+M mutes everything.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Volume {
+    master: f32,
+    music: f32,
+    sfx: f32, // Effects and the announcer
+    muted: bool,
+    announcer: bool,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Volume { master: 1.0, music: 0.7, sfx: 0.8, muted: false, announcer: true }
+    }
+}
+
+impl Volume {
+    /// The [audio] table as CONFIG_FILE writes it.
+    fn table(&self) -> String {
+        format!(
+            "[audio] # levels from 0 to 1; the game saves changes made in Settings here\n\
+             master = {:.1}\n\
+             music = {:.1}\n\
+             sfx = {:.1}\n\
+             muted = {}\n\
+             announcer = {}\n",
+            self.master, self.music, self.sfx, self.muted, self.announcer
+        )
+    }
+
+    /// `text` with the [audio] values set to these, keeping every other line and comment as it is.
+    /// Missing keys go at the end of the table, and a missing table at the end of the file.
+    fn write_into(&self, text: &str) -> String {
+        let values = [
+            ("master", format!("{:.1}", self.master)),
+            ("music", format!("{:.1}", self.music)),
+            ("sfx", format!("{:.1}", self.sfx)),
+            ("muted", self.muted.to_string()),
+            ("announcer", self.announcer.to_string()),
+        ];
+        let mut lines: Vec<String> = Vec::new();
+        let mut written = [false; 5];
+        let (mut in_audio, mut end) = (false, None);
+        for line in text.lines() {
+            let (body, comment) = line.split_at(line.find('#').unwrap_or(line.len()));
+            let body = body.trim();
+            if let Some(name) = body.strip_prefix('[').and_then(|body| body.strip_suffix(']')) {
+                in_audio = name.trim() == "audio";
+                if in_audio {
+                    end = Some(lines.len() + 1);
+                }
+            } else if in_audio && !body.is_empty() {
+                let key = body.split_once('=').map_or(body, |(key, _)| key).trim();
+                if let Some(i) = values.iter().position(|(name, _)| *name == key) {
+                    written[i] = true;
+                    let comment = if comment.is_empty() { String::new() } else { format!(" {}", comment) };
+                    lines.push(format!("{} = {}{}", key, values[i].1, comment));
+                    end = Some(lines.len());
+                    continue;
+                }
+                end = Some(lines.len() + 1);
+            }
+            lines.push(line.to_string());
+        }
+        let Some(end) = end else {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            return format!("{}\n{}", lines.join("\n"), self.table());
+        };
+        let missing = values.iter().zip(written).filter(|(_, written)| !written).map(|((key, value), _)| format!("{} = {}", key, value));
+        lines.splice(end..end, missing);
+        lines.join("\n") + "\n"
+    }
+
+    /// Saves the levels into the config file at `path`.
+    fn save(&self, path: &Path) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return eprintln!("Could not save volume settings: {}", e),
+        };
+        if let Err(e) = std::fs::write(path, self.write_into(&text)) {
+            eprintln!("Could not save volume settings: {}", e);
+        }
+    }
+
+    /// What a channel at `level` actually plays at, after the master volume and mute.
+    fn of(&self, level: f32) -> f32 {
+        if self.muted { 0.0 } else { self.master * level }
+    }
+}
+
+/// Steps a volume level up or down by VOLUME_STEP.
+fn step_volume(level: &mut f32, dir: i32) {
+    *level = (((*level + dir as f32 * VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP).clamp(0.0, 1.0);
+}
+
+/// Where the SCREEN_WIDTH x SCREEN_HEIGHT canvas lands in the window: the largest scale that fits,
+/// centered, with black bars filling the rest.
+fn letterbox(rl: &RaylibHandle) -> Rectangle {
+    let (width, height) = (rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+    let scale = (width / SCREEN_WIDTH as f32).min(height / SCREEN_HEIGHT as f32);
+    let scale = if scale > 0.0 { scale } else { 1.0 }; // Minimized
+    let (w, h) = (SCREEN_WIDTH as f32 * scale, SCREEN_HEIGHT as f32 * scale);
+    Rectangle::new((width - w) / 2.0, (height - h) / 2.0, w, h)
+}
+
+/// Post-process pass the canvas is drawn to the window through on high graphics: bright pixels
+/// bleed into a bloom, and inverse mode ripples the picture with a slight color split.
+const POST_FX_SHADER: &str = r#"#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+uniform vec2 size;
+uniform float warp;
+uniform float time;
+out vec4 finalColor;
+
+void main() {
+    vec2 uv = fragTexCoord;
+    vec2 centered = uv - 0.5;
+    uv += warp * 0.006 * (0.4 + length(centered)) * vec2(sin(uv.y * 40.0 + time * 3.0), cos(uv.x * 40.0 + time * 2.5));
+    vec2 split = vec2(warp * 0.003, 0.0);
+    vec4 source = texture(texture0, uv);
+    source.r = texture(texture0, uv + split).r;
+    source.b = texture(texture0, uv - split).b;
+
+    // Only what's brighter than the threshold spills over
+    vec4 bloom = vec4(0.0);
+    vec2 texel = 2.5 / size;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            bloom += max(texture(texture0, uv + vec2(x, y) * texel) - 0.45, 0.0);
+        }
+    }
+    finalColor = vec4((source + bloom / 25.0 * 1.6).rgb, 1.0) * colDiffuse * fragColor;
+}
+"#;
+
+/// How fast the inverse ripple fades in and out, per second.
+const WARP_EASE: f32 = 4.0;
+
+/// The loaded post-process shader and its uniforms.
+struct PostFx {
+    shader: Shader,
+    warp: f32, // Eased towards 1 while inverse mode is on
+    warp_loc: i32,
+    time_loc: i32,
+}
+
+impl PostFx {
+    /// None when the GPU or driver can't compile the shader; the game then runs on low graphics.
+    fn load(rl: &mut RaylibHandle, thread: &RaylibThread) -> Option<PostFx> {
+        let mut shader = rl.load_shader_from_memory(thread, None, Some(POST_FX_SHADER));
+        if !shader.is_shader_valid() {
+            return None;
+        }
+        let size_loc = shader.get_shader_location("size");
+        shader.set_shader_value(size_loc, Vector2::new(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32));
+        let warp_loc = shader.get_shader_location("warp");
+        let time_loc = shader.get_shader_location("time");
+        Some(PostFx { shader, warp: 0.0, warp_loc, time_loc })
+    }
+
+    /// Eases the ripple and hands this frame's uniforms to the shader.
+    fn update(&mut self, inverted: bool, time: f32, dt: f32) {
+        let target = if inverted { 1.0 } else { 0.0 };
+        self.warp += (target - self.warp) * (WARP_EASE * dt).min(1.0);
+        self.shader.set_shader_value(self.warp_loc, self.warp);
+        self.shader.set_shader_value(self.time_loc, time);
+    }
+}
+
+/// F12: the frame as the player sees it, post-processing included but without the letterbox bars,
+/// saved at canvas size to SCREENSHOTS_DIR.
+fn save_screenshot(rl: &mut RaylibHandle, thread: &RaylibThread, canvas: &RenderTexture2D, post: Option<&mut PostFx>) -> Result<PathBuf, String> {
+    let mut shot = rl.load_render_texture(thread, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32).map_err(|e| e.to_string())?;
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut shot);
+        let source = Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, -(SCREEN_HEIGHT as f32));
+        let target = Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        if let Some(post) = post {
+            let mut d = d.begin_shader_mode(&mut post.shader);
+            d.draw_texture_pro(canvas, source, target, Vector2::zero(), 0.0, Color::WHITE);
+        } else {
+            d.draw_texture_pro(canvas, source, target, Vector2::zero(), 0.0, Color::WHITE);
+        }
+    }
+    let mut image = shot.load_image().map_err(|e| e.to_string())?;
+    image.flip_vertical();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+    let path = paths::data(SCREENSHOTS_DIR).join(format!("{}.png", now));
+    std::fs::create_dir_all(paths::data(SCREENSHOTS_DIR)).map_err(|e| e.to_string())?;
+    image.export_image(&path.to_string_lossy());
+    if !path.exists() {
+        return Err(format!("could not write {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// One row of the settings menu. The language and theme are global (see `set_language` and
+/// `set_theme`), the window rows change the `Display`, the volume rows the `Volume`, and the rest
+/// are accessibility settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Preference {
+    Language,
+    Theme,
+    Window,
+    Resolution,
+    Palette,
+    HighContrast,
+    ReducedMotion,
+    Announce,
+    HudScale,
+    Graphics,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Announcer,
+}
+
+impl Preference {
+    const ALL: [Preference; 14] = [
+        Preference::Language,
+        Preference::Theme,
+        Preference::Window,
+        Preference::Resolution,
+        Preference::Palette,
+        Preference::HighContrast,
+        Preference::ReducedMotion,
+        Preference::Announce,
+        Preference::HudScale,
+        Preference::Graphics,
+        Preference::MasterVolume,
+        Preference::MusicVolume,
+        Preference::SfxVolume,
+        Preference::Announcer,
+    ];
+
+    fn label(self) -> String {
+        tr!(match self {
+            Preference::Language => "pref-language",
+            Preference::Theme => "pref-theme",
+            Preference::Window => "pref-window",
+            Preference::Resolution => "pref-resolution",
+            Preference::Palette => "pref-palette",
+            Preference::HighContrast => "pref-high-contrast",
+            Preference::ReducedMotion => "pref-reduced-motion",
+            Preference::Announce => "pref-announce",
+            Preference::HudScale => "pref-hud-scale",
+            Preference::Graphics => "pref-graphics",
+            Preference::MasterVolume => "pref-master-volume",
+            Preference::MusicVolume => "pref-music-volume",
+            Preference::SfxVolume => "pref-sfx-volume",
+            Preference::Announcer => "pref-announcer",
+        })
+    }
+
+    fn value(self, settings: &Accessibility, display: &Display, volume: &Volume) -> String {
+        let on_off = |on: bool| tr!(if on { "on" } else { "off" });
+        match self {
+            Preference::Language => tr!("language-name"),
+            Preference::Theme => theme_name(),
+            Preference::Window => display.mode.name(),
+            Preference::Resolution if display.mode == WindowMode::Borderless => tr!("resolution-monitor"),
+            Preference::Resolution => tr!("resolution", width = display.resolution.0, height = display.resolution.1),
+            Preference::Palette => settings.palette.name(),
+            Preference::HighContrast => on_off(settings.high_contrast),
+            Preference::ReducedMotion => on_off(settings.reduced_motion),
+            Preference::Announce => settings.announce.name(),
+            Preference::HudScale => format!("{:.2}x", settings.hud_scale),
+            Preference::Graphics => settings.graphics.name(),
+            Preference::MasterVolume if volume.muted => tr!("muted"),
+            Preference::MasterVolume => format!("{:.0}%", volume.master * 100.0),
+            Preference::MusicVolume => format!("{:.0}%", volume.music * 100.0),
+            Preference::SfxVolume => format!("{:.0}%", volume.sfx * 100.0),
+            Preference::Announcer => on_off(volume.announcer),
+        }
+    }
+
+    fn adjust(self, settings: &mut Accessibility, display: &mut Display, volume: &mut Volume, dir: i32) -> Result<(), String> {
+        match self {
+            Preference::Language => {
+                let languages = Locale::available();
+                let current = language();
+                let i = languages.iter().position(|language| *language == current).unwrap_or(0) as i32;
+                set_language(&languages[(i + dir).rem_euclid(languages.len() as i32) as usize])?;
+            }
+            Preference::Theme => {
+                let themes = Theme::available();
+                let current = theme_name();
+                let i = themes.iter().position(|name| *name == current).unwrap_or(0) as i32;
+                set_theme(&themes[(i + dir).rem_euclid(themes.len() as i32) as usize])?;
+            }
+            Preference::Window => {
+                let modes = [WindowMode::Windowed, WindowMode::Fullscreen, WindowMode::Borderless];
+                display.mode = cycle(&modes, display.mode, dir);
+            }
+            Preference::Resolution => display.resolution = cycle(&RESOLUTIONS, display.resolution, dir),
+            Preference::Palette => {
+                let palettes = [Palette::Picked, Palette::RedGreen, Palette::BlueYellow, Palette::Monochrome];
+                settings.palette = cycle(&palettes, settings.palette, dir);
+            }
+            Preference::HighContrast => settings.high_contrast = !settings.high_contrast,
+            Preference::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            Preference::Announce => {
+                settings.announce = cycle(&[Announce::Off, Announce::Log, Announce::Speech], settings.announce, dir);
+            }
+            Preference::HudScale => {
+                let scale = ((settings.hud_scale + dir as f32 * HUD_SCALE_STEP) / HUD_SCALE_STEP).round() * HUD_SCALE_STEP;
+                settings.hud_scale = scale.clamp(HUD_SCALE_RANGE.0, HUD_SCALE_RANGE.1);
+            }
+            Preference::Graphics => settings.graphics = cycle(&[Graphics::Low, Graphics::High], settings.graphics, dir),
+            Preference::MasterVolume => {
+                volume.muted = false;
+                step_volume(&mut volume.master, dir);
+            }
+            Preference::MusicVolume => step_volume(&mut volume.music, dir),
+            Preference::SfxVolume => step_volume(&mut volume.sfx, dir),
+            Preference::Announcer => volume.announcer = !volume.announcer,
+        }
+        Ok(())
+    }
+}
+
+/// Which page of the main menu is showing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MenuScreen {
+    Main,
+    HostSetup,
+    Join,
+    Profile, // Name, color and the ways to the next two
+    Profiles, // Who's playing: pick, add or delete a profile
+    Controls, // Key bindings
+    Cosmetics, // Trail and swap effect, and the achievements that unlock them
+    Settings,
+    History,
+    Leaderboard,
+    Failed(MenuChoice), // Starting this failed: why, with "Try again" and "Back"
+}
+
+/// What the player picked on the main menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MenuChoice {
+    Host,
+    Join,
+    Tutorial,
+    Daily,
+    Puzzle,
+    Profile,
+    Settings,
+    History,
+    Leaderboard,
+    Quit,
+    Practice(BotLevel), // Offline match against a bot, only from the command line (--bot)
+}
+
+/// Main menu rows: message id and choice.
+const MENU_ITEMS: [(&str, MenuChoice); 9] = [
+    ("menu-host", MenuChoice::Host),
+    ("menu-join", MenuChoice::Join),
+    ("menu-tutorial", MenuChoice::Tutorial),
+    ("menu-daily", MenuChoice::Daily),
+    ("menu-puzzle", MenuChoice::Puzzle),
+    ("menu-history", MenuChoice::History),
+    ("menu-leaderboard", MenuChoice::Leaderboard),
+    ("menu-settings", MenuChoice::Settings),
+    ("menu-profile", MenuChoice::Profile),
+];
+
+/// Draws text centered on the screen horizontally, for strings whose length depends on the language.
+fn draw_centered(d: &mut RaylibDrawHandle, text: &str, y: i32, size: i32, color: Color) {
+    let width = d.measure_text(text, size);
+    d.draw_text(text, SCREEN_WIDTH / 2 - width / 2, y, size, color);
+}
+
+/// Draws one group of HUD elements hung from `anchor` (a corner or edge of the screen), scaled by
+/// the HUD scale setting about that point, so the group grows or shrinks in place. `draw` lays the
+/// group out at 1x, in screen coordinates.
+fn draw_hud(d: &mut RaylibDrawHandle, anchor: Vector2, scale: f32, draw: impl FnOnce(&mut RaylibDrawHandle)) {
+    let mut hud = d.begin_mode2D(Camera2D { offset: anchor, target: anchor, rotation: 0.0, zoom: scale });
+    draw(&mut hud);
+}
+
+/// Screen points that UI hangs from. Anything placed against an anchor keeps its margin from that
+/// corner or edge whatever its size, so longer translations and bigger HUD scales grow it inwards
+/// instead of off the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Center,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Where the anchor sits across and down the screen, from 0 (left, top) to 1 (right, bottom).
+    fn fraction(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+
+    fn point(self) -> Vector2 {
+        let (fx, fy) = self.fraction();
+        Vector2::new(SCREEN_WIDTH as f32 * fx, SCREEN_HEIGHT as f32 * fy)
+    }
+
+    /// Top-left corner of a `size` box hung from this anchor, `margin` in from it. Margins point
+    /// inwards: right and down from a left or top edge, left and up from a right or bottom one.
+    /// On a centered axis the box straddles the anchor and the margin shifts it right or down.
+    fn place(self, size: Vector2, margin: Vector2) -> Vector2 {
+        let (fx, fy) = self.fraction();
+        let inward = |f: f32| if f > 0.5 { -1.0 } else { 1.0 };
+        let point = self.point();
+        Vector2::new(point.x + margin.x * inward(fx) - size.x * fx, point.y + margin.y * inward(fy) - size.y * fy)
+    }
+}
+
+/// Gap between lines stacked in a panel
+const LINE_GAP: i32 = 4;
+
+/// Draws one line of text hung from `anchor`, `margin` in from it.
+fn draw_anchored(d: &mut RaylibDrawHandle, text: &str, anchor: Anchor, margin: Vector2, size: i32, color: Color) {
+    let width = d.measure_text(text, size);
+    let at = anchor.place(Vector2::new(width as f32, size as f32), margin);
+    d.draw_text(text, at.x as i32, at.y as i32, size, color);
+}
+
+/// Draws `lines` (text, font size, color) stacked in a panel sized to fit them, with `padding`
+/// around the text, hung from `anchor` `margin` in from it. Lines line up with the anchor's side.
+/// Returns the panel, so the caller can decorate it or lay the next one out against it.
+fn draw_panel(
+    d: &mut RaylibDrawHandle,
+    anchor: Anchor,
+    margin: Vector2,
+    padding: Vector2,
+    lines: &[(String, i32, Color)],
+    fill: Color,
+) -> Rectangle {
+    let width = lines.iter().map(|(text, size, _)| d.measure_text(text, *size)).max().unwrap_or(0) as f32;
+    let height = lines.iter().map(|(_, size, _)| size + LINE_GAP).sum::<i32>().saturating_sub(LINE_GAP).max(0) as f32;
+    let size = Vector2::new(width + padding.x * 2.0, height + padding.y * 2.0);
+    let corner = anchor.place(size, margin);
+    let panel = Rectangle::new(corner.x, corner.y, size.x, size.y);
+    d.draw_rectangle_rec(panel, fill);
+    let (fx, _) = anchor.fraction();
+    let mut y = (corner.y + padding.y) as i32;
+    for (text, size, color) in lines {
+        let x = corner.x + padding.x + (width - d.measure_text(text, *size) as f32) * fx;
+        d.draw_text(text, x as i32, y, *size, *color);
+        y += size + LINE_GAP;
+    }
+    panel
+}
+
+/// Name of a player color (see `color_value`) in the current language.
+fn color_name(index: usize) -> String {
+    match EXTRA_COLORS.get(index.wrapping_sub(PLAYER_COLORS.len())) {
+        Some((id, _, _)) => tr!(id),
+        None => tr!(PLAYER_COLORS[index % PLAYER_COLORS.len()]),
+    }
+}
+
+/// Appends typed characters that pass `allowed`, up to `max` characters.
+fn type_text(rl: &mut RaylibHandle, text: &mut String, max: usize, allowed: fn(char) -> bool) {
+    while let Some(c) = rl.get_char_pressed() {
+        if allowed(c) && text.chars().count() < max {
+            text.push(c);
+        }
+    }
+}
+
+/// In-window main menu: host (with match setup), join (with the host's address) or single player.
+struct MainMenu {
+    screen: MenuScreen,
+    selected: usize,
+    config: MatchConfig,
+    address: String,
+    name: String,
+    color: u8, // PLAYER_COLORS index
+    profile: Option<String>, // The saved profile being played, by name; None for a guest
+    bindings: Bindings,
+    cosmetics: Cosmetics,
+    achievements: Vec<Achievement>, // Earned by the profile being played; a guest has none
+    profiles: Vec<Profile>, // Loaded when the profile picker opens
+    rebinding: Option<Action>, // Waiting for the key to give this action
+    error: Option<String>,
+    notice: Option<(String, Instant)>, // Shown where errors go, in the text color, for TOAST_TIME seconds
+    dev: bool, // --dev: matches get the developer console
+    accessibility: Accessibility,
+    history: Vec<MatchRecord>, // Newest first, loaded when the history or leaderboard screen opens
+    leaderboard: Vec<Standing>, // For the picked filter and order
+    leaderboard_mode: Option<GameMode>, // None for every mode
+    leaderboard_by_wins: bool, // Sorted by wins instead of rating
+}
+
+impl MainMenu {
+    fn new(name: String) -> Self {
+        MainMenu {
+            screen: MenuScreen::Main,
+            selected: 0,
+            config: MatchConfig::default(),
+            address: String::new(),
+            name,
+            color: 0,
+            profile: None,
+            bindings: Bindings::default(),
+            cosmetics: Cosmetics::default(),
+            achievements: Vec::new(),
+            profiles: Vec::new(),
+            rebinding: None,
+            error: None,
+            notice: None,
+            dev: false,
+            accessibility: Accessibility::default(),
+            history: Vec::new(),
+            leaderboard: Vec::new(),
+            leaderboard_mode: None,
+            leaderboard_by_wins: false,
+        }
+    }
+
+    fn open(&mut self, screen: MenuScreen) {
+        self.screen = screen;
+        self.selected = 0;
+        self.error = None;
+        if screen == MenuScreen::Profiles {
+            self.profiles = Profile::load_all();
+        }
+    }
+
+    /// Back to the main menu, on the row that opened `screen`.
+    fn back_to_main(&mut self, choice: MenuChoice) {
+        self.open(MenuScreen::Main);
+        self.selected = MENU_ITEMS.iter().position(|(_, item)| *item == choice).unwrap_or(0);
+    }
+
+    /// Plays as `profile`, first catching up on achievements from matches it played before they existed.
+    fn use_profile(&mut self, mut profile: Profile) {
+        let records: Vec<MatchRecord> = MatchRecord::load_all().into_iter().filter(|record| record.player == profile.name).collect();
+        let earned = Achievement::new_in(&records, &profile.achievements);
+        profile.achievements.extend(earned);
+        self.name = profile.name.clone();
+        self.color = profile.color;
+        self.bindings = profile.bindings;
+        self.cosmetics = profile.cosmetics;
+        self.achievements = profile.achievements;
+        self.profile = Some(profile.name);
+        self.save_profile();
+    }
+
+    /// Writes the name, color, keys, cosmetics and achievements back to the profile being played, if any.
+    fn save_profile(&self) {
+        if let Some(name) = self.profile.as_ref().filter(|name| !name.is_empty()) {
+            let profile = Profile {
+                name: name.clone(),
+                color: self.color,
+                bindings: self.bindings,
+                cosmetics: self.cosmetics,
+                achievements: self.achievements.clone(),
+            };
+            profile.save();
+        }
+    }
+
+    /// Leaves the profile screen, renaming the profile file to match the name. An empty name keeps
+    /// the old one, or leaves a profile that was never saved as a guest.
+    fn finish_profile(&mut self) -> Result<(), GameError> {
+        self.name = self.name.trim().to_string();
+        if let Some(old) = self.profile.clone() {
+            if self.name.is_empty() && !old.is_empty() {
+                self.name = old.clone();
+            }
+            if self.name.is_empty() {
+                self.profile = None;
+            } else if self.name != old {
+                if Profile::path(&self.name) != Profile::path(&old) && Profile::path(&self.name).exists() {
+                    return Err(GameError::ProfileExists(self.name.clone()));
+                }
+                if !old.is_empty() {
+                    Profile::delete(&old);
+                }
+                self.profile = Some(self.name.clone());
+            }
+        }
+        self.save_profile();
+        Ok(())
+    }
+
+    /// Handles this frame's keys. Returns a choice once the player confirms one.
+    fn update(&mut self, rl: &mut RaylibHandle, display: &mut Display, volume: &mut Volume) -> Option<MenuChoice> {
+        match self.screen {
+            MenuScreen::Join => type_text(rl, &mut self.address, 64, |c| c.is_ascii_alphanumeric() || c == '.' || c == '-'),
+            MenuScreen::Profile if self.selected == 0 => type_text(rl, &mut self.name, MAX_NAME_LEN, |c| !c.is_control()),
+            _ => {}
+        }
+        // The next key pressed goes to the action being rebound, Escape cancels
+        if let Some(action) = self.rebinding {
+            if let Some(key) = rl.get_key_pressed() {
+                if key != KeyboardKey::KEY_ESCAPE {
+                    self.bindings.set(action, key);
+                    self.save_profile();
+                }
+                self.rebinding = None;
+            }
+            return None;
+        }
+        let pressed = |key| rl.is_key_pressed(key) || rl.is_key_pressed_repeat(key);
+        let (up, down) = (pressed(KeyboardKey::KEY_UP), pressed(KeyboardKey::KEY_DOWN));
+        let (left, right) = (pressed(KeyboardKey::KEY_LEFT), pressed(KeyboardKey::KEY_RIGHT));
+        let (enter, back) = (rl.is_key_pressed(KeyboardKey::KEY_ENTER), rl.is_key_pressed(KeyboardKey::KEY_ESCAPE));
+
+        match self.screen {
+            MenuScreen::Main => {
+                if back {
+                    return Some(MenuChoice::Quit);
+                }
+                if up {
+                    self.selected = (self.selected + MENU_ITEMS.len() - 1) % MENU_ITEMS.len();
+                }
+                if down {
+                    self.selected = (self.selected + 1) % MENU_ITEMS.len();
+                }
+                let number_keys = [
+                    KeyboardKey::KEY_ONE,
+                    KeyboardKey::KEY_TWO,
+                    KeyboardKey::KEY_THREE,
+                    KeyboardKey::KEY_FOUR,
+                    KeyboardKey::KEY_FIVE,
+                    KeyboardKey::KEY_SIX,
+                    KeyboardKey::KEY_SEVEN,
+                    KeyboardKey::KEY_EIGHT,
+                    KeyboardKey::KEY_NINE,
+                ];
+                let picked = number_keys.iter().position(|&key| rl.is_key_pressed(key));
+                if let Some(i) = picked {
+                    self.selected = i;
+                }
+                if enter || picked.is_some() {
+                    match MENU_ITEMS[self.selected].1 {
+                        MenuChoice::Host => self.open(MenuScreen::HostSetup),
+                        MenuChoice::Join => self.open(MenuScreen::Join),
+                        MenuChoice::Profile => self.open(MenuScreen::Profile),
+                        MenuChoice::Settings => self.open(MenuScreen::Settings),
+                        MenuChoice::History => {
+                            // A profile sees its own matches, a guest everyone's
+                            self.open(MenuScreen::History);
+                            self.history = MatchRecord::load_all();
+                            self.history.retain(|record| self.profile.as_ref().is_none_or(|name| record.player == *name));
+                            self.history.reverse();
+                        }
+                        MenuChoice::Leaderboard => {
+                            self.open(MenuScreen::Leaderboard);
+                            self.history = MatchRecord::load_all();
+                            self.leaderboard = standings(&self.history, self.leaderboard_mode, self.leaderboard_by_wins);
+                        }
+                        choice => return Some(choice),
+                    }
+                }
+            }
+            MenuScreen::HostSetup => {
+                // The last row is "Start hosting"
+                let rows = Setting::rows(&self.config);
+                if back {
+                    self.open(MenuScreen::Main);
+                    return None;
+                }
+                if up {
+                    self.selected = (self.selected + rows.len()) % (rows.len() + 1);
+                }
+                if down {
+                    self.selected = (self.selected + 1) % (rows.len() + 1);
+                }
+                match rows.get(self.selected) {
+                    Some(&setting) if left || right || enter => setting.adjust(&mut self.config, if left { -1 } else { 1 }),
+                    None if enter => return Some(MenuChoice::Host),
+                    _ => {}
+                }
+                self.selected = self.selected.min(Setting::rows(&self.config).len());
+            }
+            MenuScreen::Join => {
+                if back {
+                    self.open(MenuScreen::Main);
+                    return None;
+                }
+                if pressed(KeyboardKey::KEY_BACKSPACE) {
+                    self.address.pop();
+                }
+                if enter {
+                    return Some(MenuChoice::Join);
+                }
+            }
+            MenuScreen::Profile => {
+                // Rows: the name, then "Key bindings", "Cosmetics" and "Switch profile"; Left/Right pick the color on any
+                if self.selected == 0 && pressed(KeyboardKey::KEY_BACKSPACE) {
+                    self.name.pop();
+                }
+                if up {
+                    self.selected = (self.selected + 3) % 4;
+                }
+                if down {
+                    self.selected = (self.selected + 1) % 4;
+                }
+                if left || right {
+                    self.color = cycle(&available_colors(&self.achievements), self.color, if left { -1 } else { 1 });
+                }
+                if enter || back {
+                    if let Err(e) = self.finish_profile() {
+                        self.error = Some(e.to_string());
+                        self.selected = 0;
+                        return None;
+                    }
+                    match self.selected {
+                        1 if enter => self.open(MenuScreen::Controls),
+                        2 if enter => self.open(MenuScreen::Cosmetics),
+                        3 if enter => self.open(MenuScreen::Profiles),
+                        _ => self.back_to_main(MenuChoice::Profile),
+                    }
+                }
+            }
+            MenuScreen::Profiles => {
+                // Rows: each profile, then "New profile" and "Play as guest"
+                let rows = self.profiles.len() + 2;
+                if back {
+                    self.back_to_main(MenuChoice::Profile);
+                    return None;
+                }
+                if up {
+                    self.selected = (self.selected + rows - 1) % rows;
+                }
+                if down {
+                    self.selected = (self.selected + 1) % rows;
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_DELETE) && self.selected < self.profiles.len() {
+                    let deleted = self.profiles.remove(self.selected);
+                    Profile::delete(&deleted.name);
+                    if self.profile.as_ref() == Some(&deleted.name) {
+                        self.profile = None;
+                    }
+                }
+                if enter {
+                    match self.profiles.get(self.selected).cloned() {
+                        Some(profile) => {
+                            self.use_profile(profile);
+                            self.back_to_main(MenuChoice::Profile);
+                        }
+                        None if self.selected == self.profiles.len() => {
+                            // Saved once it has a name
+                            self.use_profile(Profile::new(""));
+                            self.open(MenuScreen::Profile);
+                        }
+                        None => {
+                            self.profile = None;
+                            self.back_to_main(MenuChoice::Profile);
+                        }
+                    }
+                }
+            }
+            MenuScreen::Cosmetics => {
+                // Rows: trail, swap effect, "Back"; Left/Right step through the unlocked ones
+                if back || (enter && self.selected == 2) {
+                    self.save_profile();
+                    self.open(MenuScreen::Profile);
+                    self.selected = 2;
+                    return None;
+                }
+                if up {
+                    self.selected = (self.selected + 2) % 3;
+                }
+                if down {
+                    self.selected = (self.selected + 1) % 3;
+                }
+                if left || right || enter {
+                    let dir = if left { -1 } else { 1 };
+                    let unlocked = |by: Option<Achievement>| by.is_none_or(|a| self.achievements.contains(&a));
+                    match self.selected {
+                        0 => {
+                            let styles: Vec<TrailStyle> = TrailStyle::ALL.into_iter().filter(|style| unlocked(style.unlocked_by())).collect();
+                            self.cosmetics.trail = cycle(&styles, self.cosmetics.trail, dir);
+                        }
+                        1 => {
+                            let effects: Vec<SwapEffect> = SwapEffect::ALL.into_iter().filter(|effect| unlocked(effect.unlocked_by())).collect();
+                            self.cosmetics.swap = cycle(&effects, self.cosmetics.swap, dir);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            MenuScreen::Controls => {
+                // Rows: each action, then "Reset to defaults" and "Back"
+                let rows = Action::ALL.len() + 2;
+                if back || (enter && self.selected == rows - 1) {
+                    self.open(MenuScreen::Profile);
+                    self.selected = 1;
+                    return None;
+                }
+                if up {
+                    self.selected = (self.selected + rows - 1) % rows;
+                }
+                if down {
+                    self.selected = (self.selected + 1) % rows;
+                }
+                if enter {
+                    match Action::ALL.get(self.selected) {
+                        Some(&action) => self.rebinding = Some(action),
+                        None => {
+                            self.bindings = Bindings::default();
+                            self.save_profile();
+                        }
+                    }
+                }
+            }
+            MenuScreen::Settings => {
+                let rows = Preference::ALL;
+                if back || (enter && self.selected == rows.len()) {
+                    self.back_to_main(MenuChoice::Settings);
+                    return None;
+                }
+                if up {
+                    self.selected = (self.selected + rows.len()) % (rows.len() + 1);
+                }
+                if down {
+                    self.selected = (self.selected + 1) % (rows.len() + 1);
+                }
+                if let Some(&setting) = rows.get(self.selected)
+                    && (left || right || enter)
+                {
+                    let previous = *display;
+                    if let Err(e) = setting.adjust(&mut self.accessibility, display, volume, if left { -1 } else { 1 }) {
+                        self.error = Some(e);
+                    }
+                    if *display != previous {
+                        display.apply(previous, rl);
+                    }
+                }
+            }
+            MenuScreen::History => {
+                // `selected` is the first row shown
+                if back || enter {
+                    self.back_to_main(MenuChoice::History);
+                    return None;
+                }
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down {
+                    self.selected = (self.selected + 1).min(self.history.len().saturating_sub(HISTORY_ROWS));
+                }
+            }
+            MenuScreen::Leaderboard => {
+                // `selected` is the first row shown; Left/Right filter by mode, Tab switches the order
+                if back || enter {
+                    self.back_to_main(MenuChoice::Leaderboard);
+                    return None;
+                }
+                let tab = rl.is_key_pressed(KeyboardKey::KEY_TAB);
+                if left || right || tab {
+                    if tab {
+                        self.leaderboard_by_wins = !self.leaderboard_by_wins;
+                    } else {
+                        let filters: Vec<Option<GameMode>> = std::iter::once(None).chain(GameMode::ALL.map(Some)).collect();
+                        self.leaderboard_mode = cycle(&filters, self.leaderboard_mode, if left { -1 } else { 1 });
+                    }
+                    self.leaderboard = standings(&self.history, self.leaderboard_mode, self.leaderboard_by_wins);
+                    self.selected = 0;
+                }
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down {
+                    self.selected = (self.selected + 1).min(self.leaderboard.len().saturating_sub(HISTORY_ROWS));
+                }
+            }
+            MenuScreen::Failed(choice) => {
+                // Rows: "Try again" and "Back", which returns to wherever the attempt was made from
+                if up || down {
+                    self.selected = 1 - self.selected;
+                }
+                if enter && self.selected == 0 && choice == MenuChoice::Profile {
+                    // A profile that didn't load: pick one again
+                    self.open(MenuScreen::Profiles);
+                } else if enter && self.selected == 0 {
+                    return Some(choice);
+                } else if back || enter {
+                    match choice {
+                        MenuChoice::Host => self.open(MenuScreen::HostSetup),
+                        MenuChoice::Join => self.open(MenuScreen::Join),
+                        _ => self.back_to_main(choice),
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Shows why `choice` couldn't start, offering to try it again.
+    fn fail(&mut self, choice: MenuChoice, error: GameError) {
+        self.open(MenuScreen::Failed(choice));
+        self.error = Some(error.to_string());
+    }
+
+    /// Sets up the chosen game, opening the socket for network play.
+    fn start(&self, choice: MenuChoice) -> Result<Match, GameError> {
+        let mut session = self.setup(choice)?;
+        let game = &mut session.game;
+        game.set_name(game.player_id as usize, &self.name);
+        game.colors[game.player_id as usize] = self.color;
+        game.accessibility = self.accessibility;
+        if session.daily.is_some() || session.bot.is_some() {
+            game.set_name(1, "Bot");
+        } else if session.puzzle.is_some() || session.tutorial.is_some() {
+            game.set_name(1, "Dummy");
+        }
+        if self.dev {
+            session.console = Some(Console::new());
+        }
+        if game.transport.is_some() {
+            session.ratings = Ratings::load();
+        }
+        session.game.cosmetics[session.game.player_id as usize] = self.cosmetics;
+        session.profile = self.profile.clone();
+        session.bindings = self.bindings;
+        session.achievements = self.achievements.clone();
+        if let Some(path) = session.game.challenge.ghost_path()
+            && let Ok(ghost) = Replay::load(&path)
+        {
+            session.game.toast(tr!("ghost-loaded", time = format!("{:.1}", ghost.length())));
+            session.ghost = Some(ghost);
+        }
+        Ok(session)
+    }
+
+    fn setup(&self, choice: MenuChoice) -> Result<Match, GameError> {
+        match choice {
+            MenuChoice::Host => {
+                let mut game = GameState::new(true, self.config);
+                game.connect("")?;
+                Ok(Match::new(game))
+            }
+            MenuChoice::Join => {
+                if self.address.is_empty() {
+                    return Err(GameError::NoAddress);
+                }
+                let mut game = GameState::new(false, MatchConfig::default());
+                game.connect(&format!("{}:{}", self.address, tunables().port))?;
+                Ok(Match::new(game))
+            }
+            MenuChoice::Daily => {
+                let challenge = DailyChallenge::today();
+                let mut game = GameState::new(true, challenge.config());
+                game.challenge = Challenge::Daily(challenge.day);
+                Ok(Match { daily: Some(challenge), ..Match::new(game) })
+            }
+            MenuChoice::Puzzle => {
+                let levels = PuzzleLevel::load_all(LEVELS_DIR).map_err(GameError::PuzzleLevels)?;
+                let campaign = PuzzleCampaign::new(levels);
+                let game = GameState::new(true, campaign.level().config);
+                Ok(Match { puzzle: Some(campaign), ..Match::new(game) })
+            }
+            MenuChoice::Tutorial => {
+                let game = GameState::new(true, MatchConfig::default());
+                Ok(Match { tutorial: Some(Tutorial::new()), ..Match::new(game) })
+            }
+            MenuChoice::Practice(level) => {
+                let mut game = GameState::new(true, self.config);
+                game.start_round(1, ROUND_COUNTDOWN);
+                game.challenge = Challenge::Practice(level, self.config.arena);
+                Ok(Match { bot: Some(Bot::new(1, level)), ..Match::new(game) })
+            }
+            MenuChoice::Profile | MenuChoice::Settings | MenuChoice::History | MenuChoice::Leaderboard | MenuChoice::Quit => {
+                unreachable!("handled by the menu itself")
+            }
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, time: f32, display: &Display, volume: &Volume) {
+        d.clear_background(theme().background);
+        let title_width = d.measure_text("SHADOW SWAP", 60);
+        d.draw_text("SHADOW SWAP", SCREEN_WIDTH / 2 - title_width / 2, 50, 60, theme().accent);
+
+        let highlight = |selected: bool| if selected { theme().selected } else { theme().unselected };
+        let hint = match self.screen {
+            MenuScreen::Main => {
+                for (i, (label, choice)) in MENU_ITEMS.iter().enumerate() {
+                    let y = 190 + i as i32 * 54;
+                    if i == self.selected {
+                        d.draw_rectangle(SCREEN_WIDTH / 2 - 240, y - 10, 480, 48, theme().row);
+                    }
+                    let color_name = color_name(self.color as usize);
+                    let label = match choice {
+                        MenuChoice::Profile if self.profile.is_some() => tr!(label, name = self.name, color = color_name),
+                        MenuChoice::Profile if self.name.is_empty() => tr!("menu-profile-unset", color = color_name),
+                        MenuChoice::Profile => tr!("menu-profile-guest", name = self.name, color = color_name),
+                        _ => tr!(label),
+                    };
+                    d.draw_text(&format!("{}. {}", i + 1, label), SCREEN_WIDTH / 2 - 220, y, 28, highlight(i == self.selected));
+                }
+                tr!("hint-main-menu")
+            }
+            MenuScreen::HostSetup => {
+                let title = tr!("setup-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 120, 28, theme().text);
+                let rows = Setting::rows(&self.config);
+                for (i, setting) in rows.iter().enumerate() {
+                    let y = 170 + i as i32 * 28;
+                    let color = highlight(i == self.selected);
+                    d.draw_text(&setting.label(), SCREEN_WIDTH / 2 - 320, y, 20, color);
+                    let value = setting.value(&self.config);
+                    let value = if i == self.selected { format!("< {} >", value) } else { value };
+                    d.draw_text(&value, SCREEN_WIDTH / 2 + 80, y, 20, color);
+                }
+                let start_y = 180 + rows.len() as i32 * 28;
+                let start_selected = self.selected == rows.len();
+                if start_selected {
+                    d.draw_rectangle(SCREEN_WIDTH / 2 - 330, start_y - 6, 660, 34, theme().row);
+                }
+                d.draw_text(&tr!("setup-start", port = tunables().port), SCREEN_WIDTH / 2 - 320, start_y, 22, highlight(start_selected));
+                tr!("hint-setup")
+            }
+            MenuScreen::Join => {
+                d.draw_text(&tr!("join-address"), SCREEN_WIDTH / 2 - 250, 240, 26, theme().text);
+                d.draw_rectangle(SCREEN_WIDTH / 2 - 250, 280, 500, 50, theme().field);
+                d.draw_rectangle_lines(SCREEN_WIDTH / 2 - 250, 280, 500, 50, theme().accent);
+                let cursor = if (time * 2.0) as i32 % 2 == 0 { "_" } else { "" };
+                d.draw_text(&format!("{}{}", self.address, cursor), SCREEN_WIDTH / 2 - 238, 292, 28, theme().selected);
+                d.draw_text(&tr!("join-same-computer"), SCREEN_WIDTH / 2 - 250, 350, 20, theme().hint);
+                d.draw_text(&tr!("join-local-network"), SCREEN_WIDTH / 2 - 250, 375, 20, theme().hint);
+                tr!("hint-join")
+            }
+            MenuScreen::Profile => {
+                d.draw_text(&tr!("name-prompt"), SCREEN_WIDTH / 2 - 250, 240, 26, theme().text);
+                d.draw_rectangle(SCREEN_WIDTH / 2 - 250, 280, 500, 50, theme().field);
+                d.draw_rectangle_lines(SCREEN_WIDTH / 2 - 250, 280, 500, 50, highlight(self.selected == 0));
+                let cursor = if self.selected == 0 && (time * 2.0) as i32 % 2 == 0 { "_" } else { "" };
+                d.draw_text(&format!("{}{}", self.name, cursor), SCREEN_WIDTH / 2 - 238, 292, 28, theme().selected);
+                d.draw_text(&tr!("name-explainer"), SCREEN_WIDTH / 2 - 250, 350, 20, theme().hint);
+                let color = color_value(self.color as usize);
+                d.draw_text(&tr!("name-color", color = color_name(self.color as usize)), SCREEN_WIDTH / 2 - 250, 400, 26, color);
+                for (i, label) in ["profile-controls", "profile-cosmetics", "profile-switch"].into_iter().enumerate() {
+                    d.draw_text(&tr!(label), SCREEN_WIDTH / 2 - 250, 460 + i as i32 * 40, 26, highlight(self.selected == i + 1));
+                }
+                tr!("hint-name")
+            }
+            MenuScreen::Profiles => {
+                let title = tr!("profiles-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 150, 28, theme().text);
+                let rows = self.profiles.iter().map(|profile| (profile.name.clone(), color_value(profile.color as usize)));
+                let extra = [tr!("profiles-new"), tr!("profiles-guest")].map(|label| (label, theme().text));
+                for (i, (label, color)) in rows.chain(extra).enumerate() {
+                    let y = 210 + i as i32 * 40;
+                    if i == self.selected {
+                        d.draw_rectangle(SCREEN_WIDTH / 2 - 240, y - 8, 480, 40, theme().row);
+                    }
+                    let color = if i == self.selected || i >= self.profiles.len() { highlight(i == self.selected) } else { color };
+                    d.draw_text(&label, SCREEN_WIDTH / 2 - 220, y, 26, color);
+                }
+                tr!("hint-profiles")
+            }
+            MenuScreen::Cosmetics => {
+                let title = tr!("cosmetics-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 140, 28, theme().text);
+                let rows = [
+                    (tr!("cosmetics-trail"), self.cosmetics.trail.name()),
+                    (tr!("cosmetics-swap"), self.cosmetics.swap.name()),
+                ];
+                for (i, (label, value)) in rows.into_iter().enumerate() {
+                    let y = 190 + i as i32 * 34;
+                    let color = highlight(i == self.selected);
+                    d.draw_text(&label, SCREEN_WIDTH / 2 - 320, y, 24, color);
+                    let value = if i == self.selected { format!("< {} >", value) } else { value };
+                    d.draw_text(&value, SCREEN_WIDTH / 2, y, 24, color);
+                }
+                d.draw_text(&tr!("settings-back"), SCREEN_WIDTH / 2 - 320, 268, 24, highlight(self.selected == 2));
+
+                // Every achievement, what it asks and what it unlocks
+                let heading = tr!("cosmetics-achievements", earned = self.achievements.len(), total = Achievement::ALL.len());
+                d.draw_text(&heading, SCREEN_WIDTH / 2 - 320, 330, 22, theme().text);
+                for (i, achievement) in Achievement::ALL.into_iter().enumerate() {
+                    let y = 370 + i as i32 * 48;
+                    let earned = self.achievements.contains(&achievement);
+                    let color = if earned { theme().selected } else { theme().unselected };
+                    let mark = if earned { "[x]" } else { "[ ]" };
+                    d.draw_text(&format!("{} {}", mark, achievement.name()), SCREEN_WIDTH / 2 - 320, y, 22, color);
+                    let detail = tr!("cosmetics-unlocks", goal = achievement.goal(), reward = achievement.reward());
+                    d.draw_text(&detail, SCREEN_WIDTH / 2 - 280, y + 22, 18, theme().hint);
+                }
+                tr!(if self.profile.is_some() { "hint-cosmetics" } else { "hint-cosmetics-guest" })
+            }
+            MenuScreen::Controls => {
+                let title = tr!("controls-screen-title", name = self.profile.as_deref().unwrap_or(&tr!("profiles-guest-name")));
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 160, 28, theme().text);
+                for (i, action) in Action::ALL.into_iter().enumerate() {
+                    let y = 230 + i as i32 * 34;
+                    let color = highlight(i == self.selected);
+                    d.draw_text(&action.label(), SCREEN_WIDTH / 2 - 250, y, 24, color);
+                    let key = if self.rebinding == Some(action) { tr!("controls-press-key") } else { key_name(self.bindings.key(action)) };
+                    d.draw_text(&key, SCREEN_WIDTH / 2 + 60, y, 24, color);
+                }
+                let rows = Action::ALL.len();
+                for (i, label) in ["controls-reset", "settings-back"].into_iter().enumerate() {
+                    let y = 250 + (rows + i) as i32 * 34;
+                    d.draw_text(&tr!(label), SCREEN_WIDTH / 2 - 250, y, 24, highlight(self.selected == rows + i));
+                }
+                tr!(if self.rebinding.is_some() { "hint-rebinding" } else { "hint-controls" })
+            }
+            MenuScreen::Settings => {
+                let title = tr!("settings-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 160, 28, theme().text);
+                let rows = Preference::ALL;
+                for (i, setting) in rows.iter().enumerate() {
+                    let y = 230 + i as i32 * 30;
+                    let color = highlight(i == self.selected);
+                    d.draw_text(&setting.label(), SCREEN_WIDTH / 2 - 320, y, 24, color);
+                    let value = setting.value(&self.accessibility, display, volume);
+                    let value = if i == self.selected { format!("< {} >", value) } else { value };
+                    d.draw_text(&value, SCREEN_WIDTH / 2 - 40, y, 24, color);
+                }
+                let back_y = 250 + rows.len() as i32 * 30;
+                d.draw_text(&tr!("settings-back"), SCREEN_WIDTH / 2 - 320, back_y, 24, highlight(self.selected == rows.len()));
+
+                // Preview of both players with the current palette, right of the rows
+                if let Some(colors) = self.accessibility.palette.colors() {
+                    for (id, color) in colors.into_iter().enumerate() {
+                        let pos = Vector2::new(SCREEN_WIDTH as f32 / 2.0 + 320.0 + id as f32 * 90.0, 230.0);
+                        d.draw_circle_v(pos, PLAYER_SIZE * 1.5, color);
+                        draw_player_mark(d, id, pos, PLAYER_SIZE * 1.5);
+                    }
+                }
+                tr!("hint-settings")
+            }
+            MenuScreen::History => {
+                let title = tr!("history-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 120, 28, theme().text);
+                if self.history.is_empty() {
+                    let empty = tr!("history-empty");
+                    d.draw_text(&empty, SCREEN_WIDTH / 2 - d.measure_text(&empty, 22) / 2, 300, 22, theme().hint);
+                } else {
+                    let won = self.history.iter().filter(|record| record.won).count();
+                    let summary = tr!("history-summary", played = self.history.len(), won = won, lost = self.history.len() - won);
+                    d.draw_text(&summary, SCREEN_WIDTH / 2 - d.measure_text(&summary, 22) / 2, 165, 22, theme().hint);
+                    let columns = [-500, -360, -150, 70, 190, 400].map(|x| SCREEN_WIDTH / 2 + x);
+                    let headers = ["history-date", "history-opponent", "history-mode", "history-result", "history-traps", "history-time"];
+                    for (header, x) in headers.into_iter().zip(columns) {
+                        d.draw_text(&tr!(header), x, 215, 20, theme().hint);
+                    }
+                    for (i, record) in self.history.iter().skip(self.selected).take(HISTORY_ROWS).enumerate() {
+                        let y = 250 + i as i32 * 32;
+                        let seconds = record.duration.round() as u32;
+                        let cells = [
+                            calendar_date(record.ended / 86_400),
+                            record.opponent.clone(),
+                            record.mode.name(),
+                            tr!(if record.won { "history-won" } else { "history-lost" }),
+                            format!("{} / {}", record.traps_dealt, record.traps_received),
+                            format!("{}:{:02}", seconds / 60, seconds % 60),
+                        ];
+                        for (cell, x) in cells.iter().zip(columns) {
+                            d.draw_text(cell, x, y, 22, if record.won { theme().selected } else { theme().unselected });
+                        }
+                    }
+                }
+                tr!("hint-history")
+            }
+            MenuScreen::Leaderboard => {
+                let title = tr!("leaderboard-title");
+                d.draw_text(&title, SCREEN_WIDTH / 2 - d.measure_text(&title, 28) / 2, 120, 28, theme().text);
+                let mode = self.leaderboard_mode.map_or_else(|| tr!("leaderboard-all"), GameMode::name);
+                let order = tr!(if self.leaderboard_by_wins { "leaderboard-by-wins" } else { "leaderboard-by-rating" });
+                let filter = tr!("leaderboard-filter", mode = mode, order = order);
+                d.draw_text(&filter, SCREEN_WIDTH / 2 - d.measure_text(&filter, 22) / 2, 165, 22, theme().selected);
+                if self.leaderboard.is_empty() {
+                    let empty = tr!("leaderboard-empty");
+                    d.draw_text(&empty, SCREEN_WIDTH / 2 - d.measure_text(&empty, 22) / 2, 300, 22, theme().hint);
+                } else {
+                    let columns = [-400, -340, 20, 140, 240, 340].map(|x| SCREEN_WIDTH / 2 + x);
+                    let headers = ["leaderboard-rank", "leaderboard-player", "leaderboard-rating", "leaderboard-wins", "leaderboard-losses", "leaderboard-win-rate"];
+                    for (header, x) in headers.into_iter().zip(columns) {
+                        d.draw_text(&tr!(header), x, 215, 20, theme().hint);
+                    }
+                    for (i, standing) in self.leaderboard.iter().enumerate().skip(self.selected).take(HISTORY_ROWS) {
+                        let y = 250 + (i - self.selected) as i32 * 32;
+                        let played = standing.wins + standing.losses;
+                        let cells = [
+                            (i + 1).to_string(),
+                            standing.name.clone(),
+                            format!("{:.0}", standing.rating),
+                            standing.wins.to_string(),
+                            standing.losses.to_string(),
+                            format!("{:.0}%", standing.wins as f32 * 100.0 / played.max(1) as f32),
+                        ];
+                        // Our own line stands out
+                        let color = if standing.name == self.name { theme().selected } else { theme().unselected };
+                        for (cell, x) in cells.iter().zip(columns) {
+                            d.draw_text(cell, x, y, 22, color);
+                        }
+                    }
+                }
+                tr!("hint-leaderboard")
+            }
+            MenuScreen::Failed(_) => {
+                draw_centered(d, &tr!("failed-title"), 220, 32, theme().text);
+                draw_centered(d, self.error.as_deref().unwrap_or_default(), 280, 22, Color::RED);
+                for (i, label) in ["failed-retry", "failed-back"].into_iter().enumerate() {
+                    draw_centered(d, &tr!(label), 360 + i as i32 * 44, 28, highlight(self.selected == i));
+                }
+                tr!("hint-failed")
+            }
+        };
+        if let Some(error) = self.error.as_ref().filter(|_| !matches!(self.screen, MenuScreen::Failed(_))) {
+            draw_anchored(d, error, Anchor::Bottom, Vector2::new(0.0, 78.0), 22, Color::RED);
+        } else if let Some((notice, _)) = self.notice.as_ref().filter(|(_, at)| at.elapsed().as_secs_f32() < TOAST_TIME) {
+            draw_anchored(d, notice, Anchor::Bottom, Vector2::new(0.0, 78.0), 22, theme().text);
+        }
+        draw_anchored(d, &hint, Anchor::Bottom, Vector2::new(0.0, 30.0), 20, theme().hint);
+    }
+}
+
+/// Moves a position by normalized input and keeps it inside the arena.
+fn move_clamped(target: &mut Vec2, input: Vector2, speed: f32, dt: f32) {
+    target.x += input.x * speed * dt;
+    target.y += input.y * speed * dt;
+    target.x = target.x.max(PLAYER_SIZE).min(SCREEN_WIDTH as f32 - PLAYER_SIZE);
+    target.y = target.y.max(PLAYER_SIZE).min(SCREEN_HEIGHT as f32 - PLAYER_SIZE);
+}
+
+/// Moves a position by normalized input, re-entering from the opposite edge when it leaves the arena.
+fn move_wrapped(target: &mut Vec2, input: Vector2, speed: f32, dt: f32) {
+    target.x = (target.x + input.x * speed * dt).rem_euclid(SCREEN_WIDTH as f32);
+    target.y = (target.y + input.y * speed * dt).rem_euclid(SCREEN_HEIGHT as f32);
+}
+
+/// This machine's address on the local network, as other players would reach it.
+/// Connecting a UDP socket only picks a route; nothing is sent.
+fn local_ip() -> Option<std::net::IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// Cancel button on the connection status screen.
+fn cancel_button() -> Rectangle {
+    Rectangle::new(SCREEN_WIDTH as f32 / 2.0 - 80.0, SCREEN_HEIGHT as f32 - 130.0, 160.0, 44.0)
+}
+
+/// Waiting for the other player: how long we've waited and where we can be reached.
+struct ConnectStatus {
+    waited: f32,
+    local_ip: Option<std::net::IpAddr>,
+}
+
+/// Per-player shape drawn over a piece (or next to a name) when the palette calls for shapes:
+/// a triangle for player 1, a square for player 2.
+fn draw_player_mark(d: &mut impl RaylibDraw, id: usize, pos: Vector2, size: f32) {
+    let (sides, rotation) = if id == 0 { (3, -90.0) } else { (4, 45.0) };
+    d.draw_poly(pos, sides, size * 0.55, rotation, Color::new(0, 0, 0, 200));
+    d.draw_poly_lines(pos, sides, size * 0.55, rotation, Color::WHITE);
+}
+
+/// Hold-Tab scoreboard: per-player stats for this game, plus ping and the series score.
+fn draw_scoreboard(d: &mut RaylibDrawHandle, game: &GameState) {
+    let (w, h) = (640, 250);
+    let (x, y) = (SCREEN_WIDTH / 2 - w / 2, SCREEN_HEIGHT / 2 - h / 2);
+    let theme = theme();
+    d.draw_rectangle(x, y, w, h, theme.panel);
+    d.draw_rectangle_lines(x, y, w, h, theme.accent);
+    draw_centered(d, &tr!("scoreboard-title"), y + 15, 28, theme.title);
+
+    let columns = [("scoreboard-player", 20), ("scoreboard-dealt", 250), ("scoreboard-received", 400), ("scoreboard-swaps", 530)];
+    for (label, cx) in columns {
+        d.draw_text(&tr!(label), x + cx, y + 65, 18, theme.hint);
+    }
+    for i in 0..2 {
+        let row_y = y + 100 + i as i32 * 40;
+        let stats = game.stats[i];
+        let name = if i == game.player_id as usize { tr!("scoreboard-you", name = game.names[i]) } else { game.names[i].clone() };
+        d.draw_text(&name, x + columns[0].1, row_y, 22, game.player_color(i));
+        if game.accessibility.shapes() {
+            draw_player_mark(d, i, Vector2::new((x + 8) as f32, (row_y + 11) as f32), 16.0);
+        }
+        for (value, (_, cx)) in [stats.traps_dealt, stats.traps_received, stats.swaps].into_iter().zip(&columns[1..]) {
+            d.draw_text(&value.to_string(), x + cx, row_y, 22, theme.panel_text);
+        }
+    }
+
+    let ping = match game.ping_ms {
+        Some(ms) => format!("{:.0} ms", ms),
+        None if game.transport.is_none() => tr!("scoreboard-offline"),
+        None => "--".to_string(),
+    };
+    d.draw_text(
+        &tr!("scoreboard-footer", ping = ping, first = game.series[0], second = game.series[1]),
+        x + 20,
+        y + h - 40,
+        20,
+        theme.hint,
+    );
+}
+
+/// Edge arrow towards the piece that can trap us (the opponent's shadow, or the hunter) while it's under
+/// the title bar or the controls panel, or zoomed out of view. Yellow when it's far, red and pulsing
+/// when it's about to trap us.
+fn draw_threat_indicator(d: &mut RaylibDrawHandle, game: &GameState, camera: &ArenaCamera, arena_top: f32, arena_bottom: f32) {
+    let me = game.player_id as usize;
+    let (threat_pos, trap_radius) = game.threat(me);
+    if game.is_hunter(1 - me) && !game.can_see(me, 1 - me) {
+        return; // Fog keeps hiding the hunter
+    }
+    let seen = camera.on_screen(threat_pos);
+    let right = SCREEN_WIDTH as f32 - 30.0;
+    let (anchor, rotation) = if seen.y < arena_top {
+        (Vector2::new(seen.x.clamp(30.0, right), arena_top + 20.0), -90.0)
+    } else if seen.y > arena_bottom {
+        (Vector2::new(seen.x.clamp(30.0, right), arena_bottom - 20.0), 90.0)
+    } else if seen.x < 0.0 {
+        (Vector2::new(30.0, seen.y), 180.0)
+    } else if seen.x > SCREEN_WIDTH as f32 {
+        (Vector2::new(right, seen.y), 0.0)
+    } else {
+        return;
+    };
+    let gap = game.arena_distance(game.players[me].pos, threat_pos) - trap_radius;
+    let danger = 1.0 - (gap / THREAT_ARROW_RANGE).clamp(0.0, 1.0);
+    let pulse = if danger > 0.7 { game.accessibility.wave(game.game_time * 10.0).abs() * 4.0 } else { 0.0 };
+    let color = Color::new(255, (220.0 - danger * 170.0) as u8, 60, (150.0 + danger * 105.0) as u8);
+    d.draw_poly(anchor, 3, 12.0 + pulse, rotation, color);
+    d.draw_poly_lines(anchor, 3, 14.0 + pulse, rotation, Color::new(0, 0, 0, 160));
+}
+
+/// Notifications stacked in the bottom right corner, above the controls panel, newest at the bottom.
+/// Each one slides in and fades out.
+fn draw_toasts(d: &mut RaylibDrawHandle, game: &GameState) {
+    let theme = theme();
+    let faded = |color: Color, fade: f32| Color::new(color.r, color.g, color.b, (color.a as f32 * fade) as u8);
+    let mut bottom = 126.0;
+    for (text, age) in game.toasts.iter().rev() {
+        let fade = (age / TOAST_FADE).min((TOAST_TIME - age) / TOAST_FADE).clamp(0.0, 1.0);
+        let margin = Vector2::new(15.0 - (1.0 - fade) * 40.0, bottom);
+        let line = [(text.clone(), 18, faded(theme.panel_text, fade))];
+        let toast = draw_panel(d, Anchor::BottomRight, margin, Vector2::new(14.0, 8.0), &line, faded(theme.panel, fade));
+        d.draw_rectangle(toast.x as i32, toast.y as i32, 4, toast.height as i32, faded(theme.accent, fade));
+        bottom += toast.height + 6.0;
+    }
+}
+
+/// Accessible event log: the latest announcements, large and steady, above the danger meter.
+fn draw_event_log(d: &mut RaylibDrawHandle, game: &GameState, hud_bottom: f32) {
+    if game.accessibility.announce == Announce::Off {
+        return;
+    }
+    let mut bottom = SCREEN_HEIGHT as f32 - hud_bottom + 34.0;
+    for (slot, line) in game.event_log.iter().rev().enumerate() {
+        let color = if slot == 0 { theme().panel_text } else { theme().hint };
+        let line = [(line.clone(), 20, color)];
+        let panel = draw_panel(d, Anchor::BottomLeft, Vector2::new(14.0, bottom), Vector2::new(6.0, 2.0), &line, theme().panel);
+        bottom += panel.height + 2.0;
+    }
+}
+
+/// F3 developer overlay: frame timing, network traffic and sync, and the inverse state.
+fn draw_debug(d: &mut RaylibDrawHandle, game: &GameState, frame_times: &VecDeque<f32>) {
+    let (x, y, w) = (10, HUD_TOP as i32 + 10, 360);
+    d.draw_rectangle(x, y, w, 274, Color::new(0, 0, 0, 210));
+    d.draw_rectangle_lines(x, y, w, 274, Color::new(120, 255, 120, 200));
+    let text_color = Color::new(120, 255, 120, 255);
+    let frame_ms = frame_times.back().map_or(0.0, |dt| dt * 1000.0);
+    d.draw_text(&format!("DEBUG (F3)   {} fps   frame {:.1} ms", d.get_fps(), frame_ms), x + 10, y + 8, 16, text_color);
+
+    // Frame time graph: one bar per frame, the line is 60 fps
+    let (graph_y, graph_h) = (y + 30, 50);
+    d.draw_rectangle(x + 10, graph_y, DEBUG_FRAMES as i32 * 2, graph_h, Color::new(30, 30, 30, 255));
+    for (i, dt) in frame_times.iter().enumerate() {
+        let ms = dt * 1000.0;
+        let bar = ((ms / 33.3).min(1.0) * graph_h as f32) as i32;
+        let bar_color = if ms > 20.0 { Color::RED } else { Color::new(120, 255, 120, 200) };
+        d.draw_rectangle(x + 10 + i as i32 * 2, graph_y + graph_h - bar, 2, bar, bar_color);
+    }
+    let target_y = graph_y + graph_h - (16.7 / 33.3 * graph_h as f32) as i32;
+    d.draw_line(x + 10, target_y, x + 10 + DEBUG_FRAMES as i32 * 2, target_y, Color::YELLOW);
+
+    let me = game.player_id as usize;
+    let age = |id: usize| match game.last_update[id] {
+        _ if id == me => "local".to_string(),
+        Some(at) => format!("{:.0} ms", at.elapsed().as_secs_f32() * 1000.0),
+        None => "never".to_string(),
+    };
+    let lines = [
+        format!("Packets/s: {} in, {} out", game.packet_rates.0, game.packet_rates.1),
+        format!("Ping: {}", game.ping_ms.map_or("--".to_string(), |ms| format!("{:.0} ms", ms))),
+        format!("Last update: P1 {}, P2 {}", age(0), age(1)),
+        format!("Correction: P1 {:.1} px, P2 {:.1} px", game.correction[0], game.correction[1]),
+        format!(
+            "Inverse: {} {:.1}s, armed {}, next {:.1}s, by P{}",
+            if game.inverse_active { "ON" } else { "off" },
+            game.inverse_timer,
+            game.inverse_armed,
+            game.inverse_cooldown,
+            game.inverse_by + 1
+        ),
+        format!("Round {} {:?}, time scale {:.2}, game #{}", game.round, game.round_phase, game.time_scale, game.game_number),
+        format!("Tick {}, state {:016x}, desyncs {}", game.tick, game.sync_state().hash(), game.desyncs),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, x + 10, graph_y + graph_h + 10 + i as i32 * 24, 16, text_color);
+    }
+}
+
+/// Killcam: plays back the recorded history in a small window that follows the trapped player.
+/// `progress` runs from 0 to 1 over the intermission.
+fn draw_killcam(d: &mut RaylibDrawHandle, game: &GameState, trapped: usize, progress: f32) {
+    let scale = game.accessibility.hud_scale;
+    let (w, h) = (360.0 * scale, 220.0 * scale);
+    let frame = Rectangle::new(SCREEN_WIDTH as f32 / 2.0 - w / 2.0, SCREEN_HEIGHT as f32 / 2.0 + 90.0 * scale, w, h);
+    let title = tr!("killcam-title", name = game.names[trapped]);
+    draw_history_view(d, game, trapped, progress, frame, &title, Color::new(255, 120, 120, 255));
+}
+
+/// Plays back the recorded history from one player's point of view, inside `frame`: the view
+/// follows their character, rings the piece their keys were driving, and shows their score.
+/// The killcam and the replay's picture-in-picture are both drawn with this.
+fn draw_history_view(
+    d: &mut RaylibDrawHandle,
+    game: &GameState,
+    viewer: usize,
+    progress: f32,
+    frame: Rectangle,
+    title: &str,
+    accent: Color,
+) {
+    let Some(snapshot) = game.history_at(progress) else {
+        return;
+    };
+    let (x, y, w, h) = (frame.x as i32, frame.y as i32, frame.width as i32, frame.height as i32);
+    let camera = Camera2D {
+        offset: Vector2::new(frame.x + frame.width / 2.0, frame.y + frame.height / 2.0),
+        target: Vector2::from(snapshot.players[viewer].pos),
+        rotation: 0.0,
+        zoom: KILLCAM_ZOOM * frame.width / 360.0, // Smaller frames show the same stretch of arena
+    };
+    d.draw_rectangle(x, y, w, h, theme().panel);
+    draw_snapshot(d, game, &snapshot, viewer, frame, camera, progress);
+    d.draw_rectangle_lines(x, y, w, h, accent);
+    let scale = game.accessibility.hud_scale;
+    draw_hud(d, Vector2::new(frame.x, frame.y), scale, |d| d.draw_text(title, x + 8, y + 6, 18, accent));
+    let score = tr!("hud-trapped", score = snapshot.players[viewer].score, max = WIN_SCORE);
+    let bottom_left = Vector2::new(frame.x, frame.y + frame.height);
+    draw_hud(d, bottom_left, scale, |d| d.draw_text(&score, x + 8, y + h - 22, 16, theme().hint));
+}
+
+/// Draws one recorded frame through `camera`, clipped to `frame`: characters, shadows, the trap
+/// rings that threaten `viewer`, and a spinning ring on the piece their keys were driving.
+fn draw_snapshot(
+    d: &mut RaylibDrawHandle,
+    game: &GameState,
+    snapshot: &Snapshot,
+    viewer: usize,
+    frame: Rectangle,
+    camera: Camera2D,
+    progress: f32,
+) {
+    let Snapshot { players, steering } = snapshot;
+    let zoom = camera.zoom;
+    let to_view = |p: Vec2| camera.offset + (Vector2::from(p) - camera.target) * zoom;
+
+    let mut view = d.begin_scissor_mode(frame.x as i32, frame.y as i32, frame.width as i32, frame.height as i32);
+    for (i, player) in players.iter().enumerate() {
+        let color = game.player_color(i);
+        let pos = to_view(player.pos);
+        if !game.is_hunter(i) {
+            let shadow = to_view(player.shadow_pos);
+            if i != viewer {
+                let radius = game.config.rules.trap_radius * zoom;
+                if game.accessibility.high_contrast {
+                    view.draw_ring(shadow, radius - 2.0, radius + 2.0, 0.0, 360.0, 64, Color::YELLOW);
+                } else {
+                    let trap = theme().trap;
+                    view.draw_circle_lines(shadow.x as i32, shadow.y as i32, radius, Color::new(trap.r, trap.g, trap.b, 150));
+                }
+            }
+            view.draw_line_ex(pos, shadow, 2.0, Color::new(color.r, color.g, color.b, 100));
+            view.draw_circle_v(shadow, SHADOW_SIZE * zoom, Color::new(color.r, color.g, color.b, 120));
+        }
+        view.draw_circle_v(pos, PLAYER_SIZE * zoom, color);
+        if game.accessibility.shapes() {
+            draw_player_mark(&mut view, i, pos, PLAYER_SIZE * zoom);
+        }
+    }
+
+    // What the viewer's keys were driving
+    let (pos, size) = match steering[viewer] {
+        Piece::Character(i) => (players[i].pos, PLAYER_SIZE),
+        Piece::Shadow(i) => (players[i].shadow_pos, SHADOW_SIZE),
+    };
+    let spin = game.accessibility.spin(progress * 720.0);
+    for arc in 0..3 {
+        let start = spin + arc as f32 * 120.0;
+        view.draw_ring(to_view(pos), (size + 6.0) * zoom, (size + 8.0) * zoom + 1.0, start, start + 80.0, 12, Color::WHITE);
+    }
+}
+
+/// Play camera: shows the whole arena, and with dynamic zoom on eases in on the closest trap chase,
+/// framing the point between the chased character and what can trap it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ArenaCamera {
+    target: Vector2, // Arena point at the center of the screen
+    zoom: f32,
+    dynamic: bool, // Off keeps the fixed full-arena view (C)
+}
+
+impl ArenaCamera {
+    fn new() -> Self {
+        let center = Vector2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 / 2.0);
+        ArenaCamera { target: center, zoom: 1.0, dynamic: true }
+    }
+
+    fn camera(&self) -> Camera2D {
+        let offset = Vector2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 / 2.0);
+        Camera2D { offset, target: self.target, rotation: 0.0, zoom: self.zoom }
+    }
+
+    /// Where an arena point ends up on the canvas.
+    fn on_screen(&self, p: Vec2) -> Vector2 {
+        let camera = self.camera();
+        camera.offset + (Vector2::from(p) - camera.target) * camera.zoom
+    }
+
+    /// Eases towards the chase `game` shows our player, or back to the whole arena between rounds,
+    /// when dynamic zoom is off, and with reduced motion.
+    fn update(&mut self, game: &GameState, dt: f32) {
+        let center = Vector2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 / 2.0);
+        let (mut target, mut zoom) = (center, 1.0);
+        let playing = game.round_phase == RoundPhase::Playing && game.winner().is_none();
+        if self.dynamic
+            && playing
+            && game.accessibility.motion()
+            && let Some((focus, gap)) = game.closest_chase(game.player_id as usize)
+        {
+            let closeness = 1.0 - (gap / CAMERA_CHASE_RANGE).clamp(0.0, 1.0);
+            zoom = 1.0 + (CAMERA_MAX_ZOOM - 1.0) * closeness;
+            target = center.lerp(Vector2::from(focus), closeness);
+        }
+        // Never show past the arena's edges
+        let (half_w, half_h) = (center.x / zoom, center.y / zoom);
+        target.x = target.x.clamp(half_w, SCREEN_WIDTH as f32 - half_w);
+        target.y = target.y.clamp(half_h, SCREEN_HEIGHT as f32 - half_h);
+        let ease = 1.0 - (-CAMERA_EASE * dt).exp();
+        self.target = self.target.lerp(target, ease);
+        self.zoom += (zoom - self.zoom) * ease;
+    }
+}
+
+/// Replay camera the viewer steers: drag or WASD / arrow keys to pan, the wheel to zoom about the
+/// cursor, 1 or 2 to follow a player and 0 to see the whole arena again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FreeCamera {
+    target: Vector2, // Arena point at the center of the screen
+    zoom: f32,
+    follow: Option<usize>, // Player whose character the view stays on, until the viewer pans
+    grab: Option<Vector2>, // Arena point held under the cursor while dragging
+}
+
+impl FreeCamera {
+    fn whole_arena() -> Self {
+        let center = Vector2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 / 2.0);
+        FreeCamera { target: center, zoom: 1.0, follow: None, grab: None }
+    }
+
+    fn following(player: usize) -> Self {
+        FreeCamera { zoom: FREE_CAMERA_FOLLOW_ZOOM, follow: Some(player), ..Self::whole_arena() }
+    }
+
+    fn camera(&self) -> Camera2D {
+        let offset = Vector2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 / 2.0);
+        Camera2D { offset, target: self.target, rotation: 0.0, zoom: self.zoom }
+    }
+
+    /// Arena point under a point on the canvas.
+    fn arena_point(&self, at: Vector2) -> Vector2 {
+        let camera = self.camera();
+        camera.target + (at - camera.offset) / self.zoom
+    }
+
+    /// Handles the viewer's input, then moves onto the followed player in `snapshot`.
+    fn update(&mut self, rl: &RaylibHandle, bindings: &Bindings, snapshot: Option<&Snapshot>, dt: f32) {
+        if rl.is_key_pressed(KeyboardKey::KEY_ZERO) {
+            *self = Self::whole_arena();
+        }
+        for (player, key) in [KeyboardKey::KEY_ONE, KeyboardKey::KEY_TWO].into_iter().enumerate() {
+            if rl.is_key_pressed(key) {
+                *self = Self::following(player);
+            }
+        }
+
+        let mouse = rl.get_mouse_position();
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            let before = self.arena_point(mouse);
+            self.zoom = (self.zoom * FREE_CAMERA_ZOOM_STEP.powf(wheel)).clamp(FREE_CAMERA_ZOOM.0, FREE_CAMERA_ZOOM.1);
+            if self.follow.is_none() {
+                self.target += before - self.arena_point(mouse); // Keep the point under the cursor still
+            }
+        }
+
+        let pan = get_input(rl, bindings);
+        if pan.length_sqr() > 0.0 {
+            self.follow = None;
+            self.target += pan * (FREE_CAMERA_PAN_SPEED / self.zoom * dt);
+        }
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.follow = None;
+            self.grab = Some(self.arena_point(mouse));
+        } else if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.grab = None;
+        }
+        if let Some(grab) = self.grab {
+            self.target += grab - self.arena_point(mouse);
+        }
+
+        if let (Some(player), Some(snapshot)) = (self.follow, snapshot) {
+            self.target = Vector2::from(snapshot.players[player].pos);
+        }
+        // Never lose the arena entirely
+        self.target.x = self.target.x.clamp(0.0, SCREEN_WIDTH as f32);
+        self.target.y = self.target.y.clamp(0.0, SCREEN_HEIGHT as f32);
+    }
+}
+
+/// What a character is doing, read off the simulation each frame. Picks its squash and stretch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pose {
+    Idle,
+    Moving { speed: f32, angle: f32 }, // Speed as a share of the player speed; degrees, the way it's heading
+    Swapping(f32), // Squash left, 1 just after landing down to 0
+    Trapped,
+}
+
+impl Pose {
+    /// How the pose deforms the character's circle at `time`. Reduced motion keeps only the
+    /// trapped flattening, which is a steady state rather than movement.
+    fn squash(self, time: f32, motion: bool) -> Squash {
+        match self {
+            Pose::Trapped => {
+                let wobble = if motion { (time * 20.0).sin() * 0.06 } else { 0.0 };
+                Squash { along: 1.3 + wobble, across: 0.65 - wobble, angle: 0.0 }
+            }
+            _ if !motion => Squash::NONE,
+            Pose::Swapping(t) => Squash { along: 1.0 + 0.4 * t, across: 1.0 - 0.3 * t, angle: 0.0 },
+            Pose::Moving { speed, angle } => {
+                let along = 1.0 + 0.25 * speed.min(1.5);
+                Squash { along, across: 1.0 / along, angle }
+            }
+            Pose::Idle => {
+                let breath = (time * 3.0).sin() * 0.04;
+                Squash { along: 1.0 + breath, across: 1.0 - breath, angle: 90.0 }
+            }
+        }
+    }
+}
+
+/// A shadow's drawn size and opacity, and how far out its tether is stretched (0 to 1).
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ShadowLook {
+    size: f32,
+    alpha: u8,
+    stretch: f32,
+}
+
+/// Squash and stretch for a round shape: scaled by `along` in the `angle` direction (degrees)
+/// and by `across` at right angles to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Squash {
+    along: f32,
+    across: f32,
+    angle: f32,
+}
+
+impl Squash {
+    const NONE: Squash = Squash { along: 1.0, across: 1.0, angle: 0.0 };
+
+    /// Outline of a squashed circle of `radius` around `pos`, going clockwise on screen and
+    /// closed (the last point repeats the first).
+    fn outline(self, pos: Vector2, radius: f32) -> [Vector2; BLOB_POINTS + 1] {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        std::array::from_fn(|k| {
+            let theta = std::f32::consts::TAU * (1.0 - k as f32 / BLOB_POINTS as f32);
+            let (x, y) = (theta.cos() * radius * self.along, theta.sin() * radius * self.across);
+            Vector2::new(pos.x + x * cos - y * sin, pos.y + x * sin + y * cos)
+        })
+    }
+}
+
+/// A squashed filled circle.
+fn draw_blob(d: &mut RaylibDrawHandle, pos: Vector2, radius: f32, squash: Squash, color: Color) {
+    let mut fan = [pos; BLOB_POINTS + 2];
+    fan[1..].copy_from_slice(&squash.outline(pos, radius));
+    d.draw_triangle_fan(&fan, color);
+}
+
+/// The outline of a squashed circle, `thickness` wide.
+fn draw_blob_lines(d: &mut RaylibDrawHandle, pos: Vector2, radius: f32, squash: Squash, thickness: f32, color: Color) {
+    let outline = squash.outline(pos, radius);
+    for (from, to) in outline.iter().zip(outline.iter().skip(1)) {
+        d.draw_line_ex(*from, *to, thickness, color);
+    }
+}
+
+/// Optional PNG sprites for the arena, loaded once at startup from SPRITES_DIR. A file that is
+/// missing or doesn't load leaves its slot empty, and that piece keeps its drawn shapes. Sprites
+/// are light art on a transparent background, tinted with the player's or pad's color.
+struct Sprites {
+    character: Option<Texture2D>, // character.png
+    shadow: Option<Texture2D>, // shadow.png
+    teleporter: Option<Texture2D>, // teleporter.png, for the pads
+}
+
+impl Sprites {
+    fn load(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
+        let mut load = |file: &str| {
+            let path = std::path::Path::new(SPRITES_DIR).join(file);
+            if !path.exists() {
+                return None;
+            }
+            match rl.load_texture(thread, &path.to_string_lossy()) {
+                Ok(texture) => {
+                    texture.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+                    Some(texture)
+                }
+                Err(e) => {
+                    eprintln!("Could not load sprite {}: {}", path.display(), e);
+                    None
+                }
+            }
+        };
+        Sprites { character: load("character.png"), shadow: load("shadow.png"), teleporter: load("teleporter.png") }
+    }
+}
+
+/// Looping background music: a base track, and an intense stem played in step with it that
+/// crossfades in during inverse mode and on match point. Either file can be missing.
+struct MusicPlayer<'a> {
+    base: Option<Music<'a>>, // base.ogg
+    intense: Option<Music<'a>>, // intense.ogg
+    layer: f32, // How far the intense stem is faded in, 0 to 1
+}
+
+impl<'a> MusicPlayer<'a> {
+    fn load(audio: &'a RaylibAudio) -> Self {
+        let load = |file: &str| {
+            let path = std::path::Path::new(MUSIC_DIR).join(file);
+            if !path.exists() {
+                return None;
+            }
+            match audio.new_music(&path.to_string_lossy()) {
+                Ok(music) => Some(music),
+                Err(e) => {
+                    eprintln!("Could not load music {}: {}", path.display(), e);
+                    None
+                }
+            }
+        };
+        let player = MusicPlayer { base: load("base.ogg"), intense: load("intense.ogg"), layer: 0.0 };
+        // Both stems start together so they stay in step as they loop
+        for music in player.base.iter().chain(&player.intense) {
+            music.set_volume(0.0);
+            music.play_stream();
+        }
+        player
+    }
+
+    /// Feeds the streams and fades the intense stem towards `intense`, over MUSIC_FADE seconds.
+    fn update(&mut self, volume: f32, intense: bool, dt: f32) {
+        let step = dt / MUSIC_FADE;
+        self.layer = if intense { (self.layer + step).min(1.0) } else { (self.layer - step).max(0.0) };
+        // Equal-power curve, so the mix doesn't dip or swell halfway through the fade
+        let layer = (self.layer * std::f32::consts::FRAC_PI_2).sin();
+        for (music, level) in [(&self.base, 1.0), (&self.intense, layer)] {
+            if let Some(music) = music {
+                music.update_stream();
+                music.set_volume(volume * level);
+            }
+        }
+    }
+}
+
+/// Sound effects, each loaded from `<name>.wav` in SOUNDS_DIR when it's there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Sfx {
+    Swap,
+    Trapped,
+    InverseStarted,
+    InverseEnded,
+    Clash,
+    Won,
+    Tick, // Inverse countdown, alternating with Tock
+    Tock,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 8] = [Sfx::Swap, Sfx::Trapped, Sfx::InverseStarted, Sfx::InverseEnded, Sfx::Clash, Sfx::Won, Sfx::Tick, Sfx::Tock];
+
+    fn file(self) -> &'static str {
+        match self {
+            Sfx::Swap => "swap.wav",
+            Sfx::Trapped => "trapped.wav",
+            Sfx::InverseStarted => "inverse_start.wav",
+            Sfx::InverseEnded => "inverse_end.wav",
+            Sfx::Clash => "clash.wav",
+            Sfx::Won => "won.wav",
+            Sfx::Tick => "tick.wav",
+            Sfx::Tock => "tock.wav",
+        }
+    }
+
+    /// The sound a game event makes, if any.
+    fn for_event(event: GameEvent) -> Option<Sfx> {
+        match event {
+            GameEvent::Swapped(_) => Some(Sfx::Swap),
+            GameEvent::Trapped(_) => Some(Sfx::Trapped),
+            GameEvent::InverseStarted => Some(Sfx::InverseStarted),
+            GameEvent::InverseEnded => Some(Sfx::InverseEnded),
+            GameEvent::Clash => Some(Sfx::Clash),
+            GameEvent::Won(_) => Some(Sfx::Won),
+            _ => None,
+        }
+    }
+}
+
+/// How a sound plays: stereo pan the way raylib takes it (0.5 centered, 1 fully left,
+/// 0 fully right), a gain for distance or emphasis, and a pitch factor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Playback {
+    pan: f32,
+    gain: f32,
+    pitch: f32,
+}
+
+impl Playback {
+    const CENTER: Playback = Playback { pan: 0.5, gain: 1.0, pitch: 1.0 };
+}
+
+/// Every sound the game makes goes through here: the music stems on the music channel and
+/// effects on the SFX channel, each scaled by the master volume (see `Volume::of`).
+struct Mixer<'a> {
+    music: MusicPlayer<'a>,
+    sounds: [Option<Sound<'a>>; Sfx::ALL.len()], // In Sfx::ALL order
+    announcer: Announcer<'a>,
+}
+
+/// A sound file from `dir`, or None when it's missing or can't be read.
+fn load_sound<'a>(audio: &'a RaylibAudio, dir: &str, file: &str) -> Option<Sound<'a>> {
+    let path = std::path::Path::new(dir).join(file);
+    if !path.exists() {
+        return None;
+    }
+    audio.new_sound(&path.to_string_lossy()).map_err(|e| eprintln!("Could not load sound {}: {}", path.display(), e)).ok()
+}
+
+/// Announcer voice lines, each loaded from `<name>.wav` in ANNOUNCER_DIR when it's there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Line {
+    InverseMode,
+    MatchPoint,
+    Trapped,
+    YouWin,
+    YouLose,
+}
+
+impl Line {
+    const ALL: [Line; 5] = [Line::InverseMode, Line::MatchPoint, Line::Trapped, Line::YouWin, Line::YouLose];
+
+    fn file(self) -> &'static str {
+        match self {
+            Line::InverseMode => "inverse_mode.wav",
+            Line::MatchPoint => "match_point.wav",
+            Line::Trapped => "trapped.wav",
+            Line::YouWin => "you_win.wav",
+            Line::YouLose => "you_lose.wav",
+        }
+    }
+
+    /// Which queued line goes first: the result, then traps, match point, and inverse mode last.
+    fn priority(self) -> u8 {
+        match self {
+            Line::InverseMode => 0,
+            Line::MatchPoint => 1,
+            Line::Trapped => 2,
+            Line::YouWin | Line::YouLose => 3,
+        }
+    }
+
+    /// The line an event calls for, from `me`'s point of view.
+    fn for_event(event: GameEvent, me: usize) -> Option<Line> {
+        match event {
+            GameEvent::InverseStarted => Some(Line::InverseMode),
+            GameEvent::MatchPoint => Some(Line::MatchPoint),
+            GameEvent::Trapped(_) => Some(Line::Trapped),
+            GameEvent::Won(id) => Some(if id == me { Line::YouWin } else { Line::YouLose }),
+            _ => None,
+        }
+    }
+}
+
+/// Plays announcer lines one at a time. Lines that come in while one is speaking wait in a
+/// priority queue, and drop out after ANNOUNCER_STALE seconds; the match result clears the rest.
+struct Announcer<'a> {
+    clips: [Option<Sound<'a>>; Line::ALL.len()], // In Line::ALL order
+    queue: Vec<(Line, f32)>, // Line, and seconds it has waited
+    speaking: Option<Line>,
+}
+
+impl Announcer<'_> {
+    fn say(&mut self, line: Line) {
+        if self.clips[line as usize].is_none() {
+            return;
+        }
+        if line.priority() == Line::YouWin.priority() {
+            self.queue.clear();
+        }
+        self.queue.push((line, 0.0));
+    }
+
+    fn update(&mut self, gain: f32, dt: f32) {
+        for (_, waited) in &mut self.queue {
+            *waited += dt;
+        }
+        self.queue.retain(|&(_, waited)| waited < ANNOUNCER_STALE);
+        if self.speaking.and_then(|line| self.clips[line as usize].as_ref()).is_some_and(|clip| clip.is_playing()) {
+            return;
+        }
+        // Highest priority first, and the oldest of those (max_by_key keeps the last maximum)
+        let Some(next) = self.queue.iter().enumerate().rev().max_by_key(|(_, (line, _))| line.priority()).map(|(i, _)| i) else {
+            self.speaking = None;
+            return;
+        };
+        let (line, _) = self.queue.remove(next);
+        if let Some(clip) = &self.clips[line as usize] {
+            clip.set_volume(gain);
+            clip.play();
+        }
+        self.speaking = Some(line);
+    }
+}
+
+impl<'a> Mixer<'a> {
+    fn load(audio: &'a RaylibAudio) -> Self {
+        let sounds = Sfx::ALL.map(|sfx| load_sound(audio, SOUNDS_DIR, sfx.file()));
+        let clips = Line::ALL.map(|line| load_sound(audio, ANNOUNCER_DIR, line.file()));
+        Mixer { music: MusicPlayer::load(audio), sounds, announcer: Announcer { clips, queue: Vec::new(), speaking: None } }
+    }
+
+    fn update(&mut self, volume: &Volume, intense: bool, dt: f32) {
+        self.music.update(volume.of(volume.music), intense, dt);
+        self.announcer.update(volume.of(volume.sfx), dt);
+    }
+
+    /// Queues an announcer line, unless the announcer is switched off.
+    fn announce(&mut self, line: Line, volume: &Volume) {
+        if volume.announcer {
+            self.announcer.say(line);
+        }
+    }
+
+    fn play(&self, sfx: Sfx, playback: Playback, volume: &Volume) {
+        if let Some(sound) = &self.sounds[sfx as usize] {
+            sound.set_volume(volume.of(volume.sfx) * playback.gain);
+            sound.set_pan(playback.pan);
+            sound.set_pitch(playback.pitch);
+            sound.play();
+        }
+    }
+}
+
+/// Draws a sprite centered on `pos`, scaled to `radius` (half its width) and by `squash`.
+fn draw_sprite(d: &mut RaylibDrawHandle, sprite: &Texture2D, pos: Vector2, radius: f32, squash: Squash, tint: Color) {
+    let source = Rectangle::new(0.0, 0.0, sprite.width as f32, sprite.height as f32);
+    let width = radius * 2.0 * squash.along;
+    let height = radius * 2.0 * squash.across * sprite.height as f32 / sprite.width.max(1) as f32;
+    let dest = Rectangle::new(pos.x, pos.y, width, height);
+    d.draw_texture_pro(sprite, source, dest, Vector2::new(width / 2.0, height / 2.0), squash.angle, tint);
+}
+
+/// Movement from the bound keys or the arrows, normalized.
+fn get_input(rl: &RaylibHandle, bindings: &Bindings) -> Vector2 {
+    let mut input = Vector2::zero();
+    let down = |action, arrow| rl.is_key_down(bindings.key(action)) || rl.is_key_down(arrow);
+    
+    if down(Action::Right, KeyboardKey::KEY_RIGHT) {
+        input.x += 1.0;
+    }
+    if down(Action::Left, KeyboardKey::KEY_LEFT) {
+        input.x -= 1.0;
+    }
+    if down(Action::Up, KeyboardKey::KEY_UP) {
+        input.y -= 1.0;
+    }
+    if down(Action::Down, KeyboardKey::KEY_DOWN) {
+        input.y += 1.0;
+    }
+    
+    if input.length_sqr() > 0.0 {
+        let len = input.length();
+        Vector2::new(input.x / len, input.y / len)
+    } else {
+        input
+    }
+}
+
+/// Bot strength: how often it rethinks and how hard it pushes its keys.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum BotLevel {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl BotLevel {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(BotLevel::Easy),
+            "normal" => Some(BotLevel::Normal),
+            "hard" => Some(BotLevel::Hard),
+            _ => None,
+        }
+    }
+
+    /// Seconds between decisions.
+    fn reaction(self) -> f32 {
+        tunables().bots[self as usize].reaction
+    }
+
+    /// Fraction of full speed it steers with.
+    fn speed(self) -> f32 {
+        tunables().bots[self as usize].speed
+    }
+}
+
+/// Playtesting opponent added from the dev console: keeps the threat away from itself,
+/// swaps out of danger and onto a passing opponent, and spends a full inverse meter.
+struct Bot {
+    id: usize,
+    level: BotLevel,
+    think: f32, // Time until the next decision
+    input: Vector2,
+}
+
+impl Bot {
+    fn new(id: usize, level: BotLevel) -> Self {
+        Bot { id, level, think: 0.0, input: Vector2::zero() }
+    }
+
+    fn update(&mut self, game: &mut GameState, dt: f32) {
+        if !game.is_live() {
+            return;
+        }
+        self.think -= dt;
+        if self.think <= 0.0 {
+            self.think = self.level.reaction();
+            self.decide(game);
+        }
+        if self.input.length_sqr() > 0.0 {
+            game.update_player(self.id, self.input * self.level.speed(), dt);
+        }
+    }
+
+    fn decide(&mut self, game: &mut GameState) {
+        let me = self.id;
+        let other = 1 - me;
+        let from = game.steered_pos(me);
+        let away = |from: Vec2, danger: Vec2| Vec2 { x: from.x * 2.0 - danger.x, y: from.y * 2.0 - danger.y };
+        let target = match game.steered_piece(me) {
+            // Inverse mode: walk the opponent into our shadow
+            Piece::Character(p) if p == other => game.threat(other).0,
+            // Their shadow can trap us: keep it clear of our character
+            Piece::Shadow(p) if p == other => away(from, game.players[me].pos),
+            // Our own shadow (the shade): chase their character
+            Piece::Shadow(_) => game.players[other].pos,
+            Piece::Character(_) if game.is_hunter(me) => game.players[other].pos,
+            Piece::Character(_) => away(from, game.threat(me).0),
+        };
+        let dir = Vector2::new(target.x - from.x, target.y - from.y);
+        self.input = if dir.length_sqr() > 1.0 { dir.normalized() } else { Vector2::zero() };
+
+        // Swap out of danger, or so our shadow lands on an opponent walking past us
+        let escape = game.danger(me) > 0.6 && self.level != BotLevel::Easy;
+        let (_, trap_radius) = game.threat_distance(other);
+        let ambush = game.arena_distance(game.players[me].pos, game.players[other].pos) < trap_radius;
+        if escape || (ambush && self.level == BotLevel::Hard) {
+            game.swap_with_shadow(me);
+        }
+        if game.players[me].inverse_charge >= 1.0 {
+            game.request_inverse(me as u8);
+        }
+    }
+}
+
+/// Drop-down developer console (--dev): type a command, Enter runs it.
+struct Console {
+    open: bool,
+    input: String,
+    log: VecDeque<String>, // Commands and replies, newest last
+}
+
+impl Console {
+    fn new() -> Self {
+        Console { open: false, input: String::new(), log: VecDeque::new() }
+    }
+
+    fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.log.len() == CONSOLE_LINES {
+                self.log.pop_front();
+            }
+            self.log.push_back(line.to_string());
+        }
+    }
+
+    /// Backtick opens and closes it, Esc closes it. Returns a command once Enter submits one.
+    fn update(&mut self, rl: &mut RaylibHandle) -> Option<String> {
+        if rl.is_key_pressed(KeyboardKey::KEY_GRAVE) || (self.open && rl.is_key_pressed(KeyboardKey::KEY_ESCAPE)) {
+            self.open = !self.open;
+        }
+        if !self.open {
+            return None;
+        }
+        type_text(rl, &mut self.input, 80, |c| c != '`');
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) || rl.is_key_pressed_repeat(KeyboardKey::KEY_BACKSPACE) {
+            self.input.pop();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) && !self.input.trim().is_empty() {
+            let command = std::mem::take(&mut self.input);
+            self.print(&format!("> {}", command));
+            return Some(command);
+        }
+        None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle) {
+        if !self.open {
+            return;
+        }
+        let h = 40 + CONSOLE_LINES as i32 * 20;
+        let theme = theme();
+        d.draw_rectangle(0, 0, SCREEN_WIDTH, h, theme.console);
+        d.draw_line(0, h, SCREEN_WIDTH, h, theme.console_text);
+        for (i, line) in self.log.iter().enumerate() {
+            d.draw_text(line, 12, 8 + i as i32 * 20, 16, theme.hint);
+        }
+        let cursor = if (d.get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+        d.draw_text(&format!("> {}{}", self.input, cursor), 12, h - 26, 18, theme.console_text);
+    }
+}
+
+/// A game being played: the shared state plus the single-player driver, if there is one.
+struct Match {
+    game: GameState,
+    daily: Option<DailyChallenge>,
+    puzzle: Option<PuzzleCampaign>,
+    tutorial: Option<Tutorial>,
+    show_scoreboard: bool, // Tab held
+    show_controls: bool, // Controls panel toggled on with F1
+    show_debug: bool, // Developer overlay toggled with F3
+    show_pip: bool, // Replay inset from the other player's point of view, toggled with I
+    camera: ArenaCamera, // Play view, following the closest trap chase
+    free_camera: FreeCamera, // Replay view, steered by the viewer
+    frame_times: VecDeque<f32>, // Recent real frame times, for the debug overlay
+    console: Option<Console>, // Developer console, only with --dev
+    bot: Option<Bot>, // Added from the console in offline games, or with --bot
+    ratings: Ratings, // Network games: everyone's rating from our match history
+    profile: Option<String>, // Who is playing, None for a guest
+    bindings: Bindings, // Their keys
+    achievements: Vec<Achievement>, // Theirs, with any earned this session
+    ghost: Option<Replay>, // Daily challenge and practice: our fastest win so far, raced alongside
+    show_ghost: bool, // Toggled with G
+    heatmap: HeatmapView, // Game over: which heatmap is shown, stepped with H
+}
+
+impl Match {
+    fn new(game: GameState) -> Self {
+        Match {
+            game,
+            daily: None,
+            puzzle: None,
+            tutorial: None,
+            show_scoreboard: false,
+            show_controls: false,
+            show_debug: false,
+            show_pip: true,
+            camera: ArenaCamera::new(),
+            free_camera: FreeCamera::whole_arena(),
+            frame_times: VecDeque::with_capacity(DEBUG_FRAMES),
+            console: None,
+            bot: None,
+            ratings: Ratings::default(),
+            profile: None,
+            bindings: Bindings::default(),
+            achievements: Vec::new(),
+            ghost: None,
+            show_ghost: true,
+            heatmap: HeatmapView::Off,
+        }
+    }
+
+    /// After a recorded match: unlocks achievements the profile has now earned, saving them to it
+    /// and announcing each with a toast.
+    fn check_achievements(&mut self) {
+        let Some(name) = self.profile.clone().filter(|name| !name.is_empty()) else {
+            return;
+        };
+        let records: Vec<MatchRecord> = MatchRecord::load_all().into_iter().filter(|record| record.player == name).collect();
+        let earned = Achievement::new_in(&records, &self.achievements);
+        if earned.is_empty() {
+            return;
+        }
+        for achievement in &earned {
+            self.game.toast(tr!("achievement-unlocked", name = achievement.name(), reward = achievement.reward()));
+        }
+        self.achievements.extend(earned);
+        match Profile::load(&name) {
+            Ok(Some(mut profile)) => {
+                profile.achievements = self.achievements.clone();
+                profile.save();
+            }
+            Ok(None) => eprintln!("Could not save achievements to profile '{}': it was deleted", name),
+            Err(e) => eprintln!("Could not save achievements to profile '{}': {}", name, e),
+        }
+    }
+
+    fn console_open(&self) -> bool {
+        self.console.as_ref().is_some_and(|console| console.open)
+    }
+
+    /// Runs a developer console command, returning what to print back.
+    fn run_command(&mut self, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let game = &mut self.game;
+        match words.as_slice() {
+            ["help"] => Ok(CONSOLE_HELP.to_string()),
+            ["set", key, value] => {
+                if !game.is_host {
+                    return Err("only the host can change the rules".to_string());
+                }
+                let number = || value.parse::<f32>().map_err(|_| format!("'{}' is not a number", value));
+                let switch = || match *value {
+                    "on" | "1" | "true" => Ok(true),
+                    "off" | "0" | "false" => Ok(false),
+                    _ => Err(format!("'{}' is not on/off", value)),
+                };
+                // The rules, not the handicaps, so each player's handicap still applies on top
+                let config = &mut game.config;
+                match *key {
+                    "trap_radius" => {
+                        let radius = number()?.max(1.0);
+                        config.rules.trap_radius = radius;
+                        // A chat shrink in progress keeps going, and ends on the new radius
+                        if let Some((_, restore)) = game.shrink.as_mut() {
+                            *restore = radius;
+                            config.rules.trap_radius = radius * CHAT_SHRINK;
+                        }
+                    }
+                    "speed" => config.rules.player_speed = number()?.max(1.0), // Pixels per second
+                    "echo_delay" => config.echo_delay = number()?.max(0.1),
+                    "afk_timeout" => config.afk_timeout = number()?.max(0.0),
+                    "tether" => config.tether_range = number()?.max(0.0),
+                    "wrap" => config.wrap = switch()?,
+                    "fog" => config.fog = switch()?,
+                    "steal" => config.shadow_steal = switch()?,
+                    "streak" => config.streak_bonus = switch()?,
+                    _ => return Err(format!("unknown setting '{}'", key)),
+                }
+                Ok(format!("{} = {}", key, value))
+            }
+            ["bot", "add", rest @ ..] => {
+                if game.transport.is_some() {
+                    return Err("bots only play in offline games".to_string());
+                }
+                let level = match rest {
+                    [] => BotLevel::Normal,
+                    [name] => BotLevel::parse(name).ok_or_else(|| format!("unknown bot level '{}'", name))?,
+                    _ => return Err("usage: bot add [easy|normal|hard]".to_string()),
+                };
+                self.bot = Some(Bot::new(1, level));
+                Ok(format!("{:?} bot now plays {}", level, self.game.names[1]))
+            }
+            ["bot", "remove"] => {
+                self.bot = None;
+                Ok("bot removed".to_string())
+            }
+            ["netsim", "loss", value] => {
+                let loss = value.parse::<f32>().map_err(|_| format!("'{}' is not a number", value))?;
+                game.netsim_loss = loss.clamp(0.0, 1.0);
+                Ok(format!("dropping {:.0}% of outgoing packets", game.netsim_loss * 100.0))
+            }
+            ["netsim", "off"] => {
+                game.netsim_loss = 0.0;
+                Ok("network simulation off".to_string())
+            }
+            ["state", "dump"] => Ok(game.dump()),
+            [] => Ok(String::new()),
+            _ => Err(format!("unknown command '{}', try help", line.trim())),
+        }
+    }
+
+    /// Where the visible arena ends: the top of the controls panel when it's shown. This is the
+    /// 1x layout value, for HUD groups hung from the bottom edge.
+    fn hud_bottom(&self) -> f32 {
+        if self.show_controls { SCREEN_HEIGHT as f32 - CONTROLS_PANEL_HEIGHT } else { SCREEN_HEIGHT as f32 }
+    }
+
+    /// The stretch of arena between the title bar and the bottom HUD, once the HUD scale is applied.
+    fn arena_view(&self) -> (f32, f32) {
+        let scale = self.game.accessibility.hud_scale;
+        (HUD_TOP * scale, SCREEN_HEIGHT as f32 - (SCREEN_HEIGHT as f32 - self.hud_bottom()) * scale)
+    }
+
+    /// One frame of play: network, input, simulation and the periodic sends.
+    fn update(&mut self, rl: &mut RaylibHandle, real_dt: f32) {
+        // Developer console (backtick, --dev only) has the keyboard while it's open
+        if let Some(command) = self.console.as_mut().and_then(|console| console.update(rl)) {
+            let reply = self.run_command(&command).unwrap_or_else(|e| format!("error: {}", e));
+            if let Some(console) = self.console.as_mut() {
+                console.print(&reply);
+            }
+        }
+        let typing = self.console_open();
+        let pressed = |key| !typing && rl.is_key_pressed(key);
+        let held = |key| !typing && rl.is_key_down(key);
+
+        let Match { game, daily, puzzle, tutorial, show_scoreboard, show_controls, show_debug, frame_times, bot, camera, bindings, ghost, show_ghost, .. } = self;
+
+        // Scoreboard overlay while Tab is held, controls panel toggled with F1, debug overlay with F3
+        *show_scoreboard = held(KeyboardKey::KEY_TAB);
+        if pressed(KeyboardKey::KEY_F1) {
+            *show_controls = !*show_controls;
+        }
+        if pressed(KeyboardKey::KEY_F3) {
+            *show_debug = !*show_debug;
+        }
+        // The last few seconds as a clip to share (F9)
+        if pressed(KeyboardKey::KEY_F9) {
+            let text = match game.export_clip() {
+                Ok(path) => tr!("clip-saved", path = path.display()),
+                Err(e) => tr!("error-clip", error = e),
+            };
+            game.toast(text);
+        }
+        // Best-run ghost on and off (G)
+        if ghost.is_some() && pressed(KeyboardKey::KEY_G) {
+            *show_ghost = !*show_ghost;
+            game.toast(tr!(if *show_ghost { "ghost-shown" } else { "ghost-hidden" }));
+        }
+        // Dynamic camera zoom on and off (C)
+        if pressed(KeyboardKey::KEY_C) {
+            camera.dynamic = !camera.dynamic;
+            game.toast(tr!(if camera.dynamic { "camera-dynamic" } else { "camera-fixed" }));
+        }
+        if frame_times.len() == DEBUG_FRAMES {
+            frame_times.pop_front();
+        }
+        frame_times.push_back(real_dt);
+
+        // Network receive, then the host's simulation up to this frame's inputs
+        game.receive_messages();
+        let dt = game.begin_tick(real_dt);
+
+        game.streak_callout = (game.streak_callout - real_dt).max(0.0);
+        game.steal_fx.3 = (game.steal_fx.3 - real_dt).max(0.0);
+        game.swap_fx = game.swap_fx.map(|time| (time - real_dt).max(0.0));
+        game.clash_fx = (game.clash_fx - real_dt).max(0.0);
+        game.go_flash = (game.go_flash - real_dt).max(0.0);
+        game.update_events(real_dt);
+
+        // Pause/resume (P key), synced through the host
+        if pressed(KeyboardKey::KEY_P) {
+            game.toggle_pause();
+        }
+
+        // Inverse mode: spend a full meter (E by default), the host times it and fills the meters
+        if pressed(bindings.key(Action::Inverse)) {
+            game.trigger_inverse();
+        }
+
+        // Freeze the opponent's character (Q by default), limited uses per game
+        if pressed(bindings.key(Action::Freeze)) {
+            game.trigger_freeze();
+        }
+
+        // Get input (ignored between rounds)
+        let input = if typing { Vector2::zero() } else { get_input(rl, bindings) };
+        
+        // Update player (controls other player's shadow/character)
+        let scripted = puzzle.as_ref().is_some_and(|campaign| campaign.is_scripted(game.steered_piece(0)));
+        if input.length_sqr() > 0.0 && game.is_live() && !scripted {
+            game.update_player(game.player_id as usize, input, dt);
+        }
+
+        // Swap with shadow (SPACE by default)
+        if pressed(bindings.key(Action::Swap)) && game.is_live() {
+            game.swap_with_shadow(game.player_id as usize);
+        }
+
+        // AFK tracking (host decides)
+        game.update_idle(input.length_sqr() > 0.0 || pressed(bindings.key(Action::Swap)), real_dt);
+        game.update_afk(real_dt);
+
+        // Forfeit (hold F)
+        game.update_forfeit_hold(held(KeyboardKey::KEY_F), real_dt);
+
+        // Rematch vote (Y/N keys) - only when game is over, the host restarts once both accept
+        if pressed(KeyboardKey::KEY_Y) || pressed(KeyboardKey::KEY_R) {
+            game.vote_rematch(true);
+        }
+        if pressed(KeyboardKey::KEY_N) {
+            game.vote_rematch(false);
+        }
+
+        // Daily challenge bot
+        if let Some(challenge) = daily.as_mut() {
+            challenge.update(game, dt);
+        }
+
+        // Puzzle scripts and level clock
+        if let Some(campaign) = puzzle.as_mut() {
+            campaign.update(game, dt);
+        }
+
+        // Tutorial steps
+        if let Some(tutorial) = tutorial.as_mut() {
+            tutorial.update(game, dt);
+        }
+
+        // Console-added bot
+        if let Some(bot) = bot.as_mut() {
+            bot.update(game, dt);
+        }
+
+        game.update_particles(dt);
+        game.update_trails(dt);
+
+        // Keep recent positions for the killcam, and the whole game for its replay file
+        game.record_history(dt);
+        game.record_replay(dt);
+        game.record_heatmap(dt);
+
+        // Echo shadows, then teleporters, traps and zones (host only)
+        game.end_tick(dt);
+        camera.update(game, real_dt);
+
+        game.send_updates();
+    }
+
+    /// The arena and everything in it, in arena coordinates: drawn through the play camera.
+    fn draw_arena(&self, d: &mut RaylibDrawHandle, sprites: &Sprites) {
+        let game = &self.game;
+        let high_contrast = game.accessibility.high_contrast;
+        let fx = &game.accessibility;
+
+        // Draw subtle background pattern (dropped in high contrast, where it's only noise)
+        if !high_contrast {
+            for y in (0..SCREEN_HEIGHT).step_by(100) {
+                d.draw_line(0, y, SCREEN_WIDTH, y, theme().grid);
+            }
+            for x in (0..SCREEN_WIDTH).step_by(100) {
+                d.draw_line(x, 0, x, SCREEN_HEIGHT, theme().grid);
+            }
+        }
+
+        // Draw center divider line
+        d.draw_line(SCREEN_WIDTH / 2, 0, SCREEN_WIDTH / 2, SCREEN_HEIGHT, theme().divider);
+
+        // Wrap-around edges glow so it's clear they lead somewhere
+        if game.config.wrap {
+            let edge_alpha = if high_contrast { 255 } else { (60.0 + fx.wave(game.game_time * 2.0).abs() * 60.0) as u8 };
+            d.draw_rectangle_lines_ex(
+                Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32),
+                3.0,
+                Color::new(80, 200, 255, edge_alpha),
+            );
+        }
+
+        // Draw capture zones, tinted by whoever is currently holding them
+        if game.config.mode == GameMode::CaptureZone {
+            for zone in capture_zones() {
+                let mut zone_color = Color::new(120, 120, 255, 255);
+                for i in 0..2 {
+                    if game.players[i].shadow_pos.distance(zone) < ZONE_RADIUS
+                        && game.players[1 - i].pos.distance(zone) < ZONE_RADIUS
+                    {
+                        zone_color = game.player_color(i);
+                    }
+                }
+                d.draw_circle_v(Vector2::from(zone), ZONE_RADIUS, Color::new(zone_color.r, zone_color.g, zone_color.b, 40));
+                d.draw_circle_lines(zone.x as i32, zone.y as i32, ZONE_RADIUS, Color::new(zone_color.r, zone_color.g, zone_color.b, 180));
+            }
+        }
+
+        // Draw the hill, tinted by its current holder
+        if game.config.mode == GameMode::KingOfTheHill {
+            let hill = hill_center();
+            let on_hill = [0, 1].map(|i| game.players[i].pos.distance(hill) < HILL_RADIUS);
+            let hill_color = match on_hill {
+                [true, false] => game.player_color(0),
+                [false, true] => game.player_color(1),
+                [true, true] => Color::ORANGE, // Contested
+                [false, false] => Color::GOLD,
+            };
+            d.draw_circle_v(Vector2::from(hill), HILL_RADIUS, Color::new(hill_color.r, hill_color.g, hill_color.b, 35));
+            d.draw_circle_lines(hill.x as i32, hill.y as i32, HILL_RADIUS, Color::new(hill_color.r, hill_color.g, hill_color.b, 200));
+        }
+
+        // Draw gravity wells as a swirl of arcs spinning into the center
+        // Twitch chat's hazard is a well too, in orange
+        let wells = game.config.arena.gravity_wells().into_iter().map(|well| (well, false));
+        for (well, hazard) in wells.chain(game.hazard.map(|(pos, _)| (pos, true))) {
+            let center = Vector2::from(well);
+            let (fill, arms, core) = if hazard {
+                ((255, 130, 40), (255, 170, 80), (90, 35, 0))
+            } else {
+                ((120, 60, 200), (170, 110, 255), (40, 0, 80))
+            };
+            d.draw_circle_v(center, WELL_RADIUS, Color::new(fill.0, fill.1, fill.2, 25));
+            for ring in 0..4 {
+                let radius = WELL_RADIUS * (1.0 - ring as f32 * 0.22);
+                let angle = fx.spin((game.game_time * (1.0 + ring as f32 * 0.6)).to_degrees()) + ring as f32 * 45.0;
+                let alpha = 60 + ring as u8 * 35;
+                for arm in 0..2 {
+                    let start = angle + arm as f32 * 180.0;
+                    d.draw_ring(center, radius - 3.0, radius, start, start + 100.0, 16, Color::new(arms.0, arms.1, arms.2, alpha));
+                }
+            }
+            d.draw_circle_v(center, 8.0, Color::new(core.0, core.1, core.2, 255));
+        }
+
+        // Draw teleporter pads, each linked pair in its own color
+        for (pair, (a, b)) in game.config.arena.teleporters().into_iter().enumerate() {
+            let pad_color = if pair == 0 { Color::SKYBLUE } else { Color::VIOLET };
+            let spin = fx.spin((game.game_time * 3.0).to_degrees()) + (pair as f32).to_degrees();
+            for pad in [a, b] {
+                if let Some(sprite) = &sprites.teleporter {
+                    draw_sprite(d, sprite, Vector2::from(pad), TELEPORTER_RADIUS, Squash { angle: spin, ..Squash::NONE }, pad_color);
+                    continue;
+                }
+                d.draw_circle_v(Vector2::from(pad), TELEPORTER_RADIUS, Color::new(pad_color.r, pad_color.g, pad_color.b, 50));
+                d.draw_circle_lines(pad.x as i32, pad.y as i32, TELEPORTER_RADIUS, pad_color);
+                d.draw_ring(Vector2::from(pad), TELEPORTER_RADIUS * 0.5, TELEPORTER_RADIUS * 0.65, spin, spin + 120.0, 12, pad_color);
+            }
+        }
+
+        // Fog of war: outline what our character and shadow can see
+        if game.config.fog {
+            let me = &game.players[game.player_id as usize];
+            d.draw_circle_lines(me.pos.x as i32, me.pos.y as i32, FOG_VISION, Color::new(120, 120, 160, 60));
+            if !game.is_hunter(game.player_id as usize) {
+                d.draw_circle_lines(me.shadow_pos.x as i32, me.shadow_pos.y as i32, FOG_VISION, Color::new(120, 120, 160, 60));
+            }
+        }
+
+        // Motion trails: fading streaks behind fast characters and shadows, so direction reads
+        // at a glance. Fog hides a hidden character's trail with it.
+        for (i, trails) in game.motion_trails.iter().enumerate() {
+            let color = game.player_color(i);
+            let style = game.cosmetics[i].trail;
+            for (piece, trail) in trails.iter().enumerate() {
+                let is_shadow = piece == 1;
+                if (is_shadow && game.is_hunter(i)) || (!is_shadow && !game.can_see(game.player_id as usize, i)) {
+                    continue;
+                }
+                let (size, opacity) = if is_shadow { (SHADOW_SIZE, 90.0) } else { (PLAYER_SIZE, 140.0) };
+                for (age, (from, to)) in trail.iter().zip(trail.iter().skip(1)).enumerate() {
+                    let t = (age + 1) as f32 / trail.len() as f32; // 1 at the newest segment
+                    let speed = from.distance(*to) / TRAIL_SAMPLE;
+                    let strength = (speed / TRAIL_SPEED).clamp(0.0, 1.0) * t;
+                    if strength > 0.05 {
+                        let alpha = (opacity * strength) as u8;
+                        let (from, to) = (Vector2::from(*from), Vector2::from(*to));
+                        let tint = Color::new(color.r, color.g, color.b, alpha);
+                        match style {
+                            TrailStyle::Plain => d.draw_line_ex(from, to, size * 1.2 * t, tint),
+                            TrailStyle::Sparks => {
+                                // Every other point glints white
+                                let glint = if age % 2 == 0 { Color::new(255, 255, 255, alpha) } else { tint };
+                                d.draw_circle_v(to, size * 0.35 * t, glint);
+                            }
+                            TrailStyle::Comet => {
+                                d.draw_line_ex(from, to, size * 2.0 * t, Color::new(color.r, color.g, color.b, alpha / 2));
+                                d.draw_line_ex(from, to, size * 0.5 * t, Color::new(255, 255, 255, alpha));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Our best run's ghost, racing this attempt
+        if let Some(ghost) = self.ghost.as_ref().filter(|_| self.show_ghost)
+            && let Some((pos, shadow_pos)) = ghost.pieces_at(game.play_time)
+        {
+            d.draw_circle_v(shadow_pos, SHADOW_SIZE, Color::new(255, 255, 255, 25));
+            d.draw_circle_lines(shadow_pos.x as i32, shadow_pos.y as i32, SHADOW_SIZE, Color::new(255, 255, 255, 60));
+            d.draw_circle_v(pos, PLAYER_SIZE, Color::new(255, 255, 255, 50));
+            d.draw_circle_lines(pos.x as i32, pos.y as i32, PLAYER_SIZE, Color::new(255, 255, 255, 120));
+            let label = tr!("ghost-label");
+            let label_width = d.measure_text(&label, 14);
+            d.draw_text(&label, pos.x as i32 - label_width / 2, pos.y as i32 - PLAYER_SIZE as i32 - 20, 14, Color::new(255, 255, 255, 120));
+        }
+
+        // Draw players and shadows
+        for (i, player) in game.players.iter().enumerate() {
+            let player_color = game.player_color(i);
+            let look = game.shadow_look(i);
+            let shadow_color = Color::new(player_color.r, player_color.g, player_color.b, look.alpha);
+
+            let player_pos = Vector2::new(player.pos.x, player.pos.y);
+            let shadow_pos = Vector2::new(player.shadow_pos.x, player.shadow_pos.y);
+
+            let is_hunter = game.is_hunter(i);
+            let visible = game.can_see(game.player_id as usize, i);
+
+            if !is_hunter {
+                // Draw shadow (semi-transparent, slightly smaller), fading and swelling with `look`
+                if let Some(sprite) = &sprites.shadow {
+                    draw_sprite(d, sprite, shadow_pos, look.size, Squash::NONE, shadow_color);
+                } else {
+                    d.draw_circle_v(shadow_pos, look.size, shadow_color);
+                }
+                if high_contrast {
+                    d.draw_ring(shadow_pos, look.size, look.size + 3.0, 0.0, 360.0, 32, Color::WHITE);
+                } else if sprites.shadow.is_none() {
+                    d.draw_circle_lines(
+                        shadow_pos.x as i32,
+                        shadow_pos.y as i32,
+                        look.size,
+                        Color::new(shadow_color.r, shadow_color.g, shadow_color.b, look.alpha.saturating_add(50)),
+                    );
+                }
+                if game.accessibility.shapes() {
+                    draw_player_mark(d, i, shadow_pos, look.size);
+                }
+
+                // Draw connection line from player to shadow (with glow effect), unless fog hides the player.
+                // It strengthens as it stretches, until it goes red when taut
+                let strain = 0.5 + look.stretch;
+                if visible && game.is_tether_taut(i) {
+                    // Taut tether: the shadow can't go any farther
+                    d.draw_line_ex(player_pos, shadow_pos, 4.0, Color::new(255, 40, 40, 200));
+                } else if visible && high_contrast {
+                    d.draw_line_ex(player_pos, shadow_pos, 3.0, player_color);
+                } else if visible && fx.bloom() {
+                    let alpha = (130.0 * strain).min(255.0) as u8;
+                    d.draw_line_ex(player_pos, shadow_pos, 1.0 + strain, Color::new(player_color.r, player_color.g, player_color.b, alpha));
+                } else if visible {
+                    let line_color = Color::new(player_color.r, player_color.g, player_color.b, (80.0 * strain) as u8);
+                    d.draw_line_ex(player_pos, shadow_pos, 2.0 * strain, line_color);
+                    d.draw_line_ex(player_pos, shadow_pos, 1.5, Color::new(255, 255, 255, (55.0 * strain) as u8));
+                }
+            }
+
+            // Draw trap radius around shadow (more visible)
+            if i != game.player_id as usize && !is_hunter {
+                // Pulsing effect using game time
+                let pulse = fx.wave(game.game_time * 2.0).abs();
+                let alpha = (100.0 + pulse * 100.0) as u8;
+                let trap = theme().trap;
+                let trap_radius = game.config.rules.trap_radius * game.config.handicaps[game.player_id as usize].trap_radius;
+                // On a wrap-around arena the radius also reaches across the edges
+                let offsets: &[(i32, i32)] = if game.config.wrap {
+                    &[(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (-1, 1), (1, -1), (1, 1)]
+                } else {
+                    &[(0, 0)]
+                };
+                for &(ox, oy) in offsets {
+                    let x = shadow_pos.x as i32 + ox * SCREEN_WIDTH;
+                    let y = shadow_pos.y as i32 + oy * SCREEN_HEIGHT;
+                    if high_contrast {
+                        // One solid, thick ring: no pulse and no faint inner circle
+                        let center = Vector2::new(x as f32, y as f32);
+                        d.draw_ring(center, trap_radius - 2.0, trap_radius + 2.0, 0.0, 360.0, 64, Color::YELLOW);
+                        continue;
+                    }
+                    d.draw_circle_lines(x, y, trap_radius, Color::new(trap.r, trap.g, trap.b, alpha));
+                    // Inner warning circle
+                    d.draw_circle_lines(x, y, trap_radius * 0.7, Color::new(trap.r, trap.g, trap.b, alpha / 2));
+                }
+            }
+
+            // Fog of war hides the opponent's character, but never the shadow we steer
+            if !visible {
+                continue;
+            }
+
+            // Draw player with glow effect, or their sprite, squashed and stretched by what they're doing
+            let alpha = if player.is_trapped { 150 } else { 255 };
+            let squash = game.pose(i).squash(game.game_time, fx.motion());
+            if let Some(sprite) = &sprites.character {
+                draw_sprite(d, sprite, player_pos, PLAYER_SIZE, squash, Color::new(player_color.r, player_color.g, player_color.b, alpha));
+            } else {
+                // Outer glow, unless the bloom shader is doing it
+                if !high_contrast && !fx.bloom() {
+                    draw_blob(d, player_pos, PLAYER_SIZE + 3.0, squash, Color::new(player_color.r, player_color.g, player_color.b, alpha / 3));
+                }
+                // Main circle
+                draw_blob(d, player_pos, PLAYER_SIZE, squash, Color::new(player_color.r, player_color.g, player_color.b, alpha));
+                // Inner highlight
+                draw_blob(d, player_pos, PLAYER_SIZE * 0.6, squash, Color::new(255, 255, 255, alpha / 2));
+            }
+            // Border
+            if high_contrast {
+                draw_blob_lines(d, player_pos, PLAYER_SIZE + 2.0, squash, 4.0, Color::WHITE);
+            } else if sprites.character.is_none() {
+                draw_blob_lines(d, player_pos, PLAYER_SIZE, squash, 1.0, Color::new(255, 255, 255, alpha));
+            }
+            if game.accessibility.shapes() {
+                draw_player_mark(d, i, player_pos, PLAYER_SIZE);
+            }
+
+            // Name tag
+            let name = &game.names[i];
+            let name_width = d.measure_text(name, 16);
+            d.draw_text(
+                name,
+                player_pos.x as i32 - name_width / 2,
+                player_pos.y as i32 - PLAYER_SIZE as i32 - 22,
+                16,
+                Color::new(player_color.r, player_color.g, player_color.b, alpha),
+            );
+
+            // Hunter: contact radius and label instead of a shadow
+            if is_hunter {
+                d.draw_circle_lines(
+                    player_pos.x as i32,
+                    player_pos.y as i32,
+                    HUNTER_CONTACT_RADIUS,
+                    Color::new(255, 80, 80, 160),
+                );
+                let label = tr!("tag-hunter");
+                let width = d.measure_text(&label, 18);
+                d.draw_text(&label, player_pos.x as i32 - width / 2, player_pos.y as i32 - 64, 18, Color::new(255, 120, 120, 255));
+            }
+
+            // Tag: mark whoever is "it"
+            if game.config.mode == GameMode::Tag && i == game.tag_it as usize {
+                let pulse = fx.wave(game.game_time * 4.0).abs();
+                d.draw_circle_lines(
+                    player_pos.x as i32,
+                    player_pos.y as i32,
+                    PLAYER_SIZE + 6.0 + pulse * 4.0,
+                    Color::ORANGE,
+                );
+                let label = tr!("tag-it");
+                let width = d.measure_text(&label, 22);
+                d.draw_text(&label, player_pos.x as i32 - width / 2, player_pos.y as i32 - 50, 22, Color::ORANGE);
+            }
+
+            // Teleport flash: a collapsing ring where they left, an expanding one where they arrived
+            let (from, fx_time) = game.teleport_fx[i];
+            if fx_time > 0.0 {
+                let t = fx_time / TELEPORT_FX_TIME;
+                let fx_color = Color::new(180, 220, 255, (t * 255.0) as u8);
+                if fx.motion() {
+                    d.draw_circle_lines(from.x as i32, from.y as i32, PLAYER_SIZE * (1.0 + t), fx_color);
+                    d.draw_circle_lines(player_pos.x as i32, player_pos.y as i32, PLAYER_SIZE * (3.0 - 2.0 * t), fx_color);
+                } else {
+                    d.draw_circle_lines(from.x as i32, from.y as i32, PLAYER_SIZE * 1.5, fx_color);
+                    d.draw_circle_lines(player_pos.x as i32, player_pos.y as i32, PLAYER_SIZE * 1.5, fx_color);
+                }
+            }
+
+            // Frozen: icy shell and a thaw timer
+            if game.frozen[i] > 0.0 {
+                d.draw_circle_v(player_pos, PLAYER_SIZE + 5.0, Color::new(150, 220, 255, 120));
+                d.draw_poly_lines(player_pos, 6, PLAYER_SIZE + 9.0, fx.spin(game.game_time * 20.0), Color::new(200, 240, 255, 255));
+                d.draw_text(
+                    &tr!("frozen", time = format!("{:.1}", game.frozen[i])),
+                    player_pos.x as i32 - 45,
+                    player_pos.y as i32 + 30,
+                    16,
+                    Color::SKYBLUE,
+                );
+            }
+
+            // Spawn protection: flashing outline while immune (steady with reduced motion)
+            if game.spawn_protection[i] > 0.0 && fx.wave(game.game_time * 8.0) > 0.0 {
+                d.draw_ring(player_pos, PLAYER_SIZE + 4.0, PLAYER_SIZE + 7.0, 0.0, 360.0, 32, Color::new(120, 220, 255, 220));
+            }
+
+            // Our character is being steered by the opponent: static and a chain ring
+            if i == game.player_id as usize && game.is_controlled(i) {
+                let opponent_color = game.player_color(1 - i);
+                for _ in 0..if fx.motion() { 4 } else { 0 } {
+                    let dy = rand::random_range(-PLAYER_SIZE..PLAYER_SIZE);
+                    let half = (PLAYER_SIZE * PLAYER_SIZE - dy * dy).sqrt();
+                    d.draw_line_ex(
+                        Vector2::new(player_pos.x - half, player_pos.y + dy),
+                        Vector2::new(player_pos.x + half, player_pos.y + dy),
+                        2.0,
+                        Color::new(255, 255, 255, rand::random_range(80..200)),
+                    );
+                }
+                let spin = fx.spin(game.game_time * -120.0);
+                for link in 0..8 {
+                    let angle = (spin + link as f32 * 45.0).to_radians();
+                    let at = Vector2::new(player_pos.x + angle.cos() * (PLAYER_SIZE + 8.0), player_pos.y + angle.sin() * (PLAYER_SIZE + 8.0));
+                    d.draw_circle_lines(at.x as i32, at.y as i32, 4.0, opponent_color);
+                }
+                let label = tr!("controlled");
+                let width = d.measure_text(&label, 18);
+                d.draw_text(&label, player_pos.x as i32 - width / 2, player_pos.y as i32 + 32, 18, opponent_color);
+            }
+
+            // Flash effect when trapped
+            if game.trap_flash_timer[i] > 0.0 {
+                if fx.motion() {
+                    let flash_alpha = (game.trap_flash_timer[i] * 200.0) as u8;
+                    d.draw_circle_v(player_pos, PLAYER_SIZE + 10.0, Color::new(255, 0, 0, flash_alpha));
+                } else {
+                    d.draw_ring(player_pos, PLAYER_SIZE + 6.0, PLAYER_SIZE + 10.0, 0.0, 360.0, 32, Color::RED);
+                }
+            }
+        }
+
+        game.particles.draw(d);
+
+        // Highlight whatever our movement keys drive, unless fog hides it
+        let me = game.player_id as usize;
+        let steered = game.steered_piece(me);
+        let hidden = matches!(steered, Piece::Character(p) if !game.can_see(me, p));
+        if !hidden && game.winner().is_none() {
+            let size = if matches!(steered, Piece::Character(_)) { PLAYER_SIZE } else { SHADOW_SIZE };
+            let ring_color = if game.inverse_active { Color::new(255, 255, 0, 110) } else { Color::new(255, 255, 255, 70) };
+            let spin = fx.spin(game.game_time * 90.0);
+            let pos = Vector2::from(game.steered_pos(me));
+            for arc in 0..3 {
+                let start = spin + arc as f32 * 120.0;
+                d.draw_ring(pos, size + 9.0, size + 11.0, start, start + 80.0, 12, ring_color);
+            }
+        }
+
+        // Shadow steal: a violet streak from where the thief was to the shadow they took
+        let (thief, steal_from, steal_to, steal_time) = game.steal_fx;
+        if steal_time > 0.0 {
+            let t = steal_time / STEAL_FX_TIME;
+            let streak_color = Color::new(200, 80, 255, (t * 255.0) as u8);
+            d.draw_line_ex(Vector2::from(steal_from), Vector2::from(steal_to), 2.0 + 6.0 * t, streak_color);
+            d.draw_circle_lines(steal_to.x as i32, steal_to.y as i32, PLAYER_SIZE * (1.0 + 2.0 * (1.0 - t)), streak_color);
+            let text = tr!(if thief == game.player_id { "steal-done" } else { "steal-victim" });
+            d.draw_text(&text, steal_to.x as i32 - 80, steal_to.y as i32 - 70, 20, streak_color);
+        }
+    }
+
+    /// Arena, pieces and HUD.
+    fn draw(&self, d: &mut RaylibDrawHandle, sprites: &Sprites) {
+        let Match { game, daily, puzzle, tutorial, show_scoreboard, show_controls, show_debug, frame_times, bindings, .. } = self;
+        let hud_bottom = self.hud_bottom();
+        let (arena_top, arena_bottom) = self.arena_view();
+        let high_contrast = game.accessibility.high_contrast;
+        let fx = &game.accessibility;
+
+        // Dark gradient background
+        d.clear_background(if high_contrast { Color::BLACK } else { theme().arena });
+        
+        // Everything in the arena goes through the play camera
+        {
+            let mut world = d.begin_mode2D(self.camera.camera());
+            self.draw_arena(&mut world, sprites);
+        }
+
+        // Vignette in the opponent's color while they steer our character
+        if game.is_controlled(game.player_id as usize) {
+            let c = game.player_color(1 - game.player_id as usize);
+            let edge = Color::new(c.r, c.g, c.b, (90.0 + fx.wave(game.game_time * 5.0) * 40.0) as u8);
+            let clear = Color::new(c.r, c.g, c.b, 0);
+            let depth = 90;
+            d.draw_rectangle_gradient_v(0, 0, SCREEN_WIDTH, depth, edge, clear);
+            d.draw_rectangle_gradient_v(0, SCREEN_HEIGHT - depth, SCREEN_WIDTH, depth, clear, edge);
+            d.draw_rectangle_gradient_h(0, 0, depth, SCREEN_HEIGHT, edge, clear);
+            d.draw_rectangle_gradient_h(SCREEN_WIDTH - depth, 0, depth, SCREEN_HEIGHT, clear, edge);
+        }
+
+        // Draw UI with better styling - organized layout. Each group hangs from a screen corner or
+        // edge and is scaled about it by the HUD scale setting.
+        let player_color = game.player_color(game.player_id as usize);
+        let is_game_over = game.winner().is_some();
+        let scale = game.accessibility.hud_scale;
+        
+        // Title bar background
+        d.draw_rectangle(0, 0, SCREEN_WIDTH, arena_top as i32, theme().overlay);
+        
+        // Game title (top center)
+        draw_hud(d, Anchor::Top.point(), scale, |d| {
+            draw_centered(d, "SHADOW SWAP", 8, 32, theme().title);
+        });
+        
+        // Left side: Player info, with shapes beside the names when the palette uses them
+        draw_hud(d, Anchor::TopLeft.point(), scale, |d| {
+            if game.accessibility.shapes() {
+                draw_player_mark(d, game.player_id as usize, Vector2::new(10.0, 58.0), 14.0);
+                draw_player_mark(d, 1 - game.player_id as usize, Vector2::new(10.0, 106.0), 14.0);
+            }
+            d.draw_text(
+                &tr!("hud-you", name = game.names[game.player_id as usize]),
+                20,
+                45,
+                26,
+                player_color,
+            );
+        
+            // Scores with proper spacing
+            let my_score = game.players[game.player_id as usize].score;
+            let other_score = game.players[1 - game.player_id as usize].score;
+            d.draw_text(
+                &tr!("hud-trapped", score = my_score, max = WIN_SCORE),
+                20,
+                72,
+                22,
+                Color::WHITE,
+            );
+            d.draw_text(
+                &tr!("hud-opponent-trapped", name = game.names[1 - game.player_id as usize], score = other_score, max = WIN_SCORE),
+                20,
+                95,
+                22,
+                Color::GRAY,
+            );
+            // Handicaps, when set, next to the scores they affect
+            for (line, pid) in [game.player_id as usize, 1 - game.player_id as usize].into_iter().enumerate() {
+                if let Some(label) = game.config.handicaps[pid].label() {
+                    d.draw_text(&label, 240, 76 + line as i32 * 23, 16, Color::new(255, 200, 120, 255));
+                }
+            }
+            match game.config.mode {
+                GameMode::Classic => {}
+                GameMode::CaptureZone => {
+                    let my_zone = game.zone_points[game.player_id as usize];
+                    let other_zone = game.zone_points[1 - game.player_id as usize];
+                    d.draw_text(
+                        &tr!("hud-zones", mine = my_zone, theirs = other_zone, max = ZONE_WIN_SCORE),
+                        20,
+                        118,
+                        18,
+                        Color::new(150, 150, 255, 255),
+                    );
+                }
+                GameMode::Tag => {
+                    let it_label = tr!(if game.tag_it == game.player_id { "hud-tag-you-it" } else { "hud-tag-opponent-it" });
+                    d.draw_text(
+                        &tr!(
+                            "hud-tag",
+                            it = it_label,
+                            mine = format!("{:.1}", game.tag_time[game.player_id as usize]),
+                            theirs = format!("{:.1}", game.tag_time[1 - game.player_id as usize]),
+                            left = format!("{:.0}", game.tag_time_left),
+                        ),
+                        20,
+                        118,
+                        18,
+                        Color::ORANGE,
+                    );
+                }
+                GameMode::HunterVsShade => {
+                    let role = tr!(if game.is_hunter(game.player_id as usize) { "hud-role-hunter" } else { "hud-role-shade" });
+                    d.draw_text(&role, 20, 118, 18, Color::new(255, 120, 120, 255));
+                }
+                GameMode::KingOfTheHill => {
+                    // Hill progress bars: yours on the left, opponent's on the right
+                    for (slot, pid) in [game.player_id as usize, 1 - game.player_id as usize].into_iter().enumerate() {
+                        let x = 20 + slot as i32 * 200;
+                        let progress = game.hill_time[pid] / HILL_WIN_TIME;
+                        let bar_color = game.player_color(pid);
+                        d.draw_rectangle(x, 120, 180, 12, theme().meter);
+                        d.draw_rectangle(x, 120, (180.0 * progress) as i32, 12, bar_color);
+                        d.draw_rectangle_lines(x, 120, 180, 12, Color::new(200, 200, 200, 200));
+                    }
+                }
+            }
+        });
+
+        // Top center: single-player status lines, tutorial instructions, mode and round
+        draw_hud(d, Anchor::Top.point(), scale, |d| {
+            // Daily challenge clock and today's best
+            if let Some(challenge) = &daily {
+                let best = challenge.best.map_or("--".to_string(), |best| format!("{:.2}s", best));
+                let text = tr!("hud-daily", date = challenge.date(), time = format!("{:.2}", challenge.elapsed), best = best);
+                draw_centered(d, &text, 70, 18, Color::new(255, 220, 120, 255));
+            }
+
+            // Puzzle level, goal and clock
+            if let Some(campaign) = &puzzle {
+                let goal = match campaign.level().goal {
+                    PuzzleGoal::Trap(count) => tr!("hud-puzzle-trap", done = game.players[1].score.min(count), count = count),
+                    PuzzleGoal::Survive => tr!("hud-puzzle-survive"),
+                };
+                let text = tr!(
+                    "hud-puzzle",
+                    level = campaign.current + 1,
+                    levels = campaign.levels.len(),
+                    name = campaign.level().name,
+                    goal = goal,
+                    time = format!("{:.1}", campaign.time_left.max(0.0)),
+                );
+                draw_centered(d, &text, 70, 18, Color::new(255, 220, 120, 255));
+            }
+
+            // Tutorial instructions for the current step
+            if let Some(tutorial) = &tutorial
+                && !is_game_over
+            {
+                tutorial.draw(d, game, bindings);
+            }
+
+            // Game mode and round (top center, under the title)
+            let round = tr!("hud-round", mode = game.config.mode.name(), arena = game.config.arena.name(), round = game.round);
+            draw_centered(d, &round, 45, 18, theme().hint);
+        });
+
+        // Top right: what our movement keys drive right now
+        draw_hud(d, Anchor::TopRight.point(), scale, |d| {
+            let me = game.player_id as usize;
+            let (owner, is_shadow) = match game.steered_piece(me) {
+                Piece::Character(p) => (p, false),
+                Piece::Shadow(p) => (p, true),
+            };
+            let scripted = puzzle.as_ref().is_some_and(|campaign| campaign.is_scripted(game.steered_piece(me)));
+            let driving = match (owner == me, is_shadow) {
+                (true, true) => tr!("hud-steer-own-shadow"),
+                (true, false) => tr!("hud-steer-own-character"),
+                (false, true) => tr!("hud-steer-shadow", name = game.names[owner]),
+                (false, false) => tr!("hud-steer-character", name = game.names[owner]),
+            };
+            let driving = if scripted { tr!("hud-steer-scripted", steer = driving) } else { driving };
+            let driving_color = if game.inverse_active { Color::YELLOW } else { Color::LIGHTGRAY };
+
+            // Right side: Mode indicator
+            let my_charge = game.players[game.player_id as usize].inverse_charge;
+            let schedule = game.config.inverse_schedule;
+            let seconds = |time: f32| format!("{:.1}", time.max(0.0));
+            let inverse_text = if game.inverse_active && schedule == InverseSchedule::Endgame {
+                tr!("hud-inverse-endgame")
+            } else if game.inverse_active {
+                tr!("hud-inverse-active", time = seconds(game.inverse_timer))
+            } else {
+                match schedule {
+                    InverseSchedule::Meter if game.inverse_armed => tr!("hud-inverse-incoming", time = seconds(game.inverse_cooldown)),
+                    InverseSchedule::Meter if my_charge >= 1.0 => tr!("hud-control-ready", key = key_name(bindings.key(Action::Inverse))),
+                    InverseSchedule::Meter => tr!("hud-control"),
+                    InverseSchedule::Endgame => tr!("hud-control-endgame", traps = WIN_SCORE - 1),
+                    InverseSchedule::Random if !game.inverse_warning() => tr!("hud-control-unknown"),
+                    _ => tr!("hud-control-timer", time = seconds(game.inverse_cooldown)),
+                }
+            };
+            let inverse_color = if game.inverse_active { 
+                Color::new(255, 255, 0, 255) 
+            } else { 
+                Color::new(200, 200, 200, 255) 
+            };
+            let detail = if game.inverse_active && schedule == InverseSchedule::Meter {
+                Some((game.inverse_trigger_text(), inverse_color))
+            } else if schedule != InverseSchedule::Meter {
+                Some((tr!("hud-schedule", schedule = schedule.name()), Color::GRAY))
+            } else {
+                None
+            };
+
+            // The column is at least 350 wide so ticking timers don't shift it; longer text widens it
+            let width = [d.measure_text(&driving, 18) + 25, d.measure_text(&inverse_text, 24), 350]
+                .into_iter()
+                .chain(detail.iter().map(|(text, _)| d.measure_text(text, 16)))
+                .max()
+                .unwrap_or(350);
+            let x = Anchor::TopRight.place(Vector2::new(width as f32, 0.0), Vector2::new(20.0, 0.0)).x as i32;
+
+            let icon = Vector2::new(x as f32 + 10.0, 25.0);
+            let owner_color = game.player_color(owner);
+            if is_shadow {
+                d.draw_circle_v(icon, 7.0, Color::new(owner_color.r, owner_color.g, owner_color.b, 150));
+            } else {
+                d.draw_circle_v(icon, 8.0, owner_color);
+                d.draw_circle_lines(icon.x as i32, icon.y as i32, 8.0, Color::WHITE);
+            }
+            d.draw_text(&driving, x + 25, 16, 18, driving_color);
+        
+            // Background for mode indicator
+            if game.inverse_active {
+                let bg_alpha = (game.accessibility.wave(game.inverse_timer * 3.0).abs() * 50.0 + 30.0) as u8;
+                d.draw_rectangle(x - 10, 70, width + 10, 35, Color::new(255, 255, 0, bg_alpha));
+            }
+        
+            // Mode text
+            d.draw_text(&inverse_text, x, 75, 24, inverse_color);
+            if let Some((text, color)) = detail {
+                d.draw_text(&text, x, 50, 16, color);
+            }
+
+            // Inverse meters: yours on top, opponent's below
+            let meters = if schedule == InverseSchedule::Meter { 2 } else { 0 };
+            for (slot, pid) in [game.player_id as usize, 1 - game.player_id as usize].into_iter().take(meters).enumerate() {
+                let y = 108 + slot as i32 * 12;
+                let charge = game.players[pid].inverse_charge;
+                let meter_color = if charge >= 1.0 { Color::YELLOW } else { game.player_color(pid) };
+                d.draw_rectangle(x, y, 160, 8, theme().meter);
+                d.draw_rectangle(x, y, (160.0 * charge) as i32, 8, meter_color);
+            }
+        });
+
+        // Controls panel (F1), or a one-line reminder that it exists. The panel keeps the full
+        // width at any HUD scale; only its height and text grow.
+        if *show_controls {
+            let (panel_y, panel_h) = (arena_bottom as i32, (105.0 * scale) as i32);
+            d.draw_rectangle(10, panel_y, SCREEN_WIDTH - 20, panel_h, Color::new(0, 0, 0, 150));
+            d.draw_rectangle_lines(10, panel_y, SCREEN_WIDTH - 20, panel_h, Color::new(100, 100, 100, 200));
+
+            draw_hud(d, Anchor::BottomLeft.point(), scale, |d| {
+                let instructions_y = SCREEN_HEIGHT - 110;
+                d.draw_text(
+                    &tr!("controls-title"),
+                    20,
+                    instructions_y,
+                    20,
+                    Color::new(255, 255, 200, 255),
+                );
+                d.draw_text(
+                    &tr!(match game.config.shadow_control {
+                        ShadowControl::Opponent => "controls-move-opponent",
+                        ShadowControl::Mirror => "controls-move-mirror",
+                        ShadowControl::Echo => "controls-move-echo",
+                    }, keys = bindings.movement()),
+                    20,
+                    instructions_y + 25,
+                    18,
+                    Color::LIGHTGRAY,
+                );
+                d.draw_text(
+                    &tr!("controls-swap", key = key_name(bindings.key(Action::Swap))),
+                    20,
+                    instructions_y + 45,
+                    18,
+                    Color::LIGHTGRAY,
+                );
+                d.draw_text(
+                    &tr!("controls-goal", count = WIN_SCORE),
+                    20,
+                    instructions_y + 65,
+                    18,
+                    Color::YELLOW,
+                );
+                d.draw_text(
+                    &tr!(
+                        "controls-keys",
+                        inverse = key_name(bindings.key(Action::Inverse)),
+                        freeze = key_name(bindings.key(Action::Freeze)),
+                        freezes = game.freeze_charges[game.player_id as usize],
+                    ),
+                    20,
+                    instructions_y + 85,
+                    16,
+                    Color::new(150, 150, 150, 255),
+                );
+            });
+        } else {
+            draw_hud(d, Anchor::BottomRight.point(), scale, |d| {
+                draw_anchored(d, &tr!("controls-hint"), Anchor::BottomRight, Vector2::new(20.0, 10.0), 16, Color::new(150, 150, 150, 200));
+            });
+        }
+        
+        // What can trap us, when it's hidden behind a HUD panel
+        if !is_game_over && game.round_phase == RoundPhase::Playing {
+            draw_threat_indicator(d, game, &self.camera, arena_top, arena_bottom);
+        }
+
+        // Danger meter and edge glow, rising as our character nears the trap radius
+        let me = game.player_id as usize;
+        let hunter_hidden = game.is_hunter(1 - me) && !game.can_see(me, 1 - me);
+        let traps_on = game.config.mode != GameMode::Tag; // Tag passes the tag instead of trapping
+        if !is_game_over && game.round_phase == RoundPhase::Playing && traps_on && !hunter_hidden {
+            let danger = game.danger(me);
+            if danger > 0.0 && !game.accessibility.high_contrast {
+                let glow = Color::new(255, 30, 30, (danger * danger * 120.0) as u8);
+                let clear = Color::new(255, 30, 30, 0);
+                let height = (arena_bottom - arena_top) as i32;
+                d.draw_rectangle_gradient_h(0, arena_top as i32, 60, height, glow, clear);
+                d.draw_rectangle_gradient_h(SCREEN_WIDTH - 60, arena_top as i32, 60, height, clear, glow);
+            }
+            draw_hud(d, Anchor::BottomLeft.point(), scale, |d| {
+                let y = hud_bottom as i32 - 24;
+                let meter_color = Color::new(255, (220.0 - danger * 190.0) as u8, 40, 255);
+                d.draw_text(&tr!("hud-danger"), 20, y - 1, 16, Color::new(200, 200, 200, 200));
+                d.draw_rectangle(90, y, 140, 12, theme().meter);
+                d.draw_rectangle(90, y, (140.0 * danger) as i32, 12, meter_color);
+                d.draw_rectangle_lines(90, y, 140, 12, Color::new(200, 200, 200, 150));
+            });
+        }
+
+        // Intermission and countdown between rounds
+        if !is_game_over {
+            match game.round_phase {
+                RoundPhase::Playing => {}
+                RoundPhase::Intermission => {
+                    let trapped = (0..2).find(|&i| game.players[i].is_trapped).unwrap_or(0);
+                    let band = (160.0 * scale) as i32;
+                    d.draw_rectangle(0, SCREEN_HEIGHT / 2 - band / 2, SCREEN_WIDTH, band, Color::new(0, 0, 0, 170));
+                    draw_hud(d, Anchor::Center.point(), scale, |d| {
+                        let title = tr!("round-trapped", name = game.names[trapped].to_uppercase());
+                        draw_centered(d, &title, SCREEN_HEIGHT / 2 - 60, 50, Color::YELLOW);
+                        let scores = tr!(
+                            "round-scores",
+                            first = game.names[0],
+                            first_score = game.players[0].score,
+                            second = game.names[1],
+                            second_score = game.players[1].score,
+                            max = WIN_SCORE,
+                        );
+                        draw_centered(d, &scores, SCREEN_HEIGHT / 2 + 5, 26, Color::WHITE);
+                        let next = tr!("round-next", seconds = game.round_timer.max(0.0).ceil());
+                        draw_centered(d, &next, SCREEN_HEIGHT / 2 + 45, 20, Color::LIGHTGRAY);
+                    });
+                    let progress = 1.0 - (game.round_timer / game.config.rules.intermission).clamp(0.0, 1.0);
+                    draw_killcam(d, game, trapped, progress);
+                }
+                RoundPhase::Countdown => draw_hud(d, Anchor::Center.point(), scale, |d| {
+                    let count = game.round_timer.max(0.0).ceil() as i32;
+                    let title = if game.round == 1 { tr!("round-get-ready") } else { tr!("round-number", round = game.round) };
+                    draw_centered(d, &title, SCREEN_HEIGHT / 2 - 100, 32, theme().title);
+                    d.draw_text(
+                        &count.max(1).to_string(),
+                        SCREEN_WIDTH / 2 - 20,
+                        SCREEN_HEIGHT / 2 - 50,
+                        100,
+                        Color::YELLOW,
+                    );
+                }),
+            }
+        }
+
+        // Pre-match countdown just finished
+        if game.go_flash > 0.0 {
+            let t = game.go_flash / GO_FLASH_TIME;
+            let size = if game.accessibility.motion() { 100 + ((1.0 - t) * 40.0) as i32 } else { 120 };
+            draw_hud(d, Anchor::Center.point(), scale, |d| {
+                draw_centered(d, &tr!("round-go"), SCREEN_HEIGHT / 2 - size / 2, size, Color::new(120, 255, 120, (t * 255.0) as u8));
+            });
+        }
+
+        // Clash: both trapped at once, nobody scores
+        if game.clash_fx > 0.0 {
+            let alpha = (game.clash_fx / CLASH_FX_TIME * 255.0) as u8;
+            draw_hud(d, Anchor::Center.point(), scale, |d| {
+                draw_centered(d, &tr!("clash-title"), SCREEN_HEIGHT / 2 - 180, 60, Color::new(255, 255, 255, alpha));
+                draw_centered(d, &tr!("clash-detail"), SCREEN_HEIGHT / 2 - 115, 22, Color::new(200, 200, 200, alpha));
+            });
+        }
+
+        // Streak callout, bigger and wilder the longer the streak
+        if game.streak_callout > 0.0
+            && let Some(streaker) = (0..2).find(|&i| game.streaks[i] >= 2)
+        {
+            let streak = game.streaks[streaker];
+            let (text, color) = match streak {
+                2 => ("streak-double", Color::ORANGE),
+                3 => ("streak-triple", Color::GOLD),
+                4 => ("streak-dominating", Color::MAGENTA),
+                _ => ("streak-unstoppable", Color::RED),
+            };
+            let size = 30 + 6 * streak.min(6) as i32;
+            let shake = if streak >= 3 && game.accessibility.motion() { ((game.game_time * 40.0).sin() * (streak as f32)) as i32 } else { 0 };
+            let fade = (game.streak_callout / STREAK_CALLOUT_TIME * 2.0).min(1.0);
+            let label = tr!("streak-callout", name = game.names[streaker], streak = tr!(text), count = streak);
+            draw_hud(d, Anchor::Top.point(), scale, |d| {
+                let x = SCREEN_WIDTH / 2 - d.measure_text(&label, size) / 2 + shake;
+                d.draw_text(&label, x + 3, 213, size, Color::new(0, 0, 0, (fade * 200.0) as u8));
+                d.draw_text(&label, x, 210, size, Color::new(color.r, color.g, color.b, (fade * 255.0) as u8));
+                if game.config.streak_bonus && streak.is_multiple_of(STREAK_BONUS_AT) {
+                    draw_centered(d, &tr!("streak-bonus"), 215 + size, 22, Color::new(255, 255, 255, (fade * 255.0) as u8));
+                }
+            });
+        }
+
+        // Slow-mo: darken the screen edges while time is dipped
+        if game.time_scale < 1.0 {
+            let strength = (1.0 - game.time_scale) / (1.0 - SLOWMO_SCALE);
+            let edge = Color::new(0, 0, 40, (strength * 120.0) as u8);
+            d.draw_rectangle_gradient_v(0, arena_top as i32, SCREEN_WIDTH, 60, edge, Color::new(0, 0, 40, 0));
+            d.draw_rectangle_gradient_v(0, SCREEN_HEIGHT - 180, SCREEN_WIDTH, 60, Color::new(0, 0, 40, 0), edge);
+            draw_hud(d, Anchor::TopRight.point(), scale, |d| {
+                draw_anchored(d, &tr!("hud-slowmo"), Anchor::TopRight, Vector2::new(30.0, 150.0), 20, Color::new(150, 180, 255, (strength * 255.0) as u8));
+            });
+        }
+
+        // Inversion about to start: pulsing banner with the countdown, on both screens
+        if game.inverse_warning() && game.is_live() {
+            let pulse = game.accessibility.wave(game.game_time * 6.0) * 0.5 + 0.5;
+            let band = Color::new(255, 220, 0, (40.0 + pulse * 60.0) as u8);
+            d.draw_rectangle(0, (190.0 * scale) as i32, SCREEN_WIDTH, (50.0 * scale) as i32, band);
+            draw_hud(d, Anchor::Top.point(), scale, |d| {
+                let text = tr!("inverse-countdown", seconds = game.inverse_cooldown.max(0.0).ceil());
+                let size = 36 + (pulse * 6.0) as i32;
+                let width = d.measure_text(&text, size);
+                d.draw_text(&text, SCREEN_WIDTH / 2 - width / 2, 215 - size / 2, size, Color::YELLOW);
+                if game.inverse_armed {
+                    d.draw_text(&game.inverse_trigger_text(), SCREEN_WIDTH / 2 + width / 2 + 20, 207, 18, Color::new(255, 240, 150, 255));
+                }
+            });
+        }
+
+        // AFK warning for both players
+        if let Some(afk) = game.afk_player {
+            let seconds = game.afk_grace.max(0.0).ceil();
+            let text = if afk == game.player_id {
+                tr!("afk-you", seconds = seconds)
+            } else {
+                tr!("afk-opponent", name = game.names[afk as usize], seconds = seconds)
+            };
+            d.draw_rectangle(0, (145.0 * scale) as i32, SCREEN_WIDTH, (36.0 * scale) as i32, Color::new(120, 40, 0, 200));
+            draw_hud(d, Anchor::Top.point(), scale, |d| draw_centered(d, &text, 152, 24, Color::ORANGE));
+        }
+
+        // Forfeit hold progress
+        if game.forfeit_hold > 0.0 {
+            let progress = game.forfeit_hold / FORFEIT_HOLD_TIME;
+            draw_hud(d, Anchor::Center.point(), scale, |d| {
+                d.draw_rectangle(SCREEN_WIDTH / 2 - 150, SCREEN_HEIGHT / 2 + 160, 300, 16, Color::new(60, 60, 60, 220));
+                d.draw_rectangle(SCREEN_WIDTH / 2 - 150, SCREEN_HEIGHT / 2 + 160, (300.0 * progress) as i32, 16, Color::RED);
+                draw_centered(d, &tr!("forfeit-holding"), SCREEN_HEIGHT / 2 + 135, 20, Color::RED);
+            });
+        }
+
+        // Pause overlay with owner and resume countdown
+        if game.paused {
+            let owner = if game.pause_owner == game.player_id {
+                tr!("pause-by-you")
+            } else {
+                tr!("pause-by", name = game.names[game.pause_owner as usize])
+            };
+            d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0, 0, 0, 140));
+            draw_hud(d, Anchor::Center.point(), scale, |d| {
+                draw_centered(d, &tr!("pause-title"), SCREEN_HEIGHT / 2 - 60, 60, Color::WHITE);
+                draw_centered(d, &owner, SCREEN_HEIGHT / 2 + 10, 24, Color::LIGHTGRAY);
+                let resume_text = if game.resume_timer > 0.0 {
+                    tr!("pause-resuming", seconds = game.resume_timer.ceil())
+                } else {
+                    tr!("pause-resume-hint")
+                };
+                draw_centered(d, &resume_text, SCREEN_HEIGHT / 2 + 45, 24, Color::YELLOW);
+            });
+        }
+
+        draw_hud(d, Anchor::BottomRight.point(), scale, |d| draw_toasts(d, game));
+        draw_hud(d, Anchor::BottomLeft.point(), scale, |d| draw_event_log(d, game, hud_bottom));
+
+        if *show_scoreboard {
+            draw_hud(d, Anchor::Center.point(), scale, |d| draw_scoreboard(d, game));
+        }
+
+        if *show_debug {
+            draw_debug(d, game, frame_times);
+        }
+
+        // FPS counter (top right, above instructions)
+        draw_hud(d, Anchor::TopRight.point(), scale, |d| {
+            let at = Anchor::TopRight.place(Vector2::new(80.0, 20.0), Vector2::new(20.0, 115.0));
+            d.draw_fps(at.x as i32, at.y as i32);
+        });
+    }
+
+    /// Winner banner and rematch prompt, drawn over the arena.
+    fn draw_game_over(&self, d: &mut RaylibDrawHandle) {
+        let Match { game, puzzle, tutorial, .. } = self;
+        if self.heatmap != HeatmapView::Off {
+            self.draw_heatmap(d);
+            return;
+        }
+
+        // Draw win condition with better visuals
+        if let Some(winner) = game.winner() {
+            let loser = 1 - winner;
+            let winner_color = game.player_color(winner);
+            let reason = if let Some(campaign) = &puzzle {
+                campaign.result_text()
+            } else if let Some(tutorial) = &tutorial {
+                tutorial.result_text()
+            } else if game.forfeited.is_some() {
+                tr!("win-forfeit", name = game.names[loser])
+            } else if game.players[loser].score >= WIN_SCORE {
+                tr!("win-trapped", name = game.names[loser])
+            } else {
+                match game.config.mode {
+                    GameMode::Tag => tr!("win-tag", name = game.names[winner]),
+                    GameMode::KingOfTheHill => tr!("win-hill", name = game.names[winner]),
+                    _ => tr!("win-zones", name = game.names[winner]),
+                }
+            };
+            // Semi-transparent overlay
+            d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0, 0, 0, 180));
+            draw_hud(d, Anchor::Center.point(), game.accessibility.hud_scale, |d| {
+                draw_centered(d, &tr!("win-title", name = game.names[winner].to_uppercase()), SCREEN_HEIGHT / 2 - 40, 60, winner_color);
+                draw_centered(d, &reason, SCREEN_HEIGHT / 2 + 30, 28, Color::WHITE);
+
+                // Rematch prompt: both players must accept
+                let vote_text = |vote: Option<bool>| {
+                    tr!(match vote {
+                        None => "rematch-waiting",
+                        Some(true) => "rematch-wants",
+                        Some(false) => "rematch-declined",
+                    })
+                };
+                let my_vote = game.rematch_votes[game.player_id as usize];
+                let their_vote = game.rematch_votes[1 - game.player_id as usize];
+                if their_vote == Some(true) && my_vote.is_none() {
+                    // Opponent is waiting on us: make it hard to miss
+                    let pulse = (game.accessibility.wave(game.game_time * 5.0) * 0.5 + 0.5) * 155.0 + 100.0;
+                    let prompt = tr!("rematch-asked");
+                    let width = d.measure_text(&prompt, 22).max(500);
+                    d.draw_rectangle(SCREEN_WIDTH / 2 - width / 2 - 10, SCREEN_HEIGHT / 2 + 82, width + 20, 40, Color::new(60, 60, 0, 200));
+                    draw_centered(d, &prompt, SCREEN_HEIGHT / 2 + 90, 22, Color::new(255, 255, 0, pulse as u8));
+                } else {
+                    draw_centered(d, &tr!("rematch-prompt"), SCREEN_HEIGHT / 2 + 90, 25, Color::YELLOW);
+                }
+                let votes = tr!("rematch-votes", you = vote_text(my_vote), opponent = vote_text(their_vote));
+                draw_centered(d, &votes, SCREEN_HEIGHT / 2 + 125, 20, Color::LIGHTGRAY);
+                let hint = if game.history.is_empty() { tr!("rematch-report-hint") } else { tr!("rematch-replay-hint") };
+                draw_centered(d, &hint, SCREEN_HEIGHT / 2 + 155, 18, Color::GRAY);
+                draw_centered(d, &tr!("rematch-heatmap-hint"), SCREEN_HEIGHT / 2 + 180, 18, Color::GRAY);
+            });
+        }
+    }
+
+    /// Game over heatmap: each shown player's time per cell in their color, brighter where they
+    /// spent longer, and an X wherever someone was trapped, in the trapped player's color.
+    fn draw_heatmap(&self, d: &mut RaylibDrawHandle) {
+        let game = &self.game;
+        let shown: Vec<usize> = match self.heatmap {
+            HeatmapView::Player(id) => vec![id],
+            _ => vec![0, 1],
+        };
+        d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0, 0, 0, 150));
+        {
+            let mut world = d.begin_mode2D(self.camera.camera());
+            // Shared scale, so the two players' colors compare fairly; square root lifts brief visits
+            let peak = shown.iter().flat_map(|&id| game.heatmap.time[id].iter()).fold(0.0f32, |peak, &time| peak.max(time));
+            for &id in &shown {
+                let c = game.player_color(id);
+                for (cell, &time) in game.heatmap.time[id].iter().enumerate() {
+                    if time <= 0.0 || peak <= 0.0 {
+                        continue;
+                    }
+                    let alpha = (time / peak).sqrt() * 200.0;
+                    let (x, y) = ((cell % HEATMAP_COLS) as i32 * HEATMAP_CELL, (cell / HEATMAP_COLS) as i32 * HEATMAP_CELL);
+                    world.draw_rectangle(x, y, HEATMAP_CELL, HEATMAP_CELL, Color::new(c.r, c.g, c.b, alpha as u8));
+                }
+            }
+            for &(id, pos) in game.heatmap.traps.iter().filter(|(id, _)| shown.contains(id)) {
+                let c = game.player_color(id);
+                let size = 14.0;
+                world.draw_line_ex(Vector2::new(pos.x - size, pos.y - size), Vector2::new(pos.x + size, pos.y + size), 5.0, Color::WHITE);
+                world.draw_line_ex(Vector2::new(pos.x - size, pos.y + size), Vector2::new(pos.x + size, pos.y - size), 5.0, Color::WHITE);
+                world.draw_line_ex(Vector2::new(pos.x - size, pos.y - size), Vector2::new(pos.x + size, pos.y + size), 3.0, c);
+                world.draw_line_ex(Vector2::new(pos.x - size, pos.y + size), Vector2::new(pos.x + size, pos.y - size), 3.0, c);
+            }
+        }
+        let title = match self.heatmap {
+            HeatmapView::Player(id) => tr!("heatmap-player", name = game.names[id]),
+            _ => tr!("heatmap-both"),
+        };
+        let (arena_top, _) = self.arena_view();
+        draw_hud(d, Anchor::Top.point(), game.accessibility.hud_scale, |d| {
+            draw_centered(d, &title, arena_top as i32 + 12, 24, Color::WHITE);
+            draw_centered(d, &tr!("heatmap-hint"), arena_top as i32 + 42, 18, Color::LIGHTGRAY);
+        });
+    }
+
+    /// Socket is open, waiting to hear from the other player: spinner, addresses and a cancel button.
+    fn draw_connecting(&self, d: &mut RaylibDrawHandle, status: &ConnectStatus) {
+        d.clear_background(theme().background);
+        let title_width = d.measure_text("SHADOW SWAP", 60);
+        d.draw_text("SHADOW SWAP", SCREEN_WIDTH / 2 - title_width / 2, 50, 60, theme().accent);
+
+        // Spinner
+        let center = Vector2::new(SCREEN_WIDTH as f32 / 2.0, 250.0);
+        let angle = (d.get_time() as f32 * 300.0) % 360.0;
+        d.draw_ring(center, 30.0, 38.0, 0.0, 360.0, 48, Color::new(40, 40, 70, 255));
+        d.draw_ring(center, 30.0, 38.0, angle, angle + 90.0, 24, theme().accent);
+
+        let mut lines = Vec::new();
+        let headline = if self.game.is_host {
+            lines.push(match status.local_ip {
+                Some(ip) => tr!("connect-local-network", ip = ip),
+                None => tr!("connect-no-local-ip"),
+            });
+            lines.push(tr!("connect-same-computer"));
+            lines.push(tr!("connect-internet", port = tunables().port));
+            tr!("connect-waiting", port = tunables().port)
+        } else {
+            let target = self.game.transport.as_ref().and_then(|transport| transport.peer());
+            if status.waited > 10.0 {
+                lines.push(tr!("connect-no-answer"));
+            }
+            match target {
+                Some(addr) => tr!("connect-to", address = addr),
+                None => tr!("connect-to-host"),
+            }
+        };
+        let width = d.measure_text(&headline, 30);
+        d.draw_text(&headline, SCREEN_WIDTH / 2 - width / 2, 320, 30, theme().text);
+        let waited = format!("{:.0}s", status.waited);
+        let width = d.measure_text(&waited, 20);
+        d.draw_text(&waited, SCREEN_WIDTH / 2 - width / 2, 360, 20, theme().hint);
+        for (i, line) in lines.iter().enumerate() {
+            let width = d.measure_text(line, 20);
+            d.draw_text(line, SCREEN_WIDTH / 2 - width / 2, 420 + i as i32 * 30, 20, theme().unselected);
+        }
+
+        let button = cancel_button();
+        let hovered = button.check_collision_point_rec(d.get_mouse_position());
+        let fill = if hovered { Color::new(90, 40, 40, 255) } else { Color::new(60, 30, 30, 255) };
+        d.draw_rectangle_rec(button, fill);
+        d.draw_rectangle_lines_ex(button, 2.0, Color::new(255, 120, 120, 255));
+        draw_centered(d, &tr!("connect-cancel"), button.y as i32 + 11, 22, Color::WHITE);
+        draw_anchored(d, &tr!("connect-cancel-key"), Anchor::Bottom, Vector2::new(0.0, 57.0), 18, theme().hint);
+    }
+
+    /// Both players are in: the match rules, then the first round.
+    fn draw_lobby(&self, d: &mut RaylibDrawHandle) {
+        let config = &self.game.config;
+        d.clear_background(theme().background);
+        draw_centered(d, &tr!("lobby-title"), 120, 40, theme().accent);
+        let names = &self.game.names;
+        let rating = |i: usize| self.ratings.of(&names[i]).round();
+        let you = tr!("lobby-players", first = names[0], second = names[1], first_rating = rating(0), second_rating = rating(1));
+        let width = d.measure_text(&you, 24);
+        d.draw_text(&you, SCREEN_WIDTH / 2 - width / 2, 180, 24, theme().text);
+        for i in 0..2 {
+            let x = SCREEN_WIDTH / 2 + (i as i32 * 2 - 1) * (width / 2 + 30);
+            d.draw_circle(x, 192, 12.0, self.game.player_color(i));
+        }
+
+        let mut rules = vec![
+            format!("{} - {}", config.mode.name(), config.arena.name()),
+            tr!("lobby-inverse", schedule = config.inverse_schedule.name()),
+            tr!("lobby-shadows", shadows = config.shadow_control.name()),
+        ];
+        let toggles = [
+            (config.wrap, "setting-wrap"),
+            (config.shadow_steal, "setting-steal"),
+            (config.fog, "setting-fog"),
+            (config.streak_bonus, "lobby-streak-bonus"),
+            (config.tether_range > 0.0, "setting-tether"),
+        ];
+        rules.extend(toggles.iter().filter(|(on, _)| *on).map(|(_, id)| tr!(id)));
+        for (i, rule) in rules.iter().enumerate() {
+            let width = d.measure_text(rule, 22);
+            d.draw_text(rule, SCREEN_WIDTH / 2 - width / 2, 250 + i as i32 * 32, 22, theme().unselected);
+        }
+
+        // Color picker: ours can change, a clash moves player 2 to the next color
+        let me = self.game.player_id as usize;
+        let (picked, shown) = (self.game.colors[me] as usize, self.game.color_index(me));
+        let color = color_value(shown);
+        let picker = if shown == picked {
+            tr!("lobby-color", color = color_name(shown))
+        } else {
+            tr!("lobby-color-taken", color = color_name(picked), shown = color_name(shown))
+        };
+        draw_anchored(d, &picker, Anchor::Bottom, Vector2::new(0.0, 196.0), 24, color);
+        let hint = if self.game.accessibility.palette == Palette::Picked {
+            tr!("lobby-color-hint")
+        } else {
+            tr!("lobby-color-hint-palette", palette = self.game.accessibility.palette.name())
+        };
+        draw_anchored(d, &hint, Anchor::Bottom, Vector2::new(0.0, 172.0), 18, theme().hint);
+
+        // Ready check: the countdown starts once both are ready
+        let me = self.game.player_id as usize;
+        let status = |ready: bool| tr!(if ready { "lobby-ready" } else { "lobby-not-ready" });
+        let ready_text = tr!("lobby-ready-status", you = status(self.game.ready[me]), opponent = status(self.game.ready[1 - me]));
+        draw_anchored(d, &ready_text, Anchor::Bottom, Vector2::new(0.0, 110.0), 30, theme().selected);
+        let hint = tr!(if self.game.ready[me] { "lobby-unready-hint" } else { "lobby-ready-hint" });
+        draw_anchored(d, &hint, Anchor::Bottom, Vector2::new(0.0, 80.0), 20, theme().hint);
+
+        draw_toasts(d, &self.game);
+    }
+
+    /// Game over: plays back the final recorded moments around the loser.
+    fn draw_replay(&self, d: &mut RaylibDrawHandle, time: f32) {
+        let game = &self.game;
+        let length = game.history.len() as f32 * KILLCAM_SAMPLE;
+        let loser = game.winner().map_or(0, |winner| 1 - winner);
+        let scale = game.accessibility.hud_scale;
+        let progress = (time / length).min(1.0);
+        d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, theme().overlay);
+
+        // The recorded moments fill the screen, through the camera the viewer steers
+        if let Some(snapshot) = game.history_at(progress) {
+            let camera = self.free_camera.camera();
+            let corner = camera.offset - camera.target * camera.zoom;
+            let arena = Rectangle::new(corner.x, corner.y, SCREEN_WIDTH as f32 * camera.zoom, SCREEN_HEIGHT as f32 * camera.zoom);
+            d.draw_rectangle_rec(arena, Color::new(10, 10, 20, 235));
+            d.draw_rectangle_lines_ex(arena, 2.0, Color::new(100, 100, 120, 200));
+            let screen = Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+            draw_snapshot(d, game, &snapshot, loser, screen, camera, progress);
+        }
+
+        draw_hud(d, Anchor::Top.point(), scale, |d| {
+            draw_anchored(d, &tr!("replay-title"), Anchor::Top, Vector2::new(0.0, 20.0), 40, Color::YELLOW);
+            let view = match self.free_camera.follow {
+                Some(player) => tr!("replay-following", name = game.names[player]),
+                None => tr!("replay-free"),
+            };
+            draw_anchored(d, &view, Anchor::Top, Vector2::new(0.0, 68.0), 20, Color::LIGHTGRAY);
+        });
+        if self.show_pip {
+            let winner = 1 - loser;
+            // Scaled about the top-right corner by hand: the view scissors in canvas coordinates
+            let (w, h) = (240.0 * scale, 150.0 * scale);
+            let frame = Rectangle::new(SCREEN_WIDTH as f32 - w - 20.0 * scale, (HUD_TOP + 20.0) * scale, w, h);
+            let title = tr!("pip-title", name = game.names[winner]);
+            draw_history_view(d, game, winner, progress, frame, &title, game.player_color(winner));
+        }
+        draw_hud(d, Anchor::Bottom.point(), scale, |d| {
+            let hint_color = theme().hint;
+            draw_anchored(d, &tr!("replay-camera-hint", keys = self.bindings.movement()), Anchor::Bottom, Vector2::new(0.0, 56.0), 18, hint_color);
+            draw_anchored(d, &tr!("replay-hint"), Anchor::Bottom, Vector2::new(0.0, 30.0), 20, hint_color);
+        });
+    }
+
+}
+
+/// Top-level screens. Each updates and draws itself, and `update` hands back the scene for the next frame.
+enum Scene {
+    MainMenu(MainMenu),
+    Connecting(Match, ConnectStatus), // Socket open, waiting to hear from the other player
+    Lobby(Match), // Both connected: rules and color picker until both are ready
+    Playing(Match),
+    GameOver(Match),
+    Replay(Match, f32), // Game over: replaying the final moments, seconds in
+}
+
+impl Scene {
+    /// Starts what the menu picked: the connecting screen for network games, straight into play
+    /// otherwise, or back to the menu with the error.
+    fn start(mut menu: MainMenu, choice: MenuChoice) -> Scene {
+        match menu.start(choice) {
+            Ok(session) if session.game.transport.is_some() => {
+                let status = ConnectStatus { waited: 0.0, local_ip: local_ip() };
+                Scene::Connecting(session, status)
+            }
+            Ok(session) => Scene::Playing(session),
+            Err(e) => {
+                menu.fail(choice, e);
+                Scene::MainMenu(menu)
+            }
+        }
+    }
+
+    /// Runs one frame and returns the next scene, or None to quit.
+    fn update(self, rl: &mut RaylibHandle, real_dt: f32, display: &mut Display, volume: &mut Volume) -> Option<Scene> {
+        let alt = rl.is_key_down(KeyboardKey::KEY_LEFT_ALT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_ALT);
+        if alt && rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            // Swallow the Enter so menus and the lobby don't also act on it
+            display.toggle_fullscreen(rl);
+            return Some(self);
+        }
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_M) {
+            volume.muted = !volume.muted;
+            return Some(self);
+        }
+        let back = rl.is_key_pressed(KeyboardKey::KEY_ESCAPE);
+        match self {
+            Scene::MainMenu(mut menu) => match menu.update(rl, display, volume) {
+                Some(MenuChoice::Quit) => None,
+                Some(choice) => Some(Scene::start(menu, choice)),
+                None => Some(Scene::MainMenu(menu)),
+            },
+            Scene::Connecting(mut session, mut status) => {
+                let cancel = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)
+                    && cancel_button().check_collision_point_rec(rl.get_mouse_position());
+                if back || cancel {
+                    let me = session.game.player_id as usize;
+                    let mut menu = MainMenu::new(session.game.names[me].clone());
+                    menu.color = session.game.colors[me];
+                    menu.profile = session.profile.clone();
+                    menu.bindings = session.bindings;
+                    menu.cosmetics = session.game.cosmetics[me];
+                    menu.achievements = session.achievements.clone();
+                    menu.dev = session.console.is_some();
+                    menu.accessibility = session.game.accessibility;
+                    return Some(Scene::MainMenu(menu));
+                }
+                session.game.receive_messages();
+                session.game.send_player_info();
+                session.game.send_updates();
+                if session.game.peer_seen {
+                    Some(Scene::Lobby(session))
+                } else {
+                    status.waited += real_dt;
+                    Some(Scene::Connecting(session, status))
+                }
+            }
+            Scene::Lobby(mut session) => {
+                if back {
+                    return None;
+                }
+                session.game.receive_messages();
+                session.game.send_player_info();
+                session.game.send_updates();
+                session.game.update_events(real_dt);
+                if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+                    session.game.cycle_color(-1, &available_colors(&session.achievements));
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+                    session.game.cycle_color(1, &available_colors(&session.achievements));
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    let me = session.game.player_id as usize;
+                    session.game.ready[me] = !session.game.ready[me];
+                }
+                let game = &mut session.game;
+                if game.is_host && game.ready == [true, true] {
+                    // The host starts the countdown; CountdownStart brings the client along
+                    game.start_round(1, ROUND_COUNTDOWN);
+                    return Some(Scene::Playing(session));
+                }
+                if !game.is_host && game.round_phase == RoundPhase::Countdown {
+                    return Some(Scene::Playing(session));
+                }
+                Some(Scene::Lobby(session))
+            }
+            Scene::Playing(mut session) => {
+                if back && !session.console_open() {
+                    session.game.save_replay(ReplayEnd::Abandoned);
+                    session.game.save_input_log();
+                    return None;
+                }
+                session.update(rl, real_dt);
+                if let Some(winner) = session.game.winner() {
+                    session.game.save_input_log();
+                    // A faster win becomes the ghost for the next attempt
+                    if let Some(replay) = session.game.replay(ReplayEnd::Won(winner as u8)) {
+                        replay.save();
+                        if replay.save_ghost() {
+                            session.game.toast(tr!("ghost-new-best", time = format!("{:.1}", replay.length())));
+                            session.ghost = Some(replay);
+                        }
+                    }
+                    // Single-player challenges keep their own records
+                    if session.daily.is_none() && session.puzzle.is_none() && session.tutorial.is_none()
+                        && let Some(record) = session.game.match_record()
+                    {
+                        record.save();
+                        session.ratings.record(&record);
+                        session.check_achievements();
+                    }
+                    Some(Scene::GameOver(session))
+                } else {
+                    Some(Scene::Playing(session))
+                }
+            }
+            Scene::GameOver(mut session) => {
+                if back && !session.console_open() {
+                    return None;
+                }
+                session.update(rl, real_dt);
+                if session.game.winner().is_none() {
+                    // The host started the rematch
+                    session.heatmap = HeatmapView::Off;
+                    Some(Scene::Playing(session))
+                } else if rl.is_key_pressed(KeyboardKey::KEY_H) && !session.console_open() {
+                    session.heatmap = session.heatmap.next();
+                    Some(Scene::GameOver(session))
+                } else if rl.is_key_pressed(KeyboardKey::KEY_J) && !session.console_open() {
+                    // Export the match for tools outside the game
+                    let text = match session.game.export_report(None) {
+                        Ok(path) => tr!("report-exported", path = path.display()),
+                        Err(e) => tr!("error-report", error = e),
+                    };
+                    session.game.toast(text);
+                    Some(Scene::GameOver(session))
+                } else if rl.is_key_pressed(KeyboardKey::KEY_V) && !session.console_open() && !session.game.history.is_empty() {
+                    // The replay opens on the loser, like the killcam
+                    let loser = session.game.winner().map_or(0, |winner| 1 - winner);
+                    session.free_camera = FreeCamera::following(loser);
+                    Some(Scene::Replay(session, 0.0))
+                } else {
+                    Some(Scene::GameOver(session))
+                }
+            }
+            Scene::Replay(mut session, time) => {
+                // Keep the match running underneath so rematch votes still go through
+                session.update(rl, real_dt);
+                let time = time + real_dt;
+                let length = session.game.history.len() as f32 * KILLCAM_SAMPLE;
+                if !session.console_open() {
+                    if rl.is_key_pressed(KeyboardKey::KEY_I) {
+                        session.show_pip = !session.show_pip;
+                    }
+                    let snapshot = session.game.history_at((time / length).min(1.0));
+                    session.free_camera.update(rl, &session.bindings, snapshot.as_ref(), real_dt);
+                }
+                if session.game.winner().is_none() {
+                    Some(Scene::Playing(session))
+                } else if !session.console_open() && (back || rl.is_key_pressed(KeyboardKey::KEY_V)) || time >= length {
+                    Some(Scene::GameOver(session))
+                } else {
+                    Some(Scene::Replay(session, time))
+                }
+            }
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, display: &Display, volume: &Volume, sprites: &Sprites) {
+        match self {
+            Scene::MainMenu(menu) => {
+                let time = d.get_time() as f32;
+                menu.draw(d, time, display, volume);
+            }
+            Scene::Connecting(session, status) => session.draw_connecting(d, status),
+            Scene::Lobby(session) => session.draw_lobby(d),
+            Scene::Playing(session) => session.draw(d, sprites),
+            Scene::GameOver(session) => {
+                session.draw(d, sprites);
+                session.draw_game_over(d);
+            }
+            Scene::Replay(session, time) => {
+                session.draw(d, sprites);
+                session.draw_replay(d, *time);
+            }
+        }
+        // The dev console drops down over whatever is being played
+        if let Scene::Playing(session) | Scene::GameOver(session) | Scene::Replay(session, _) = self
+            && let Some(console) = &session.console
+        {
+            console.draw(d);
+        }
+        if volume.muted {
+            draw_anchored(d, &tr!("muted-hint"), Anchor::BottomRight, Vector2::new(10.0, 6.0), 16, Color::GRAY);
+        }
+    }
+
+    /// Sound effects and announcer lines the match queued this frame.
+    fn take_sounds(&mut self) -> (Vec<(Sfx, Playback)>, Vec<Line>) {
+        match self {
+            Scene::MainMenu(_) => (Vec::new(), Vec::new()),
+            Scene::Connecting(session, _)
+            | Scene::Lobby(session)
+            | Scene::Playing(session)
+            | Scene::GameOver(session)
+            | Scene::Replay(session, _) => (std::mem::take(&mut session.game.sounds), std::mem::take(&mut session.game.lines)),
+        }
+    }
+
+    /// Applies a config file saved while the game runs: everything but the window size and port,
+    /// which only make sense at startup. Running matches keep their rules; the next one gets them.
+    fn reload_config(&mut self, config: &ConfigFile, rl: &mut RaylibHandle) {
+        let current = tunables();
+        if (config.tunables.window, config.tunables.port) != (current.window, current.port) {
+            eprintln!("Window size and port changes apply on the next start");
+        }
+        set_tunables(Tunables { window: current.window, port: current.port, ..config.tunables });
+        rl.set_target_fps(config.tunables.fps);
+        if config.theme != theme_name()
+            && let Err(e) = set_theme(&config.theme)
+        {
+            eprintln!("Could not load theme '{}': {}", config.theme, e);
+        }
+        if config.language != language()
+            && let Err(e) = set_language(&config.language)
+        {
+            eprintln!("Could not load language '{}': {}", config.language, e);
+        }
+        if let Scene::MainMenu(menu) = self {
+            menu.config.rules = config.tunables.rules;
+            menu.accessibility.hud_scale = config.hud_scale;
+        } else if let Scene::Connecting(session, _)
+        | Scene::Lobby(session)
+        | Scene::Playing(session)
+        | Scene::GameOver(session)
+        | Scene::Replay(session, _) = self
+        {
+            session.game.accessibility.hud_scale = config.hud_scale;
+        }
+    }
+
+    /// Puts edited level files into a running puzzle campaign.
+    fn reload_levels(&mut self) {
+        if let Scene::Playing(session) | Scene::GameOver(session) = self
+            && let Some(campaign) = session.puzzle.as_mut()
+        {
+            match PuzzleLevel::load_all(LEVELS_DIR) {
+                Ok(levels) => campaign.reload(levels, &mut session.game),
+                Err(e) => eprintln!("Keeping the old levels: {}", e),
+            }
+        }
+    }
+
+    /// Display settings of whatever is on screen: the menu's own, or the match's.
+    fn accessibility(&self) -> &Accessibility {
+        match self {
+            Scene::MainMenu(menu) => &menu.accessibility,
+            Scene::Connecting(session, _)
+            | Scene::Lobby(session)
+            | Scene::Playing(session)
+            | Scene::GameOver(session)
+            | Scene::Replay(session, _) => &session.game.accessibility,
+        }
+    }
+
+    /// A corner notification in a match, or a line at the bottom of the menu.
+    fn toast(&mut self, text: String) {
+        match self {
+            Scene::MainMenu(menu) => menu.notice = Some((text, Instant::now())),
+            Scene::Connecting(session, _)
+            | Scene::Lobby(session)
+            | Scene::Playing(session)
+            | Scene::GameOver(session)
+            | Scene::Replay(session, _) => session.game.toast(text),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Scene::MainMenu(_) => "main menu",
+            Scene::Connecting(..) => "connecting",
+            Scene::Lobby(_) => "lobby",
+            Scene::Playing(_) => "playing",
+            Scene::GameOver(_) => "game over",
+            Scene::Replay(..) => "replay",
+        }
+    }
+
+    /// The match being connected, set up or played, if any.
+    fn game(&self) -> Option<&GameState> {
+        match self {
+            Scene::MainMenu(_) => None,
+            Scene::Connecting(session, _)
+            | Scene::Lobby(session)
+            | Scene::Playing(session)
+            | Scene::GameOver(session)
+            | Scene::Replay(session, _) => Some(&session.game),
+        }
+    }
+
+    fn game_mut(&mut self) -> Option<&mut GameState> {
+        match self {
+            Scene::MainMenu(_) => None,
+            Scene::Connecting(session, _)
+            | Scene::Lobby(session)
+            | Scene::Playing(session)
+            | Scene::GameOver(session)
+            | Scene::Replay(session, _) => Some(&mut session.game),
+        }
+    }
+
+    /// Whether inverse mode is on in a match being played, for the post-process ripple.
+    fn inverted(&self) -> bool {
+        matches!(self, Scene::Playing(session) if session.game.inverse_active)
+    }
+
+    /// Whether the music should layer in its intense stem: inverse mode or match point.
+    fn intense(&self) -> bool {
+        matches!(self, Scene::Playing(session) if session.game.inverse_active || session.game.match_point())
+    }
+}
+
+/// --metrics: a long-running host's numbers, one CSV row every METRICS_INTERVAL seconds. The file
+/// rolls over to `<file>.1` past METRICS_MAX_BYTES, so it never grows without bound.
+struct Metrics {
+    path: PathBuf,
+    since: Instant, // Start of the interval the next row covers
+    ticks: u32, // Frames simulated this interval
+    tick_total: f32, // Seconds they took, for the average
+    tick_max: f32,
+    matches: u32, // Matches finished since the game started
+    match_over: bool, // The last frame was past a win, so it's been counted
+}
+
+impl Metrics {
+    fn new(path: PathBuf) -> Self {
+        Metrics { path, since: Instant::now(), ticks: 0, tick_total: 0.0, tick_max: 0.0, matches: 0, match_over: false }
+    }
+
+    /// Counts one frame's update, taking `tick` seconds, and writes a row once the interval is up.
+    fn record(&mut self, tick: f32, game: Option<&GameState>) {
+        self.ticks += 1;
+        self.tick_total += tick;
+        self.tick_max = self.tick_max.max(tick);
+        if let Some(game) = game {
+            let over = game.winner().is_some();
+            if over && !self.match_over {
+                self.matches += 1;
+            }
+            self.match_over = over;
+        }
+        if self.since.elapsed().as_secs_f32() < METRICS_INTERVAL {
+            return;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        if let Err(e) = self.append(&self.row(now, game)) {
+            eprintln!("Could not write metrics to {}: {}", self.path.display(), e);
+        }
+        (self.since, self.ticks, self.tick_total, self.tick_max) = (Instant::now(), 0, 0.0, 0.0);
+    }
+
+    /// One CSV line under METRICS_HEADER. Network columns are left empty outside a hosted match.
+    fn row(&self, now: u64, game: Option<&GameState>) -> String {
+        let average = if self.ticks > 0 { self.tick_total / self.ticks as f32 } else { 0.0 };
+        let network = match game.filter(|game| game.is_host && game.transport.is_some()) {
+            Some(game) => format!(
+                "{},{},{},{},{},{}",
+                game.packet_rates.0,
+                game.packet_rates.1,
+                game.packet_loss().map_or(String::new(), |loss| format!("{:.0}", loss * 100.0)),
+                game.ping_ms.map_or(String::new(), |ping| format!("{:.0}", ping)),
+                (game.peer_seen && !game.peer_lost) as u8,
+                game.desyncs,
+            ),
+            None => ",,,,0,".to_string(),
+        };
+        format!("{},{:.2},{:.2},{},{}", now, average * 1000.0, self.tick_max * 1000.0, network, self.matches)
+    }
+
+    fn append(&self, row: &str) -> std::io::Result<()> {
+        use std::io::Write;
+        let size = std::fs::metadata(&self.path).map_or(0, |meta| meta.len());
+        if size > METRICS_MAX_BYTES {
+            let mut rolled = self.path.clone().into_os_string();
+            rolled.push(".1");
+            std::fs::rename(&self.path, rolled)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        if size == 0 || size > METRICS_MAX_BYTES {
+            writeln!(file, "{}", METRICS_HEADER)?;
+        }
+        writeln!(file, "{}", row)
+    }
+}
+
+/// What Twitch chat can vote for.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+enum ChatEvent {
+    Hazard, // A gravity well somewhere in the arena for CHAT_EVENT_TIME
+    Shrink, // Both trap radii shrink to CHAT_SHRINK for CHAT_EVENT_TIME
+}
+
+impl ChatEvent {
+    const ALL: [ChatEvent; 2] = [ChatEvent::Hazard, ChatEvent::Shrink];
+
+    fn command(self) -> &'static str {
+        match self {
+            ChatEvent::Hazard => "!hazard",
+            ChatEvent::Shrink => "!shrink",
+        }
+    }
+
+    /// The vote in a chat message, if it's only a command.
+    fn parse(message: &str) -> Option<Self> {
+        ChatEvent::ALL.into_iter().find(|event| message.trim().eq_ignore_ascii_case(event.command()))
+    }
+}
+
+/// --twitch: reads a channel's chat anonymously on a background thread and runs votes on
+/// `ChatEvent`s. Each vote stays open for CHAT_VOTE_WINDOW seconds of play with each chatter's
+/// latest command counting once, and as it closes the most-voted event goes to the host's
+/// simulation, so chat can't fire more than one event per window.
+struct TwitchChat {
+    votes_in: std::sync::mpsc::Receiver<(String, ChatEvent)>, // Chatter and their command
+    votes: HashMap<String, ChatEvent>,
+    window: f32, // Seconds of play left in the open vote
+}
+
+impl TwitchChat {
+    fn join(channel: String) -> Self {
+        let (sender, votes_in) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            loop {
+                if let Err(e) = TwitchChat::read(&channel, &sender) {
+                    eprintln!("Twitch chat: {}, reconnecting in {} seconds", e, TWITCH_RETRY);
+                }
+                std::thread::sleep(std::time::Duration::from_secs(TWITCH_RETRY));
+            }
+        });
+        TwitchChat { votes_in, votes: HashMap::new(), window: CHAT_VOTE_WINDOW }
+    }
+
+    /// Passes on the channel's votes until the connection drops. Anonymous "justinfan" logins can
+    /// read any channel's chat without an account or token.
+    fn read(channel: &str, votes: &std::sync::mpsc::Sender<(String, ChatEvent)>) -> std::io::Result<()> {
+        use std::io::{BufRead, Write};
+        let stream = TcpStream::connect(TWITCH_IRC)?;
+        let mut writer = stream.try_clone()?;
+        write!(writer, "NICK justinfan{}\r\nJOIN #{}\r\n", rand::random_range(10_000..100_000), channel)?;
+        for line in std::io::BufReader::new(stream).lines() {
+            let line = line?;
+            if let Some(server) = line.strip_prefix("PING ") {
+                write!(writer, "PONG {}\r\n", server)?;
+            } else if let Some(vote) = chat_vote(&line) {
+                let _ = votes.send(vote);
+            }
+        }
+        Err(std::io::Error::other("disconnected"))
+    }
+
+    /// Collects votes, and on the host while a round is being played, counts down the open vote
+    /// and fires its winner.
+    fn update(&mut self, dt: f32, scene: &mut Scene) {
+        while let Ok((chatter, event)) = self.votes_in.try_recv() {
+            self.votes.insert(chatter, event);
+        }
+        let Some(game) = scene.game_mut().filter(|game| game.is_host && game.is_live() && game.winner().is_none()) else {
+            return;
+        };
+        self.window -= dt;
+        if self.window > 0.0 {
+            return;
+        }
+        self.window = CHAT_VOTE_WINDOW;
+        if let Some(event) = self.leader() {
+            game.chat_event(event);
+        }
+        self.votes.clear();
+    }
+
+    fn count(&self, event: ChatEvent) -> usize {
+        self.votes.values().filter(|&&vote| vote == event).count()
+    }
+
+    /// The most-voted event, ties going to the first in ChatEvent::ALL; None without votes.
+    fn leader(&self) -> Option<ChatEvent> {
+        ChatEvent::ALL.into_iter().rev().filter(|&event| self.count(event) > 0).max_by_key(|&event| self.count(event))
+    }
+
+    /// The open vote's tally along the bottom edge, while the host plays.
+    fn draw(&self, d: &mut RaylibDrawHandle, scene: &Scene) {
+        if !matches!(scene, Scene::Playing(session) if session.game.is_host) {
+            return;
+        }
+        let text = tr!(
+            "chat-vote",
+            hazard = self.count(ChatEvent::Hazard),
+            shrink = self.count(ChatEvent::Shrink),
+            seconds = format!("{:.0}", self.window.max(0.0).ceil())
+        );
+        draw_anchored(d, &text, Anchor::Bottom, Vector2::new(0.0, 6.0), 16, theme().hint);
+    }
+}
+
+/// The chatter and vote in an IRC line like `:name!name@name.tmi.twitch.tv PRIVMSG #channel :!shrink`.
+fn chat_vote(line: &str) -> Option<(String, ChatEvent)> {
+    let (chatter, rest) = line.strip_prefix(':')?.split_once('!')?;
+    let (_, message) = rest.split_once(" PRIVMSG ")?.1.split_once(" :")?;
+    Some((chatter.to_lowercase(), ChatEvent::parse(message)?))
+}
+
+/// --overlay: a WebSocket server on localhost streaming the match as JSON for stream overlays, such
+/// as an OBS browser source: a `state` message every OVERLAY_INTERVAL seconds, and an `event`
+/// message for each announcement as it happens. It only sends; whatever clients send is dropped.
+struct Overlay {
+    listener: TcpListener,
+    clients: Vec<OverlayClient>,
+    timer: f32,
+    events_sent: usize, // Entries of the match's timeline already sent
+    delayed: FeedDelay<String>, // Messages held back by --feed-delay
+}
+
+struct OverlayClient {
+    stream: TcpStream,
+    request: Vec<u8>, // The HTTP upgrade request, until it's answered
+    open: bool,
+}
+
+impl Overlay {
+    fn bind(port: u16, delay: f32) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Overlay { listener, clients: Vec::new(), timer: 0.0, events_sent: 0, delayed: FeedDelay::new(delay) })
+    }
+
+    /// Takes new connections, answers handshakes and sends whatever is due.
+    fn update(&mut self, dt: f32, scene: &Scene) {
+        use std::io::Write;
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(OverlayClient { stream, request: Vec::new(), open: false });
+            }
+        }
+        self.clients.retain_mut(OverlayClient::poll);
+        let mut messages = Vec::new();
+        let timeline = scene.game().map_or(&[][..], |game| &game.timeline[..]);
+        if timeline.len() < self.events_sent {
+            self.events_sent = 0; // A rematch or a new match
+        }
+        for (time, event) in &timeline[self.events_sent..] {
+            messages.push(format!("{{\"type\": \"event\", \"time\": {:.2}, \"event\": {{{}}}}}", time, event.json()));
+        }
+        self.events_sent = timeline.len();
+        self.timer += dt;
+        if self.timer >= OVERLAY_INTERVAL {
+            self.timer = 0.0;
+            messages.push(Overlay::state(scene.name(), scene.game()));
+        }
+        let messages = self.delayed.pass(dt, messages);
+        let frames: Vec<u8> = messages.iter().flat_map(|message| websocket_frame(message)).collect();
+        if !frames.is_empty() {
+            self.clients.retain_mut(|client| !client.open || client.stream.write_all(&frames).is_ok());
+        }
+    }
+
+    /// The `state` message: the scene, and outside the menu the match's scores, names, round and
+    /// inverse mode.
+    fn state(scene: &str, game: Option<&GameState>) -> String {
+        let Some(game) = game else {
+            return format!("{{\"type\": \"state\", \"scene\": \"{}\"}}", scene);
+        };
+        let players: Vec<String> = (0..2)
+            .map(|i| {
+                format!(
+                    "{{\"id\": {}, \"name\": {}, \"color\": {}, \"score\": {}, \"series_wins\": {}, \"inverse_meter\": {:.2}, \"frozen\": {}}}",
+                    i,
+                    json_string(&game.names[i]),
+                    json_string(color_key(game.colors[i] as usize)),
+                    game.players[i].score,
+                    game.series[i],
+                    game.players[i].inverse_charge,
+                    game.frozen[i] > 0.0,
+                )
+            })
+            .collect();
+        let scheduled = !game.inverse_active && (game.inverse_armed || game.config.inverse_schedule.clocked());
+        format!(
+            "{{\"type\": \"state\", \"scene\": \"{}\", \"mode\": \"{:?}\", \"time\": {:.2}, \"round\": {}, \"phase\": \"{:?}\", \"paused\": {}, \"players\": [{}], \"inverse\": {{\"active\": {}, \"by\": {}, \"time_left\": {}, \"next_in\": {}}}, \"winner\": {}}}",
+            scene,
+            game.config.mode,
+            game.play_time,
+            game.round,
+            game.round_phase,
+            game.paused,
+            players.join(", "),
+            game.inverse_active,
+            if game.inverse_active { game.inverse_by.to_string() } else { "null".to_string() },
+            if game.inverse_active { format!("{:.2}", game.inverse_timer.max(0.0)) } else { "null".to_string() },
+            if scheduled { format!("{:.2}", game.inverse_cooldown.max(0.0)) } else { "null".to_string() },
+            game.winner().map_or("null".to_string(), |winner| winner.to_string()),
+        )
+    }
+}
+
+/// --feed-delay: holds what spectators see back by a fixed number of seconds, so a player can't
+/// watch the stream to see where the other one is.
+struct FeedDelay<T> {
+    delay: f32,
+    clock: f32, // Seconds since the feed started
+    queue: VecDeque<(f32, T)>, // Items not yet due, with when they came in
+}
+
+impl<T> FeedDelay<T> {
+    fn new(delay: f32) -> Self {
+        FeedDelay { delay, clock: 0.0, queue: VecDeque::new() }
+    }
+
+    /// Takes this frame's `items` and returns the ones now `delay` seconds old, oldest first.
+    fn pass(&mut self, dt: f32, items: Vec<T>) -> Vec<T> {
+        self.clock += dt;
+        self.queue.extend(items.into_iter().map(|item| (self.clock, item)));
+        let due = self.queue.iter().take_while(|(time, _)| *time <= self.clock - self.delay).count();
+        self.queue.drain(..due).map(|(_, item)| item).collect()
+    }
+}
+
+impl OverlayClient {
+    /// Reads what the client sent, answering its handshake once the request is in. Returns whether
+    /// to keep the client.
+    fn poll(&mut self) -> bool {
+        use std::io::{Read, Write};
+        let mut buf = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return false,
+                Ok(size) if !self.open => self.request.extend(&buf[..size]),
+                Ok(_) => {} // Client frames, pings and close included; a close is followed by the end of the stream
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(_) => return false,
+            }
+        }
+        if self.open || !self.request.windows(4).any(|end| end == b"\r\n\r\n") {
+            return self.request.len() < 8192;
+        }
+        let Some(key) = websocket_key(&self.request) else {
+            let _ = self.stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+            return false;
+        };
+        let response = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            websocket_accept(&key)
+        );
+        self.open = self.stream.write_all(response.as_bytes()).is_ok();
+        self.request.clear();
+        self.open
+    }
+}
+
+/// The Sec-WebSocket-Key of a WebSocket upgrade request, if it is one.
+fn websocket_key(request: &[u8]) -> Option<String> {
+    let request = std::str::from_utf8(request).ok()?;
+    request.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("sec-websocket-key").then(|| value.trim().to_string())
+    })
+}
+
+/// The handshake's answer to `key`: base64 of the SHA-1 of it and WEBSOCKET_GUID.
+fn websocket_accept(key: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()).chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            encoded.push(if i <= chunk.len() { ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char } else { '=' });
+        }
+    }
+    encoded
+}
+
+/// A single unmasked text frame, as servers send them.
+fn websocket_frame(text: &str) -> Vec<u8> {
+    let mut frame = vec![0x81]; // Final fragment, text
+    match text.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+    frame.extend(text.as_bytes());
+    frame
+}
+
+/// SHA-1, for the WebSocket handshake and signing tournament reports.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for i in 0..80 {
+            w[i] = if i < 16 {
+                u32::from_be_bytes([block[i * 4], block[i * 4 + 1], block[i * 4 + 2], block[i * 4 + 3]])
+            } else {
+                (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1)
+            };
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// --status: a plain HTTP endpoint on the host, open to the local network, so organizers and scripts
+/// can check a match without joining it. `GET /status` answers with JSON; each connection gets one
+/// answer and is closed.
+struct StatusServer {
+    listener: TcpListener,
+    requests: Vec<(TcpStream, Vec<u8>)>, // Connections whose request isn't all in yet
+    started: Instant, // For the uptime
+    timer: f32, // Until the next snapshot of the status
+    delayed: FeedDelay<String>, // Snapshots held back by --feed-delay
+    current: Option<String>, // The snapshot being served, None until the first is due
+}
+
+impl StatusServer {
+    fn bind(port: u16, delay: f32) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(StatusServer {
+            listener,
+            requests: Vec::new(),
+            started: Instant::now(),
+            timer: OVERLAY_INTERVAL,
+            delayed: FeedDelay::new(delay),
+            current: None,
+        })
+    }
+
+    /// Takes a snapshot every OVERLAY_INTERVAL seconds, takes new connections and answers the
+    /// requests that have arrived.
+    fn update(&mut self, dt: f32, scene: &Scene) {
+        use std::io::{Read, Write};
+        let mut snapshots = Vec::new();
+        self.timer += dt;
+        if self.timer >= OVERLAY_INTERVAL {
+            self.timer = 0.0;
+            snapshots.push(StatusServer::status(scene.name(), scene.game(), self.started.elapsed().as_secs_f32()));
+        }
+        if let Some(latest) = self.delayed.pass(dt, snapshots).pop() {
+            self.current = Some(latest);
+        }
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.requests.push((stream, Vec::new()));
+            }
+        }
+        let current = &self.current;
+        self.requests.retain_mut(|(stream, request)| {
+            let mut buf = [0u8; 1024];
+            loop {
+                match stream.read(&mut buf) {
+                    Ok(0) => return false,
+                    Ok(size) => request.extend(&buf[..size]),
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(_) => return false,
+                }
+            }
+            if !request.windows(4).any(|end| end == b"\r\n\r\n") {
+                return request.len() < 8192;
+            }
+            let line = String::from_utf8_lossy(request).lines().next().unwrap_or_default().to_string();
+            let (status, body) = match line.split(' ').collect::<Vec<_>>()[..] {
+                ["GET", "/status", _] => match current {
+                    Some(status) => ("200 OK", status.clone()),
+                    None => ("503 Service Unavailable", "{\"error\": \"delayed\"}".to_string()),
+                },
+                ["GET", ..] => ("404 Not Found", "{\"error\": \"not found\"}".to_string()),
+                _ => ("405 Method Not Allowed", "{\"error\": \"method not allowed\"}".to_string()),
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes());
+            false
+        });
+    }
+
+    /// The `/status` body: the scene and uptime, and in a match its phase, scores and the other
+    /// player's connection.
+    fn status(scene: &str, game: Option<&GameState>, uptime: f32) -> String {
+        let Some(game) = game else {
+            return format!("{{\"scene\": \"{}\", \"uptime\": {:.0}}}", scene, uptime);
+        };
+        let players: Vec<String> = (0..2)
+            .map(|i| {
+                format!(
+                    "{{\"id\": {}, \"name\": {}, \"score\": {}, \"series_wins\": {}}}",
+                    i,
+                    json_string(&game.names[i]),
+                    game.players[i].score,
+                    game.series[i],
+                )
+            })
+            .collect();
+        format!(
+            "{{\"scene\": \"{}\", \"uptime\": {:.0}, \"mode\": \"{:?}\", \"phase\": \"{:?}\", \"round\": {}, \"time\": {:.2}, \"paused\": {}, \"players\": [{}], \"winner\": {}, \"peer\": {{\"connected\": {}, \"ping_ms\": {}, \"loss_pct\": {}}}}}",
+            scene,
+            uptime,
+            game.config.mode,
+            game.round_phase,
+            game.round,
+            game.play_time,
+            game.paused,
+            players.join(", "),
+            game.winner().map_or("null".to_string(), |winner| winner.to_string()),
+            game.peer_seen && !game.peer_lost,
+            game.ping_ms.map_or("null".to_string(), |ping| format!("{:.0}", ping)),
+            game.packet_loss().map_or("null".to_string(), |loss| format!("{:.0}", loss * 100.0)),
+        )
+    }
+}
+
+/// --admin: a line-based console for whoever runs a hosting machine, over TCP from anywhere on the
+/// network, e.g. with `nc`. A connection's first line must be `auth <key>`; after that each
+/// command is answered with a line starting `ok` or `error`, and more lines for `list`.
+struct AdminConsole {
+    listener: TcpListener,
+    key: Vec<u8>,
+    clients: Vec<AdminClient>,
+    shutdown: bool, // Quit once no round is being played
+}
+
+struct AdminClient {
+    stream: TcpStream,
+    input: Vec<u8>, // Received, up to the next newline
+    authed: bool,
+    connected: Instant, // Dropped ADMIN_AUTH_TIMEOUT after this if it hasn't sent the key
+}
+
+impl AdminConsole {
+    fn bind(port: u16, key: Vec<u8>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(AdminConsole { listener, key, clients: Vec::new(), shutdown: false })
+    }
+
+    /// Takes new connections and runs the commands that have arrived.
+    fn update(&mut self, scene: &mut Scene) {
+        use std::io::{Read, Write};
+        while let Ok((mut stream, _)) = self.listener.accept() {
+            if self.clients.iter().filter(|client| !client.authed).count() >= ADMIN_MAX_PENDING {
+                let _ = stream.write_all(b"error: too many connections waiting to authenticate\n");
+                continue;
+            }
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(AdminClient { stream, input: Vec::new(), authed: false, connected: Instant::now() });
+            }
+        }
+        let mut clients = std::mem::take(&mut self.clients);
+        clients.retain_mut(|client| {
+            let mut buf = [0u8; 1024];
+            let mut open = true;
+            loop {
+                match client.stream.read(&mut buf) {
+                    Ok(0) => {
+                        open = false;
+                        break;
+                    }
+                    Ok(size) => client.input.extend(&buf[..size]),
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(_) => return false,
+                }
+            }
+            while let Some(end) = client.input.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = client.input.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line).trim().to_string();
+                if !client.authed {
+                    // Anything but the key ends the connection
+                    client.authed = line.strip_prefix("auth ").is_some_and(|key| key.trim().as_bytes() == self.key);
+                    let reply: &[u8] = if client.authed { b"ok\n" } else { b"error: auth <key> first\n" };
+                    if client.stream.write_all(reply).is_err() || !client.authed {
+                        return false;
+                    }
+                    continue;
+                }
+                let reply = match self.run(&line, scene) {
+                    Ok(text) if text.is_empty() => "ok\n".to_string(),
+                    Ok(text) => format!("ok\n{}\n", text),
+                    Err(e) => format!("error: {}\n", e),
+                };
+                if client.stream.write_all(reply.as_bytes()).is_err() {
+                    return false;
+                }
+            }
+            if !client.authed && client.connected.elapsed().as_secs_f32() > ADMIN_AUTH_TIMEOUT {
+                let _ = client.stream.write_all(b"error: auth <key> first\n");
+                return false;
+            }
+            open && client.input.len() < 4096
+        });
+        self.clients = clients;
+    }
+
+    /// Runs one command from an authenticated connection, returning what to print back.
+    fn run(&mut self, line: &str, scene: &mut Scene) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, playing) = (scene.name(), matches!(scene, Scene::Playing(_)));
+        let game = scene.game_mut().filter(|game| game.is_host);
+        match (words.as_slice(), game) {
+            (["help"], _) => Ok(ADMIN_HELP.to_string()),
+            (["list"], None) => Ok(format!("scene: {}", name)),
+            (["list"], Some(game)) => {
+                let client = game.client_addr.map_or("none".to_string(), |addr| addr.to_string());
+                let ping = game.ping_ms.map_or("--".to_string(), |ping| format!("{:.0} ms", ping));
+                let rotation: Vec<String> = game.arena_rotation.iter().map(|arena| format!("{:?}", arena).to_lowercase()).collect();
+                Ok(format!(
+                    "scene: {}\nclient: {} ({}), ping {}\nscore: {} {} - {} {}, game {}, round {}{}\narena: {:?}, rotation: {}",
+                    name,
+                    client,
+                    game.names[1 - game.player_id as usize],
+                    ping,
+                    game.names[0],
+                    game.players[0].score,
+                    game.players[1].score,
+                    game.names[1],
+                    game.game_number + 1,
+                    game.round,
+                    if game.paused { ", paused" } else { "" },
+                    game.config.arena,
+                    if rotation.is_empty() { "off".to_string() } else { rotation.join(" ") },
+                ))
+            }
+            (["kick"], Some(game)) => {
+                let client = game.kick(playing).ok_or("no client is connected")?;
+                Ok(format!("kicked {}", client))
+            }
+            (["pause" | "resume"], Some(game)) if playing => {
+                let pause = words[0] == "pause";
+                if pause == game.paused && game.resume_timer <= 0.0 {
+                    return Err(format!("already {}", if pause { "paused" } else { "playing" }));
+                }
+                game.request_pause(game.player_id, pause);
+                Ok(String::new())
+            }
+            (["rotate", arenas @ ..], Some(game)) => {
+                let rotation = arenas.iter().map(|name| Arena::parse(name).ok_or_else(|| format!("unknown arena '{}'", name))).collect::<Result<Vec<_>, _>>()?;
+                game.arena_rotation = rotation;
+                Ok(if arenas.is_empty() { "rotation off".to_string() } else { "from the next game".to_string() })
+            }
+            (["shutdown"], _) => {
+                self.shutdown = true;
+                Ok("quitting once no round is being played".to_string())
+            }
+            ([], _) => Ok(String::new()),
+            (["kick" | "pause" | "resume" | "rotate", ..], _) => Err("no hosted match is being played".to_string()),
+            _ => Err(format!("unknown command '{}', try help", line.trim())),
+        }
+    }
+
+    /// After `shutdown`: whether to quit now, which waits for the round in play to end.
+    fn quit_now(&self, scene: &Scene) -> bool {
+        self.shutdown && !matches!(scene, Scene::Playing(session) if session.game.detect_winner().is_none() && session.game.round_phase == RoundPhase::Playing)
+    }
+}
+
+/// HMAC-SHA1 (RFC 2104) of `message` under `key`.
+fn hmac_sha1(key: &[u8], message: &[u8]) -> [u8; 20] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..20].copy_from_slice(&sha1(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+    let inner = sha1(&[pad(0x36), message.to_vec()].concat());
+    sha1(&[pad(0x5c), inner.to_vec()].concat())
+}
+
+/// A secret from a key file, without surrounding whitespace.
+fn read_key(path: &Path) -> Result<Vec<u8>, String> {
+    let key = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let key = key.trim_ascii().to_vec();
+    if key.is_empty() {
+        return Err(format!("{}: the key is empty", path.display()));
+    }
+    Ok(key)
+}
+
+/// --tournament: signs each finished match's report with the organizer's key, exporting it on its
+/// own when the match ends, so a bracket can be decided from the reports and an edited one caught.
+struct Tournament {
+    key: Vec<u8>,
+    match_over: bool, // The last frame was past a win, so it's been exported
+}
+
+impl Tournament {
+    /// The key is the file's contents, without surrounding whitespace.
+    fn load(path: &Path) -> Result<Self, String> {
+        Ok(Tournament { key: read_key(path)?, match_over: false })
+    }
+
+    /// The contents of a report's `.sig` file.
+    fn sign(&self, report: &[u8]) -> String {
+        let mac: String = hmac_sha1(&self.key, report).iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("hmac-sha1 {}\n", mac)
+    }
+
+    fn verify(&self, report: &[u8], signature: &str) -> bool {
+        signature.trim() == self.sign(report).trim()
+    }
+
+    fn signature_path(report: &Path) -> PathBuf {
+        let mut path = report.as_os_str().to_owned();
+        path.push(".sig");
+        PathBuf::from(path)
+    }
+
+    /// Exports the report once, as the match reaches the game over screen.
+    fn update(&mut self, scene: &mut Scene) {
+        let Scene::GameOver(session) = scene else {
+            self.match_over = false;
+            return;
+        };
+        let over = session.game.winner().is_some();
+        if over && !self.match_over {
+            let text = match session.game.export_report(Some(self)) {
+                Ok(path) => tr!("report-signed", path = path.display()),
+                Err(e) => tr!("error-report", error = e),
+            };
+            session.game.toast(text);
+        }
+        self.match_over = over;
+    }
+}
+
+/// What a crash report says besides the panic and backtrace, refreshed every frame so the panic hook
+/// can write it without touching the game's state mid-crash.
+#[derive(Clone, Debug, Default)]
+struct CrashContext {
+    config_path: PathBuf,
+    scene: &'static str,
+    config: Option<MatchConfig>, // The match's rules, outside the menu
+    events: Vec<(f32, GameEvent)>, // The newest CRASH_EVENTS of the match, seconds in
+    net: Option<String>, // The connection, as on the F3 overlay
+    // Copied from their globals, whose locks the panicking thread may hold
+    language: String,
+    theme: String,
+    tunables: Option<Tunables>,
+}
+
+static CRASH_CONTEXT: LazyLock<RwLock<CrashContext>> = LazyLock::new(|| RwLock::new(CrashContext::default()));
+
+impl CrashContext {
+    /// Notes the current scene and match for a crash report.
+    fn remember(scene: &Scene) {
+        let mut context = CRASH_CONTEXT.write().unwrap_or_else(|e| e.into_inner());
+        context.scene = scene.name();
+        context.language = language();
+        context.theme = theme_name();
+        context.tunables = Some(tunables());
+        let game = scene.game();
+        context.config = game.map(|game| game.config);
+        context.events = game.map_or(Vec::new(), |game| game.timeline[game.timeline.len().saturating_sub(CRASH_EVENTS)..].to_vec());
+        context.net = game.filter(|game| game.transport.is_some()).map(|game| {
+            format!(
+                "{}, tick {}, peer {}, {} in/s {} out/s, ping {}, loss {}, {} desyncs",
+                if game.is_host { "host" } else { "client" },
+                game.tick,
+                if game.peer_lost { "lost" } else if game.peer_seen { "connected" } else { "not seen yet" },
+                game.packet_rates.0,
+                game.packet_rates.1,
+                game.ping_ms.map_or("-".to_string(), |ping| format!("{:.0} ms", ping)),
+                game.packet_loss().map_or("-".to_string(), |loss| format!("{:.0}%", loss * 100.0)),
+                game.desyncs,
+            )
+        });
+    }
+
+    /// The text of a crash report for a panic with `message` at `location`.
+    fn report(&self, message: &str, location: &str, backtrace: &str, now: u64) -> String {
+        let mut lines = vec![
+            format!("Shadow Swap {} crash report", env!("CARGO_PKG_VERSION")),
+            format!("time: {}", now),
+            format!("os: {} {}", std::env::consts::OS, std::env::consts::ARCH),
+            format!("panic: {}", message),
+            format!("at: {}", location),
+            format!("scene: {}", self.scene),
+            String::new(),
+            format!("config file: {}", self.config_path.display()),
+            format!("language: {}, theme: {}", self.language, self.theme),
+            format!("tunables: {}", self.tunables.map_or("-".to_string(), |tunables| format!("{:?}", tunables))),
+            format!("match: {}", self.config.map_or("-".to_string(), |config| format!("{:?}", config))),
+            format!("network: {}", self.net.as_deref().unwrap_or("-")),
+            String::new(),
+            format!("last {} events:", self.events.len()),
+        ];
+        lines.extend(self.events.iter().map(|(time, event)| format!("  {:>8.2}s  {:?}", time, event)));
+        lines.push(String::new());
+        lines.push("backtrace:".to_string());
+        lines.push(backtrace.to_string());
+        lines.join("\n")
+    }
+
+    /// Chains a panic hook that saves a crash report to CRASHES_DIR and says where, in the terminal
+    /// and, through CRASH_NOTICE, on the menu the next time the game starts.
+    fn install(config_path: PathBuf) {
+        CRASH_CONTEXT.write().unwrap_or_else(|e| e.into_inner()).config_path = config_path;
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            default_hook(info);
+            let message = info.payload_as_str().unwrap_or("(no message)");
+            let location = info.location().map_or("unknown".to_string(), |at| at.to_string());
+            let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+            // try_read: the panic may have happened while this thread was updating the context
+            let context = CRASH_CONTEXT.try_read().map(|context| context.clone()).unwrap_or_default();
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            let path = paths::data(CRASHES_DIR).join(format!("{}.txt", now));
+            let written = std::fs::create_dir_all(paths::data(CRASHES_DIR))
+                .and_then(|_| std::fs::write(&path, context.report(message, &location, &backtrace, now)));
+            match written {
+                Ok(()) => {
+                    eprintln!("The game crashed. A crash report was saved to {}, please attach it to a bug report", path.display());
+                    let _ = std::fs::write(paths::data(CRASH_NOTICE), path.display().to_string());
+                }
+                Err(e) => eprintln!("The game crashed, and the crash report could not be saved: {}", e),
+            }
+        }));
+    }
+
+    /// The crash report left by the last run, once: the next start shows it on the menu.
+    fn take_notice() -> Option<String> {
+        let path = std::fs::read_to_string(paths::data(CRASH_NOTICE)).ok()?;
+        let _ = std::fs::remove_file(paths::data(CRASH_NOTICE));
+        Some(path)
+    }
+}
+
+/// Command line flags, see USAGE. They win over the config file.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Args {
+    config: Option<PathBuf>,
+    name: Option<String>,
+    profile: Option<String>,
+    lang: Option<String>,
+    theme: Option<String>,
+    port: Option<u16>,
+    start: Option<MenuChoice>, // --host, --join or --bot skip the menu
+    address: String, // --join's
+    fullscreen: bool,
+    dev: bool,
+    portable: bool,
+    help: bool,
+    inspect_replay: Option<PathBuf>,
+    record_inputs: Option<PathBuf>,
+    check_inputs: Option<PathBuf>,
+    metrics: Option<PathBuf>,
+    net_dump: Option<PathBuf>,
+    print_dump: Option<PathBuf>,
+    overlay: Option<u16>,
+    status: Option<u16>,
+    admin: Option<u16>,
+    admin_key: Option<PathBuf>,
+    feed_delay: f32, // Seconds --overlay and --status lag behind the game
+    tournament: Option<PathBuf>, // Key file
+    verify_report: Option<PathBuf>,
+    twitch: Option<String>, // Channel name, without the #
+}
+
+impl Args {
+    /// Parses the arguments after the program name.
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.iter().peekable();
+        let start = |parsed: &mut Args, choice: MenuChoice| match parsed.start.replace(choice) {
+            Some(_) => Err("pick only one of --host, --join and --bot".to_string()),
+            None => Ok(()),
+        };
+        while let Some(arg) = args.next() {
+            let value = |args: &mut std::iter::Peekable<std::slice::Iter<String>>| {
+                args.next().cloned().ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg.as_str() {
+                "--config" => parsed.config = Some(PathBuf::from(value(&mut args)?)),
+                "--name" => parsed.name = Some(value(&mut args)?),
+                "--profile" => {
+                    let name = value(&mut args)?;
+                    if name.trim().is_empty() {
+                        return Err("--profile needs a name".to_string());
+                    }
+                    parsed.profile = Some(name);
+                }
+                "--lang" => parsed.lang = Some(value(&mut args)?),
+                "--theme" => parsed.theme = Some(value(&mut args)?),
+                "--port" => {
+                    let port = value(&mut args)?;
+                    parsed.port = Some(port.parse().map_err(|_| format!("'{}' is not a port number", port))?);
+                }
+                "--host" => start(&mut parsed, MenuChoice::Host)?,
+                "--join" => {
+                    parsed.address = value(&mut args)?;
+                    start(&mut parsed, MenuChoice::Join)?;
+                }
+                "--bot" => {
+                    // The level can be left out
+                    let level = match args.next_if(|next| !next.starts_with("--")) {
+                        Some(name) => BotLevel::parse(name).ok_or_else(|| format!("unknown bot level '{}'", name))?,
+                        None => BotLevel::Normal,
+                    };
+                    start(&mut parsed, MenuChoice::Practice(level))?;
+                }
+                "--fullscreen" => parsed.fullscreen = true,
+                "--dev" => parsed.dev = true,
+                "--portable" => parsed.portable = true,
+                "--help" | "-h" => parsed.help = true,
+                "--inspect-replay" => parsed.inspect_replay = Some(PathBuf::from(value(&mut args)?)),
+                "--record-inputs" => parsed.record_inputs = Some(PathBuf::from(value(&mut args)?)),
+                "--check-inputs" => parsed.check_inputs = Some(PathBuf::from(value(&mut args)?)),
+                "--metrics" => parsed.metrics = Some(PathBuf::from(value(&mut args)?)),
+                "--net-dump" => parsed.net_dump = Some(PathBuf::from(value(&mut args)?)),
+                "--print-dump" => parsed.print_dump = Some(PathBuf::from(value(&mut args)?)),
+                "--twitch" => {
+                    let channel = value(&mut args)?.trim_start_matches('#').to_lowercase();
+                    if channel.is_empty() || !channel.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                        return Err(format!("'{}' is not a Twitch channel name", channel));
+                    }
+                    parsed.twitch = Some(channel);
+                }
+                "--overlay" => {
+                    let port = value(&mut args)?;
+                    parsed.overlay = Some(port.parse().map_err(|_| format!("'{}' is not a port number", port))?);
+                }
+                "--admin" => {
+                    let port = value(&mut args)?;
+                    parsed.admin = Some(port.parse().map_err(|_| format!("'{}' is not a port number", port))?);
+                }
+                "--admin-key" => parsed.admin_key = Some(PathBuf::from(value(&mut args)?)),
+                "--feed-delay" => {
+                    let seconds = value(&mut args)?;
+                    parsed.feed_delay = seconds.parse().ok().filter(|s: &f32| s.is_finite() && *s >= 0.0).ok_or_else(|| format!("'{}' is not a number of seconds", seconds))?;
+                }
+                "--tournament" => parsed.tournament = Some(PathBuf::from(value(&mut args)?)),
+                "--verify-report" => parsed.verify_report = Some(PathBuf::from(value(&mut args)?)),
+                "--status" => {
+                    let port = value(&mut args)?;
+                    parsed.status = Some(port.parse().map_err(|_| format!("'{}' is not a port number", port))?);
+                }
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+        if parsed.name.is_some() && parsed.profile.is_some() {
+            return Err("pick only one of --name and --profile".to_string());
+        }
+        // Only bot practice is all inputs: network play and the scripted modes move pieces directly
+        if parsed.record_inputs.is_some() && !matches!(parsed.start, Some(MenuChoice::Practice(_))) {
+            return Err("--record-inputs needs --bot".to_string());
+        }
+        if parsed.metrics.is_some() && parsed.start != Some(MenuChoice::Host) {
+            return Err("--metrics needs --host".to_string());
+        }
+        if parsed.status.is_some() && parsed.start != Some(MenuChoice::Host) {
+            return Err("--status needs --host".to_string());
+        }
+        if parsed.admin.is_some() && parsed.start != Some(MenuChoice::Host) {
+            return Err("--admin needs --host".to_string());
+        }
+        if parsed.admin.is_some() != parsed.admin_key.is_some() {
+            return Err("--admin and --admin-key go together".to_string());
+        }
+        if parsed.feed_delay > 0.0 && parsed.overlay.is_none() && parsed.status.is_none() {
+            return Err("--feed-delay needs --overlay or --status".to_string());
+        }
+        if parsed.verify_report.is_some() && parsed.tournament.is_none() {
+            return Err("--verify-report needs --tournament".to_string());
+        }
+        if parsed.net_dump.is_some() && !matches!(parsed.start, Some(MenuChoice::Host | MenuChoice::Join)) {
+            return Err("--net-dump needs --host or --join".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Puts the flags that win over the config file into it.
+    fn override_config(&self, config: &mut ConfigFile) {
+        if let Some(lang) = &self.lang {
+            config.language = lang.clone();
+        }
+        if let Some(theme) = &self.theme {
+            config.theme = theme.clone();
+        }
+        config.tunables.port = self.port.unwrap_or(config.tunables.port);
+    }
+}
+
+fn main() {
+    let args = Args::parse(&std::env::args().skip(1).collect::<Vec<_>>()).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Some(path) = &args.inspect_replay {
+        match Replay::load(path) {
+            Ok(replay) => println!("{}", replay.summary()),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &args.print_dump {
+        match std::fs::read(path).map_err(|e| e.to_string()).and_then(|bytes| NetDump::print(&bytes)) {
+            Ok(text) => println!("{}", text),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let mut tournament = args.tournament.as_deref().map(Tournament::load).transpose().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    if let (Some(path), Some(tournament)) = (&args.verify_report, &tournament) {
+        let report = std::fs::read(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1);
+        });
+        let signature = std::fs::read_to_string(Tournament::signature_path(path)).unwrap_or_default();
+        if !tournament.verify(&report, &signature) {
+            eprintln!("{}: the signature doesn't match, or is missing", path.display());
+            std::process::exit(1);
+        }
+        println!("{}: the signature matches", path.display());
+        return;
+    }
+    if let Some(path) = &args.check_inputs {
+        let log = InputLog::load(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1);
+        });
+        let checksum = log.play().checksum();
+        if checksum != log.checksum {
+            eprintln!("{}: ended on checksum {:016x}, recorded {:016x}", path.display(), checksum, log.checksum);
+            std::process::exit(1);
+        }
+        println!("{}: {} ticks, checksum {:016x} matches", path.display(), log.ticks.len(), checksum);
+        return;
+    }
+    paths::set_portable(args.portable);
+    let path = args.config.clone().unwrap_or_else(|| paths::config(CONFIG_FILE));
+    let mut config = ConfigFile::load(&path).unwrap_or_else(|e| {
+        eprintln!("{}, using the defaults", e);
+        ConfigFile::default()
+    });
+    args.override_config(&mut config);
+    set_tunables(config.tunables);
+    CrashContext::install(path.clone());
+    if let Err(e) = set_language(&config.language) {
+        eprintln!("Could not load language '{}': {}", config.language, e);
+    }
+    if let Err(e) = set_theme(&config.theme) {
+        eprintln!("Could not load theme '{}': {}", config.theme, e);
+    }
+
+    let (width, height) = config.tunables.window;
+    let (mut rl, thread) = raylib::init()
+        .size(width, height)
+        .title("Shadow Swap - Multiplayer Duel")
+        .resizable()
+        .build();
+
+    rl.set_target_fps(tunables().fps);
+    rl.set_exit_key(None); // Each scene decides what Escape does
+    rl.set_window_min_size(SCREEN_WIDTH / 4, SCREEN_HEIGHT / 4);
+    // Everything is drawn at SCREEN_WIDTH x SCREEN_HEIGHT, then scaled into the window
+    // Nothing can be drawn without it, so this one ends the game, with the reason
+    let mut canvas = rl
+        .load_render_texture(&thread, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
+        .map_err(|e| GameError::Graphics(e.to_string()))
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+    canvas.set_texture_filter(&thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+    let sprites = Sprites::load(&mut rl, &thread);
+    let mut post_fx = PostFx::load(&mut rl, &thread);
+    // Without a sound device the game runs silent
+    let audio = RaylibAudio::init_audio_device().ok().filter(|audio| audio.is_audio_device_ready());
+    let mut mixer = audio.as_ref().map(Mixer::load);
+    let mut menu = MainMenu::new(args.name.clone().unwrap_or_default());
+    // Who's playing: the --profile one, else ask when there are profiles to pick from
+    match &args.profile {
+        Some(name) => {
+            let name: String = name.trim().chars().filter(|c| !c.is_control()).take(MAX_NAME_LEN).collect();
+            // A profile that exists but doesn't load is left alone, rather than replaced by a blank one
+            match Profile::load(&name) {
+                Ok(Some(profile)) => menu.use_profile(profile),
+                Ok(None) => {
+                    let profile = Profile::new(&name);
+                    profile.save();
+                    menu.use_profile(profile);
+                }
+                Err(e) => menu.fail(MenuChoice::Profile, GameError::ProfileLoad(name, e)),
+            }
+        }
+        None if args.name.is_none() && args.start.is_none() => {
+            menu.open(MenuScreen::Profiles);
+            if menu.profiles.is_empty() {
+                menu.open(MenuScreen::Main);
+            }
+        }
+        None => {}
+    }
+    menu.dev = args.dev;
+    menu.address = args.address.clone();
+    if menu.error.is_none() && let Some(report) = CrashContext::take_notice() {
+        menu.error = Some(tr!("crash-reported", path = report));
+    }
+    menu.accessibility.hud_scale = config.hud_scale;
+    if post_fx.is_none() {
+        eprintln!("Post-processing shader unavailable, falling back to low graphics");
+        menu.accessibility.graphics = Graphics::Low;
+    }
+    let mut scene = match args.start {
+        // Not as a guest when the --profile asked for didn't load
+        Some(choice) if !matches!(menu.screen, MenuScreen::Failed(_)) => Scene::start(menu, choice),
+        Some(_) => Scene::MainMenu(menu),
+        None => Scene::MainMenu(menu),
+    };
+    if let (Some(path), Scene::Playing(session)) = (&args.record_inputs, &mut scene) {
+        session.game.record_inputs(path.clone());
+    }
+    if let (Some(path), Scene::Connecting(session, _)) = (&args.net_dump, &mut scene) {
+        match NetDump::create(path, session.game.is_host) {
+            Ok(dump) => session.game.net_dump = Some(dump),
+            Err(e) => eprintln!("Could not start the network dump at {}: {}", path.display(), e),
+        }
+    }
+    let mut display = Display::default();
+    if args.fullscreen {
+        display.toggle_fullscreen(&mut rl);
+    }
+    let mut volume = config.volume;
+    let mut watcher = Watcher::new(path.clone());
+    let mut last_frame = Instant::now();
+    let mut metrics = args.metrics.clone().map(Metrics::new);
+    let mut chat = args.twitch.clone().map(TwitchChat::join);
+    let mut overlay = args.overlay.and_then(|port| match Overlay::bind(port, args.feed_delay) {
+        Ok(overlay) => Some(overlay),
+        Err(e) => {
+            eprintln!("Could not start the overlay server on port {}: {}", port, e);
+            None
+        }
+    });
+    let admin_key = args.admin_key.as_deref().map(read_key).transpose().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let mut admin = args.admin.zip(admin_key).and_then(|(port, key)| match AdminConsole::bind(port, key) {
+        Ok(admin) => Some(admin),
+        Err(e) => {
+            eprintln!("Could not start the admin console on port {}: {}", port, e);
+            None
+        }
+    });
+    let mut status = args.status.and_then(|port| match StatusServer::bind(port, args.feed_delay) {
+        Ok(status) => Some(status),
+        Err(e) => {
+            eprintln!("Could not start the status endpoint on port {}: {}", port, e);
+            None
+        }
+    });
+
+    loop {
+        if rl.window_should_close() || admin.as_ref().is_some_and(|admin| admin.quit_now(&scene)) {
+            // Closing the window mid-match still keeps its replay and input recording
+            if let Scene::Playing(session) = &mut scene {
+                session.game.save_replay(ReplayEnd::Abandoned);
+                session.game.save_input_log();
+            }
+            break;
+        }
+        let real_dt = last_frame.elapsed().as_secs_f32();
+        last_frame = Instant::now();
+
+        // Mouse positions come back in canvas coordinates, like everything else
+        let screen = letterbox(&rl);
+        rl.set_mouse_offset(Vector2::new(-screen.x, -screen.y));
+        rl.set_mouse_scale(SCREEN_WIDTH as f32 / screen.width, SCREEN_HEIGHT as f32 / screen.height);
+
+        let update_start = Instant::now();
+        let previous_volume = volume;
+        let Some(next) = scene.update(&mut rl, real_dt, &mut display, &mut volume) else {
+            break;
+        };
+        if volume != previous_volume {
+            volume.save(&path);
+        }
+        scene = next;
+        CrashContext::remember(&scene);
+        if let Some(overlay) = overlay.as_mut() {
+            overlay.update(real_dt, &scene);
+        }
+        if let Some(status) = status.as_mut() {
+            status.update(real_dt, &scene);
+        }
+        if let Some(admin) = admin.as_mut() {
+            admin.update(&mut scene);
+        }
+        if let Some(tournament) = tournament.as_mut() {
+            tournament.update(&mut scene);
+        }
+        if let Some(chat) = chat.as_mut() {
+            chat.update(real_dt, &mut scene);
+        }
+        if let Some(metrics) = metrics.as_mut() {
+            metrics.record(update_start.elapsed().as_secs_f32(), scene.game());
+        }
+        for file in watcher.poll(real_dt) {
+            match file {
+                Watched::Config => match ConfigFile::load(&path) {
+                    Ok(mut config) => {
+                        args.override_config(&mut config);
+                        scene.reload_config(&config, &mut rl);
+                        volume = config.volume;
+                    }
+                    Err(e) => eprintln!("Keeping the old settings: {}", e),
+                },
+                Watched::Theme => {
+                    if let Err(e) = set_theme(&theme_name()) {
+                        eprintln!("Keeping the old colors: {}", e);
                     }
                 }
+                Watched::Levels => scene.reload_levels(),
             }
         }
-        if should_reset {
-            self.reset_game();
-        }
-    }
-
-    fn update_inverse_timer(&mut self, dt: f32) {
-        if !self.is_host {
-            return;
+        let (sounds, lines) = scene.take_sounds();
+        if let Some(mixer) = &mut mixer {
+            for line in lines {
+                mixer.announce(line, &volume);
+            }
+            mixer.update(&volume, scene.intense(), real_dt);
+            for (sfx, playback) in sounds {
+                mixer.play(sfx, playback, &volume);
+            }
         }
 
-        if self.inverse_active {
-            self.inverse_timer -= dt;
-            if self.inverse_timer <= 0.0 {
-                self.inverse_active = false;
-                self.inverse_cooldown = INVERSE_COOLDOWN;
-                self.send_message(Message::InverseControl { active: false, time_left: 0.0 });
+        let screen = letterbox(&rl);
+        let screenshot = rl.is_key_pressed(KeyboardKey::KEY_F12);
+        let fx = *scene.accessibility();
+        let time = rl.get_time() as f32;
+        let post = post_fx.as_mut().filter(|_| fx.bloom());
+        let mut d = rl.begin_drawing(&thread);
+        {
+            let mut d = d.begin_texture_mode(&thread, &mut canvas);
+            scene.draw(&mut d, &display, &volume, &sprites);
+            if let Some(chat) = &chat {
+                chat.draw(&mut d, &scene);
             }
+        }
+        d.clear_background(Color::BLACK);
+        // Render textures are stored upside down, hence the negative source height
+        let source = Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, -(SCREEN_HEIGHT as f32));
+        if let Some(post) = post {
+            post.update(scene.inverted() && fx.motion(), time, real_dt);
+            let mut d = d.begin_shader_mode(&mut post.shader);
+            d.draw_texture_pro(&canvas, source, screen, Vector2::zero(), 0.0, Color::WHITE);
         } else {
-            self.inverse_cooldown -= dt;
-            if self.inverse_cooldown <= 0.0 {
-                self.inverse_active = true;
-                self.inverse_timer = INVERSE_DURATION;
-                self.inverse_cooldown = INVERSE_COOLDOWN;
-                self.send_message(Message::InverseControl { active: true, time_left: INVERSE_DURATION });
+            d.draw_texture_pro(&canvas, source, screen, Vector2::zero(), 0.0, Color::WHITE);
+        }
+        drop(d);
+        if screenshot {
+            let post = post_fx.as_mut().filter(|_| fx.bloom());
+            match save_screenshot(&mut rl, &thread, &canvas, post) {
+                Ok(path) => scene.toast(tr!("screenshot-saved", path = path.display().to_string())),
+                Err(e) => scene.toast(tr!("error-screenshot", error = e)),
             }
         }
     }
+}
 
-    fn update_player(&mut self, input: Vector2, dt: f32) {
-        let other_id = (1 - self.player_id as usize) as usize;
-        
-        // Determine what we're controlling
-        let controlling_shadow = !self.inverse_active;
-        
-        if controlling_shadow {
-            // Control other player's shadow
-            let target = &mut self.players[other_id].shadow_pos;
-            target.x += input.x * PLAYER_SPEED * dt;
-            target.y += input.y * PLAYER_SPEED * dt;
-            
-            // Keep shadow in bounds
-            target.x = target.x.max(PLAYER_SIZE).min(SCREEN_WIDTH as f32 - PLAYER_SIZE);
-            target.y = target.y.max(PLAYER_SIZE).min(SCREEN_HEIGHT as f32 - PLAYER_SIZE);
-        } else {
-            // Control other player's actual character (INVERSE MODE!)
-            let target = &mut self.players[other_id].pos;
-            target.x += input.x * PLAYER_SPEED * dt;
-            target.y += input.y * PLAYER_SPEED * dt;
-            
-            // Keep in bounds
-            target.x = target.x.max(PLAYER_SIZE).min(SCREEN_WIDTH as f32 - PLAYER_SIZE);
-            target.y = target.y.max(PLAYER_SIZE).min(SCREEN_HEIGHT as f32 - PLAYER_SIZE);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_trap_scores_that_player() {
+        assert_eq!(resolve_traps([None, None]), TrapOutcome::Clear);
+        assert_eq!(resolve_traps([Some(0.5), None]), TrapOutcome::Trapped(0));
+        assert_eq!(resolve_traps([None, Some(0.9)]), TrapOutcome::Trapped(1));
     }
 
-    fn swap_with_shadow(&mut self) {
-        let player = &mut self.players[self.player_id as usize];
-        std::mem::swap(&mut player.pos, &mut player.shadow_pos);
+    #[test]
+    fn simultaneous_traps_score_the_deeper_player() {
+        assert_eq!(resolve_traps([Some(0.2), Some(0.8)]), TrapOutcome::Trapped(0));
+        assert_eq!(resolve_traps([Some(0.9), Some(0.1)]), TrapOutcome::Trapped(1));
     }
 
-    fn reset_game(&mut self) {
-        // Reset player positions
-        self.players[0].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 };
-        self.players[0].shadow_pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.3, y: SCREEN_HEIGHT as f32 / 2.0 + 100.0 };
-        self.players[0].score = 0;
-        self.players[0].is_trapped = false;
-        
-        self.players[1].pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 };
-        self.players[1].shadow_pos = Vec2 { x: SCREEN_WIDTH as f32 * 0.7, y: SCREEN_HEIGHT as f32 / 2.0 - 100.0 };
-        self.players[1].score = 0;
-        self.players[1].is_trapped = false;
-        
-        // Reset timers
-        self.inverse_active = false;
-        self.inverse_timer = 0.0;
-        self.inverse_cooldown = 0.0;
-        self.trap_flash_timer = [0.0, 0.0];
-        // Note: game_time is not reset to keep visual effects smooth
+    #[test]
+    fn equal_depths_clash() {
+        assert_eq!(resolve_traps([Some(0.5), Some(0.5)]), TrapOutcome::Clash);
+        assert_eq!(resolve_traps([Some(0.5), Some(0.5 + CLASH_TOLERANCE / 2.0)]), TrapOutcome::Clash);
     }
 
-    fn check_traps(&mut self, dt: f32) {
-        if !self.is_host {
-            return;
+    /// Puts each character at `offset` from the opponent's shadow, with play live.
+    fn host_with_offsets(offsets: [f32; 2]) -> GameState {
+        let mut game = GameState::new(true, MatchConfig::default());
+        game.spawn_protection = [0.0, 0.0];
+        for (i, offset) in offsets.into_iter().enumerate() {
+            let shadow = Vec2 { x: 300.0 + i as f32 * 500.0, y: 400.0 };
+            game.players[1 - i].shadow_pos = shadow;
+            game.players[i].pos = Vec2 { x: shadow.x + offset, y: shadow.y };
         }
+        game
+    }
 
-        // Update flash timers
-        for i in 0..2 {
-            if self.trap_flash_timer[i] > 0.0 {
-                self.trap_flash_timer[i] -= dt;
-            }
+    /// A 60-second match Sam played against `opponent`.
+    fn record(mode: GameMode, won: bool, opponent: &str, dealt: u32, received: u32) -> MatchRecord {
+        MatchRecord {
+            ended: 0,
+            mode,
+            won,
+            traps_dealt: dealt,
+            traps_received: received,
+            duration: 60.0,
+            player: "Sam".to_string(),
+            opponent: opponent.to_string(),
         }
+    }
 
-        for i in 0..2 {
-            let other_id = 1 - i;
-            let player_pos = self.players[i].pos;
-            let other_shadow_pos = self.players[other_id].shadow_pos;
-            
-            // Calculate distance once
-            let dx = player_pos.x - other_shadow_pos.x;
-            let dy = player_pos.y - other_shadow_pos.y;
-            let dist = (dx * dx + dy * dy).sqrt();
-            
-            // Check if player is near other player's shadow (trapped!)
-            if dist < TRAP_RADIUS && !self.players[i].is_trapped {
-                self.players[i].is_trapped = true;
-                self.players[i].score += 1; // Positive score = times trapped (bad!)
-                self.trap_flash_timer[i] = 1.0; // Flash for 1 second
-                self.send_message(Message::TrapEvent { player_id: i as u8 });
-            }
-            
-            // Reset trap after a moment
-            if self.players[i].is_trapped && dist > TRAP_RADIUS * 2.0 {
-                self.players[i].is_trapped = false;
-            }
-        }
+    /// Gives `game` a loopback socket to receive on, returning its address.
+    fn listen(game: &mut GameState) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+        game.transport = Some(Box::new(socket));
+        addr
     }
-}
 
-fn get_input(rl: &RaylibHandle) -> Vector2 {
-    let mut input = Vector2::zero();
-    
-    if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        input.x += 1.0;
+    /// Sends `messages` from `peer` to `to`, then lets `game` take them in.
+    fn deliver(peer: &UdpSocket, game: &mut GameState, to: SocketAddr, messages: &[Message]) {
+        for message in messages {
+            peer.send_to(&bincode::serialize(message).unwrap(), to).unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        game.receive_messages();
     }
-    if rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        input.x -= 1.0;
+
+    #[test]
+    fn tethers_reach_across_wrapped_edges() {
+        let width = SCREEN_WIDTH as f32;
+        let mut game = GameState::new(true, MatchConfig { wrap: true, tether_range: 100.0, ..MatchConfig::default() });
+        game.players[0].pos = Vec2 { x: 10.0, y: 300.0 };
+        game.players[0].shadow_pos = Vec2 { x: width - 20.0, y: 300.0 };
+        game.enforce_tethers();
+        assert_eq!(game.players[0].shadow_pos.x, width - 20.0, "30px away across the edge");
+        assert!(!game.is_tether_taut(0));
+        game.players[0].shadow_pos = Vec2 { x: width - 150.0, y: 300.0 };
+        game.enforce_tethers();
+        assert!((game.players[0].shadow_pos.x - (width - 90.0)).abs() < 0.01, "pulled back over the edge: {:?}", game.players[0].shadow_pos);
+        assert!(game.is_tether_taut(0));
+        game.config.wrap = false;
+        game.enforce_tethers();
+        assert!((game.players[0].shadow_pos.x - 110.0).abs() < 0.01, "without wrap it's across the arena");
     }
-    if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
-        input.y -= 1.0;
+
+    #[test]
+    fn both_inside_only_deeper_player_scores() {
+        let mut game = host_with_offsets([10.0, 40.0]);
+        game.check_traps(0.016);
+        assert_eq!([game.players[0].score, game.players[1].score], [1, 0]);
+        assert_eq!(game.round_phase, RoundPhase::Intermission);
+        assert_eq!(game.streaks, [0, 1]);
     }
-    if rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN) {
-        input.y += 1.0;
+
+    #[test]
+    fn clash_scores_nobody_and_restarts_round() {
+        let mut game = host_with_offsets([25.0, 25.0]);
+        game.check_traps(0.016);
+        assert_eq!([game.players[0].score, game.players[1].score], [0, 0]);
+        assert_eq!(game.round, 2);
+        assert_eq!(game.round_phase, RoundPhase::Countdown);
     }
-    
-    if input.length_sqr() > 0.0 {
-        let len = input.length();
-        Vector2::new(input.x / len, input.y / len)
-    } else {
-        input
+
+    #[test]
+    fn no_double_win_from_one_tick() {
+        let mut game = host_with_offsets([10.0, 20.0]);
+        game.players[0].score = WIN_SCORE - 1;
+        game.players[1].score = WIN_SCORE - 1;
+        game.check_traps(0.016);
+        game.check_game_over();
+        assert_eq!(game.winner(), Some(1));
+        assert_eq!(game.players[1].score, WIN_SCORE - 1);
     }
-}
 
-fn main() {
-    println!("=== SHADOW SWAP ===");
-    println!("1. Host (wait for connection)");
-    println!("2. Join (connect to host)");
-    print!("Choose (1/2): ");
-    
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    let is_host = input.trim() == "1";
-
-    let mut game = GameState::new(is_host);
-
-    if is_host {
-        println!("\nWaiting for connection on port {}...", PORT);
-        game.connect("").unwrap();
-        println!("Server started! Waiting for player to connect...");
-        println!("(Share your IP address with the other player)");
-        std::thread::sleep(Duration::from_secs(1));
-    } else {
-        println!("\nEnter host IP address:");
-        println!("  - For local network: Enter the host's local IP (e.g., 192.168.1.31)");
-        println!("  - For same computer: Enter 127.0.0.1");
-        print!("\nHost IP: ");
-        let mut addr = String::new();
-        std::io::stdin().read_line(&mut addr).unwrap();
-        let addr = format!("{}:{}", addr.trim(), PORT);
-        println!("\nConnecting to {}...", addr);
-        game.connect(&addr).unwrap();
-        println!("Connected! Starting game...");
+    #[test]
+    fn traps_are_announced_through_the_event_log() {
+        let mut game = host_with_offsets([10.0, 200.0]);
+        game.accessibility.announce = Announce::Log;
+        game.check_traps(0.016);
+        game.update_events(0.016);
+        assert!(game.events.is_empty());
+        assert_eq!(game.event_log.back().map(String::as_str), Some("Player 2 trapped you, 1 of 3"));
     }
 
-    let (mut rl, thread) = raylib::init()
-        .size(SCREEN_WIDTH, SCREEN_HEIGHT)
-        .title("Shadow Swap - Multiplayer Duel")
-        .build();
+    #[test]
+    fn swaps_burst_into_particles_without_an_announcement() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        game.accessibility.announce = Announce::Log;
+        game.swap_with_shadow(0);
+        game.update_events(0.016);
+        assert!(game.event_log.is_empty());
+        assert!(game.particles.pool.iter().any(|particle| particle.life > 0.0));
+    }
 
-    rl.set_target_fps(60);
-    let mut last_frame = Instant::now();
+    #[test]
+    fn poses_follow_the_simulation() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        assert_eq!(game.pose(0), Pose::Idle);
+        game.update_trails(TRAIL_SAMPLE);
+        game.players[0].pos.x += 5.0;
+        game.update_trails(TRAIL_SAMPLE);
+        assert!(matches!(game.pose(0), Pose::Moving { angle, .. } if angle.abs() < 0.001));
+        game.swap_with_shadow(0);
+        game.update_events(0.016);
+        assert_eq!(game.pose(0), Pose::Swapping(1.0));
+        game.players[0].is_trapped = true;
+        assert_eq!(game.pose(0), Pose::Trapped);
+    }
 
-    while !rl.window_should_close() {
-        let dt = last_frame.elapsed().as_secs_f32();
-        last_frame = Instant::now();
+    #[test]
+    fn shadows_solidify_near_their_victim_and_thin_out_far_from_home() {
+        let far = host_with_offsets([300.0, 300.0]);
+        let mut close = host_with_offsets([60.0, 300.0]);
+        let (calm, closing) = (far.shadow_look(1), close.shadow_look(1));
+        assert!(closing.alpha > calm.alpha && closing.size > calm.size);
+        close.players[1].pos = close.players[1].shadow_pos;
+        let home = close.shadow_look(1);
+        assert_eq!((closing.stretch, home.stretch), (1.0, 0.0));
+        assert!(home.alpha > closing.alpha && home.size > closing.size);
+    }
 
-        // Network receive
-        game.receive_messages();
+    #[test]
+    fn sounds_pan_and_fade_with_distance() {
+        let mut game = host_with_offsets([0.0, 0.0]);
+        let me = game.players[0].pos;
+        assert_eq!(game.place_sound(me), Playback::CENTER);
+        let right = game.place_sound(Vec2 { x: me.x + 600.0, y: me.y });
+        assert!(right.pan < 0.5 && right.gain < 1.0);
+        let left = game.place_sound(Vec2 { x: me.x - 600.0, y: me.y });
+        assert!((left.pan - (1.0 - right.pan)).abs() < 1e-5 && left.gain == right.gain);
+        // Across the edge of a wrap-around arena, the same spot is close by on the left
+        game.config.wrap = true;
+        let wrapped = game.place_sound(Vec2 { x: me.x + SCREEN_WIDTH as f32 - 100.0, y: me.y });
+        assert!(wrapped.pan > 0.5 && wrapped.gain > right.gain);
+    }
 
-        // Update game time for visual effects
-        game.game_time += dt;
+    #[test]
+    fn inverse_countdown_ticks_once_a_second() {
+        let mut game = host_with_offsets([300.0, 300.0]);
+        game.config.inverse_schedule = InverseSchedule::Fixed;
+        let mut ticks = Vec::new();
+        for cooldown in [3.5, 2.9, 2.5, 1.6, 0.4] {
+            game.inverse_cooldown = cooldown;
+            game.update_events(0.0);
+            ticks.extend(game.sounds.drain(..).map(|(sfx, playback)| (sfx, playback.pitch)));
+        }
+        let sfx: Vec<Sfx> = ticks.iter().map(|&(sfx, _)| sfx).collect();
+        assert_eq!(sfx, [Sfx::Tick, Sfx::Tock, Sfx::Tick]);
+        assert!(ticks.windows(2).all(|pair| pair[1].1 > pair[0].1));
+    }
 
-        // Update inverse timer (host only)
-        game.update_inverse_timer(dt);
+    #[test]
+    fn shipped_levels_load() {
+        let levels = PuzzleLevel::load_all(LEVELS_DIR).unwrap();
+        assert!(levels.iter().all(|level| level.time_limit > 0.0 && !level.scripts.is_empty()));
+        let err = PuzzleLevel::parse("name Broken\nspawn ghost 1 10 10").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
 
-        // Get input
-        let input = get_input(&rl);
-        
-        // Update player (controls other player's shadow/character)
-        if input.length_sqr() > 0.0 {
-            game.update_player(input, dt);
+    #[test]
+    fn shipped_themes_load() {
+        for name in Theme::available() {
+            Theme::load(&name).unwrap();
         }
+        let red = Theme::dark().players[1];
+        assert_eq!((red.r, red.g, red.b, red.a), (255, 0, 0, 255));
+        let err = Theme::parse(&DARK_THEME.replace("grid = #14141e32", "grid = gray")).unwrap_err();
+        assert!(err.contains("'gray' is not a #rrggbb or #rrggbbaa color"), "{}", err);
+        assert_eq!(Theme::parse("").unwrap_err(), "'color-green' is missing");
+    }
 
-        // Swap with shadow (SPACE key)
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            game.swap_with_shadow();
-        }
+    #[test]
+    fn command_line_flags_parse() {
+        let parse = |line: &str| Args::parse(&line.split_whitespace().map(str::to_string).collect::<Vec<_>>());
+        let args = parse("--join 10.0.0.2 --port 6000 --name Alex --fullscreen --portable").unwrap();
+        assert_eq!(args.start, Some(MenuChoice::Join));
+        assert_eq!((args.address.as_str(), args.port, args.name.as_deref()), ("10.0.0.2", Some(6000), Some("Alex")));
+        assert!(args.fullscreen && args.portable && !args.dev);
+        assert_eq!(parse("--bot --dev").unwrap().start, Some(MenuChoice::Practice(BotLevel::Normal)));
+        assert_eq!(parse("--bot hard").unwrap().start, Some(MenuChoice::Practice(BotLevel::Hard)));
+        assert_eq!(parse("--host --bot").unwrap_err(), "pick only one of --host, --join and --bot");
+        assert_eq!(parse("--join").unwrap_err(), "--join needs a value");
+        assert!(parse("--port 99999").is_err());
+        assert!(parse("--hots").is_err());
+        assert_eq!(parse("--profile Sam").unwrap().profile.as_deref(), Some("Sam"));
+        assert!(parse("--profile Sam --name Alex").is_err());
+        assert_eq!(parse("--record-inputs run.inputs").unwrap_err(), "--record-inputs needs --bot");
+        assert!(parse("--bot hard --record-inputs run.inputs").unwrap().record_inputs.is_some());
+        assert_eq!(parse("--bot --metrics host.csv").unwrap_err(), "--metrics needs --host");
+        assert_eq!(parse("--bot --net-dump game.dump").unwrap_err(), "--net-dump needs --host or --join");
+        assert!(parse("--join 10.0.0.2 --net-dump game.dump").unwrap().net_dump.is_some());
+        assert_eq!(parse("--overlay 7777").unwrap().overlay, Some(7777));
+        assert!(parse("--overlay obs").is_err());
+        assert_eq!(parse("--host --status 8080").unwrap().status, Some(8080));
+        assert!(parse("--bot --status 8080").is_err());
+        assert_eq!(parse("--host --overlay 7777 --feed-delay 30").unwrap().feed_delay, 30.0);
+        assert_eq!(parse("--host --admin 9000 --admin-key admin.key").unwrap().admin, Some(9000));
+        assert!(parse("--host --admin 9000").is_err());
+        assert!(parse("--join 10.0.0.2 --admin 9000 --admin-key admin.key").is_err());
+        assert!(parse("--host --feed-delay 30").is_err());
+        assert!(parse("--host --overlay 7777 --feed-delay -1").is_err());
+        assert!(parse("--verify-report reports/1.json").is_err());
+        assert!(parse("--verify-report reports/1.json --tournament key.txt").is_ok());
+        assert_eq!(parse("--twitch #SomeStreamer").unwrap().twitch.as_deref(), Some("somestreamer"));
+        assert!(parse("--twitch 'bad name'").is_err());
+    }
 
-        // Restart game (R key) - only when game is over
-        let is_game_over = game.players[0].score >= WIN_SCORE || game.players[1].score >= WIN_SCORE;
-        if rl.is_key_pressed(KeyboardKey::KEY_R) && is_game_over {
-            game.reset_game();
-            game.send_message(Message::GameReset);
+    #[test]
+    fn replays_record_play_and_round_trip() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        game.set_name(1, "Alex");
+        for _ in 0..10 {
+            game.play_time += 0.03;
+            game.record_replay(0.03);
         }
+        assert_eq!(game.replay_frames.len(), 5, "one frame to start, then one per REPLAY_SAMPLE");
+        let replay = game.replay(ReplayEnd::Won(1)).unwrap();
+        let bytes = replay.encode().unwrap();
+        let decoded = Replay::decode(&bytes).unwrap();
+        assert_eq!((&decoded.frames, &decoded.names, decoded.end), (&replay.frames, &replay.names, ReplayEnd::Won(1)));
+        assert!(decoded.summary().contains("won by Alex"));
+        assert!(Replay::decode(b"garbage").is_err());
+        let mut future = replay.clone();
+        future.version = REPLAY_VERSION + 1;
+        assert!(Replay::decode(&future.encode().unwrap()).unwrap_err().contains("version"));
+        game.reset_game();
+        assert!(game.replay(ReplayEnd::Abandoned).is_none());
+    }
 
-        // Check traps (host only)
-        game.check_traps(dt);
+    #[test]
+    fn ghosts_follow_the_recorded_run_between_frames() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        game.record_replay(0.0);
+        game.players[0].pos.x += 50.0;
+        game.play_time = 1.0;
+        game.record_replay(1.0);
+        let ghost = game.replay(ReplayEnd::Won(0)).unwrap();
+        let [x, y, ..] = ghost.frames[0].pieces[0].map(f32::from);
+        let (halfway, _) = ghost.pieces_at(0.5).unwrap();
+        assert_eq!((halfway.x - x, halfway.y - y), (25.0, 0.0));
+        assert!(ghost.pieces_at(1.5).is_none(), "the ghost is gone once its run ends");
+    }
 
-        // Send updates
-        if game.last_send.elapsed().as_millis() > 16 {
-            // Always send our own player update
-            game.send_message(Message::PlayerUpdate(game.players[game.player_id as usize]));
-            
-            // If we're controlling the opponent's shadow/character, send their update too
-            let other_id = (1 - game.player_id as usize) as usize;
-            game.send_message(Message::PlayerUpdate(game.players[other_id]));
-            
-            if game.is_host {
-                game.send_message(Message::InverseControl { 
-                    active: game.inverse_active, 
-                    time_left: game.inverse_timer 
-                });
+    #[test]
+    fn lasting_hash_mismatch_is_a_desync_and_a_snapshot_fixes_it() {
+        // The client missed the TrapEvent for the host's trap
+        let mut host = host_with_offsets([10.0, 200.0]);
+        host.check_traps(0.016);
+        let mut client = GameState::new(false, MatchConfig::default());
+        let hash = host.sync_state().hash();
+        assert_ne!(client.sync_state().hash(), hash);
+
+        // A brief disagreement is just messages in flight
+        client.check_sync(100, hash);
+        assert_eq!((client.desyncs, client.desync_since.map(|(tick, _)| tick)), (0, Some(100)));
+        client.desync_since = Some((100, Instant::now() - std::time::Duration::from_secs_f32(DESYNC_GRACE * 2.0)));
+        client.check_sync(160, hash);
+        assert_eq!(client.desyncs, 1);
+
+        client.apply_resync(host.sync_state(), host.players);
+        assert_eq!(client.sync_state(), host.sync_state());
+        client.check_sync(170, hash);
+        assert!(client.desync_since.is_none());
+    }
+
+    #[test]
+    fn input_recordings_play_back_to_the_same_checksum() {
+        // Random inverse gaps come from the seed; player 0 weaves about while a hard bot plays player 1
+        let config = MatchConfig { inverse_schedule: InverseSchedule::Random, ..MatchConfig::default() };
+        let mut game = GameState::seeded(true, config, 42);
+        game.start_round(1, ROUND_COUNTDOWN);
+        game.record_inputs(PathBuf::new());
+        let mut bot = Bot::new(1, BotLevel::Hard);
+        for tick in 0..3600 {
+            let dt = game.begin_tick(1.0 / 60.0);
+            if game.is_live() {
+                let angle = tick as f32 / 50.0;
+                game.update_player(0, Vector2::new(angle.cos(), angle.sin()), dt);
             }
-            game.last_send = Instant::now();
+            if tick % 240 == 0 && game.is_live() {
+                game.swap_with_shadow(0);
+            }
+            bot.update(&mut game, dt);
+            game.end_tick(dt);
         }
+        let (_, mut log) = game.input_log.take().unwrap();
+        log.checksum = game.checksum();
+        assert!(game.players.iter().any(|player| player.score > 0), "the minute of play should have seen a trap");
 
-        // Draw
-        let mut d = rl.begin_drawing(&thread);
-        // Dark gradient background
-        d.clear_background(Color::new(10, 10, 20, 255));
-        
-        // Draw subtle background pattern
-        for y in (0..SCREEN_HEIGHT).step_by(100) {
-            d.draw_line(0, y, SCREEN_WIDTH, y, Color::new(20, 20, 30, 50));
+        assert_eq!(log.play().checksum(), log.checksum);
+        assert_eq!(log.play().checksum(), log.checksum);
+        // Dropping a second of player 0's steering lands somewhere else
+        let mut edited = log.clone();
+        for tick in &mut edited.ticks[1000..1060] {
+            tick.inputs.retain(|input| !matches!(input, TickInput::Move(0, _)));
         }
-        for x in (0..SCREEN_WIDTH).step_by(100) {
-            d.draw_line(x, 0, x, SCREEN_HEIGHT, Color::new(20, 20, 30, 50));
+        assert_ne!(edited.play().checksum(), log.checksum);
+        // And so does another seed's inverse schedule
+        let reseeded = InputLog { seed: 43, ..log.clone() };
+        assert_ne!(GameState::seeded(true, config, 43).inverse_cooldown, GameState::seeded(true, config, 42).inverse_cooldown);
+        assert_ne!(reseeded.play().checksum(), log.play().checksum());
+    }
+
+    #[test]
+    fn achievements_unlock_from_match_history() {
+        let mut records = vec![record(GameMode::Classic, false, "Alex", 2, 3), record(GameMode::Classic, true, "Alex", 3, 1)];
+        assert_eq!(Achievement::new_in(&records, &[]), vec![Achievement::FirstWin]);
+        records.push(record(GameMode::Classic, true, "Alex", 3, 0));
+        assert_eq!(Achievement::new_in(&records, &[Achievement::FirstWin]), vec![Achievement::Flawless]);
+        assert_eq!(available_colors(&[]).len(), PLAYER_COLORS.len());
+        let metals = available_colors(&[Achievement::Veteran]);
+        assert_eq!(metals.len(), PLAYER_COLORS.len() + 3);
+        assert_eq!(color_name(*metals.last().unwrap() as usize), "Bronze");
+    }
+
+    #[test]
+    fn profiles_round_trip_and_rebind_without_clashes() {
+        let mut profile = Profile::new("Sam Lee");
+        profile.color = 2;
+        profile.bindings.set(Action::Swap, KeyboardKey::KEY_LEFT_SHIFT);
+        // Taking Q from Freeze hands it Swap's old key
+        profile.bindings.set(Action::Swap, KeyboardKey::KEY_Q);
+        assert_eq!(profile.bindings.key(Action::Freeze), KeyboardKey::KEY_LEFT_SHIFT);
+        profile.cosmetics = Cosmetics { trail: TrailStyle::Comet, swap: SwapEffect::Implode };
+        profile.achievements = vec![Achievement::Trapper, Achievement::FirstWin];
+        assert_eq!(Profile::parse(&profile.text()).unwrap(), profile);
+        assert_eq!(Bindings::default().movement(), "WASD");
+        assert_eq!(parse_key("left shift"), Some(KeyboardKey::KEY_LEFT_SHIFT));
+        assert!(Profile::parse("name Sam\njump SPACE").is_err());
+        assert!(Profile::parse("color 1").is_err());
+    }
+
+    #[test]
+    fn match_records_round_trip() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        game.set_name(1, "Alex B");
+        game.stats[0].traps_dealt = 3;
+        game.play_time = 62.25;
+        assert!(game.match_record().is_none());
+        game.declare_winner(0);
+        let record = game.match_record().unwrap();
+        assert!(record.won);
+        let parsed = MatchRecord::parse(&record.line()).unwrap();
+        assert_eq!((parsed.opponent.as_str(), parsed.traps_dealt, parsed.duration), ("Alex B", 3, 62.2));
+        assert_eq!(parsed.player, game.names[0]);
+        assert_eq!(parsed.mode, GameMode::Classic);
+        assert!(MatchRecord::parse("garbage").is_none());
+        assert_eq!(calendar_date(20_000), "2024-10-04");
+    }
+
+    #[test]
+    fn metrics_rows_average_ticks_and_count_finished_matches() {
+        let mut metrics = Metrics::new(PathBuf::from("metrics.csv"));
+        let mut game = host_with_offsets([100.0, 300.0]);
+        metrics.record(0.002, Some(&game));
+        game.declare_winner(0);
+        metrics.record(0.004, Some(&game));
+        metrics.record(0.004, Some(&game));
+        assert_eq!(metrics.matches, 1, "a win counts once, however long the game over screen stays up");
+        // Offline, so the network columns stay empty
+        let row = metrics.row(100, Some(&game));
+        assert_eq!(row, "100,3.33,4.00,,,,,0,,1");
+        assert_eq!(row.split(',').count(), METRICS_HEADER.split(',').count());
+    }
+
+    #[test]
+    fn heatmap_counts_time_per_cell_and_marks_traps() {
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.record_heatmap(0.5);
+        game.record_heatmap(0.25);
+        let cell = |x: f32, y: f32| Heatmap::cell(Vec2 { x, y });
+        assert_eq!(game.heatmap.time[0][cell(310.0, 400.0)], 0.75);
+        assert_eq!(game.heatmap.time[1][cell(1100.0, 400.0)], 0.75);
+        assert_eq!(cell(-5.0, 5000.0), (HEATMAP_ROWS - 1) * HEATMAP_COLS, "off-arena positions land on the edge");
+
+        game.check_traps(0.016);
+        game.update_events(0.016);
+        assert_eq!(game.heatmap.traps.len(), 1);
+        assert_eq!(game.heatmap.traps[0].0, 0);
+        game.record_heatmap(1.0);
+        assert_eq!(game.heatmap.time[0].iter().sum::<f32>(), 0.75, "intermissions don't count");
+        assert_eq!(HeatmapView::Player(1).next(), HeatmapView::Off);
+    }
+
+    #[test]
+    fn match_reports_hold_settings_totals_and_the_timeline() {
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.set_name(1, "Alex \"B\"");
+        assert!(game.match_report(0).is_none());
+        game.play_time = 12.5;
+        game.check_traps(0.016);
+        game.declare_winner(1);
+        game.update_events(0.016);
+        let report = game.match_report(20_000 * 86_400).unwrap();
+        for expected in [
+            "\"date\": \"2024-10-04\"",
+            "\"winner\": 1,",
+            "\"mode\": \"Classic\"",
+            "\"name\": \"Alex \\\"B\\\"\"",
+            "\"traps_received\": 1,",
+            "{\"time\": 12.50, \"type\": \"trapped\", \"player\": 0}",
+            "{\"time\": 12.50, \"type\": \"won\", \"player\": 1}",
+        ] {
+            assert!(report.contains(expected), "{} missing from\n{}", expected, report);
         }
+        assert_eq!(report.matches('{').count(), report.matches('}').count());
+    }
 
-        // Draw center divider line
-        d.draw_line(SCREEN_WIDTH / 2, 0, SCREEN_WIDTH / 2, SCREEN_HEIGHT, Color::new(100, 100, 120, 80));
+    #[test]
+    fn leaderboard_sorts_and_filters_by_mode() {
+        let result = |mode, won, opponent| record(mode, won, opponent, 0, 0);
+        let records = [
+            result(GameMode::Classic, true, "Alex"),
+            result(GameMode::Classic, true, "Alex"),
+            result(GameMode::Tag, false, "Kim"),
+            result(GameMode::Tag, false, "Kim"),
+            result(GameMode::Tag, false, "Kim"),
+        ];
+        let names = |standings: Vec<Standing>| standings.into_iter().map(|standing| standing.name).collect::<Vec<_>>();
+        assert_eq!(names(standings(&records, None, true)), ["Kim", "Sam", "Alex"]);
+        assert_eq!(names(standings(&records, Some(GameMode::Classic), false)), ["Sam", "Alex"]);
+        let tag = standings(&records, Some(GameMode::Tag), false);
+        assert_eq!((tag[1].name.as_str(), tag[1].wins, tag[1].losses), ("Sam", 0, 3));
+        assert!(standings(&records, Some(GameMode::HunterVsShade), false).is_empty());
+    }
 
-        // Draw players and shadows
-        for (i, player) in game.players.iter().enumerate() {
-            let player_color = if i == 0 { Color::GREEN } else { Color::RED };
-            let shadow_color = if i == 0 { 
-                Color::new(0, 150, 0, 150) 
-            } else { 
-                Color::new(150, 0, 0, 150) 
-            };
+    #[test]
+    fn ratings_favor_whoever_keeps_winning() {
+        let mut ratings = Ratings::default();
+        let result = |won| record(GameMode::Classic, won, "Alex", 0, 0);
+        ratings.record(&result(true));
+        assert_eq!((ratings.of("Sam"), ratings.of("Alex")), (ELO_START + ELO_K / 2.0, ELO_START - ELO_K / 2.0));
+        for _ in 0..10 {
+            ratings.record(&result(true));
+        }
+        assert!(ratings.of("Sam") > ratings.of("Alex"));
+        // An upset moves more than an even result
+        let before = ratings.of("Alex");
+        ratings.record(&result(false));
+        assert!(ratings.of("Alex") - before > ELO_K / 2.0);
+        assert_eq!(ratings.of("Nobody"), ELO_START);
+    }
 
-            let player_pos = Vector2::new(player.pos.x, player.pos.y);
-            let shadow_pos = Vector2::new(player.shadow_pos.x, player.shadow_pos.y);
+    #[test]
+    fn config_file_round_trips_and_rejects_typos() {
+        let defaults = ConfigFile::default();
+        assert_eq!(ConfigFile::parse(&defaults.text()).unwrap(), defaults);
+        let config = ConfigFile::parse("[gameplay]\ntrap_radius = 65 # bigger\n\n[look]\ntheme = \"light\"").unwrap();
+        assert_eq!(config.tunables.rules.trap_radius, 65.0);
+        assert_eq!(config.tunables.port, defaults.tunables.port);
+        assert_eq!(config.theme, "light");
+        let err = ConfigFile::parse("[network]\nprot = 5555").unwrap_err();
+        assert_eq!(err, "line 2: 'network.prot' is not a setting");
+        assert!(ConfigFile::parse("[window]\nfps = fast").is_err());
+        assert!(ConfigFile::parse("[look]\nlanguage = es").is_err());
+        let config = ConfigFile::parse("[bot]\nhard_reaction = 0.05\n[look]\nhud_scale = 9").unwrap();
+        assert_eq!((config.tunables.bots[2].reaction, config.hud_scale), (0.05, HUD_SCALE_RANGE.1));
+        assert!(ConfigFile::parse("[bot]\neasy_speed = 1.5").is_err());
+    }
 
-            // Draw shadow (semi-transparent, slightly smaller)
-            d.draw_circle_v(shadow_pos, SHADOW_SIZE, shadow_color);
-            d.draw_circle_lines(
-                shadow_pos.x as i32,
-                shadow_pos.y as i32,
-                SHADOW_SIZE,
-                Color::new(shadow_color.r, shadow_color.g, shadow_color.b, 200),
-            );
+    #[test]
+    fn volume_saves_into_the_audio_table_only() {
+        let volume = Volume { master: 0.5, music: 0.0, sfx: 1.0, muted: true, announcer: false };
+        let text = "[look]\ntheme = \"light\" # mine\n\n[audio]\nmaster = 0.9 # loud\n\n[bot]\nhard_reaction = 0.05\n";
+        let saved = volume.write_into(text);
+        assert!(saved.contains("theme = \"light\" # mine\n") && saved.contains("master = 0.5 # loud\n"));
+        let config = ConfigFile::parse(&saved).unwrap();
+        assert_eq!((config.volume, config.theme.as_str()), (volume, "light"));
+        assert_eq!(config.tunables.bots[2].reaction, 0.05);
+        // No table yet: one is added at the end
+        assert_eq!(ConfigFile::parse(&volume.write_into("[look]\ntheme = \"light\"")).unwrap().volume, volume);
+        assert_eq!(volume.write_into(&volume.write_into(text)), saved);
+        assert!(ConfigFile::parse("[audio]\nmuted = yes").is_err());
+    }
 
-            // Draw connection line from player to shadow (with glow effect)
-            let line_color = Color::new(player_color.r, player_color.g, player_color.b, 120);
-            d.draw_line_ex(player_pos, shadow_pos, 3.0, line_color);
-            d.draw_line_ex(player_pos, shadow_pos, 1.5, Color::new(255, 255, 255, 80));
+    #[test]
+    fn every_locale_matches_english() {
+        fn placeables(template: &str) -> Vec<&str> {
+            let mut names: Vec<&str> = template.split("{ $").skip(1).filter_map(|rest| rest.split_once(" }")).map(|(name, _)| name).collect();
+            names.sort();
+            names
+        }
+        for language in Locale::available() {
+            let locale = Locale::load(&language).unwrap();
+            for (id, template) in &ENGLISH.messages {
+                let translated = locale.messages.get(id).unwrap_or_else(|| panic!("{}: missing '{}'", language, id));
+                assert_eq!(placeables(translated), placeables(template), "{}: '{}'", language, id);
+            }
+            assert_eq!(locale.messages.len(), ENGLISH.messages.len(), "{}: unknown message ids", language);
+        }
+        let err = Locale::parse("xx", "menu-host = Host\nmenu-host = Again").err().unwrap();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
 
-            // Draw player with glow effect
-            let alpha = if player.is_trapped { 150 } else { 255 };
-            // Outer glow
-            d.draw_circle_v(player_pos, PLAYER_SIZE + 3.0, Color::new(player_color.r, player_color.g, player_color.b, alpha / 3));
-            // Main circle
-            d.draw_circle_v(player_pos, PLAYER_SIZE, Color::new(player_color.r, player_color.g, player_color.b, alpha));
-            // Inner highlight
-            d.draw_circle_v(player_pos, PLAYER_SIZE * 0.6, Color::new(255, 255, 255, alpha / 2));
-            // Border
-            d.draw_circle_lines(
-                player_pos.x as i32,
-                player_pos.y as i32,
-                PLAYER_SIZE,
-                Color::new(255, 255, 255, alpha),
-            );
+    #[test]
+    fn console_commands_change_rules() {
+        let mut session = Match::new(GameState::new(true, MatchConfig::default()));
+        session.game.config.handicaps[1].speed = 1.2;
+        session.game.config.handicaps[1].trap_radius = 0.8;
+        session.run_command("set trap_radius 70").unwrap();
+        session.run_command("set speed 240").unwrap();
+        assert_eq!((session.game.config.rules.trap_radius, session.game.config.rules.player_speed), (70.0, 240.0));
+        assert_eq!((session.game.config.handicaps[1].speed, session.game.config.handicaps[1].trap_radius), (1.2, 0.8), "handicaps stay");
+        session.game.chat_event(ChatEvent::Shrink);
+        session.run_command("set trap_radius 50").unwrap();
+        assert_eq!(session.game.config.rules.trap_radius, 50.0 * CHAT_SHRINK);
+        session.game.end_chat_events();
+        assert_eq!(session.game.config.rules.trap_radius, 50.0);
+        session.run_command("set wrap on").unwrap();
+        assert!(session.game.config.wrap);
+        assert!(session.run_command("set wrap maybe").is_err());
+        assert!(session.run_command("launch rockets").is_err());
+        session.run_command("bot add hard").unwrap();
+        assert!(session.bot.as_ref().is_some_and(|bot| bot.level == BotLevel::Hard));
+    }
 
-            // Draw trap radius around shadow (more visible)
-            if i != game.player_id as usize {
-                // Pulsing effect using game time
-                let pulse = (game.game_time * 2.0).sin().abs();
-                let alpha = (100.0 + pulse * 100.0) as u8;
-                d.draw_circle_lines(
-                    shadow_pos.x as i32,
-                    shadow_pos.y as i32,
-                    TRAP_RADIUS,
-                    Color::new(255, 255, 0, alpha),
-                );
-                // Inner warning circle
-                d.draw_circle_lines(
-                    shadow_pos.x as i32,
-                    shadow_pos.y as i32,
-                    TRAP_RADIUS * 0.7,
-                    Color::new(255, 200, 0, alpha / 2),
-                );
+    #[test]
+    fn camera_frames_the_closest_chase() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        let (middle, gap) = game.closest_chase(0).unwrap();
+        assert_eq!((middle.x, middle.y), (350.0, 400.0));
+        assert!((gap - (100.0 - game.config.rules.trap_radius)).abs() < 0.001);
+        game.config.mode = GameMode::Tag;
+        assert!(game.closest_chase(0).is_none());
+    }
+
+    /// Undoes `lzw`, as a GIF viewer would.
+    fn unlzw(min_size: u8, data: &[u8]) -> Vec<u8> {
+        let (clear, end) = (1usize << min_size, (1usize << min_size) + 1);
+        let reset = || (0..clear).map(|i| vec![i as u8]).chain([Vec::new(), Vec::new()]).collect::<Vec<Vec<u8>>>();
+        let (mut table, mut size, mut previous) = (reset(), min_size + 1, None::<Vec<u8>>);
+        let (mut out, mut at) = (Vec::new(), 0);
+        loop {
+            let code = (0..size as usize).map(|bit| ((data[(at + bit) / 8] >> ((at + bit) % 8)) & 1) as usize).rev().fold(0, |code, bit| code << 1 | bit);
+            at += size as usize;
+            if code == clear {
+                (table, size, previous) = (reset(), min_size + 1, None);
+                continue;
             }
-            
-            // Flash effect when trapped
-            if game.trap_flash_timer[i] > 0.0 {
-                let flash_alpha = (game.trap_flash_timer[i] * 200.0) as u8;
-                d.draw_circle_v(player_pos, PLAYER_SIZE + 10.0, Color::new(255, 0, 0, flash_alpha));
+            if code == end {
+                return out;
+            }
+            let entry = match (&previous, table.get(code)) {
+                (_, Some(entry)) => entry.clone(),
+                (Some(previous), None) => [previous.clone(), vec![previous[0]]].concat(),
+                (None, None) => panic!("code {} before any output", code),
+            };
+            if let Some(previous) = previous {
+                table.push([previous, vec![entry[0]]].concat());
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
             }
+            out.extend(&entry);
+            previous = Some(entry);
         }
+    }
 
-        // Draw UI with better styling - organized layout
-        let player_color = if game.player_id == 0 { Color::GREEN } else { Color::RED };
-        let is_game_over = game.players[0].score >= WIN_SCORE || game.players[1].score >= WIN_SCORE;
-        
-        // Title bar background
-        d.draw_rectangle(0, 0, SCREEN_WIDTH, 140, Color::new(0, 0, 0, 200));
-        
-        // Game title (top center)
-        d.draw_text(
-            "SHADOW SWAP",
-            SCREEN_WIDTH / 2 - 120,
-            8,
-            32,
-            Color::new(200, 200, 255, 255),
-        );
-        
-        // Left side: Player info
-        d.draw_text(
-            &format!("Player {} (YOU)", game.player_id + 1),
-            20,
-            45,
-            26,
-            player_color,
-        );
-        
-        // Scores with proper spacing
-        let my_score = game.players[game.player_id as usize].score;
-        let other_score = game.players[1 - game.player_id as usize].score;
-        d.draw_text(
-            &format!("Trapped: {} / {}", my_score, WIN_SCORE),
-            20,
-            72,
-            22,
-            Color::WHITE,
-        );
-        d.draw_text(
-            &format!("Opponent: {} / {}", other_score, WIN_SCORE),
-            20,
-            95,
-            22,
-            Color::GRAY,
-        );
+    #[test]
+    fn clips_keep_the_last_seconds_and_encode_as_gif() {
+        let mut game = host_with_offsets([100.0, 300.0]);
+        for _ in 0..(CLIP_SECONDS * 60.0) as usize + 120 {
+            game.play_time += 1.0 / 60.0;
+            game.record_replay(1.0 / 60.0);
+        }
+        let clip = game.clip().unwrap();
+        assert_eq!(clip.end, ReplayEnd::Clip);
+        assert!(clip.frames[0].time >= clip.length() - CLIP_SECONDS && clip.frames[0].time > 1.5);
+        assert!(clip.length() - clip.frames[0].time > CLIP_SECONDS - 0.1);
+        let short = Replay { frames: clip.frames[..3].to_vec(), ..clip };
+        let gif = clip_gif(&short, [Color::RED, Color::BLUE]);
+        assert!(gif.starts_with(b"GIF89a") && gif.ends_with(&[0x3b]));
+        assert_eq!(u16::from_le_bytes([gif[6], gif[7]]) as i32, SCREEN_WIDTH / CLIP_SCALE);
+        assert_eq!(u16::from_le_bytes([gif[8], gif[9]]) as i32, SCREEN_HEIGHT / CLIP_SCALE);
+        // Runs long enough to fill the table and start over, mixed with noise
+        let pixels: Vec<u8> = (0..20_000u32).map(|i| if i % 7 == 0 { (i * 31 % 8) as u8 } else { (i / 300 % 8) as u8 }).collect();
+        assert_eq!(unlzw(3, &lzw(3, &pixels)), pixels);
+        assert_eq!(unlzw(3, &lzw(3, &[])), Vec::<u8>::new());
+    }
 
-        // Right side: Mode indicator
-        let inverse_text = if game.inverse_active {
-            format!("⚡ INVERSE MODE! ⚡ ({:.1}s)", game.inverse_timer.max(0.0))
-        } else {
-            format!("Shadow Control ({:.1}s)", game.inverse_cooldown.max(0.0))
-        };
-        let inverse_color = if game.inverse_active { 
-            Color::new(255, 255, 0, 255) 
-        } else { 
-            Color::new(200, 200, 200, 255) 
-        };
-        
-        // Background for mode indicator
-        if game.inverse_active {
-            let bg_alpha = ((game.inverse_timer * 3.0).sin().abs() * 50.0 + 30.0) as u8;
-            d.draw_rectangle(
-                SCREEN_WIDTH - 380,
-                70,
-                360,
-                35,
-                Color::new(255, 255, 0, bg_alpha),
-            );
+    #[test]
+    fn twitch_chat_votes_fire_one_event_per_window_on_the_host() {
+        assert_eq!(chat_vote(":sam!sam@sam.tmi.twitch.tv PRIVMSG #streamer :!SHRINK "), Some(("sam".to_string(), ChatEvent::Shrink)));
+        assert_eq!(chat_vote(":sam!sam@sam.tmi.twitch.tv PRIVMSG #streamer :gg !shrink"), None);
+        assert_eq!(chat_vote(":tmi.twitch.tv 001 justinfan12345 :Welcome, GLHF!"), None);
+        let (sender, votes_in) = std::sync::mpsc::channel();
+        let mut chat = TwitchChat { votes_in, votes: HashMap::new(), window: CHAT_VOTE_WINDOW };
+        for (chatter, vote) in [("a", ChatEvent::Hazard), ("b", ChatEvent::Shrink), ("a", ChatEvent::Shrink), ("c", ChatEvent::Hazard)] {
+            sender.send((chatter.to_string(), vote)).unwrap();
         }
-        
-        // Mode text (right aligned)
-        d.draw_text(
-            &inverse_text,
-            SCREEN_WIDTH - 370,
-            75,
-            24,
-            inverse_color,
-        );
+        let mut scene = Scene::Playing(Match::new(host_with_offsets([100.0, 300.0])));
+        let radius = scene.game().unwrap().config.rules.trap_radius;
+        chat.update(CHAT_VOTE_WINDOW / 2.0, &mut scene);
+        assert_eq!((chat.count(ChatEvent::Hazard), chat.count(ChatEvent::Shrink)), (1, 2), "a changed their vote");
+        assert_eq!(scene.game().unwrap().config.rules.trap_radius, radius, "nothing fires before the window closes");
+        chat.update(CHAT_VOTE_WINDOW / 2.0, &mut scene);
+        let game = scene.game_mut().unwrap();
+        assert_eq!(game.config.rules.trap_radius, radius * CHAT_SHRINK);
+        assert!(chat.votes.is_empty() && chat.window == CHAT_VOTE_WINDOW);
+        game.update_events(0.016);
+        assert!(matches!(game.timeline.last(), Some((_, GameEvent::ChatVote(ChatEvent::Shrink)))));
+        game.chat_event(ChatEvent::Hazard);
+        let (well, _) = game.hazard.unwrap();
+        game.players[0].pos = Vec2 { x: well.x + WELL_RADIUS / 2.0, y: well.y };
+        game.update_gravity_wells(0.1);
+        assert!(game.players[0].pos.x < well.x + WELL_RADIUS / 2.0, "the hazard pulls like a well");
+        game.update_chat_events(CHAT_EVENT_TIME);
+        assert!(game.hazard.is_none() && game.shrink.is_none());
+        assert_eq!(game.config.rules.trap_radius, radius);
+        chat.votes.insert("d".to_string(), ChatEvent::Hazard);
+        chat.votes.insert("e".to_string(), ChatEvent::Shrink);
+        assert_eq!(chat.leader(), Some(ChatEvent::Hazard), "ties go to the first event");
+    }
 
-        // Draw instructions in a panel
-        let instructions_y = SCREEN_HEIGHT - 110;
-        d.draw_rectangle(10, instructions_y - 10, SCREEN_WIDTH - 20, 105, Color::new(0, 0, 0, 150));
-        d.draw_rectangle_lines(10, instructions_y - 10, SCREEN_WIDTH - 20, 105, Color::new(100, 100, 100, 200));
-        
-        d.draw_text(
-            "CONTROLS:",
-            20,
-            instructions_y,
-            20,
-            Color::new(255, 255, 200, 255),
-        );
-        d.draw_text(
-            "WASD/Arrows → Move opponent's shadow/character",
-            20,
-            instructions_y + 25,
-            18,
-            Color::LIGHTGRAY,
-        );
-        d.draw_text(
-            "SPACE → Swap YOUR position with YOUR shadow",
-            20,
-            instructions_y + 45,
-            18,
-            Color::LIGHTGRAY,
-        );
-        d.draw_text(
-            &format!("GOAL → Trap opponent {} times to win!", WIN_SCORE),
-            20,
-            instructions_y + 65,
-            18,
-            Color::YELLOW,
-        );
-        d.draw_text(
-            "R → Restart (after game ends)",
-            20,
-            instructions_y + 85,
-            16,
-            Color::new(150, 150, 150, 255),
-        );
-        
-        // Show restart instruction (only when game is over)
-        if is_game_over {
-            d.draw_text(
-                "Press R to restart the game",
-                SCREEN_WIDTH / 2 - 120,
-                SCREEN_HEIGHT / 2 + 100,
-                25,
-                Color::YELLOW,
-            );
+    #[test]
+    fn websocket_handshake_and_frames_follow_the_rfc() {
+        let hex: String = sha1(b"abc").iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        // The worked example in RFC 6455
+        let request = b"GET /chat HTTP/1.1\r\nHost: server.example.com\r\nUpgrade: websocket\r\nsec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        assert_eq!(websocket_key(request).as_deref(), Some("dGhlIHNhbXBsZSBub25jZQ=="));
+        assert_eq!(websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+        assert!(websocket_key(b"GET / HTTP/1.1\r\n\r\n").is_none());
+        assert_eq!(websocket_frame("Hello"), b"\x81\x05Hello");
+        assert_eq!(websocket_frame(&"x".repeat(300))[..4], [0x81, 126, 1, 44]);
+        assert_eq!(websocket_frame(&"x".repeat(70_000))[..10], [0x81, 127, 0, 0, 0, 0, 0, 1, 0x11, 0x70]);
+    }
+
+    #[test]
+    fn overlay_streams_state_and_events_to_websocket_clients() {
+        use std::io::{Read, Write};
+        let mut overlay = Overlay::bind(0, 0.0).unwrap();
+        let mut client = TcpStream::connect(overlay.listener.local_addr().unwrap()).unwrap();
+        client.set_read_timeout(Some(std::time::Duration::from_secs(2))).unwrap();
+        client.write_all(b"GET / HTTP/1.1\r\nUpgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n").unwrap();
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.set_name(1, "Alex");
+        let mut scene = Scene::Playing(Match::new(game));
+        let mut received = Vec::new();
+        for _ in 0..200 {
+            overlay.update(0.0, &scene);
+            if overlay.clients.first().is_some_and(|client| client.open) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let mut buf = [0u8; 4096];
+        let size = client.read(&mut buf).unwrap();
+        assert!(String::from_utf8_lossy(&buf[..size]).contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        if let Scene::Playing(session) = &mut scene {
+            session.game.check_traps(0.016);
+            session.game.update_events(0.016);
         }
+        overlay.update(OVERLAY_INTERVAL, &scene);
+        while !String::from_utf8_lossy(&received).contains("\"type\": \"state\"") {
+            let size = client.read(&mut buf).unwrap();
+            received.extend(&buf[..size]);
+        }
+        let text = String::from_utf8_lossy(&received);
+        assert!(text.contains("{\"type\": \"event\", \"time\": 0.00, \"event\": {\"type\": \"trapped\", \"player\": 0}}"), "{}", text);
+        assert!(text.contains("\"scene\": \"playing\", \"mode\": \"Classic\""), "{}", text);
+        assert!(text.contains("\"name\": \"Alex\"") && text.contains("\"score\": 1"), "{}", text);
+        assert!(text.contains("\"paused\": false, \"players\": ["), "{}", text);
+        assert_eq!(Overlay::state("main menu", None), "{\"type\": \"state\", \"scene\": \"main menu\"}");
+    }
 
-        // Draw win condition with better visuals
-        if game.players[0].score >= WIN_SCORE {
-            // Semi-transparent overlay
-            d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0, 0, 0, 180));
-            d.draw_text(
-                "PLAYER 2 WINS!",
-                SCREEN_WIDTH / 2 - 180,
-                SCREEN_HEIGHT / 2 - 40,
-                60,
-                Color::RED,
-            );
-            d.draw_text(
-                "Player 1 was trapped too many times!",
-                SCREEN_WIDTH / 2 - 220,
-                SCREEN_HEIGHT / 2 + 30,
-                28,
-                Color::WHITE,
-            );
-        } else if game.players[1].score >= WIN_SCORE {
-            // Semi-transparent overlay
-            d.draw_rectangle(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT, Color::new(0, 0, 0, 180));
-            d.draw_text(
-                "PLAYER 1 WINS!",
-                SCREEN_WIDTH / 2 - 180,
-                SCREEN_HEIGHT / 2 - 40,
-                60,
-                Color::GREEN,
-            );
-            d.draw_text(
-                "Player 2 was trapped too many times!",
-                SCREEN_WIDTH / 2 - 220,
-                SCREEN_HEIGHT / 2 + 30,
-                28,
-                Color::WHITE,
-            );
+    #[test]
+    fn status_endpoint_answers_with_the_match_as_json() {
+        use std::io::{Read, Write};
+        let mut server = StatusServer::bind(0, 0.0).unwrap();
+        let port = server.listener.local_addr().unwrap().port();
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.set_name(1, "Alex");
+        game.check_traps(0.016);
+        let scene = Scene::Playing(Match::new(game));
+        let get = |server: &mut StatusServer, request: &[u8]| {
+            let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            client.set_read_timeout(Some(std::time::Duration::from_millis(5))).unwrap();
+            client.write_all(request).unwrap();
+            for _ in 0..400 {
+                server.update(0.0, &scene);
+                if client.peek(&mut [0]).is_ok() {
+                    break;
+                }
+            }
+            let mut response = String::new();
+            client.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get(&mut server, b"GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\nContent-Type: application/json"), "{}", response);
+        assert!(response.contains("\"scene\": \"playing\", \"uptime\": 0, \"mode\": \"Classic\", \"phase\": \"Intermission\""), "{}", response);
+        assert!(response.contains("{\"id\": 0, \"name\": \"Player 1\", \"score\": 1, \"series_wins\": 0}"), "{}", response);
+        assert!(response.ends_with("\"peer\": {\"connected\": false, \"ping_ms\": null, \"loss_pct\": null}}"), "{}", response);
+        assert!(get(&mut server, b"GET / HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404"));
+        assert!(get(&mut server, b"POST /status HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405"));
+        assert_eq!(StatusServer::status("lobby", None, 61.4), "{\"scene\": \"lobby\", \"uptime\": 61}");
+    }
+
+    #[test]
+    fn admin_console_needs_the_key_then_kicks_rotates_and_shuts_down() {
+        use std::io::{Read, Write};
+        let mut admin = AdminConsole::bind(0, b"secret".to_vec()).unwrap();
+        let port = admin.listener.local_addr().unwrap().port();
+        let mut game = host_with_offsets([100.0, 300.0]);
+        game.client_addr = Some("192.168.1.30:40000".parse().unwrap());
+        let mut scene = Scene::Playing(Match::new(game));
+        let session = |admin: &mut AdminConsole, scene: &mut Scene, lines: &str| {
+            let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            client.set_read_timeout(Some(std::time::Duration::from_millis(5))).unwrap();
+            client.write_all(lines.as_bytes()).unwrap();
+            client.shutdown(std::net::Shutdown::Write).unwrap();
+            let mut replies = Vec::new();
+            for _ in 0..400 {
+                admin.update(scene);
+                let mut buf = [0u8; 4096];
+                match client.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(size) => replies.extend(&buf[..size]),
+                    Err(_) => {}
+                }
+            }
+            String::from_utf8(replies).unwrap()
+        };
+        assert_eq!(session(&mut admin, &mut scene, "list\n"), "error: auth <key> first\n");
+        assert_eq!(session(&mut admin, &mut scene, "auth wrong\nlist\n"), "error: auth <key> first\n");
+        let replies = session(&mut admin, &mut scene, "auth secret\nlist\nrotate portals vortex\nrotate maze\npause\n");
+        assert!(replies.starts_with("ok\nok\nscene: playing\nclient: 192.168.1.30:40000 (Player 2)"), "{}", replies);
+        assert!(replies.contains("arena: Open, rotation: off\nok\nfrom the next game\nerror: unknown arena 'maze'\nok\n"), "{}", replies);
+        let game = scene.game().unwrap();
+        assert!(game.paused && game.arena_rotation == [Arena::Portals, Arena::Vortex]);
+        let replies = session(&mut admin, &mut scene, "auth secret\nkick\nkick\nshutdown\n");
+        assert_eq!(replies, "ok\nok\nkicked 192.168.1.30:40000\nerror: no client is connected\nok\nquitting once no round is being played\n");
+        assert!(admin.quit_now(&scene), "the kick ended the match");
+        let game = scene.game_mut().unwrap();
+        assert_eq!((game.forfeited, game.client_addr), (Some(1), None));
+        game.rematch_votes = [Some(true), Some(true)];
+        game.check_rematch();
+        assert_eq!(game.config.arena, Arena::Portals);
+        game.rematch_votes = [Some(true), Some(true)];
+        game.check_rematch();
+        assert_eq!(game.config.arena, Arena::Vortex);
+        assert!(!admin.quit_now(&scene), "a new game is being played");
+
+        // Connections that never send the key are capped, then timed out
+        let silent: Vec<TcpStream> = (0..ADMIN_MAX_PENDING + 2).map(|_| TcpStream::connect(("127.0.0.1", port)).unwrap()).collect();
+        for _ in 0..200 {
+            admin.update(&mut scene);
+            std::thread::sleep(std::time::Duration::from_millis(1));
         }
+        assert_eq!(admin.clients.len(), ADMIN_MAX_PENDING);
+        for client in &mut admin.clients {
+            client.connected -= std::time::Duration::from_secs_f32(ADMIN_AUTH_TIMEOUT + 1.0);
+        }
+        admin.update(&mut scene);
+        assert!(admin.clients.is_empty());
+        drop(silent);
+    }
 
-        // FPS counter (top right, above instructions)
-        d.draw_fps(SCREEN_WIDTH - 100, 115);
+    #[test]
+    fn tournament_reports_are_signed_and_spectator_feeds_delayed() {
+        let hex = |mac: [u8; 20]| mac.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        assert_eq!(hex(hmac_sha1(&[0x0b; 20], b"Hi There")), "b617318655057264e28bc0b6fb378c8ef146be00");
+        assert_eq!(hex(hmac_sha1(&[0xaa; 80], b"Test Using Larger Than Block-Size Key - Hash Key First")), "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.check_traps(0.016);
+        game.declare_winner(1);
+        game.update_events(0.016);
+        let report = game.match_report(1_760_486_400).unwrap();
+        let tournament = Tournament { key: b"bracket-key".to_vec(), match_over: false };
+        let signature = tournament.sign(report.as_bytes());
+        assert!(signature.starts_with("hmac-sha1 ") && signature.len() == 51);
+        assert!(tournament.verify(report.as_bytes(), &signature));
+        assert!(!tournament.verify(report.replace("\"winner\": 1", "\"winner\": 0").as_bytes(), &signature), "an edited result is caught");
+        assert!(!Tournament { key: b"other-key".to_vec(), match_over: false }.verify(report.as_bytes(), &signature));
+        assert_eq!(Tournament::signature_path(Path::new("reports/1.json")), PathBuf::from("reports/1.json.sig"));
+
+        let mut feed = FeedDelay::new(2.0);
+        assert!(feed.pass(0.5, vec!["a", "b"]).is_empty());
+        assert!(feed.pass(1.0, vec!["c"]).is_empty());
+        assert_eq!(feed.pass(1.0, Vec::new()), ["a", "b"]);
+        assert_eq!(feed.pass(1.0, Vec::new()), ["c"]);
+        assert_eq!(FeedDelay::new(0.0).pass(0.016, vec!["live"]), ["live"]);
+    }
+
+    #[test]
+    fn crash_reports_hold_the_panic_config_and_last_events() {
+        let mut game = host_with_offsets([10.0, 300.0]);
+        game.check_traps(0.016);
+        game.update_events(0.016);
+        let context = CrashContext {
+            config_path: PathBuf::from("shadow-swap.toml"),
+            scene: "playing",
+            config: Some(game.config),
+            events: game.timeline.clone(),
+            net: Some("host, tick 12".to_string()),
+            language: "es".to_string(),
+            theme: "dark".to_string(),
+            tunables: Some(tunables()),
+        };
+        let report = context.report("index out of bounds", "src/main.rs:1:1", "0: main", 1760486400);
+        assert!(report.contains("panic: index out of bounds\nat: src/main.rs:1:1\nscene: playing"));
+        assert!(report.contains("config file: shadow-swap.toml") && report.contains("network: host, tick 12"));
+        assert!(report.contains("language: es, theme: dark\ntunables: Tunables {"));
+        assert!(report.contains(&format!("last {} events:", game.timeline.len())) && report.contains("Trapped"));
+        assert!(report.ends_with("backtrace:\n0: main"));
+        assert!(CrashContext::default().report("boom", "here", "", 0).contains("match: -\nnetwork: -"));
+    }
+
+    #[test]
+    fn net_dumps_print_each_datagram_decoded() {
+        let hash = bincode::serialize(&Message::StateHash { tick: 7, hash: 42 }).unwrap();
+        let mut bytes = [NET_DUMP_MAGIC.as_slice(), &[1]].concat();
+        bytes.extend(NetDump::encode(0.25, true, &hash));
+        bytes.extend(NetDump::encode(1.5, false, &[0xff; 3]));
+        let text = NetDump::print(&bytes).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Captured by the host");
+        assert!(lines[1].contains("0.250  sent") && lines[1].ends_with("StateHash { tick: 7, hash: 42 }"));
+        assert!(lines[2].contains("1.500  recv     3 B  (not a message)"));
+        assert_eq!(lines[3], "2 datagrams: 1 sent, 1 received");
+        bytes.extend(&NetDump::encode(2.0, true, &hash)[..15]);
+        assert!(NetDump::print(&bytes).unwrap().contains("(cut off mid-datagram)"));
+        assert!(NetDump::print(b"SSRP").is_err());
+    }
+
+    #[test]
+    fn network_failures_are_errors_not_panics() {
+        // Holding the port (or finding it already held) makes hosting on it fail
+        let _held = UdpSocket::bind(("0.0.0.0", tunables().port));
+        let mut host = GameState::new(true, MatchConfig::default());
+        assert!(matches!(host.connect(""), Err(GameError::PortInUse(port)) if port == tunables().port));
+        assert!(host.transport.is_none());
+        let mut client = GameState::new(false, MatchConfig::default());
+        assert!(matches!(client.connect("no-such-host.invalid:5555"), Err(GameError::Resolve(_))));
+        assert!(matches!(client.connect("not an address"), Err(GameError::Resolve(_))));
+    }
+
+    #[test]
+    fn bad_or_stray_datagrams_are_dropped() {
+        let mut host = GameState::new(true, MatchConfig::default());
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let addr = socket.local_addr().unwrap();
+        host.transport = Some(Box::new(socket));
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let stranger = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let send = |from: &UdpSocket, message: Message| from.send_to(&bincode::serialize(&message).unwrap(), addr).unwrap();
+        let mut player = host.players[1];
+        player.id = 7;
+        send(&client, Message::PlayerUpdate(player));
+        send(&client, Message::TrapEvent { event_id: 1, player_id: 9, scores: [0, 5], streaks: [0, 0] });
+        send(&client, Message::Forfeit { player_id: 2, game_number: 0 });
+        send(&client, Message::Teleported { player_id: 200, from: Vec2 { x: 0.0, y: 0.0 } });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        host.receive_messages();
+        assert_eq!(host.client_addr, Some(client.local_addr().unwrap()));
+        assert!(host.forfeited.is_none() && host.players[1].score == 0 && host.trap_event_id == 0);
+        send(&stranger, Message::Forfeit { player_id: 1, game_number: 0 });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        host.receive_messages();
+        assert!(host.forfeited.is_none(), "only the client is listened to");
+        send(&client, Message::Forfeit { player_id: 1, game_number: 0 });
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        host.receive_messages();
+        assert_eq!(host.forfeited, Some(1));
+    }
+
+    #[test]
+    fn forfeits_only_count_for_the_game_being_played() {
+        let mut host = GameState::new(true, MatchConfig::default());
+        let addr = listen(&mut host);
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let game_number = host.game_number;
+        deliver(&client, &mut host, addr, &[Message::Forfeit { player_id: 1, game_number: game_number + 1 }]);
+        assert!(host.forfeited.is_none() && host.detect_winner().is_none());
+        deliver(&client, &mut host, addr, &[Message::Forfeit { player_id: 1, game_number }]);
+        assert_eq!((host.forfeited, host.detect_winner()), (Some(1), Some(0)));
+    }
+
+    #[test]
+    fn the_host_drops_updates_sent_before_a_teleport() {
+        let mut host = GameState::new(true, MatchConfig::default());
+        let addr = listen(&mut host);
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        host.players[1].warps = 1;
+        let at = host.players[1].pos;
+        let mut update = host.players[1];
+        update.warps = 0;
+        update.pos.x = at.x + 50.0;
+        deliver(&client, &mut host, addr, &[Message::PlayerUpdate(update)]);
+        assert_eq!(host.players[1].pos.x, at.x, "sent before the client saw the teleport");
+        update.warps = 1;
+        deliver(&client, &mut host, addr, &[Message::PlayerUpdate(update)]);
+        assert_eq!(host.players[1].pos.x, at.x + 50.0);
+    }
+
+    #[test]
+    fn only_the_host_spends_inverse_meters() {
+        let mut host = GameState::new(true, MatchConfig::default());
+        let addr = listen(&mut host);
+        let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut update = host.players[1];
+        update.inverse_charge = 1.0;
+        deliver(&peer, &mut host, addr, &[Message::PlayerUpdate(update), Message::InverseRequest]);
+        assert_eq!(host.players[1].inverse_charge, 0.0, "the host owns the meters");
+        assert!(!host.inverse_armed);
+        host.players[1].inverse_charge = 1.0;
+        deliver(&peer, &mut host, addr, &[Message::InverseRequest]);
+        assert!(host.inverse_armed && host.inverse_by == 1 && host.players[1].inverse_charge == 0.0);
+
+        let mut client = GameState::new(false, MatchConfig::default());
+        let addr = listen(&mut client);
+        client.players[0].inverse_charge = 1.0;
+        deliver(&peer, &mut client, addr, &[Message::InverseRequest]);
+        assert!(!client.inverse_armed, "only the host acts on requests");
+    }
+
+    #[test]
+    fn trap_events_are_applied_at_most_once() {
+        let mut client = GameState::new(false, MatchConfig::default());
+        let addr = listen(&mut client);
+        let host = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let trap = |event_id, scores| Message::TrapEvent { event_id, player_id: 0, scores, streaks: [0, 1] };
+        deliver(&host, &mut client, addr, &[trap(1, [0, 1]), trap(1, [0, 1])]);
+        assert_eq!((client.stats[0].traps_received, client.players[1].score), (1, 1));
+        deliver(&host, &mut client, addr, &[trap(2, [0, 2]), trap(1, [0, 1])]);
+        assert_eq!((client.stats[0].traps_received, client.trap_event_id, client.players[1].score), (2, 2, 2));
+    }
+
+    #[test]
+    fn anchored_boxes_keep_their_margin_inwards() {
+        let size = Vector2::new(100.0, 20.0);
+        let margin = Vector2::new(20.0, 10.0);
+        let at = Anchor::TopLeft.place(size, margin);
+        assert_eq!((at.x, at.y), (20.0, 10.0));
+        let at = Anchor::BottomRight.place(size, margin);
+        assert_eq!((at.x + size.x, at.y + size.y), (SCREEN_WIDTH as f32 - 20.0, SCREEN_HEIGHT as f32 - 10.0));
+        let at = Anchor::Bottom.place(size, margin);
+        assert_eq!((at.x, at.y), (SCREEN_WIDTH as f32 / 2.0 - 30.0, SCREEN_HEIGHT as f32 - 30.0));
     }
 }